@@ -3,9 +3,9 @@ use std::sync::Arc;
 use proto::bedrock::Command;
 use util::CowString;
 
-use crate::{instance::Instance, net::BedrockClient};
+use crate::instance::Instance;
 
-use super::{ParseResult, ParsedCommand};
+use super::{CommandSender, ParseResult, ParsedCommand};
 
 /// Represents a single output message in the command service response.
 #[derive(Debug)]
@@ -64,8 +64,8 @@ pub type HandlerResult = Result<HandlerOutput, HandlerOutput>;
 
 /// Contains the caller of this command and the server instance.
 pub struct Context {
-    /// User that executed this command.
-    pub caller: Arc<BedrockClient>,
+    /// Source that executed this command.
+    pub caller: CommandSender,
     /// Access to all server data.
     pub instance: Arc<Instance>
 }
@@ -94,7 +94,8 @@ where
 {
     fn call(&self, input: &str, ctx: &Context) -> HandlerResult {
         // Parse command with default parser.
-        let parsed = match ParsedCommand::default_parser(&self.structure, input) {
+        let commands = ctx.instance.commands();
+        let parsed = match ParsedCommand::parse_with_enums(&self.structure, input, &|id| commands.dynamic_enum(id)) {
             Ok(cmd) => cmd,
             Err(err) => {
                 return Err(HandlerOutput {
@@ -5,3 +5,6 @@ use ::util::glob_export;
 glob_export!(service);
 glob_export!(handler);
 glob_export!(parser);
+glob_export!(sender);
+glob_export!(target);
+glob_export!(tokenizer);
@@ -1,10 +1,12 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::str::Split;
+use std::str::FromStr;
 
-use util::CowString;
+use util::{CowString, Vector};
 
-use proto::bedrock::{Command, CommandDataType, CommandOverload};
+use proto::bedrock::{Command, CommandDataType, CommandOverload, CommandParameter};
+
+use super::{CommandTarget, Tokenizer};
 
 /// A type of error that occurred while parsing a command.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -31,34 +33,172 @@ pub struct ParseError {
 /// Type alias for `Result<ParsedCommand, ParseError>`.
 pub type ParseResult = Result<ParsedCommand, ParseError>;
 
-/// A target used in a command parameter.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum CommandTarget {
-    /// Targets all players in the game. This is equivalent to `@a`.
-    AllPlayers,
-    /// All entities in the game. This is equivalent to `@e`.
-    AllEntities,
-    /// Targets the closest player to the caller. This is equivalent to `@p`. 
-    ClosestPlayer,
-    /// A random player. This is equivalent to `@r`.
-    RandomPlayer,
-    /// The caller themselves. This is equivalent to `@s`.
-    Yourself,
-    /// A specific player, this occurs when a name is given instead of a selector.
-    SpecificPlayer(String)
-}
-
-impl From<&str> for CommandTarget {
+/// A single component of a command position.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Coordinate {
+    /// An absolute coordinate, such as `5`.
+    Absolute(f32),
+    /// A coordinate relative to the position of the caller, such as `~5`.
+    Relative(f32),
+    /// A coordinate relative to the position and rotation of the caller, such as `^5`.
+    Local(f32),
+}
+
+impl Coordinate {
+    /// Parses a single coordinate.
+    fn parse(input: &str) -> Result<Coordinate, String> {
+        let (constructor, value): (fn(f32) -> Coordinate, &str) = if let Some(value) = input.strip_prefix('~') {
+            (Coordinate::Relative, value)
+        } else if let Some(value) = input.strip_prefix('^') {
+            (Coordinate::Local, value)
+        } else {
+            (Coordinate::Absolute, input)
+        };
+
+        if value.is_empty() && !matches!(constructor(0.0), Coordinate::Absolute(_)) {
+            return Ok(constructor(0.0));
+        }
+
+        value
+            .parse()
+            .map(constructor)
+            .map_err(|_| format!("Failed to parse coordinate '{input}'. Expected a number."))
+    }
+
+    /// Resolves a relative or absolute coordinate using the given origin.
     #[inline]
-    fn from(value: &str) -> CommandTarget {
-        match value {
-            "@a" => Self::AllPlayers,
-            "@e" => Self::AllEntities,
-            "@p" => Self::ClosestPlayer,
-            "@r" => Self::RandomPlayer,
-            "@s" => Self::Yourself,
-            username => Self::SpecificPlayer(username.to_owned())
+    fn resolve(self, origin: f32) -> f32 {
+        match self {
+            Coordinate::Absolute(value) => value,
+            Coordinate::Relative(offset) => origin + offset,
+            // Local coordinates are resolved together, see `CommandPosition::resolve`.
+            Coordinate::Local(_) => origin,
+        }
+    }
+}
+
+/// A position given as a command argument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandPosition {
+    /// The X-coordinate, or the left offset in case of local coordinates.
+    pub x: Coordinate,
+    /// The Y-coordinate, or the upwards offset in case of local coordinates.
+    pub y: Coordinate,
+    /// The Z-coordinate, or the forward offset in case of local coordinates.
+    pub z: Coordinate,
+}
+
+impl CommandPosition {
+    /// Parses a position from the next arguments.
+    ///
+    /// Components can be written separately (`~ ~1 ~`) or together (`~~1~`).
+    fn parse(tokens: &mut Tokenizer) -> Result<CommandPosition, String> {
+        let mut components = Vec::with_capacity(3);
+        while components.len() < 3 {
+            let Some(token) = tokens.next_raw()? else {
+                return Err(format!("Expected 3 coordinates, got {}", components.len()));
+            };
+
+            // Split tokens such as `~1~2~3` into their components.
+            let mut start = 0;
+            for (i, c) in token.char_indices().skip(1) {
+                if c == '~' || c == '^' {
+                    components.push(Coordinate::parse(&token[start..i])?);
+                    start = i;
+                }
+            }
+            components.push(Coordinate::parse(&token[start..])?);
+        }
+
+        let &[x, y, z] = components.as_slice() else {
+            return Err(format!("Expected 3 coordinates, got {}", components.len()));
+        };
+
+        let local = components.iter().filter(|c| matches!(c, Coordinate::Local(_))).count();
+        if local != 0 && local != 3 {
+            return Err(String::from("Local coordinates (^) cannot be mixed with other coordinates"));
+        }
+
+        Ok(CommandPosition { x, y, z })
+    }
+
+    /// Converts this position to an absolute position.
+    ///
+    /// The origin and rotation are those of the caller. The rotation consists of the pitch and yaw in degrees.
+    pub fn resolve(&self, origin: &Vector<f32, 3>, rotation: &Vector<f32, 2>) -> Vector<f32, 3> {
+        let (Coordinate::Local(left), Coordinate::Local(up), Coordinate::Local(forward)) = (self.x, self.y, self.z) else {
+            return Vector::from([self.x.resolve(origin.x), self.y.resolve(origin.y), self.z.resolve(origin.z)]);
+        };
+
+        let (pitch, yaw) = (rotation.x.to_radians(), rotation.y.to_radians());
+        let half_pi = std::f32::consts::FRAC_PI_2;
+
+        let forward_axis = [-yaw.sin() * pitch.cos(), -pitch.sin(), yaw.cos() * pitch.cos()];
+        let up_axis = [-yaw.sin() * (pitch - half_pi).cos(), -(pitch - half_pi).sin(), yaw.cos() * (pitch - half_pi).cos()];
+        // Cross product of the up and forward axes.
+        let left_axis = [
+            up_axis[1] * forward_axis[2] - up_axis[2] * forward_axis[1],
+            up_axis[2] * forward_axis[0] - up_axis[0] * forward_axis[2],
+            up_axis[0] * forward_axis[1] - up_axis[1] * forward_axis[0],
+        ];
+
+        let component = |i: usize| left_axis[i] * left + up_axis[i] * up + forward_axis[i] * forward;
+        Vector::from([origin.x + component(0), origin.y + component(1), origin.z + component(2)])
+    }
+
+    /// Converts this position to the absolute position of the block it is located in.
+    pub fn resolve_block(&self, origin: &Vector<f32, 3>, rotation: &Vector<f32, 2>) -> Vector<i32, 3> {
+        let position = self.resolve(origin, rotation);
+        Vector::from([position.x.floor() as i32, position.y.floor() as i32, position.z.floor() as i32])
+    }
+}
+
+/// A range of integers, such as `1..5`, `..5` or `!3..`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IntegerRange {
+    /// Inclusive lower bound of the range.
+    pub min: Option<i32>,
+    /// Inclusive upper bound of the range.
+    pub max: Option<i32>,
+    /// Whether the range was negated, i.e. it matches values outside of the range.
+    pub inverted: bool,
+}
+
+impl IntegerRange {
+    /// Parses an integer range.
+    fn parse(input: &str) -> Result<IntegerRange, String> {
+        let (inverted, range) = match input.strip_prefix('!') {
+            Some(range) => (true, range),
+            None => (false, input),
+        };
+
+        let bound = |bound: &str| -> Result<Option<i32>, String> {
+            if bound.is_empty() {
+                Ok(None)
+            } else {
+                bound.parse().map(Some).map_err(|_| format!("Failed to parse integer range '{input}'"))
+            }
+        };
+
+        let (min, max) = match range.split_once("..") {
+            Some((min, max)) => (bound(min)?, bound(max)?),
+            None => {
+                let value = bound(range)?;
+                (value, value)
+            }
+        };
+
+        if min.is_none() && max.is_none() {
+            return Err(format!("Integer range '{input}' has no bounds"));
         }
+
+        Ok(IntegerRange { min, max, inverted })
+    }
+
+    /// Whether the given value lies in this range.
+    pub fn contains(&self, value: i32) -> bool {
+        let within = self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max);
+        within != self.inverted
     }
 }
 
@@ -69,25 +209,49 @@ pub enum ParsedArgument {
     Int(i32),
     /// A floating point argument.
     Float(f32),
+    /// A numeric value argument.
+    Value(f32),
+    /// An integer that can also be a wildcard (`*`), in which case it is `None`.
+    WildcardInt(Option<i32>),
     /// A string argument.
     String(String),
+    /// One of the options of an enum parameter.
+    Enum(String),
+    /// An operator such as `+=` or `<`.
+    Operator(String),
     /// A selector or target argument. These are the `@s`, `@p`, etc. targets that you often see in commands.
-    Target(CommandTarget)
+    Target(CommandTarget),
+    /// A position that can use relative (`~`) or local (`^`) coordinates.
+    Position(CommandPosition),
+    /// The position of a block, which can also use relative (`~`) or local (`^`) coordinates.
+    BlockPosition(CommandPosition),
+    /// A range of integers.
+    IntegerRange(IntegerRange),
+    /// The rest of the command input as a message.
+    Message(String),
+    /// A JSON value.
+    Json(serde_json::Value),
+    /// A list of block states such as `["age"=3,"open_bit"=true]`.
+    BlockStates(HashMap<String, nbt::Value>),
+    /// Another command, used by commands such as `/execute`.
+    Command(String),
 }
 
 impl ParsedArgument {
-    /// Converts the argument to a string if it is a string type.
+    /// Converts the argument to a string if it is a textual type.
+    ///
+    /// This includes strings, enum options, messages and commands.
     pub fn as_string(&self) -> Option<&str> {
         match self {
-            Self::String(s) => Some(s),
+            Self::String(s) | Self::Enum(s) | Self::Message(s) | Self::Command(s) => Some(s),
             _ => None
         }
     }
 
-    /// Converts the argument to a float if it is a float type.
+    /// Converts the argument to a float if it is a float or value type.
     pub const fn as_float(&self) -> Option<f32> {
         match self {
-            Self::Float(f) => Some(*f),
+            Self::Float(f) | Self::Value(f) => Some(*f),
             _ => None
         }
     }
@@ -103,7 +267,23 @@ impl ParsedArgument {
     /// Converts the argument to an integer if it is an integer type.
     pub const fn as_int(&self) -> Option<i32> {
         match self {
-            Self::Int(i) => Some(*i),
+            Self::Int(i) | Self::WildcardInt(Some(i)) => Some(*i),
+            _ => None
+        }
+    }
+
+    /// Converts the argument to a position if it is a position or block position type.
+    pub const fn as_position(&self) -> Option<&CommandPosition> {
+        match self {
+            Self::Position(p) | Self::BlockPosition(p) => Some(p),
+            _ => None
+        }
+    }
+
+    /// Converts the argument to a JSON value if it is a JSON type.
+    pub const fn as_json(&self) -> Option<&serde_json::Value> {
+        match self {
+            Self::Json(j) => Some(j),
             _ => None
         }
     }
@@ -121,26 +301,36 @@ pub struct ParsedCommand {
 
 impl ParsedCommand {
     /// Parses the command and verifies the arguments.
+    ///
+    /// Arguments of dynamic enums are checked against the options the enum was created with.
+    /// Use [`parse_with_enums`](Self::parse_with_enums) to check them against their current options instead.
     pub fn default_parser(syntax: &Command, input: &str) -> ParseResult {
-        let mut parts = input.split(' ');
+        Self::parse_with_enums(syntax, input, &|_| None)
+    }
+
+    /// Parses the command and verifies the arguments.
+    ///
+    /// `dynamic_options` returns the current options of the dynamic enum with the given ID.
+    /// If it returns `None`, the options the enum was created with are used.
+    pub fn parse_with_enums(syntax: &Command, input: &str, dynamic_options: &dyn Fn(&str) -> Option<Vec<String>>) -> ParseResult {
+        let mut tokens = Tokenizer::new(input);
 
         // Make sure the string is not empty.
-        let name = if let Some(name) = parts.next() {
-            let mut chars = name.chars();
-            chars.next();
-            chars.as_str().to_owned()
-        } else {
-            return Err(ParseError {
-                kind: ParseErrorKind::InvalidSyntax,
-                description: "Command cannot be empty".into()
-            })
+        let name = match tokens.next_raw() {
+            Ok(Some(name)) => name.strip_prefix('/').unwrap_or(name).to_owned(),
+            _ => {
+                return Err(ParseError {
+                    kind: ParseErrorKind::InvalidSyntax,
+                    description: "Command cannot be empty".into()
+                })
+            }
         };
 
         let mut latest_error = String::new();
         let mut furthest_param = -1i32;
 
         for overload in &syntax.overloads {
-            let parse_result = parse_overload(overload, parts.clone());
+            let parse_result = parse_overload(overload, tokens.clone(), dynamic_options);
             match parse_result {
                 Ok(parsed) => {
                     return Ok(Self {
@@ -173,58 +363,155 @@ impl ParsedCommand {
 }
 
 /// Parses a specific overload from the command.
-fn parse_overload(overload: &CommandOverload, mut parts: Split<char>)
+fn parse_overload(overload: &CommandOverload, mut tokens: Tokenizer, dynamic_options: &dyn Fn(&str) -> Option<Vec<String>>)
     -> Result<HashMap<String, ParsedArgument>, (String, usize)>
 {
     let mut parsed = HashMap::new();
     for (i, parameter) in overload.parameters.iter().enumerate() {
-        let Some(part) = parts.next() else {
+        if tokens.is_empty() {
             if parameter.optional {
                 return Ok(parsed);
             } else {
                 return Err((format!("Expected {} arguments, got {}", overload.parameters.len(), i), i));
             }
-        };
+        }
 
-        // Verify that the argument matches one of the predefined options.
-        if let Some(ref cmd_enum) = parameter.command_enum {
-            let valid = cmd_enum
-                .options
-                .iter()
-                .any(|o| o == part);
-
-            if !valid {
-                // Invalid option.
-                let mut options_tip = cmd_enum.options.iter().take(3).cloned().collect::<String>();
-                if cmd_enum.options.len() > 3 {
-                    options_tip += "..";
-                }
+        let value = parse_argument(parameter, &mut tokens, dynamic_options).map_err(|msg| (msg, i))?;
+        parsed.insert(parameter.name.clone(), value);
+    }
+
+    if !tokens.is_empty() {
+        return Err((String::from("Too many arguments given to command"), overload.parameters.len()))
+    }
+
+    Ok(parsed)
+}
 
-                return Err((format!("Option '{part}' is invalid. Help: use one of the predefined options: {options_tip}."), i));
+/// Parses a single argument into the type of the given parameter.
+fn parse_argument(
+    parameter: &CommandParameter,
+    tokens: &mut Tokenizer,
+    dynamic_options: &dyn Fn(&str) -> Option<Vec<String>>
+) -> Result<ParsedArgument, String> {
+    let next = |tokens: &mut Tokenizer| -> Result<String, String> {
+        tokens
+            .next_token()?
+            .map(|token| token.into_owned())
+            .ok_or_else(|| format!("Expected a value for '{}'", parameter.name))
+    };
+
+    // Verify that the argument matches one of the predefined options.
+    if let Some(ref cmd_enum) = parameter.command_enum {
+        let part = next(tokens)?;
+
+        // Dynamic enums can be modified at any time, so their current options have to be looked up.
+        let current = cmd_enum.dynamic.then(|| dynamic_options(&cmd_enum.enum_id)).flatten();
+        let options = current.as_ref().unwrap_or(&cmd_enum.options);
+
+        let valid = options.contains(&part);
+        if !valid {
+            // Invalid option.
+            let mut options_tip = options.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+            if options.len() > 3 {
+                options_tip += "..";
             }
+
+            return Err(format!("Option '{part}' is invalid. Help: use one of the predefined options: {options_tip}."));
         }
 
-        // Parse the value into the correct type.
-        let value = match parameter.data_type {
-            CommandDataType::String => ParsedArgument::String(part.into()),
-            CommandDataType::Target => ParsedArgument::Target(part.into()),
-            CommandDataType::Int => {
-                let result = part.parse();
-                if let Ok(value) = result {
-                    ParsedArgument::Int(value)
-                } else {
-                    return Err((format!("Failed to parse argument '{}'. Expected a valid integer.", part), i));
-                }
+        return Ok(ParsedArgument::Enum(part));
+    }
+
+    // Parse the value into the correct type.
+    let value = match parameter.data_type {
+        CommandDataType::String | CommandDataType::Filepath | CommandDataType::EquipmentSlots => {
+            ParsedArgument::String(next(tokens)?)
+        }
+        CommandDataType::Int => ParsedArgument::Int(parse_number(&next(tokens)?, "integer")?),
+        CommandDataType::Float => ParsedArgument::Float(parse_number(&next(tokens)?, "number")?),
+        CommandDataType::Value => ParsedArgument::Value(parse_number(&next(tokens)?, "number")?),
+        CommandDataType::WildcardInt => {
+            let part = next(tokens)?;
+            if part == "*" {
+                ParsedArgument::WildcardInt(None)
+            } else {
+                ParsedArgument::WildcardInt(Some(parse_number(&part, "integer or '*'")?))
             }
-            _ => todo!()
+        }
+        CommandDataType::Operator | CommandDataType::CompareOperator => {
+            let part = next(tokens)?;
+            let operators: &[&str] = if matches!(parameter.data_type, CommandDataType::Operator) {
+                &["=", "+=", "-=", "*=", "/=", "%=", "<", ">", "><"]
+            } else {
+                &["<", "<=", "=", ">=", ">"]
+            };
+
+            if !operators.contains(&part.as_str()) {
+                return Err(format!("Invalid operator '{part}'. Expected one of: {}.", operators.join(" ")));
+            }
+
+            ParsedArgument::Operator(part)
+        }
+        CommandDataType::Target | CommandDataType::WildcardTarget => {
+            let part = tokens.next_raw()?.ok_or_else(|| format!("Expected a target for '{}'", parameter.name))?;
+            let allow_wildcard = matches!(parameter.data_type, CommandDataType::WildcardTarget);
+
+            ParsedArgument::Target(CommandTarget::parse(part, allow_wildcard)?)
+        }
+        CommandDataType::IntegerRange => ParsedArgument::IntegerRange(IntegerRange::parse(&next(tokens)?)?),
+        CommandDataType::Position => ParsedArgument::Position(CommandPosition::parse(tokens)?),
+        CommandDataType::BlockPosition => ParsedArgument::BlockPosition(CommandPosition::parse(tokens)?),
+        CommandDataType::Message | CommandDataType::RawText => {
+            ParsedArgument::Message(tokens.rest().unwrap_or_default().to_owned())
+        }
+        CommandDataType::Command => ParsedArgument::Command(tokens.rest().unwrap_or_default().to_owned()),
+        CommandDataType::Json => {
+            let part = tokens.next_raw()?.ok_or_else(|| format!("Expected JSON for '{}'", parameter.name))?;
+            let json = serde_json::from_str(part).map_err(|err| format!("Failed to parse JSON argument: {err}"))?;
+
+            ParsedArgument::Json(json)
+        }
+        CommandDataType::BlockStates => {
+            let part = tokens.next_raw()?.ok_or_else(|| format!("Expected block states for '{}'", parameter.name))?;
+            ParsedArgument::BlockStates(parse_block_states(part)?)
+        }
+    };
+
+    Ok(value)
+}
+
+/// Parses a number, producing a descriptive error on failure.
+fn parse_number<T: FromStr>(part: &str, expected: &str) -> Result<T, String> {
+    part.parse().map_err(|_| format!("Failed to parse argument '{part}'. Expected a valid {expected}."))
+}
+
+/// Parses a list of block states such as `["age"=3,"open_bit"=true,"color"="red"]`.
+fn parse_block_states(input: &str) -> Result<HashMap<String, nbt::Value>, String> {
+    let Some(inner) = input.strip_prefix('[').and_then(|i| i.strip_suffix(']')) else {
+        return Err(format!("Block states '{input}' should be surrounded by brackets"));
+    };
+
+    let mut states = HashMap::new();
+    for state in inner.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let Some((key, value)) = state.split_once(['=', ':']) else {
+            return Err(format!("Expected '=' in block state '{state}'"));
         };
 
-        parsed.insert(parameter.name.clone(), value);
-    }
+        let Some(key) = key.trim().strip_prefix('"').and_then(|k| k.strip_suffix('"')) else {
+            return Err(format!("Block state name '{}' should be quoted", key.trim()));
+        };
+
+        let value = value.trim();
+        let value = if let Some(string) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            nbt::Value::String(string.to_owned())
+        } else if let Ok(flag) = value.parse::<bool>() {
+            nbt::Value::Byte(i8::from(flag))
+        } else {
+            nbt::Value::Int(parse_number(value, "block state value")?)
+        };
 
-    if parts.count() != 0 {
-        return Err((String::from("Too many arguments given to command"), 0))
+        states.insert(key.to_owned(), value);
     }
 
-    Ok(parsed)
+    Ok(states)
 }
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use proto::bedrock::CommandPermissionLevel;
+use util::Vector;
+
+use crate::net::BedrockClient;
+
+/// The source that requested execution of a command.
+#[derive(Clone)]
+pub enum CommandSender {
+    /// A player that is connected to the server.
+    Player(Arc<BedrockClient>),
+    /// The server console.
+    ///
+    /// The console has access to every command.
+    Console,
+    /// Commands executed by code, such as plugins, tests or automation scripts.
+    Programmatic {
+        /// Name used to identify the sender in command output.
+        name: String,
+        /// Permission level that the commands are executed with.
+        permission_level: CommandPermissionLevel,
+    },
+}
+
+impl CommandSender {
+    /// The name of this sender.
+    pub fn name(&self) -> &str {
+        match self {
+            CommandSender::Player(client) => client.name().unwrap_or("<unknown>"),
+            CommandSender::Console => "Server",
+            CommandSender::Programmatic { name, .. } => name,
+        }
+    }
+
+    /// Returns the client if this sender is a player.
+    pub const fn as_player(&self) -> Option<&Arc<BedrockClient>> {
+        match self {
+            CommandSender::Player(client) => Some(client),
+            _ => None,
+        }
+    }
+
+    /// The permission level that commands of this sender are executed with.
+    ///
+    /// Players that have not finished logging in only have the lowest permission level.
+    pub fn permission_level(&self) -> CommandPermissionLevel {
+        match self {
+            CommandSender::Player(client) => client
+                .player()
+                .map_or(CommandPermissionLevel::Normal, |player| player.command_permission_level()),
+            CommandSender::Console => CommandPermissionLevel::Internal,
+            CommandSender::Programmatic { permission_level, .. } => *permission_level,
+        }
+    }
+
+    /// The position that relative coordinates and selectors are resolved from.
+    ///
+    /// Senders that are not located in the world use the origin of the world.
+    pub fn position(&self) -> Vector<f32, 3> {
+        self.as_player()
+            .and_then(|client| client.player().ok())
+            .map(|player| player.position())
+            .unwrap_or_default()
+    }
+}
+
+impl From<Arc<BedrockClient>> for CommandSender {
+    fn from(client: Arc<BedrockClient>) -> CommandSender {
+        CommandSender::Player(client)
+    }
+}
@@ -1,23 +1,23 @@
 use std::{sync::{Arc, OnceLock, Weak}, time::Duration};
 
 use anyhow::Context as _;
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use parking_lot::RwLock;
-use proto::bedrock::{AvailableCommands, Command, DynamicEnumAction, UpdateDynamicEnum};
+use proto::bedrock::{AvailableCommands, Command, CommandPermissionLevel, DynamicEnumAction, UpdateDynamicEnum};
 use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 use util::Joinable;
 
-use crate::{instance::Instance, net::BedrockClient};
+use crate::instance::Instance;
 
-use super::{CommandHandler, Context, HandlerImpl, HandlerOutput, HandlerResult, ParseResult, ParsedCommand, ParserHandlerImpl};
+use super::{CommandHandler, CommandSender, Context, HandlerImpl, HandlerOutput, HandlerResult, ParseResult, ParsedCommand, ParserHandlerImpl};
 
 const SERVICE_TIMEOUT: Duration = Duration::from_millis(10);
 
 /// A request that can be sent to the command [`Service`].
 pub struct ServiceRequest {
     command: String,
-    caller: Arc<BedrockClient>,
+    caller: CommandSender,
     sender: oneshot::Sender<HandlerResult>
 }
 
@@ -66,9 +66,38 @@ impl Service {
         self.instance.set(Arc::downgrade(instance)).map_err(|_| anyhow::anyhow!("Instance was already set"))
     }   
 
-    /// Returns the current [`AvailableCommands`] packet.
-    pub(crate) fn available_commands(&self) -> AvailableCommands<'static> {
-        self.available.read().clone()
+    /// Returns an [`AvailableCommands`] packet containing only the commands that can be used
+    /// with the given permission level.
+    pub(crate) fn available_commands_for(&self, level: CommandPermissionLevel) -> AvailableCommands<'static> {
+        let commands = self.available
+            .read()
+            .commands
+            .iter()
+            .filter(|command| command.permission_level <= level)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        AvailableCommands { commands: commands.into() }
+    }
+
+    /// Sends the current command list to every connected client.
+    ///
+    /// Each client only receives the commands that its command permission level gives access to.
+    /// Clients that could not be sent the list are skipped, they will receive it on the next resync.
+    pub fn resync(&self) {
+        for client in self.instance().clients().connected() {
+            // Clients that have not logged in yet will receive the list during login.
+            let Ok(player) = client.player() else { continue };
+
+            if let Err(err) = client.send(self.available_commands_for(player.command_permission_level())) {
+                tracing::warn!("Failed to send command list to {}: {err:#}", client.name().unwrap_or("<unknown>"));
+            }
+        }
+    }
+
+    /// Returns the current options of the given dynamic enum.
+    pub fn dynamic_enum(&self, enum_id: &str) -> Option<Vec<String>> {
+        self.dynamic_enums.get(enum_id).map(|options| options.clone())
     }
 
     /// Updates autocompletion entries for the given dynamic enum.
@@ -102,14 +131,75 @@ impl Service {
 
     /// Registers a raw handler with this service.
     /// 
-    /// This function returns an error if the service failed to notify clients 
-    /// of an updated command list.
+    /// This function returns an error if a command with the same name or alias already exists.
     pub fn register_handler(&self, handler: Arc<dyn CommandHandler>) -> anyhow::Result<()> {
+        self.insert(handler)?;
+        self.resync();
+
+        Ok(())
+    }
+
+    /// Replaces the command with the same name as the given handler.
+    ///
+    /// If the command did not exist yet, this is equivalent to [`register_handler`](Self::register_handler).
+    /// The previous handler is returned.
+    ///
+    /// This function returns an error if one of the aliases of the new handler is in use by another command.
+    pub fn replace(&self, handler: Arc<dyn CommandHandler>) -> anyhow::Result<Option<Arc<dyn CommandHandler>>> {
+        let previous = self.remove(&handler.structure().name);
+        if let Err(err) = self.insert(handler) {
+            // Restore the previous command so that a failed replacement leaves the registry untouched.
+            if let Some(previous) = &previous {
+                if let Err(restore_err) = self.insert(Arc::clone(previous)) {
+                    tracing::error!("Failed to restore command /{}: {restore_err:#}", previous.structure().name);
+                }
+            }
+
+            return Err(err);
+        }
+
+        self.resync();
+        Ok(previous)
+    }
+
+    /// Removes a command and all of its aliases from the registry and returns its handler.
+    /// 
+    /// This function does not accept command aliases, you should use the original name of the command.
+    pub fn unregister<S: AsRef<str>>(&self, name: S) -> Option<Arc<dyn CommandHandler>> {
+        let removed = self.remove(name.as_ref());
+        if removed.is_some() {
+            self.resync();
+        }
+
+        removed
+    }
+
+    /// Adds a handler to the registry without notifying clients.
+    ///
+    /// Names are claimed one at a time, so that concurrent registrations can never overwrite each other.
+    /// If one of the names is taken, the names claimed so far are released again.
+    fn insert(&self, handler: Arc<dyn CommandHandler>) -> anyhow::Result<()> {
         let structure = handler.structure();
-        self.available.write().commands.push(structure.clone());
 
-        for alias in &structure.aliases {
-            self.registry.insert(alias.clone(), Arc::clone(&handler));
+        let names = std::iter::once(&structure.name).chain(&structure.aliases);
+        for (i, name) in names.clone().enumerate() {
+            let claimed = match self.registry.entry(name.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(Arc::clone(&handler));
+                    true
+                }
+                // Aliases that are equal to the name or another alias have already been claimed.
+                Entry::Occupied(entry) => Arc::ptr_eq(entry.get(), &handler),
+            };
+
+            // The entry has to be released before the registry can be modified again.
+            if !claimed {
+                for claimed in names.take(i) {
+                    self.registry.remove_if(claimed, |_, other| Arc::ptr_eq(other, &handler));
+                }
+
+                anyhow::bail!("Command or alias '{name}' is already registered");
+            }
         }
 
         for overload in &structure.overloads {
@@ -121,8 +211,38 @@ impl Service {
             }
         }
 
-        self.registry.insert(structure.name.clone(), handler);
-        self.instance().clients().broadcast(self.available_commands())
+        self.available.write().commands.push(structure.clone());
+        Ok(())
+    }
+
+    /// Removes a handler and its aliases from the registry without notifying clients.
+    fn remove(&self, name: &str) -> Option<Arc<dyn CommandHandler>> {
+        let (_, handler) = self.registry.remove_if(name, |_, handler| handler.structure().name == name)?;
+        let structure = handler.structure();
+
+        for alias in &structure.aliases {
+            self.registry.remove_if(alias, |_, other| Arc::ptr_eq(other, &handler));
+        }
+
+        let mut available = self.available.write();
+        let commands = available.commands
+            .iter()
+            .filter(|command| command.name != structure.name)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Dynamic enums can be shared by multiple commands, only remove those that are no longer in use.
+        for overload in &structure.overloads {
+            for parameter in &overload.parameters {
+                let Some(denum) = &parameter.command_enum else { continue };
+                if denum.dynamic && !commands.iter().any(|command| uses_enum(command, &denum.enum_id)) {
+                    self.dynamic_enums.remove(&denum.enum_id);
+                }
+            }
+        }
+
+        available.commands = commands.into();
+        Some(handler)
     }
 
     /// Registers a new command with the default syntax parser. 
@@ -165,20 +285,11 @@ impl Service {
         self.register_handler(handler)
     }
 
-    /// Removes a command from the registry and returns its handler.
-    /// 
-    /// This function does not accept command aliases, you should use the original name of the command.
-    pub fn unregister<S: AsRef<str>>(&self, name: S) -> Option<Arc<dyn CommandHandler>> {
-        todo!("Remove function from commands packet");
-
-        self.registry.remove(name.as_ref()).map(|(_, v)| v)
-    }
-
     /// Request execution of a command.
     /// 
     /// This method will return a receiver that will receive the output when the command has been executed.
     /// Execution of the command might not happen within the same tick.
-    pub(crate) async fn execute(&self, caller: Arc<BedrockClient>, command: String) 
+    pub async fn execute(&self, caller: CommandSender, command: String) 
         -> anyhow::Result<oneshot::Receiver<HandlerResult>> 
     {
         let (sender, receiver) = oneshot::channel();
@@ -198,23 +309,18 @@ impl Service {
 
     /// Parses the syntactic structure of a command before sending it off to a custom handler.
     fn execute_handler(&self, command: &str, ctx: &Context) -> HandlerResult {
-        let command_name = {
-            let mut split = command.split(' ');
-            let first = split
-                .next()
-                .ok_or_else(|| {
-                    HandlerOutput {
-                        message: "Expected command name after /".into(),
-                        parameters: Vec::new()
-                    }
-                })?;
-
-            // Get rid of slash in front of name.
-            let mut chars = first.chars();
-            chars.next();
-            chars.as_str()
-        };
-        
+        // Get rid of slash in front of name.
+        let command_name = command
+            .trim_start_matches('/')
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| {
+                HandlerOutput {
+                    message: "Expected command name after /".into(),
+                    parameters: Vec::new()
+                }
+            })?;
+
         let Some(handler) = self.registry.get(command_name) else {
             return Err(HandlerOutput {
                 message: format!("Unknown command {command_name}. Make sure the command exists and you have permission to use it.").into(),
@@ -222,6 +328,13 @@ impl Service {
             })
         };
         
+        if handler.structure().permission_level > ctx.caller.permission_level() {
+            return Err(HandlerOutput {
+                message: format!("You do not have permission to use /{command_name}.").into(),
+                parameters: Vec::new()
+            })
+        }
+
         handler.call(command, ctx)
     }
 
@@ -269,6 +382,14 @@ impl Service {
     }
 }
 
+/// Whether the given command has a parameter that uses the enum with the given ID.
+fn uses_enum(command: &Command, enum_id: &str) -> bool {
+    command.overloads
+        .iter()
+        .flat_map(|overload| &overload.parameters)
+        .any(|parameter| parameter.command_enum.as_ref().is_some_and(|denum| denum.enum_id == enum_id))
+}
+
 impl Joinable for Service {
     async fn join(&self) -> anyhow::Result<()> {
         self.shutdown_token.cancelled().await;
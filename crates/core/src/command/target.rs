@@ -0,0 +1,396 @@
+use std::sync::Arc;
+
+use proto::bedrock::GameMode;
+use rand::seq::SliceRandom;
+use util::Vector;
+
+use crate::entity::Entity;
+use crate::net::BedrockClient;
+
+use super::Context;
+
+/// Identifier of the player entity type.
+const PLAYER_IDENTIFIER: &str = "minecraft:player";
+
+/// The kind of entities a target selector looks for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// Targets all players in the game. This is equivalent to `@a`.
+    AllPlayers,
+    /// All entities in the game. This is equivalent to `@e`.
+    AllEntities,
+    /// Targets the closest player to the caller. This is equivalent to `@p`.
+    ClosestPlayer,
+    /// A random player. This is equivalent to `@r`.
+    RandomPlayer,
+    /// The caller themselves. This is equivalent to `@s`.
+    Yourself,
+}
+
+/// A player or entity that a [`CommandTarget`] resolved to.
+#[derive(Clone)]
+pub enum Target {
+    /// A connected player.
+    Player(Arc<BedrockClient>),
+    /// An entity other than a player.
+    Entity(Arc<Entity>),
+}
+
+impl Target {
+    /// Returns the player if this target is a player.
+    pub const fn as_player(&self) -> Option<&Arc<BedrockClient>> {
+        match self {
+            Self::Player(client) => Some(client),
+            Self::Entity(_) => None,
+        }
+    }
+
+    /// Returns the entity if this target is not a player.
+    pub const fn as_entity(&self) -> Option<&Arc<Entity>> {
+        match self {
+            Self::Player(_) => None,
+            Self::Entity(entity) => Some(entity),
+        }
+    }
+
+    /// Identifier of the entity type of this target, such as `minecraft:pig`.
+    pub fn identifier(&self) -> &str {
+        match self {
+            Self::Player(_) => PLAYER_IDENTIFIER,
+            Self::Entity(entity) => entity.identifier(),
+        }
+    }
+
+    /// Position and rotation of this target.
+    ///
+    /// The rotation consists of the pitch and yaw. This returns `None` if the target is a player that has not spawned yet.
+    fn location(&self) -> Option<(Vector<f32, 3>, Vector<f32, 2>)> {
+        match self {
+            Self::Player(client) => {
+                let player = client.player().ok()?;
+                let movement = player.movement.read();
+                Some((movement.position.clone(), Vector::from([movement.rotation.x, movement.rotation.y])))
+            }
+            Self::Entity(entity) => {
+                let transform = entity.transform();
+                Some((transform.position, Vector::from([transform.rotation.x, transform.rotation.y])))
+            }
+        }
+    }
+}
+
+/// A selector argument that can be negated using `!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negatable<T> {
+    /// The value of the argument.
+    pub value: T,
+    /// Whether the argument was negated, i.e. the target should *not* match the value.
+    pub negated: bool,
+}
+
+impl<T: PartialEq> Negatable<T> {
+    /// Whether the given value satisfies this argument.
+    #[inline]
+    pub fn matches(&self, value: &T) -> bool {
+        (self.value == *value) != self.negated
+    }
+}
+
+/// Filters that can be applied to a target selector, such as `@a[r=10,m=creative]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelectorArguments {
+    /// Overrides the X-coordinate of the selection origin (`x`).
+    pub x: Option<f32>,
+    /// Overrides the Y-coordinate of the selection origin (`y`).
+    pub y: Option<f32>,
+    /// Overrides the Z-coordinate of the selection origin (`z`).
+    pub z: Option<f32>,
+    /// Maximum distance from the origin (`r`).
+    pub max_radius: Option<f32>,
+    /// Minimum distance from the origin (`rm`).
+    pub min_radius: Option<f32>,
+    /// Size of the selection volume starting at the origin (`dx`, `dy` and `dz`).
+    pub volume: [Option<f32>; 3],
+    /// Maximum amount of targets (`c`).
+    ///
+    /// A negative count selects the targets furthest away from the origin.
+    pub count: Option<i32>,
+    /// Name of the target (`name`).
+    pub name: Option<Negatable<String>>,
+    /// Game mode of the target (`m`).
+    pub game_mode: Option<Negatable<GameMode>>,
+    /// Entity type of the target (`type`).
+    pub entity_type: Option<Negatable<String>>,
+    /// Minimum and maximum pitch of the target (`rxm` and `rx`).
+    pub pitch: (Option<f32>, Option<f32>),
+    /// Minimum and maximum yaw of the target (`rym` and `ry`).
+    pub yaw: (Option<f32>, Option<f32>),
+}
+
+impl SelectorArguments {
+    /// Parses the contents of the brackets of a selector.
+    fn parse(input: &str) -> Result<SelectorArguments, String> {
+        let mut arguments = SelectorArguments::default();
+        for argument in split_arguments(input) {
+            let argument = argument.trim();
+            if argument.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = argument.split_once('=') else {
+                return Err(format!("Expected '=' in selector argument '{argument}'"));
+            };
+
+            let key = key.trim();
+            let value = value.trim();
+
+            let float = || value.parse::<f32>().map_err(|_| format!("Selector argument '{key}' expects a number, got '{value}'"));
+            let negatable = || {
+                let (negated, value) = match value.strip_prefix('!') {
+                    Some(value) => (true, value.trim_start()),
+                    None => (false, value),
+                };
+
+                Negatable { value: unquote(value).to_owned(), negated }
+            };
+
+            match key {
+                "x" => arguments.x = Some(float()?),
+                "y" => arguments.y = Some(float()?),
+                "z" => arguments.z = Some(float()?),
+                "r" => arguments.max_radius = Some(float()?),
+                "rm" => arguments.min_radius = Some(float()?),
+                "dx" => arguments.volume[0] = Some(float()?),
+                "dy" => arguments.volume[1] = Some(float()?),
+                "dz" => arguments.volume[2] = Some(float()?),
+                "rx" => arguments.pitch.1 = Some(float()?),
+                "rxm" => arguments.pitch.0 = Some(float()?),
+                "ry" => arguments.yaw.1 = Some(float()?),
+                "rym" => arguments.yaw.0 = Some(float()?),
+                "c" => {
+                    let count = value.parse().map_err(|_| format!("Selector argument 'c' expects an integer, got '{value}'"))?;
+                    arguments.count = Some(count);
+                }
+                "name" => arguments.name = Some(negatable()),
+                "type" => arguments.entity_type = Some(negatable()),
+                "m" => {
+                    let Negatable { value, negated } = negatable();
+                    let game_mode = parse_game_mode(&value).ok_or_else(|| format!("Unknown game mode '{value}'"))?;
+
+                    arguments.game_mode = Some(Negatable { value: game_mode, negated });
+                }
+                _ => return Err(format!("Unknown selector argument '{key}'")),
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    /// Whether the given target satisfies all filters in these arguments.
+    fn matches(&self, target: &Target, origin: &Vector<f32, 3>) -> bool {
+        let Some((position, rotation)) = target.location() else {
+            return false;
+        };
+
+        if let Some(name) = &self.name {
+            // Entities do not have names yet.
+            let matches = target.as_player().is_some_and(|client| client.name().is_ok_and(|n| n.eq_ignore_ascii_case(&name.value)));
+            if matches == name.negated {
+                return false;
+            }
+        }
+
+        if let Some(game_mode) = &self.game_mode {
+            // Only players have a game mode.
+            let Some(player) = target.as_player().and_then(|client| client.player().ok()) else {
+                return false;
+            };
+
+            if !game_mode.matches(&player.gamemode()) {
+                return false;
+            }
+        }
+
+        if let Some(entity_type) = &self.entity_type {
+            let strip = |identifier: &'_ str| identifier.strip_prefix("minecraft:").unwrap_or(identifier).to_owned();
+            let matches = strip(target.identifier()).eq_ignore_ascii_case(&strip(&entity_type.value));
+            if matches == entity_type.negated {
+                return false;
+            }
+        }
+
+        let distance = distance(&position, origin);
+        if self.max_radius.is_some_and(|r| distance > r) || self.min_radius.is_some_and(|rm| distance < rm) {
+            return false;
+        }
+
+        if self.volume.iter().any(Option::is_some) {
+            let [dx, dy, dz] = self.volume.map(Option::unwrap_or_default);
+            let within = |pos: f32, origin: f32, delta: f32| pos >= origin.min(origin + delta) && pos <= origin.max(origin + delta) + 1.0;
+
+            if !within(position.x, origin.x, dx) || !within(position.y, origin.y, dy) || !within(position.z, origin.z, dz) {
+                return false;
+            }
+        }
+
+        let within = |value: f32, (min, max): (Option<f32>, Option<f32>)| {
+            min.map_or(true, |min| value >= min) && max.map_or(true, |max| value <= max)
+        };
+
+        within(rotation.x, self.pitch) && within(rotation.y, self.yaw)
+    }
+}
+
+/// A target used in a command parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandTarget {
+    /// A target selector such as `@a` or `@e[r=10]`.
+    Selector {
+        /// The kind of selector.
+        kind: SelectorKind,
+        /// Filters given in the brackets of the selector.
+        arguments: SelectorArguments,
+    },
+    /// Every target, written as `*`. This is only allowed in wildcard target parameters.
+    Wildcard,
+    /// A specific player, this occurs when a name is given instead of a selector.
+    SpecificPlayer(String),
+}
+
+impl CommandTarget {
+    /// Parses a target from a command argument.
+    pub fn parse(input: &str, allow_wildcard: bool) -> Result<CommandTarget, String> {
+        if input == "*" {
+            return if allow_wildcard {
+                Ok(CommandTarget::Wildcard)
+            } else {
+                Err(String::from("Wildcard targets are not allowed here"))
+            };
+        }
+
+        let Some(selector) = input.strip_prefix('@') else {
+            return Ok(CommandTarget::SpecificPlayer(unquote(input).to_owned()));
+        };
+
+        let (name, arguments) = match selector.split_once('[') {
+            Some((name, arguments)) => {
+                let Some(arguments) = arguments.strip_suffix(']') else {
+                    return Err(format!("Expected ']' at the end of selector '{input}'"));
+                };
+
+                (name, SelectorArguments::parse(arguments)?)
+            }
+            None => (selector, SelectorArguments::default()),
+        };
+
+        let kind = match name {
+            "a" => SelectorKind::AllPlayers,
+            "e" => SelectorKind::AllEntities,
+            "p" => SelectorKind::ClosestPlayer,
+            "r" => SelectorKind::RandomPlayer,
+            "s" => SelectorKind::Yourself,
+            _ => return Err(format!("Unknown selector '@{name}'")),
+        };
+
+        Ok(CommandTarget::Selector { kind, arguments })
+    }
+
+    /// Resolves this target to the players and entities it refers to.
+    ///
+    /// Only the `@e` selector is able to target entities other than players.
+    pub fn resolve(&self, ctx: &Context) -> Vec<Target> {
+        let clients = ctx.instance.clients();
+        let players = || clients.connected().into_iter().filter(|client| client.initialized()).map(Target::Player);
+
+        let (kind, arguments) = match self {
+            CommandTarget::SpecificPlayer(name) => return clients.by_username(name).map(Target::Player).into_iter().collect(),
+            CommandTarget::Wildcard => return players().collect(),
+            CommandTarget::Selector { kind, arguments } => (*kind, arguments),
+        };
+
+        let caller_position = ctx.caller.position();
+        let origin = Vector::from([
+            arguments.x.unwrap_or(caller_position.x),
+            arguments.y.unwrap_or(caller_position.y),
+            arguments.z.unwrap_or(caller_position.z),
+        ]);
+
+        let candidates: Vec<Target> = match kind {
+            // Senders that are not players cannot target themselves.
+            SelectorKind::Yourself => ctx.caller.as_player().map(|client| Target::Player(Arc::clone(client))).into_iter().collect(),
+            SelectorKind::AllEntities => players().chain(ctx.instance.entities().all().into_iter().map(Target::Entity)).collect(),
+            _ => players().collect(),
+        };
+
+        let mut targets = candidates
+            .into_iter()
+            .filter_map(|target| {
+                let (position, _) = target.location()?;
+                arguments.matches(&target, &origin).then(|| (distance(&position, &origin), target))
+            })
+            .collect::<Vec<_>>();
+
+        let default_count = match kind {
+            SelectorKind::ClosestPlayer | SelectorKind::RandomPlayer => Some(1),
+            _ => None,
+        };
+
+        if kind == SelectorKind::RandomPlayer {
+            targets.shuffle(&mut rand::thread_rng());
+        } else {
+            targets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        if let Some(count) = arguments.count.or(default_count) {
+            if count < 0 {
+                targets.reverse();
+            }
+            targets.truncate(count.unsigned_abs() as usize);
+        }
+
+        targets.into_iter().map(|(_, target)| target).collect()
+    }
+}
+
+/// Splits selector arguments on commas that are not located within quotes.
+fn split_arguments(input: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                arguments.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    arguments.push(&input[start..]);
+    arguments
+}
+
+/// Removes surrounding quotes from a value.
+fn unquote(value: &str) -> &str {
+    value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value)
+}
+
+/// Parses a game mode as used in the `m` selector argument.
+fn parse_game_mode(value: &str) -> Option<GameMode> {
+    Some(match value {
+        "0" | "s" | "survival" => GameMode::Survival,
+        "1" | "c" | "creative" => GameMode::Creative,
+        "2" | "a" | "adventure" => GameMode::Adventure,
+        "5" | "d" | "default" => GameMode::WorldDefault,
+        "6" | "spectator" => GameMode::Spectator,
+        _ => return None,
+    })
+}
+
+/// Euclidean distance between two points.
+fn distance(a: &Vector<f32, 3>, b: &Vector<f32, 3>) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
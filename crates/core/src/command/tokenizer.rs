@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+
+/// Splits command input into separate arguments.
+///
+/// Arguments are separated by whitespace, unless the whitespace is located within
+/// double quotes or brackets. This allows arguments such as `"hello world"`, `@a[r=10, name="Steve"]`
+/// and JSON objects to be read as a single argument.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a new tokenizer over the given input.
+    pub const fn new(input: &'a str) -> Tokenizer<'a> {
+        Tokenizer { input, position: 0 }
+    }
+
+    /// The input that has not been consumed yet.
+    #[inline]
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    /// Whether all arguments have been consumed.
+    pub fn is_empty(&mut self) -> bool {
+        self.skip_whitespace();
+        self.position == self.input.len()
+    }
+
+    /// Returns the next argument without consuming it.
+    pub fn peek(&self) -> Result<Option<&'a str>, String> {
+        self.clone().next_raw()
+    }
+
+    /// Consumes the next argument.
+    ///
+    /// If the entire argument is surrounded by quotes, the quotes are removed and escape sequences are resolved.
+    pub fn next_token(&mut self) -> Result<Option<Cow<'a, str>>, String> {
+        let Some(raw) = self.next_raw()? else {
+            return Ok(None);
+        };
+
+        if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+            let inner = &raw[1..raw.len() - 1];
+            if !inner.contains('\\') {
+                return Ok(Some(Cow::Borrowed(inner)));
+            }
+
+            let mut unescaped = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    // The raw tokenizer guarantees that an escape is always followed by another character.
+                    unescaped.extend(chars.next());
+                } else {
+                    unescaped.push(c);
+                }
+            }
+
+            return Ok(Some(Cow::Owned(unescaped)));
+        }
+
+        Ok(Some(Cow::Borrowed(raw)))
+    }
+
+    /// Consumes the next argument exactly as it was written, including any quotes and brackets.
+    pub fn next_raw(&mut self) -> Result<Option<&'a str>, String> {
+        self.skip_whitespace();
+
+        let start = self.position;
+        let mut depth = 0usize;
+        let mut quoted = false;
+        let mut escaped = false;
+
+        for (offset, c) in self.input[start..].char_indices() {
+            if quoted {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => quoted = false,
+                    _ => (),
+                }
+                continue;
+            }
+
+            match c {
+                '"' => quoted = true,
+                '[' | '{' => depth += 1,
+                ']' | '}' => {
+                    depth = depth.checked_sub(1).ok_or_else(|| format!("Unexpected '{c}' in argument"))?;
+                }
+                c if c.is_whitespace() && depth == 0 => {
+                    self.position = start + offset;
+                    return Ok(Some(&self.input[start..self.position]));
+                }
+                _ => (),
+            }
+        }
+
+        if quoted {
+            return Err(String::from("Unterminated quoted string"));
+        }
+
+        if depth != 0 {
+            return Err(String::from("Unclosed bracket in argument"));
+        }
+
+        self.position = self.input.len();
+        Ok((start != self.position).then(|| &self.input[start..]))
+    }
+
+    /// Consumes the remainder of the input.
+    ///
+    /// This returns `None` if there is no input left.
+    pub fn rest(&mut self) -> Option<&'a str> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let rest = self.remaining().trim_end();
+        self.position = self.input.len();
+
+        Some(rest)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let remaining = self.remaining();
+        self.position += remaining.len() - remaining.trim_start().len();
+    }
+}
@@ -1,6 +1,7 @@
 //! Server configuration
 
 use std::{
+    io::{Read, Write},
     net::{SocketAddrV4, SocketAddrV6},
     sync::{
         atomic::{AtomicUsize, Ordering},
@@ -8,8 +9,9 @@ use std::{
     },
 };
 
+use flate2::{read::DeflateDecoder, write::DeflateEncoder};
 use proto::bedrock::{CompressionAlgorithm, ThrottleSettings};
-use util::CowString;
+use util::{BinaryWrite, CowString, RVec};
 
 use crate::instance::{Instance, IPV4_LOCAL_ADDR};
 
@@ -21,12 +23,102 @@ pub struct Compression {
     pub threshold: u16,
 }
 
+impl Compression {
+    /// Header byte of packets that have not been compressed.
+    pub const UNCOMPRESSED: u8 = 0xff;
+    /// Maximum size of a packet after decompression.
+    ///
+    /// Packets sent by clients are far smaller than this,
+    /// larger sizes would only be used to make the server allocate large amounts of memory.
+    pub const MAX_DECOMPRESSED_SIZE: usize = 8 * 1024 * 1024;
+
+    /// Writes the compression header followed by the packet into `out`.
+    ///
+    /// The packet is only compressed if it exceeds the configured threshold.
+    pub fn compress(&self, packet: &[u8], out: &mut RVec) -> anyhow::Result<()> {
+        if packet.len() <= self.threshold as usize {
+            out.write_u8(Self::UNCOMPRESSED)?;
+            out.write_all(packet)?;
+
+            return Ok(());
+        }
+
+        out.write_u8(self.algorithm as u8)?;
+        match self.algorithm {
+            CompressionAlgorithm::Flate => {
+                let mut writer = DeflateEncoder::new(out, flate2::Compression::best());
+                writer.write_all(packet)?;
+                writer.finish()?;
+            }
+            CompressionAlgorithm::Snappy => {
+                let compressed = snap::raw::Encoder::new().compress_vec(packet)?;
+                out.write_all(&compressed)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decompresses a packet, including its compression header.
+    ///
+    /// The algorithm is determined by the header rather than the configuration,
+    /// clients are free to choose whether to compress their packets.
+    pub fn decompress(mut packet: RVec) -> anyhow::Result<RVec> {
+        if packet.is_empty() {
+            anyhow::bail!("Packet is missing compression header");
+        }
+
+        let header = packet.remove(0);
+        if header == Self::UNCOMPRESSED {
+            return Ok(packet);
+        }
+
+        match CompressionAlgorithm::try_from(header)? {
+            CompressionAlgorithm::Flate => {
+                let mut reader = DeflateDecoder::new(packet.as_slice()).take(Self::MAX_DECOMPRESSED_SIZE as u64 + 1);
+                let mut decompressed = RVec::alloc_with_capacity(packet.len() * 2);
+
+                reader.read_to_end(&mut decompressed)?;
+                anyhow::ensure!(
+                    decompressed.len() <= Self::MAX_DECOMPRESSED_SIZE,
+                    "Decompressed packet exceeds maximum size of {} bytes",
+                    Self::MAX_DECOMPRESSED_SIZE
+                );
+
+                Ok(decompressed)
+            }
+            CompressionAlgorithm::Snappy => {
+                let len = snap::raw::decompress_len(&packet)?;
+                anyhow::ensure!(
+                    len <= Self::MAX_DECOMPRESSED_SIZE,
+                    "Decompressed packet size of {len} bytes exceeds maximum of {} bytes",
+                    Self::MAX_DECOMPRESSED_SIZE
+                );
+
+                let mut decompressed = RVec::alloc_with_capacity(len);
+                decompressed.resize(len, 0);
+
+                snap::raw::Decoder::new().decompress(&packet, &mut decompressed)?;
+                Ok(decompressed)
+            }
+        }
+    }
+}
+
 /// Configuration of the level
 pub struct LevelConfig {
     /// The path to the level.
     pub path: String,
 }
 
+/// Configuration of the resource and behavior packs that are sent to clients.
+pub struct PackConfig {
+    /// Directory containing `.mcpack` and `.zip` pack archives.
+    pub path: String,
+    /// Whether clients must accept the packs to be able to join.
+    pub required: bool,
+}
+
 /// A callback for the message of the day.
 pub type MotdCallback = Box<dyn Fn(&Arc<Instance>) -> CowString<'static> + Send + Sync>;
 
@@ -54,6 +146,10 @@ pub struct Config {
     pub(super) max_render_distance: AtomicUsize,
     /// Level configuration
     pub(super) level: LevelConfig,
+    /// Pack configuration
+    pub(super) packs: PackConfig,
+    /// Names of the players that are given operator permissions when they join.
+    pub(super) operators: Vec<String>,
     /// Callback that generates a new message of the day.
     pub(super) motd_callback: MotdCallback,
 }
@@ -74,6 +170,8 @@ impl Config {
                 threshold: 0,
             },
             level: LevelConfig { path: String::from("resources\\level") },
+            packs: PackConfig { path: String::from("resources/packs"), required: false },
+            operators: Vec::new(),
             max_connections: AtomicUsize::new(10),
             max_render_distance: AtomicUsize::new(12),
             motd_callback: Box::new(|_| "Powered by Mirai".into()),
@@ -146,4 +244,17 @@ impl Config {
     pub const fn level(&self) -> &LevelConfig {
         &self.level
     }
+
+    /// Returns the pack configuration.
+    #[inline]
+    pub const fn packs(&self) -> &PackConfig {
+        &self.packs
+    }
+
+    /// Whether the player with the given name is an operator.
+    ///
+    /// Names are compared case-insensitively.
+    pub fn is_operator(&self, name: &str) -> bool {
+        self.operators.iter().any(|operator| operator.eq_ignore_ascii_case(name))
+    }
 }
@@ -0,0 +1,282 @@
+//! Entities other than players, such as NPCs, dropped items and projectiles.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use proto::bedrock::{ActorData, ActorFlag, AddActor, ConnectedPacket, MoveActorAbsolute, SetActorData, SetActorMotion};
+use util::{Serialize, Vector};
+
+use crate::instance::Instance;
+
+/// Position, rotation and velocity of an entity.
+#[derive(Debug, Clone)]
+pub struct Transform {
+    /// Position of the entity.
+    pub position: Vector<f32, 3>,
+    /// Rotation of the entity in degrees.
+    ///
+    /// The x and y components are the pitch and yaw, the z component is the head yaw.
+    pub rotation: Vector<f32, 3>,
+    /// Velocity of the entity in blocks per tick.
+    pub velocity: Vector<f32, 3>,
+}
+
+/// An entity that exists in the world.
+///
+/// Entities are created using [`Entities::spawn`], which also makes them visible to all nearby players.
+pub struct Entity {
+    /// Runtime ID of the entity. This is only valid for the current session.
+    runtime_id: u64,
+    /// Unique ID of the entity.
+    unique_id: i64,
+    /// Identifier of the entity type, such as `minecraft:pig`.
+    identifier: String,
+    transform: RwLock<Transform>,
+    metadata: RwLock<ActorData>,
+}
+
+impl Entity {
+    /// Runtime ID of the entity.
+    #[inline]
+    pub const fn runtime_id(&self) -> u64 {
+        self.runtime_id
+    }
+
+    /// Unique ID of the entity.
+    #[inline]
+    pub const fn unique_id(&self) -> i64 {
+        self.unique_id
+    }
+
+    /// Identifier of the entity type, such as `minecraft:pig`.
+    #[inline]
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The current position, rotation and velocity of the entity.
+    pub fn transform(&self) -> Transform {
+        self.transform.read().clone()
+    }
+
+    /// The current position of the entity.
+    pub fn position(&self) -> Vector<f32, 3> {
+        self.transform.read().position.clone()
+    }
+
+    /// The metadata of the entity.
+    pub fn metadata(&self) -> RwLockReadGuard<'_, ActorData> {
+        self.metadata.read()
+    }
+
+    /// Moves the entity and notifies all players that can see it.
+    ///
+    /// Players that the entity moves into or out of range of will have the entity spawned or despawned.
+    pub fn move_to(&self, instance: &Instance, position: Vector<f32, 3>, rotation: Vector<f32, 3>) -> anyhow::Result<()> {
+        {
+            let mut transform = self.transform.write();
+            transform.position = position;
+            transform.rotation = rotation;
+        }
+
+        for client in instance.clients().connected() {
+            if let Err(err) = client.sync_entity(self, true) {
+                tracing::warn!("Failed to move entity {} for {}: {err:#}", self.runtime_id, client.name().unwrap_or("<unknown>"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the velocity of the entity and notifies all players that can see it.
+    pub fn set_velocity(&self, instance: &Instance, velocity: Vector<f32, 3>) -> anyhow::Result<()> {
+        self.transform.write().velocity = velocity.clone();
+
+        let packet = SetActorMotion { runtime_id: self.runtime_id, velocity, tick: instance.ticker().current() };
+        self.send_to_viewers(instance, &packet);
+
+        Ok(())
+    }
+
+    /// Modifies the metadata of the entity and notifies all players that can see it.
+    pub fn update_metadata<F>(&self, instance: &Instance, f: F) -> anyhow::Result<()>
+    where
+        F: FnOnce(&mut ActorData),
+    {
+        let mut metadata = self.metadata.write();
+        f(&mut metadata);
+
+        // Downgrade the lock so that the metadata cannot change before it has been sent.
+        let metadata = RwLockWriteGuard::downgrade(metadata);
+        let packet = SetActorData { runtime_id: self.runtime_id, metadata: &metadata, tick: instance.ticker().current() };
+        self.send_to_viewers(instance, &packet);
+        drop(metadata);
+
+        Ok(())
+    }
+
+    /// Sends a packet to every client that has this entity in its world.
+    ///
+    /// A failure to send to one client does not prevent the packet from being sent to the others.
+    fn send_to_viewers<P: ConnectedPacket + Serialize + Clone>(&self, instance: &Instance, packet: &P) {
+        for client in instance.clients().connected() {
+            if !client.sees_entity(self.runtime_id) {
+                continue
+            }
+
+            if let Err(err) = client.send(packet.clone()) {
+                tracing::warn!("Failed to update entity {} for {}: {err:#}", self.runtime_id, client.name().unwrap_or("<unknown>"));
+            }
+        }
+    }
+
+    /// Creates the packet that adds this entity to a client's world.
+    ///
+    /// The metadata is passed in separately so that the caller controls how long it stays locked.
+    pub(crate) fn add_packet<'a>(&'a self, metadata: &'a ActorData) -> AddActor<'a> {
+        let transform = self.transform.read();
+        AddActor {
+            unique_id: self.unique_id,
+            runtime_id: self.runtime_id,
+            identifier: &self.identifier,
+            position: transform.position.clone(),
+            velocity: transform.velocity.clone(),
+            rotation: transform.rotation.clone(),
+            body_yaw: transform.rotation.y,
+            metadata,
+            links: &[],
+        }
+    }
+
+    /// Creates the packet that moves this entity to its current position.
+    pub(crate) fn move_packet(&self) -> MoveActorAbsolute {
+        let transform = self.transform.read();
+        MoveActorAbsolute {
+            runtime_id: self.runtime_id,
+            flags: 0,
+            position: transform.position.clone(),
+            rotation: transform.rotation.clone(),
+        }
+    }
+}
+
+/// Keeps track of all entities in the world and allocates their IDs.
+pub struct Entities {
+    /// Runtime ID given to the next entity or player.
+    next_id: AtomicU64,
+    /// Unique ID given to the next entity or player.
+    next_unique_id: AtomicI64,
+    entities: DashMap<u64, Arc<Entity>>,
+}
+
+impl Entities {
+    /// Creates an empty entity registry.
+    pub(crate) fn new() -> Entities {
+        Entities {
+            // The client treats an ID of 0 as invalid.
+            next_id: AtomicU64::new(1),
+            next_unique_id: AtomicI64::new(1),
+            entities: DashMap::new(),
+        }
+    }
+
+    /// Allocates a new ID.
+    ///
+    /// Players and entities share the same ID space, so this is also used to give players their runtime ID.
+    pub fn allocate_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Allocates a new unique ID.
+    ///
+    /// Unique IDs are allocated separately from runtime IDs, so that entities loaded from the level
+    /// can keep their stored unique ID. Like runtime IDs, they are shared with players.
+    pub fn allocate_unique_id(&self) -> i64 {
+        self.next_unique_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Retrieves the entity with the given runtime ID.
+    pub fn get(&self, runtime_id: u64) -> Option<Arc<Entity>> {
+        self.entities.get(&runtime_id).map(|entry| Arc::clone(entry.value()))
+    }
+
+    /// Returns all entities in the world.
+    pub fn all(&self) -> Vec<Arc<Entity>> {
+        self.entities.iter().map(|entry| Arc::clone(entry.value())).collect()
+    }
+
+    /// Amount of entities in the world.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether there are no entities in the world.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Creates a new entity and adds it to the world of every player that is in range.
+    pub fn spawn(
+        &self,
+        instance: &Instance,
+        identifier: &str,
+        position: Vector<f32, 3>,
+        rotation: Vector<f32, 3>,
+    ) -> anyhow::Result<Arc<Entity>> {
+        let entity = Arc::new(self.create(identifier, position, rotation));
+        self.entities.insert(entity.runtime_id, Arc::clone(&entity));
+
+        for client in instance.clients().connected() {
+            if let Err(err) = client.sync_entity(&entity, false) {
+                tracing::warn!("Failed to spawn entity {} for {}: {err:#}", entity.runtime_id, client.name().unwrap_or("<unknown>"));
+            }
+        }
+
+        Ok(entity)
+    }
+
+    /// Removes the entity with the given runtime ID from the world of every player.
+    ///
+    /// This returns the entity if it existed.
+    pub fn despawn(&self, instance: &Instance, runtime_id: u64) -> anyhow::Result<Option<Arc<Entity>>> {
+        let Some((_, entity)) = self.entities.remove(&runtime_id) else {
+            return Ok(None)
+        };
+
+        for client in instance.clients().connected() {
+            if let Err(err) = client.hide_entity(&entity) {
+                tracing::warn!("Failed to despawn entity {} for {}: {err:#}", entity.runtime_id, client.name().unwrap_or("<unknown>"));
+            }
+        }
+
+        Ok(Some(entity))
+    }
+
+    /// Creates an entity with newly allocated IDs and default metadata.
+    pub(crate) fn create(&self, identifier: &str, position: Vector<f32, 3>, rotation: Vector<f32, 3>) -> Entity {
+        let runtime_id = self.allocate_id();
+        let unique_id = self.allocate_unique_id();
+
+        let mut metadata = ActorData::new();
+        metadata.set_scale(1.0);
+        for flag in [ActorFlag::HasGravity, ActorFlag::HasCollision] {
+            metadata.set_flag(flag, true);
+        }
+
+        Entity {
+            runtime_id,
+            unique_id,
+            identifier: identifier.to_owned(),
+            transform: RwLock::new(Transform {
+                position,
+                rotation,
+                velocity: Vector::from([0.0, 0.0, 0.0]),
+            }),
+            metadata: RwLock::new(metadata),
+        }
+    }
+}
@@ -3,26 +3,32 @@
 use anyhow::Context;
 
 use parking_lot::RwLock;
+use prometheus_client::registry::Registry;
 use raknet::RakNetCreateDescription;
+use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
-use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use socket2::{Domain, Protocol, Socket, Type};
 use tokio::net::UdpSocket;
 
 use tokio_util::sync::CancellationToken;
 
 use util::{CowString, Deserialize, Joinable, RVec, ReserveTo, Serialize};
 
-use crate::command::{self, HandlerOutput, HandlerResult, ParsedCommand};
-use crate::config::Config;
+use crate::command::{self, CommandSender, HandlerOutput, HandlerResult, ParsedCommand};
+use crate::config::{Compression, Config};
+use crate::entity::Entities;
 use crate::net::{Clients, ForwardablePacket};
+use crate::pack::Packs;
+use crate::tick::Ticker;
 use level::{BlockStates, CreativeItems, ItemNetworkIds};
 use proto::bedrock::{
-    Command, CommandDataType, CommandEnum, CommandOverload, CommandParameter, CommandPermissionLevel, CreditsStatus, CreditsUpdate, MovePlayer,
+    Command, CommandDataType, CommandEnum, CommandOverload, CommandParameter, CommandPermissionLevel, CompressionAlgorithm, CreditsStatus, CreditsUpdate, MovePlayer,
     MovementMode, TeleportCause, CLIENT_VERSION_STRING, PROTOCOL_VERSION,
 };
 use proto::raknet::{
@@ -39,6 +45,8 @@ const RECV_BUF_SIZE: usize = 2048;
 /// Refresh rate of the server's metadata.
 /// This data is displayed in the server menu.
 const METADATA_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Amount of console lines that can be queued before the console reader waits for execution.
+const CONSOLE_BUFFER_SIZE: usize = 8;
 
 /// Configures and instance and constructs it.
 pub struct InstanceBuilder(Config);
@@ -55,6 +63,18 @@ impl InstanceBuilder {
         self
     }
 
+    /// Sets the directory that resource and behavior packs are loaded from.
+    pub fn pack_path<P: Into<String>>(mut self, path: P) -> InstanceBuilder {
+        self.0.packs.path = path.into();
+        self
+    }
+
+    /// Sets whether clients must accept the server's packs to be able to join.
+    pub fn require_packs(mut self, required: bool) -> InstanceBuilder {
+        self.0.packs.required = required;
+        self
+    }
+
     /// Sets the IPv4 address of the instance.
     pub fn ipv4_addr<A: Into<SocketAddrV4>>(mut self, addr: A) -> InstanceBuilder {
         self.0.ipv4_addr = addr.into();
@@ -67,6 +87,21 @@ impl InstanceBuilder {
         self
     }
 
+    /// Adds a player that is given operator permissions when they join.
+    ///
+    /// Operators are able to use commands up to the [`Admin`](CommandPermissionLevel::Admin) level,
+    /// all other players only have access to [`Normal`](CommandPermissionLevel::Normal) commands.
+    pub fn operator<S: Into<String>>(mut self, name: S) -> InstanceBuilder {
+        self.0.operators.push(name.into());
+        self
+    }
+
+    /// Sets the compression algorithm and the size threshold above which packets are compressed.
+    pub fn compression(mut self, algorithm: CompressionAlgorithm, threshold: u16) -> InstanceBuilder {
+        self.0.compression = Compression { algorithm, threshold };
+        self
+    }
+
     /// Produces an [`Instance`] with the configured options, consuming the builder.
     pub async fn build(self) -> anyhow::Result<Arc<Instance>> {
         tracing::info!(
@@ -78,10 +113,11 @@ impl InstanceBuilder {
         let item_network_ids = ItemNetworkIds::new()?;
         let block_states = BlockStates::new()?;
         let creative_items = CreativeItems::new(&item_network_ids, &block_states)?;
+        let packs = Packs::load(&self.0.packs.path)?;
 
         let ipv4_socket = UdpSocket::bind(self.0.ipv4_addr).await.context("Unable to create IPv4 UDP socket")?;
         let ipv6_socket = match self.0.ipv6_addr {
-            Some(addr) => Some(UdpSocket::bind(addr).await.context("Unable to create IPv6 UDP socket")?),
+            Some(addr) => Some(Instance::bind_ipv6(addr).context("Unable to create IPv6 UDP socket")?),
             None => None,
         };
 
@@ -98,12 +134,25 @@ impl InstanceBuilder {
 
         let user_map = Arc::new(Clients::new(Arc::clone(&command_service), Arc::clone(&level_service)));
         let user_map = Arc::new(Clients::new(Arc::clone(&command_service), Arc::clone(&level_service)));
+
+        let ticker = {
+            let settings = level_service.settings();
+            Ticker::new(u64::try_from(settings.current_tick).unwrap_or(0), settings.time)
+        };
+
+        let mut metrics = Registry::default();
+        ticker.metrics().register(&mut metrics);
+
         let instance = Instance {
             ipv4_socket,
             ipv6_socket,
             clients: user_map,
             command_service,
             level_service,
+            ticker,
+            metrics,
+            entities: Entities::new(),
+            packs,
             config: self.0,
 
             raknet_guid: rand::random(),
@@ -148,6 +197,14 @@ pub struct Instance {
     command_service: Arc<crate::command::Service>,
     /// Keeps track of the level state.
     level_service: Arc<crate::level::service::Service>,
+    /// Drives the global server tick.
+    ticker: Ticker,
+    /// Metrics collected by the server.
+    metrics: Registry,
+    /// Entities that exist in the world.
+    entities: Entities,
+    /// Resource and behavior packs that are sent to clients.
+    packs: Packs,
     /// Keeps track of the current configuration of the server.
     config: Config,
     /// Cancelled when the server has started up successfully.
@@ -187,6 +244,20 @@ impl Instance {
         &self.config
     }
 
+    /// Gets the address that the IPv4 socket is bound to.
+    ///
+    /// Unlike the configured address, this contains the actual port if the socket was bound to port 0.
+    pub fn ipv4_local_addr(&self) -> anyhow::Result<SocketAddr> {
+        Ok(self.ipv4_socket.local_addr()?)
+    }
+
+    /// Gets the address that the IPv6 socket is bound to, if IPv6 is enabled.
+    ///
+    /// Unlike the configured address, this contains the actual port if the socket was bound to port 0.
+    pub fn ipv6_local_addr(&self) -> anyhow::Result<Option<SocketAddr>> {
+        Ok(self.ipv6_socket.as_ref().map(|socket| socket.local_addr()).transpose()?)
+    }
+
     /// Gets the command service of this instance.
     #[inline]
     pub const fn commands(&self) -> &Arc<crate::command::Service> {
@@ -205,6 +276,30 @@ impl Instance {
         &self.clients
     }
 
+    /// Gets the metrics registry of this instance.
+    #[inline]
+    pub const fn metrics(&self) -> &Registry {
+        &self.metrics
+    }
+
+    /// Gets the server ticker of this instance.
+    #[inline]
+    pub const fn ticker(&self) -> &Ticker {
+        &self.ticker
+    }
+
+    /// Gets the entity registry of this instance.
+    #[inline]
+    pub const fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// Gets the resource and behavior packs of this instance.
+    #[inline]
+    pub const fn packs(&self) -> &Packs {
+        &self.packs
+    }
+
     /// Refreshes the message of the day by calling the generating function again.
     pub fn refresh_motd(self: &Arc<Instance>) {
         let motd: CowString<'_> = (self.config.motd_callback)(self);
@@ -307,7 +402,7 @@ impl Instance {
                 description: "Shuts down the server".to_owned(),
                 name: "shutdown".to_owned(),
                 overloads: vec![CommandOverload { parameters: Vec::new() }],
-                permission_level: CommandPermissionLevel::Normal,
+                permission_level: CommandPermissionLevel::Admin,
             },
             |_input, ctx| {
                 ctx.instance.shutdown();
@@ -328,10 +423,14 @@ impl Instance {
                 permission_level: CommandPermissionLevel::Normal,
             },
             |_input, ctx| {
-                let _ = ctx.caller.send(CreditsUpdate {
-                    runtime_id: 1,
-                    status: CreditsStatus::Start,
-                });
+                if let Some(caller) = ctx.caller.as_player() {
+                    if let Ok(runtime_id) = caller.runtime_id() {
+                        let _ = caller.send(CreditsUpdate {
+                            runtime_id,
+                            status: CreditsStatus::Start,
+                        });
+                    }
+                }
 
                 Ok(HandlerOutput { message: "".into(), parameters: vec![] })
             },
@@ -445,6 +544,18 @@ impl Instance {
             tracing::info!("IPv6 listener ready");
         }
 
+        {
+            let this = Arc::clone(self);
+            let token = self.running_token.clone();
+
+            tokio::spawn(Ticker::run(this, token));
+        }
+
+        {
+            let this = Arc::clone(self);
+            tokio::spawn(Instance::console_reader(this));
+        }
+
         {
             let this = Arc::clone(self);
             tokio::spawn(async move {
@@ -461,6 +572,80 @@ impl Instance {
         Ok(())
     }
 
+    /// Reads commands from the standard input and executes them as the console.
+    ///
+    /// The output of every command is written to the log.
+    async fn console_reader(self: Arc<Instance>) {
+        let (sender, mut receiver) = mpsc::channel(CONSOLE_BUFFER_SIZE);
+
+        // Reading from stdin blocks, which would prevent the runtime from shutting down if it
+        // were to run on a blocking tokio thread. A detached thread does not keep the process alive.
+        let spawned = std::thread::Builder::new().name("console".to_owned()).spawn(move || {
+            for line in std::io::stdin().lines() {
+                let Ok(line) = line else { break };
+                if sender.blocking_send(line).is_err() {
+                    break
+                }
+            }
+        });
+
+        if let Err(err) = spawned {
+            tracing::error!("Failed to spawn console reader: {err:#}");
+            return
+        }
+
+        loop {
+            let line = tokio::select! {
+                line = receiver.recv() => line,
+                _ = self.running_token.cancelled() => break
+            };
+
+            // Standard input has been closed.
+            let Some(line) = line else { break };
+
+            let command = line.trim();
+            if command.is_empty() {
+                continue
+            }
+
+            let receiver = match self.command_service.execute(CommandSender::Console, command.to_owned()).await {
+                Ok(receiver) => receiver,
+                Err(err) => {
+                    tracing::error!("Failed to execute console command: {err:#}");
+                    continue
+                }
+            };
+
+            match receiver.await {
+                Ok(Ok(output)) => tracing::info!("{}", Instance::format_output(&output)),
+                Ok(Err(output)) => tracing::warn!("{}", Instance::format_output(&output)),
+                Err(_) => tracing::error!("Command service shut down while awaiting execution")
+            }
+        }
+    }
+
+    /// Formats command output for the log.
+    fn format_output(output: &HandlerOutput) -> String {
+        if output.parameters.is_empty() {
+            output.message.as_str().to_owned()
+        } else {
+            let parameters = output.parameters.iter().map(CowString::as_str).collect::<Vec<_>>().join(", ");
+            format!("{} ({parameters})", output.message.as_str())
+        }
+    }
+
+    /// Binds an IPv6-only UDP socket.
+    ///
+    /// IPv4 traffic is handled by the IPv4 socket, which allows both sockets to use the same port.
+    fn bind_ipv6(addr: SocketAddrV6) -> anyhow::Result<UdpSocket> {
+        let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_only_v6(true)?;
+        socket.set_nonblocking(true)?;
+        socket.bind(&SocketAddr::V6(addr).into())?;
+
+        Ok(UdpSocket::from_std(socket.into())?)
+    }
+
     /// Generates a response to the [`UnconnectedPing`] packet with [`UnconnectedPong`].
     #[inline]
     #[tracing::instrument(
@@ -560,7 +745,9 @@ impl Instance {
         Ok(packet)
     }
 
-    /// Receives raknet from IPv4 clients and adds them to the receive queue
+    /// Receives packets from clients on the given socket and adds them to the receive queue.
+    ///
+    /// Clients are connected through the socket that they first contacted the server on.
     async fn net_receiver(self: Arc<Instance>, udp_socket: Arc<UdpSocket>) {
         // This is heap-allocated because stack data is stored inline in tasks.
         // If it were to be stack-allocated, Tokio would have to copy the entire buffer each time
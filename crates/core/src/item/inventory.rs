@@ -0,0 +1,509 @@
+use std::collections::HashSet;
+
+use proto::bedrock::{
+    ContainerSlotType, ItemInstance, ItemStack, StackRequest, StackRequestAction, StackRequestSlotInfo,
+    TransactionAction, TransactionSourceType, WindowId,
+};
+
+/// Maximum amount of items in a single stack.
+pub const MAX_STACK_SIZE: u16 = 64;
+/// Amount of slots in the hotbar.
+pub const HOTBAR_SIZE: usize = 9;
+/// Amount of slots in the main inventory, including the hotbar.
+pub const MAIN_INVENTORY_SIZE: usize = 36;
+/// Amount of armour slots.
+pub const ARMOR_SIZE: usize = 4;
+
+/// Window ID of the UI window that contains the cursor.
+const UI_WINDOW_ID: u32 = 124;
+
+/// A part of the player's inventory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InventoryWindow {
+    /// The hotbar and main inventory.
+    Main,
+    /// Armour slots, from helmet to boots.
+    Armor,
+    /// The off-hand slot.
+    Offhand,
+    /// Item that is currently being held by the cursor.
+    Cursor,
+    /// Temporary slot that items created by crafting are placed in.
+    CreatedOutput,
+}
+
+impl InventoryWindow {
+    /// Finds the window that a stack request container refers to.
+    pub const fn from_container(container: ContainerSlotType) -> Option<InventoryWindow> {
+        Some(match container {
+            ContainerSlotType::Hotbar | ContainerSlotType::Inventory | ContainerSlotType::CombinedHotbarAndInventory => Self::Main,
+            ContainerSlotType::Armor => Self::Armor,
+            ContainerSlotType::Offhand => Self::Offhand,
+            ContainerSlotType::Cursor => Self::Cursor,
+            ContainerSlotType::CreatedOutput => Self::CreatedOutput,
+            _ => return None,
+        })
+    }
+
+    /// Finds the window and slot that a legacy transaction refers to.
+    pub fn from_window_id(window: WindowId, slot: u32) -> Option<(InventoryWindow, usize)> {
+        Some(match window {
+            WindowId::Inventory => (Self::Main, slot as usize),
+            WindowId::Armor => (Self::Armor, slot as usize),
+            WindowId::OffHand => (Self::Offhand, 0),
+            WindowId::Ui if slot == 0 => (Self::Cursor, 0),
+            _ => return None,
+        })
+    }
+
+    /// The window ID used to synchronise this window with the client.
+    pub const fn window_id(self) -> u32 {
+        match self {
+            Self::Main => 0,
+            Self::Armor => 120,
+            Self::Offhand => 119,
+            Self::Cursor | Self::CreatedOutput => UI_WINDOW_ID,
+        }
+    }
+
+    /// Amount of slots in this window.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Main => MAIN_INVENTORY_SIZE,
+            Self::Armor => ARMOR_SIZE,
+            Self::Offhand | Self::Cursor | Self::CreatedOutput => 1,
+        }
+    }
+
+    /// Converts a slot index used by the client into an index into this window.
+    ///
+    /// Single-slot windows are addressed by different indices depending on the context,
+    /// those are all mapped to the first slot.
+    pub const fn index(self, slot: u8) -> usize {
+        match self {
+            Self::Main | Self::Armor => slot as usize,
+            Self::Offhand | Self::Cursor | Self::CreatedOutput => 0,
+        }
+    }
+}
+
+/// Whether two items are of the same kind and can therefore be stacked.
+pub fn stackable(a: &ItemInstance, b: &ItemInstance) -> bool {
+    a.network_id == b.network_id && a.metadata == b.metadata && a.block_runtime_id == b.block_runtime_id && a.nbt == b.nbt
+}
+
+/// Whether two items are of the same kind and have the same count.
+///
+/// The stack network ID is ignored because the client does not always include it.
+pub fn same_item(a: &ItemInstance, b: &ItemInstance) -> bool {
+    (a.network_id == 0 && b.network_id == 0) || (stackable(a, b) && a.count == b.count)
+}
+
+/// Converts an item from the creative inventory into an item instance.
+///
+/// Creative items do not restrict which blocks they can be placed on or destroy.
+pub fn creative_item(stack: &ItemStack, count: u16) -> ItemInstance<'static> {
+    ItemInstance {
+        network_id: stack.item_type.network_id,
+        count,
+        metadata: stack.item_type.meta,
+        stack_id: None,
+        block_runtime_id: stack.block_runtime_id,
+        nbt: stack.nbt_data.clone(),
+        can_place_on: vec![],
+        can_destroy: vec![],
+        blocking_tick: 0,
+    }
+}
+
+/// The inventory of a player.
+///
+/// This is the server's view of the inventory. Any changes made by the client are validated against
+/// it and rejected if they do not match.
+#[derive(Debug, Clone)]
+pub struct PlayerInventory {
+    main: Vec<ItemInstance<'static>>,
+    armor: Vec<ItemInstance<'static>>,
+    offhand: ItemInstance<'static>,
+    cursor: ItemInstance<'static>,
+    created_output: ItemInstance<'static>,
+    /// Hotbar slot that is currently selected.
+    held_slot: u8,
+    /// Stack network ID given to the next item stack.
+    next_stack_id: i32,
+}
+
+impl PlayerInventory {
+    /// Creates an empty inventory.
+    pub fn new() -> PlayerInventory {
+        PlayerInventory {
+            main: vec![ItemInstance::air(); MAIN_INVENTORY_SIZE],
+            armor: vec![ItemInstance::air(); ARMOR_SIZE],
+            offhand: ItemInstance::air(),
+            cursor: ItemInstance::air(),
+            created_output: ItemInstance::air(),
+            held_slot: 0,
+            next_stack_id: 1,
+        }
+    }
+
+    /// All items in the given window.
+    pub fn window(&self, window: InventoryWindow) -> &[ItemInstance<'static>] {
+        match window {
+            InventoryWindow::Main => &self.main,
+            InventoryWindow::Armor => &self.armor,
+            InventoryWindow::Offhand => std::slice::from_ref(&self.offhand),
+            InventoryWindow::Cursor => std::slice::from_ref(&self.cursor),
+            InventoryWindow::CreatedOutput => std::slice::from_ref(&self.created_output),
+        }
+    }
+
+    fn window_mut(&mut self, window: InventoryWindow) -> &mut [ItemInstance<'static>] {
+        match window {
+            InventoryWindow::Main => &mut self.main,
+            InventoryWindow::Armor => &mut self.armor,
+            InventoryWindow::Offhand => std::slice::from_mut(&mut self.offhand),
+            InventoryWindow::Cursor => std::slice::from_mut(&mut self.cursor),
+            InventoryWindow::CreatedOutput => std::slice::from_mut(&mut self.created_output),
+        }
+    }
+
+    /// The item in the given slot.
+    pub fn get(&self, window: InventoryWindow, slot: usize) -> Option<&ItemInstance<'static>> {
+        self.window(window).get(slot)
+    }
+
+    /// Replaces the item in the given slot.
+    ///
+    /// A new stack network ID is assigned to the item if it does not have one yet.
+    pub fn set(&mut self, window: InventoryWindow, slot: usize, mut item: ItemInstance<'static>) -> anyhow::Result<()> {
+        if item.network_id != 0 && item.stack_id.is_none() {
+            item.stack_id = Some(self.next_stack_id());
+        }
+
+        let Some(entry) = self.window_mut(window).get_mut(slot) else {
+            anyhow::bail!("Slot {slot} is out of range for {window:?}");
+        };
+
+        *entry = item;
+        Ok(())
+    }
+
+    /// The hotbar slot that is currently selected.
+    #[inline]
+    pub const fn held_slot(&self) -> u8 {
+        self.held_slot
+    }
+
+    /// Selects a different hotbar slot.
+    pub fn set_held_slot(&mut self, slot: u8) -> anyhow::Result<()> {
+        if slot as usize >= HOTBAR_SIZE {
+            anyhow::bail!("Hotbar slot {slot} is out of range");
+        }
+
+        self.held_slot = slot;
+        Ok(())
+    }
+
+    /// The item in the currently selected hotbar slot.
+    pub fn held_item(&self) -> &ItemInstance<'static> {
+        &self.main[self.held_slot as usize]
+    }
+
+    /// Allocates a new stack network ID.
+    fn next_stack_id(&mut self) -> i32 {
+        let id = self.next_stack_id;
+        self.next_stack_id += 1;
+        id
+    }
+
+    /// Applies an item stack request to this inventory.
+    ///
+    /// The request is applied in full or not at all. On success, the slots that were changed are returned.
+    pub fn apply_request(
+        &mut self,
+        request: &StackRequest,
+        creative_items: &[ItemStack],
+        creative: bool,
+    ) -> anyhow::Result<Vec<StackRequestSlotInfo>> {
+        let mut staged = self.clone();
+        let mut changed = Vec::new();
+
+        for action in &request.actions {
+            match action {
+                StackRequestAction::Take { count, source, destination }
+                | StackRequestAction::Place { count, source, destination }
+                | StackRequestAction::PlaceInContainer { count, source, destination }
+                | StackRequestAction::TakeOutContainer { count, source, destination } => {
+                    staged.move_items(u16::from(*count), source, destination)?;
+                    changed.extend([*source, *destination]);
+                }
+                StackRequestAction::Swap { source, destination } => {
+                    let (src_window, src) = staged.resolve(source)?;
+                    let (dst_window, dst) = staged.resolve(destination)?;
+
+                    let source_item = std::mem::replace(&mut staged.window_mut(src_window)[src], ItemInstance::air());
+                    let destination_item = std::mem::replace(&mut staged.window_mut(dst_window)[dst], source_item);
+                    staged.window_mut(src_window)[src] = destination_item;
+
+                    changed.extend([*source, *destination]);
+                }
+                StackRequestAction::Drop { .. } => anyhow::bail!("Dropping items is not supported"),
+                StackRequestAction::Consume { count, source } => {
+                    staged.remove_items(u16::from(*count), source)?;
+                    changed.push(*source);
+                }
+                StackRequestAction::Destroy { count, source } => {
+                    if !creative {
+                        anyhow::bail!("Items can only be destroyed in creative mode");
+                    }
+
+                    staged.remove_items(u16::from(*count), source)?;
+                    changed.push(*source);
+                }
+                StackRequestAction::CraftCreative { creative_network_id } => {
+                    if !creative {
+                        anyhow::bail!("Creative items can only be taken in creative mode");
+                    }
+
+                    // Creative network IDs start at 1.
+                    let Some(stack) = (*creative_network_id as usize).checked_sub(1).and_then(|index| creative_items.get(index)) else {
+                        anyhow::bail!("Creative item {creative_network_id} does not exist");
+                    };
+
+                    staged.set(InventoryWindow::CreatedOutput, 0, creative_item(stack, MAX_STACK_SIZE))?;
+                }
+                StackRequestAction::MineBlock { hotbar_slot, stack_network_id, .. } => {
+                    // Durability is not tracked yet, only verify that the item exists.
+                    let item = usize::try_from(*hotbar_slot).ok().and_then(|slot| staged.main.get(slot).filter(|_| slot < HOTBAR_SIZE));
+                    if item.map_or(true, |item| item.stack_id.unwrap_or(0) != *stack_network_id) {
+                        anyhow::bail!("Mined with an item that is not in hotbar slot {hotbar_slot}");
+                    }
+                }
+                // Crafting is not implemented yet, the client reverts the request when it is rejected.
+                StackRequestAction::CraftRecipe { .. }
+                | StackRequestAction::AutoCraftRecipe { .. }
+                | StackRequestAction::CraftRecipeOptional { .. }
+                | StackRequestAction::CraftGrindstoneRecipe { .. }
+                | StackRequestAction::CraftLoomRecipe { .. }
+                | StackRequestAction::CraftNonImplemented => anyhow::bail!("Crafting is not supported"),
+                // This only describes the results the client expects and does not change the inventory itself.
+                StackRequestAction::CraftResults { .. } => {}
+                action => anyhow::bail!("Unsupported item stack action: {action:?}"),
+            }
+        }
+
+        // Created items that were not taken out are discarded.
+        staged.created_output = ItemInstance::air();
+        *self = staged;
+
+        let mut seen = HashSet::new();
+        changed.retain(|info| {
+            InventoryWindow::from_container(info.container.container) != Some(InventoryWindow::CreatedOutput)
+                && seen.insert((info.container, info.slot))
+        });
+
+        Ok(changed)
+    }
+
+    /// Applies the actions of a legacy inventory transaction to this inventory.
+    ///
+    /// Every action replaces the content of a slot. A transaction can only move items around: for every kind of item,
+    /// the amount put into slots must equal the amount taken out. Taking items from and returning them to the creative
+    /// inventory is only allowed in creative mode. Items cannot be dropped into the world yet, so drops are refused.
+    pub fn apply_transaction(&mut self, actions: &[TransactionAction], creative_items: &[ItemStack], creative: bool) -> anyhow::Result<()> {
+        let mut staged = self.clone();
+        // Items that were taken out of slots and have not been put anywhere yet.
+        let mut taken: Vec<ItemInstance<'static>> = Vec::new();
+
+        let mut placed = Vec::new();
+        let mut seen = HashSet::new();
+        for action in actions {
+            match action.source_type {
+                TransactionSourceType::Container { inventory_id } => {
+                    let Some((window, slot)) = InventoryWindow::from_window_id(inventory_id, action.slot) else {
+                        anyhow::bail!("Transaction refers to unknown window {inventory_id:?}");
+                    };
+
+                    let Some(current) = staged.window(window).get(slot) else {
+                        anyhow::bail!("Slot {slot} is out of range for {window:?}");
+                    };
+
+                    if !same_item(current, &action.old_item) {
+                        anyhow::bail!("Item in slot {slot} of {window:?} does not match");
+                    }
+
+                    if !seen.insert((window, slot)) {
+                        anyhow::bail!("Transaction changes slot {slot} of {window:?} more than once");
+                    }
+
+                    if current.network_id != 0 {
+                        add_taken(&mut taken, current.clone())?;
+                    }
+                    placed.push((window, slot, &action.new_item));
+                }
+                TransactionSourceType::Creative if !creative => anyhow::bail!("Creative items can only be used in creative mode"),
+                // Slot 1 takes the old item out of the creative inventory, slot 0 puts the new item into it.
+                TransactionSourceType::Creative if action.slot == 1 => {
+                    let Some(stack) = creative_items.iter().find(|stack| stackable(&creative_item(stack, 1), &action.old_item)) else {
+                        anyhow::bail!("Item {} is not in the creative inventory", action.old_item.network_id);
+                    };
+
+                    add_taken(&mut taken, creative_item(stack, action.old_item.count.min(MAX_STACK_SIZE)))?;
+                }
+                TransactionSourceType::Creative => {
+                    remove_taken(&mut taken, &action.new_item)?;
+                }
+                TransactionSourceType::WorldInteraction { .. } => anyhow::bail!("Dropping items is not supported"),
+                source => anyhow::bail!("Unsupported transaction source: {source:?}"),
+            }
+        }
+
+        // Items put into slots are copied from the items that were taken out, so that the client cannot alter them.
+        for (window, slot, new_item) in placed {
+            let item = if new_item.network_id == 0 {
+                ItemInstance::air()
+            } else {
+                let item = remove_taken(&mut taken, new_item)?;
+                if item.count > MAX_STACK_SIZE {
+                    anyhow::bail!("Cannot put {} items into a single slot", item.count);
+                }
+
+                item
+            };
+
+            staged.set(window, slot, item)?;
+        }
+
+        if let Some(item) = taken.first() {
+            anyhow::bail!("Transaction removed {} items of type {} without putting them anywhere", item.count, item.network_id);
+        }
+
+        *self = staged;
+        Ok(())
+    }
+
+    /// Finds the slot referred to by a stack request and verifies that it contains the expected item.
+    fn resolve(&self, info: &StackRequestSlotInfo) -> anyhow::Result<(InventoryWindow, usize)> {
+        let Some(window) = InventoryWindow::from_container(info.container.container) else {
+            anyhow::bail!("Unsupported container {:?}", info.container);
+        };
+
+        let slot = window.index(info.slot);
+        let Some(item) = self.get(window, slot) else {
+            anyhow::bail!("Slot {} is out of range for {window:?}", info.slot);
+        };
+
+        // Items created by the request itself are referred to by a client-predicted ID.
+        if window != InventoryWindow::CreatedOutput && item.stack_id.unwrap_or(0) != info.stack_network_id {
+            anyhow::bail!(
+                "Stack network ID mismatch in slot {slot} of {window:?}: expected {}, got {:?}",
+                info.stack_network_id,
+                item.stack_id
+            );
+        }
+
+        Ok((window, slot))
+    }
+
+    /// Moves items from one slot to another, merging them with any items already in the destination.
+    fn move_items(&mut self, count: u16, source: &StackRequestSlotInfo, destination: &StackRequestSlotInfo) -> anyhow::Result<()> {
+        let (src_window, src) = self.resolve(source)?;
+        let (dst_window, dst) = self.resolve(destination)?;
+        if (src_window, src) == (dst_window, dst) {
+            anyhow::bail!("Cannot move items into the slot they came from");
+        }
+
+        let item = self.window(src_window)[src].clone();
+        if count == 0 || item.network_id == 0 || item.count < count {
+            anyhow::bail!("Cannot move {count} items out of a stack of {}", item.count);
+        }
+
+        let target = &self.window(dst_window)[dst];
+        let moved = if target.network_id == 0 {
+            let mut moved = item.clone();
+            moved.count = count;
+            // The full stack keeps its ID, a split stack gets a new one.
+            if count != item.count {
+                moved.stack_id = Some(self.next_stack_id());
+            }
+            moved
+        } else if stackable(target, &item) && target.count + count <= MAX_STACK_SIZE {
+            let mut merged = target.clone();
+            merged.count += count;
+            merged
+        } else {
+            anyhow::bail!("Cannot place {count} items on top of a different or full stack");
+        };
+
+        self.window_mut(dst_window)[dst] = moved;
+        self.take(src_window, src, count);
+
+        Ok(())
+    }
+
+    /// Removes items from the given slot.
+    fn remove_items(&mut self, count: u16, source: &StackRequestSlotInfo) -> anyhow::Result<()> {
+        let (window, slot) = self.resolve(source)?;
+        let item = &self.window(window)[slot];
+        if count == 0 || item.network_id == 0 || item.count < count {
+            anyhow::bail!("Cannot remove {count} items from a stack of {}", item.count);
+        }
+
+        self.take(window, slot, count);
+        Ok(())
+    }
+
+    /// Decreases the count of an item, replacing it with air if none are left.
+    fn take(&mut self, window: InventoryWindow, slot: usize, count: u16) {
+        let item = &mut self.window_mut(window)[slot];
+        item.count -= count;
+        if item.count == 0 {
+            *item = ItemInstance::air();
+        }
+    }
+}
+
+/// Adds an item to the items taken out of slots by a transaction, merging it with an existing stack of the same kind.
+fn add_taken(taken: &mut Vec<ItemInstance<'static>>, mut item: ItemInstance<'static>) -> anyhow::Result<()> {
+    item.stack_id = None;
+    match taken.iter_mut().find(|other| stackable(other, &item)) {
+        Some(other) => {
+            other.count = other.count.checked_add(item.count).ok_or_else(|| anyhow::anyhow!("Transaction moves too many items"))?;
+        }
+        None => taken.push(item),
+    }
+
+    Ok(())
+}
+
+/// Removes the given amount of items from the items taken out of slots by a transaction.
+///
+/// This returns the server's copy of the item with the requested count.
+fn remove_taken(taken: &mut Vec<ItemInstance<'static>>, item: &ItemInstance) -> anyhow::Result<ItemInstance<'static>> {
+    let Some(index) = taken.iter().position(|other| stackable(other, item)) else {
+        anyhow::bail!("Transaction puts item {} into a slot without taking it out of another", item.network_id);
+    };
+
+    let Some(remaining) = taken[index].count.checked_sub(item.count) else {
+        anyhow::bail!("Transaction puts {} items into slots but only took out {}", item.count, taken[index].count);
+    };
+
+    let mut removed = taken[index].clone();
+    removed.count = item.count;
+
+    if remaining == 0 {
+        taken.swap_remove(index);
+    } else {
+        taken[index].count = remaining;
+    }
+
+    Ok(removed)
+}
+
+impl Default for PlayerInventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
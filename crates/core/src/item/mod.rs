@@ -1,3 +1,5 @@
 //! Everything related to items in Minecraft.
 
 use util::glob_export;
+
+glob_export!(inventory);
@@ -0,0 +1,196 @@
+//! Block hardness and the time it takes to break blocks.
+//!
+//! The game data shipped with the server does not include block hardness,
+//! so the values of the most common blocks are listed here.
+
+use std::collections::HashMap;
+
+/// Enchantment ID of efficiency.
+const EFFICIENCY_ID: i16 = 15;
+
+/// Kind of tool that breaks a block faster.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ToolKind {
+    /// Pickaxes.
+    Pickaxe,
+    /// Axes.
+    Axe,
+    /// Shovels.
+    Shovel,
+    /// Hoes.
+    Hoe,
+    /// Swords.
+    Sword,
+    /// Shears.
+    Shears,
+}
+
+/// A tool that is held by a player.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tool {
+    /// Kind of tool.
+    pub kind: ToolKind,
+    /// Speed multiplier when the tool is used on a block that it is effective against.
+    pub speed: f32,
+}
+
+impl Tool {
+    /// Looks up the tool that corresponds to the given item name.
+    ///
+    /// Returns `None` if the item is not a tool.
+    pub fn from_item(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        if name == "shears" {
+            return Some(Self { kind: ToolKind::Shears, speed: 5.0 });
+        }
+
+        let (material, kind) = name.rsplit_once('_')?;
+        let kind = match kind {
+            "pickaxe" => ToolKind::Pickaxe,
+            "axe" => ToolKind::Axe,
+            "shovel" => ToolKind::Shovel,
+            "hoe" => ToolKind::Hoe,
+            "sword" => ToolKind::Sword,
+            _ => return None,
+        };
+
+        let speed = match material {
+            "wooden" => 2.0,
+            "stone" => 4.0,
+            "iron" => 6.0,
+            "diamond" => 8.0,
+            "netherite" => 9.0,
+            "golden" => 12.0,
+            _ => return None,
+        };
+
+        Some(Self { kind, speed })
+    }
+}
+
+/// How hard a block is to break.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Hardness {
+    /// Hardness of the block.
+    pub value: f32,
+    /// Tool that breaks the block faster.
+    pub tool: Option<ToolKind>,
+    /// Whether the block breaks a lot slower without the right tool.
+    pub requires_tool: bool,
+}
+
+impl Hardness {
+    /// Hardness of blocks that break instantly.
+    pub const INSTANT: Self = Self { value: 0.0, tool: None, requires_tool: false };
+
+    const fn new(value: f32, tool: Option<ToolKind>, requires_tool: bool) -> Self {
+        Self { value, tool, requires_tool }
+    }
+
+    /// Looks up the hardness of the block with the given name.
+    ///
+    /// Returns `None` if the block cannot be broken in survival mode.
+    /// Blocks that are not listed are assumed to break instantly, so that breaking them is never rejected.
+    pub fn of(name: &str) -> Option<Self> {
+        use ToolKind::*;
+
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        Some(match name {
+            "air" | "water" | "flowing_water" | "lava" | "flowing_lava" | "bedrock" | "invisible_bedrock" | "barrier" | "border_block" | "allow"
+            | "deny" | "light_block" | "command_block" | "chain_command_block" | "repeating_command_block" | "structure_block" | "structure_void"
+            | "jigsaw" | "end_portal" | "end_portal_frame" | "end_gateway" | "portal" => return None,
+
+            "obsidian" | "crying_obsidian" | "respawn_anchor" | "netherite_block" => Self::new(50.0, Some(Pickaxe), true),
+            "ancient_debris" => Self::new(30.0, Some(Pickaxe), true),
+            "ender_chest" => Self::new(22.5, Some(Pickaxe), true),
+            "iron_block" | "diamond_block" | "emerald_block" | "coal_block" | "redstone_block" | "raw_iron_block" | "raw_gold_block"
+            | "raw_copper_block" | "anvil" | "mob_spawner" | "enchanting_table" | "iron_bars" | "iron_door" | "iron_trapdoor" => {
+                Self::new(5.0, Some(Pickaxe), true)
+            }
+            name if name.starts_with("deepslate_") && name.ends_with("_ore") => Self::new(4.5, Some(Pickaxe), true),
+            "gold_block" | "lapis_block" | "copper_block" | "hopper" | "end_stone" | "deepslate" | "polished_deepslate" | "deepslate_bricks"
+            | "deepslate_tiles" => Self::new(3.0, Some(Pickaxe), true),
+            name if name.ends_with("_ore") => Self::new(3.0, Some(Pickaxe), true),
+            "cobbled_deepslate" | "furnace" | "lit_furnace" | "blast_furnace" | "smoker" | "dispenser" | "dropper" | "stonecutter_block" => {
+                Self::new(3.5, Some(Pickaxe), true)
+            }
+            "cobblestone" | "mossy_cobblestone" | "brick_block" | "nether_brick" | "red_nether_brick" => Self::new(2.0, Some(Pickaxe), true),
+            name if name.ends_with("concrete") => Self::new(1.8, Some(Pickaxe), true),
+            "stone" | "granite" | "diorite" | "andesite" | "polished_granite" | "polished_diorite" | "polished_andesite" | "stonebrick"
+            | "stone_bricks" | "smooth_stone" | "blackstone" | "tuff" | "prismarine" => Self::new(1.5, Some(Pickaxe), true),
+            name if name.ends_with("terracotta") => Self::new(1.25, Some(Pickaxe), true),
+            "basalt" | "polished_basalt" => Self::new(1.25, Some(Pickaxe), true),
+            "sandstone" | "red_sandstone" | "quartz_block" => Self::new(0.8, Some(Pickaxe), true),
+            "calcite" => Self::new(0.75, Some(Pickaxe), true),
+            "magma" => Self::new(0.5, Some(Pickaxe), true),
+            "netherrack" => Self::new(0.4, Some(Pickaxe), true),
+            "ice" | "packed_ice" => Self::new(0.5, Some(Pickaxe), false),
+
+            "chest" | "trapped_chest" | "barrel" | "crafting_table" => Self::new(2.5, Some(Axe), false),
+            name if name.ends_with("_door") => Self::new(3.0, Some(Axe), false),
+            name if name.ends_with("_log") || name.ends_with("_wood") || name.ends_with("_stem") || name.ends_with("_planks") => {
+                Self::new(2.0, Some(Axe), false)
+            }
+            name if name.ends_with("fence") || name.ends_with("fence_gate") => Self::new(2.0, Some(Axe), false),
+            "log" | "log2" | "wood" | "planks" => Self::new(2.0, Some(Axe), false),
+            "bookshelf" => Self::new(1.5, Some(Axe), false),
+            "pumpkin" | "carved_pumpkin" | "lit_pumpkin" | "melon_block" => Self::new(1.0, Some(Axe), false),
+            "ladder" => Self::new(0.4, Some(Axe), false),
+
+            "grass_block" | "grass" | "gravel" | "clay" | "farmland" | "mycelium" | "grass_path" | "dirt_path" => Self::new(0.6, Some(Shovel), false),
+            name if name.ends_with("concrete_powder") => Self::new(0.5, Some(Shovel), false),
+            "dirt" | "coarse_dirt" | "podzol" | "sand" | "red_sand" | "soul_sand" | "soul_soil" | "mud" => Self::new(0.5, Some(Shovel), false),
+            "snow" => Self::new(0.2, Some(Shovel), false),
+            "snow_layer" => Self::new(0.1, Some(Shovel), false),
+
+            "nether_wart_block" | "warped_wart_block" | "shroomlight" => Self::new(1.0, Some(Hoe), false),
+            "sponge" | "wet_sponge" => Self::new(0.6, Some(Hoe), false),
+            "hay_block" | "target" => Self::new(0.5, Some(Hoe), false),
+            name if name.ends_with("leaves") || name == "leaves2" => Self::new(0.2, Some(Hoe), false),
+            "moss_block" => Self::new(0.1, Some(Hoe), false),
+
+            "web" | "cobweb" => Self::new(4.0, Some(Shears), true),
+            name if name.ends_with("wool") => Self::new(0.8, Some(Shears), false),
+
+            name if name.ends_with("glass") || name.ends_with("glass_pane") => Self::new(0.3, None, false),
+            "glowstone" => Self::new(0.3, None, false),
+
+            _ => Self::INSTANT,
+        })
+    }
+
+    /// Amount of ticks it takes to break this block with the given tool.
+    ///
+    /// `efficiency` is the level of the efficiency enchantment on the tool.
+    pub fn break_ticks(&self, tool: Option<Tool>, efficiency: u8) -> u64 {
+        let effective = tool.filter(|tool| Some(tool.kind) == self.tool);
+
+        let mut speed = effective.map_or(1.0, |tool| tool.speed);
+        if effective.is_some() && efficiency > 0 {
+            speed += f32::from(efficiency).mul_add(f32::from(efficiency), 1.0);
+        }
+
+        let multiplier = if self.requires_tool && effective.is_none() { 5.0 } else { 1.5 };
+        (self.value * multiplier / speed * 20.0).ceil() as u64
+    }
+}
+
+/// Reads the level of the efficiency enchantment from the NBT of an item.
+pub fn efficiency_level(nbt: &HashMap<String, nbt::Value>) -> u8 {
+    let Some(nbt::Value::List(enchantments)) = nbt.get("ench") else {
+        return 0;
+    };
+
+    enchantments
+        .iter()
+        .filter_map(|enchantment| match enchantment {
+            nbt::Value::Compound(enchantment) => Some(enchantment),
+            _ => None,
+        })
+        .find(|enchantment| matches!(enchantment.get("id"), Some(nbt::Value::Short(EFFICIENCY_ID))))
+        .and_then(|enchantment| match enchantment.get("lvl") {
+            Some(nbt::Value::Short(level)) => u8::try_from(*level).ok(),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
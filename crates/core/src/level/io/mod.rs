@@ -1,5 +1,4 @@
 pub mod r#box;
-pub mod io;
 pub mod point;
 pub mod radial;
 pub mod region;
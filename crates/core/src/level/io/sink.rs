@@ -2,10 +2,11 @@ use std::{
     future::Future,
     pin::Pin,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicU64, Ordering},
         Arc,
     },
     task::{Context, Poll, Waker},
+    time::Duration,
 };
 
 use futures::Sink;
@@ -13,12 +14,17 @@ use level::provider::Provider;
 use parking_lot::Mutex;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
 use tokio_util::sync::CancellationToken;
-use util::Joinable;
+use util::{Joinable, Vector};
 
 use super::stream::IndexedSubChunk;
 
-/// Future that resolves when [`FlushState`] transitions into a busy state.
+/// Interval at which the collector writes its buffered subchunks to disk,
+/// even if no flush has been requested.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Future that resolves when a flush has been requested on a [`FlushState`].
 pub struct Flushing<'state> {
     state: &'state FlushStateInner,
 }
@@ -28,9 +34,12 @@ impl<'state> Future for Flushing<'state> {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
         if self.state.is_complete() {
-            // Register flush waker
+            // Register flush waker and check again to make sure a request that came in
+            // in the meantime is not missed.
             self.state.busy_wakers.lock().push(cx.waker().clone());
-            return Poll::Pending;
+            if self.state.is_complete() {
+                return Poll::Pending;
+            }
         }
 
         Poll::Ready(())
@@ -42,19 +51,24 @@ struct FlushStateInner {
     idle_wakers: Mutex<Vec<Waker>>,
     /// Tasks that should be woken when the state triggers processing.
     busy_wakers: Mutex<Vec<Waker>>,
-    /// Whether the state is currently in idle mode.
-    completed: AtomicBool,
+    /// Ticket of the most recently requested flush.
+    requested: AtomicU64,
+    /// Ticket of the most recently completed flush.
+    completed: AtomicU64,
+    /// Ticket of the most recent flush that failed to write its data.
+    failed: AtomicU64,
 }
 
 impl FlushStateInner {
     pub fn is_complete(&self) -> bool {
-        self.completed.load(Ordering::SeqCst)
+        self.completed.load(Ordering::SeqCst) >= self.requested.load(Ordering::SeqCst)
     }
 }
 
-/// Token that can be in two states.
+/// Keeps track of flush requests made by sinks and their completion by the collector.
 ///
-/// State changes can be awaited.
+/// Every flush request is given a ticket. A ticket is completed once the collector
+/// has written all data that was sent before the ticket was handed out.
 #[derive(Clone)]
 pub struct FlushState {
     inner: Arc<FlushStateInner>,
@@ -67,17 +81,24 @@ impl FlushState {
             inner: Arc::new(FlushStateInner {
                 idle_wakers: Mutex::new(Vec::new()),
                 busy_wakers: Mutex::new(Vec::new()),
-                completed: AtomicBool::new(true),
+                requested: AtomicU64::new(0),
+                completed: AtomicU64::new(0),
+                failed: AtomicU64::new(0),
             }),
         }
     }
 
-    /// Transitions the state to idle and wakes all waiting tasks.
-    pub fn finish(&self) {
-        self.inner.completed.store(true, Ordering::SeqCst);
+    /// Marks all flushes up to and including `ticket` as finished and wakes all waiting tasks.
+    ///
+    /// If `success` is false, tasks waiting for these tickets will receive an error.
+    pub fn finish(&self, ticket: u64, success: bool) {
+        if !success {
+            self.inner.failed.fetch_max(ticket, Ordering::SeqCst);
+        }
 
         let wakers = {
             let mut lock = self.inner.idle_wakers.lock();
+            self.inner.completed.fetch_max(ticket, Ordering::SeqCst);
             std::mem::take(&mut *lock)
         };
 
@@ -86,9 +107,9 @@ impl FlushState {
         }
     }
 
-    /// Transitions the state to busy and wakes all waiting tasks.
-    pub fn flush(&self) {
-        self.inner.completed.store(false, Ordering::SeqCst);
+    /// Requests a flush and wakes the collector, returning the ticket of this request.
+    pub fn flush(&self) -> u64 {
+        let ticket = self.inner.requested.fetch_add(1, Ordering::SeqCst) + 1;
 
         let wakers = {
             let mut lock = self.inner.busy_wakers.lock();
@@ -98,19 +119,41 @@ impl FlushState {
         for waker in wakers {
             waker.wake();
         }
+
+        ticket
     }
 
-    /// Resolves when the state transitions into processing.
-    pub fn flushing(&self) -> Flushing {
+    /// Returns the ticket of the most recently requested flush.
+    pub fn requested(&self) -> u64 {
+        self.inner.requested.load(Ordering::SeqCst)
+    }
+
+    /// Resolves when a flush has been requested.
+    pub fn flushing(&self) -> Flushing<'_> {
         Flushing { state: &self.inner }
     }
 
-    // /// Resolves when the state transitions into idle.
-    // pub fn idle(&self) -> Idle {
-    //     Idle { state: &self.inner }
-    // }
+    /// Polls whether the flush with the given ticket has completed.
+    ///
+    /// Resolves with an error if the data could not be written to disk.
+    pub fn poll_flushed(&self, ticket: u64, cx: &mut Context) -> Poll<anyhow::Result<()>> {
+        if self.inner.completed.load(Ordering::SeqCst) < ticket {
+            let mut lock = self.inner.idle_wakers.lock();
+            // Check again while holding the lock, `finish` updates the counter while holding it.
+            if self.inner.completed.load(Ordering::SeqCst) < ticket {
+                lock.push(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+
+        if self.inner.failed.load(Ordering::SeqCst) >= ticket {
+            return Poll::Ready(Err(anyhow::anyhow!("Failed to write subchunks to disk")));
+        }
 
-    /// Whether the state is currently idle.
+        Poll::Ready(Ok(()))
+    }
+
+    /// Whether all requested flushes have been completed.
     pub fn is_complete(&self) -> bool {
         self.inner.is_complete()
     }
@@ -122,19 +165,6 @@ impl Default for FlushState {
     }
 }
 
-impl Future for FlushState {
-    type Output = ();
-
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
-        if !self.inner.completed.load(Ordering::SeqCst) {
-            self.inner.idle_wakers.lock().push(cx.waker().clone());
-            return Poll::Pending;
-        }
-
-        Poll::Ready(())
-    }
-}
-
 /// Collects all subchunk updates and writes them to disk periodically.
 pub struct Collector {
     producer: mpsc::Sender<IndexedSubChunk>,
@@ -150,7 +180,8 @@ impl Collector {
         let shutdown_token = CancellationToken::new();
 
         tokio::spawn(Collector::collection(
-            instance_token.clone(),
+            Arc::clone(&provider),
+            instance_token,
             shutdown_token.clone(),
             consumer,
             state.clone(),
@@ -170,41 +201,85 @@ impl Collector {
         RegionSink {
             producer: self.producer.clone(),
             state: self.state.clone(),
+            pending: None,
+            dirty: false,
         }
     }
 
     async fn collection(
+        provider: Arc<Provider>,
         instance_token: CancellationToken,
         shutdown_token: CancellationToken,
         mut receiver: mpsc::Receiver<IndexedSubChunk>,
         state: FlushState,
         collector_size: usize,
     ) {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // Subchunks that could not be written to disk and are retried by the next flush.
+        let mut failed = Vec::new();
         loop {
             tokio::select! {
                 _ = state.flushing() => {
-                    // Empty channel and collect all changes.
-                    let collected = Collector::collect(&mut receiver, collector_size);
-
-                    // Resume normal sink operations.
-                    state.finish();
-
-                    Collector::flush(collected);
+                    Collector::flush_requested(&provider, &mut receiver, &state, &mut failed, collector_size).await;
                 },
-                _ = instance_token.cancelled() => {
-                    shutdown_token.cancel();
-                    break
-                }
+                _ = interval.tick() => {
+                    // Periodically write changes to disk, even if no sink has requested it.
+                    if !receiver.is_empty() || !failed.is_empty() {
+                        state.flush();
+                    }
+                },
+                _ = instance_token.cancelled() => break
             }
         }
 
         // Final flush before closing to prevent data loss
-        let collected = Collector::collect(&mut receiver, collector_size);
-        Collector::flush(collected);
+        state.flush();
+        Collector::flush_requested(&provider, &mut receiver, &state, &mut failed, collector_size).await;
+        if !failed.is_empty() {
+            tracing::error!("Lost {} subchunks that could not be written to disk", failed.len());
+        }
 
+        shutdown_token.cancel();
         tracing::info!("Level sink closed");
     }
 
+    /// Writes all currently buffered subchunks to disk and completes the flush requests
+    /// that were made before collection started.
+    ///
+    /// Subchunks that failed to be written by an earlier flush are written first, so that newer versions take precedence.
+    /// If writing fails, it is retried once. If that also fails, the requests are completed with an error
+    /// and the subchunks are kept in `failed` for the next flush.
+    async fn flush_requested(
+        provider: &Arc<Provider>,
+        receiver: &mut mpsc::Receiver<IndexedSubChunk>,
+        state: &FlushState,
+        failed: &mut Vec<IndexedSubChunk>,
+        collector_size: usize,
+    ) {
+        // Any request made after this point might not be included in this flush
+        // and will be handled by the next one.
+        let ticket = state.requested();
+        let mut collected = std::mem::take(failed);
+        collected.extend(Collector::collect(receiver, collector_size));
+
+        let (collected, result) = Collector::flush(Arc::clone(provider), collected).await;
+        let Err(err) = result else {
+            state.finish(ticket, true);
+            return;
+        };
+
+        tracing::warn!("Failed to flush level sink, retrying: {err:#}");
+        let (collected, result) = Collector::flush(Arc::clone(provider), collected).await;
+        if let Err(err) = &result {
+            tracing::error!("Failed to flush level sink, keeping {} subchunks for the next flush: {err:#}", collected.len());
+            *failed = collected;
+        }
+
+        state.finish(ticket, result.is_ok());
+    }
+
     #[inline]
     fn collect(receiver: &mut mpsc::Receiver<IndexedSubChunk>, collector_size: usize) -> Vec<IndexedSubChunk> {
         let mut buffered = Vec::with_capacity(collector_size);
@@ -215,10 +290,58 @@ impl Collector {
         buffered
     }
 
-    fn flush(data: Vec<IndexedSubChunk>) {
-        rayon::spawn(|| {
-            data.into_par_iter().for_each(|chunk| {});
-        });
+    /// Serializes the given subchunks and commits them to the database in a single batch.
+    ///
+    /// The subchunks are handed back so that they can be retried if writing failed.
+    async fn flush(provider: Arc<Provider>, data: Vec<IndexedSubChunk>) -> (Vec<IndexedSubChunk>, anyhow::Result<()>) {
+        if data.is_empty() {
+            return (data, Ok(()));
+        }
+
+        let data = Arc::new(data);
+        let result = tokio::task::spawn_blocking({
+            let data = Arc::clone(&data);
+            move || Collector::write(&provider, &data)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+
+        // The blocking task has finished, so this is normally the only reference left.
+        let data = Arc::try_unwrap(data).unwrap_or_else(|data| data.as_ref().clone());
+        (data, result)
+    }
+
+    /// Writes the given subchunks to the database.
+    fn write(provider: &Provider, data: &[IndexedSubChunk]) -> anyhow::Result<()> {
+        let count = data.len();
+
+        // Serialization is done in parallel, only the database write itself is sequential.
+        let serialized = data
+            .into_par_iter()
+            .map(|chunk| {
+                let coord: Vector<i32, 3> = chunk.index.into();
+                let data = chunk.data.serialize_disk()?;
+
+                Ok((coord, chunk.dimension, chunk.data.index(), data))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut batch = Provider::batch();
+        for (coord, dimension, index, data) in serialized {
+            let key = level::DataKey {
+                coordinates: Vector::from([coord.x, coord.z]),
+                dimension,
+                data: level::KeyType::SubChunk { index },
+            };
+
+            batch.put_key(&key, data)?;
+        }
+
+        provider.execute(&batch)?;
+        tracing::debug!("Wrote {count} subchunks to disk");
+
+        Ok(())
     }
 }
 
@@ -232,45 +355,71 @@ impl Joinable for Collector {
 /// All unreferenced subchunks are thrown into this sink
 /// and will automatically be written to disk at a fixed interval or
 /// when the sink is filled up.
+///
+/// When the collector is full, [`poll_ready`](Sink::poll_ready) waits until it has been flushed.
+/// [`poll_flush`](Sink::poll_flush) only resolves once all data sent through this sink has been written to disk.
 pub struct RegionSink {
     producer: mpsc::Sender<IndexedSubChunk>,
     state: FlushState,
+    /// Ticket of the flush this sink is currently waiting on.
+    pending: Option<u64>,
+    /// Whether data has been sent since the last flush request.
+    dirty: bool,
+}
+
+impl RegionSink {
+    /// Requests a flush if this sink is not already waiting on one and polls its completion.
+    fn poll_pending(&mut self, cx: &mut Context) -> Poll<anyhow::Result<()>> {
+        let ticket = *self.pending.get_or_insert_with(|| {
+            self.dirty = false;
+            self.state.flush()
+        });
+
+        let poll = self.state.poll_flushed(ticket, cx);
+        if poll.is_ready() {
+            self.pending = None;
+        }
+
+        poll
+    }
 }
 
 impl Sink<IndexedSubChunk> for RegionSink {
     type Error = anyhow::Error;
 
-    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<anyhow::Result<()>> {
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<anyhow::Result<()>> {
+        let this = self.get_mut();
+
         // Check whether collector has space.
-        // If not, notify it to flush.
-        if self.producer.capacity() == 0 {
-            self.state.flush();
+        // If not, notify it to flush and wait until it has been emptied.
+        while this.producer.capacity() == 0 {
+            if this.producer.is_closed() {
+                return Poll::Ready(Err(anyhow::anyhow!("Level collector has shut down")));
+            }
 
-            // SAFETY: This is safe because the state objects it not moved while this pin is used.
-            let pin = unsafe { Pin::new_unchecked(&mut self.state) };
-            return pin.poll(cx).map(|_| Ok(()));
+            std::task::ready!(this.poll_pending(cx))?;
         }
 
         Poll::Ready(Ok(()))
     }
 
     fn start_send(self: Pin<&mut Self>, item: IndexedSubChunk) -> anyhow::Result<()> {
-        println!("Send");
-        self.producer.try_send(item)?;
+        let this = self.get_mut();
+
+        this.producer.try_send(item)?;
+        this.dirty = true;
+
         Ok(())
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<anyhow::Result<()>> {
-        if self.producer.capacity() == self.producer.max_capacity() {
-            // Don't flush when the collector is empty.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<anyhow::Result<()>> {
+        let this = self.get_mut();
+        if this.pending.is_none() && !this.dirty {
+            // Nothing has been sent since the last flush.
             return Poll::Ready(Ok(()));
         }
 
-        self.state.flush();
-
-        // SAFETY: The state object is not moved while this pin is active.
-        let pin = unsafe { Pin::new_unchecked(&mut self.state) };
-        pin.poll(cx).map(|_| Ok(()))
+        this.poll_pending(cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<anyhow::Result<()>> {
@@ -5,6 +5,7 @@ use std::{
 
 use futures::Stream;
 use level::SubChunk;
+use proto::types::Dimension;
 use tokio::sync::mpsc;
 use util::Vector;
 
@@ -13,20 +14,46 @@ use util::Vector;
 /// First 6 bits are the vertical index,
 /// then 29 bits for the x-coordinate
 /// and 29 bits for the z-coordinate.
+///
+/// All components are stored in two's complement so that subchunks below zero
+/// and chunks with negative coordinates can be indexed as well.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct RegionIndex(u64);
 
+impl RegionIndex {
+    /// Amount of bits used to store the vertical index.
+    const Y_BITS: u32 = 6;
+    /// Amount of bits used to store the X and Z coordinates.
+    const XZ_BITS: u32 = 29;
+    /// Mask of the vertical index.
+    const Y_MASK: u64 = (1 << Self::Y_BITS) - 1;
+    /// Mask of the X and Z coordinates.
+    const XZ_MASK: u64 = (1 << Self::XZ_BITS) - 1;
+
+    /// Whether the given value fits into a signed integer of `bits` bits.
+    #[inline]
+    const fn fits(value: i32, bits: u32) -> bool {
+        let bound = 1 << (bits - 1);
+        value >= -bound && value < bound
+    }
+
+    /// Sign-extends the lowest `bits` bits of `value`.
+    #[inline]
+    const fn sign_extend(value: u64, bits: u32) -> i32 {
+        let shift = 64 - bits;
+        ((value << shift) as i64 >> shift) as i32
+    }
+}
+
 impl From<Vector<i32, 3>> for RegionIndex {
     fn from(value: Vector<i32, 3>) -> Self {
-        const XZ_MASK: u64 = 2u64.pow(29) - 1;
+        assert!(RegionIndex::fits(value.y, RegionIndex::Y_BITS), "Region Y-coordinate out of range");
+        assert!(RegionIndex::fits(value.x, RegionIndex::XZ_BITS), "Region X-coordinate out of range");
+        assert!(RegionIndex::fits(value.z, RegionIndex::XZ_BITS), "Region Z-coordinate out of range");
 
-        assert!((value.y as u64) < 63, "Region Y-coordinate too large");
-        assert!((value.x as u64) < XZ_MASK, "Region X-coordinate too large");
-        assert!((value.z as u64) < XZ_MASK, "Region Z-coordinate too large");
-
-        let mut index = (value.y as u64) << 58;
-        index |= ((value.x as u64) & XZ_MASK) << 29;
-        index |= (value.z as u64) & XZ_MASK;
+        let mut index = (value.y as u64 & RegionIndex::Y_MASK) << (2 * RegionIndex::XZ_BITS);
+        index |= (value.x as u64 & RegionIndex::XZ_MASK) << RegionIndex::XZ_BITS;
+        index |= value.z as u64 & RegionIndex::XZ_MASK;
 
         RegionIndex(index)
     }
@@ -34,22 +61,22 @@ impl From<Vector<i32, 3>> for RegionIndex {
 
 impl From<RegionIndex> for Vector<i32, 3> {
     fn from(value: RegionIndex) -> Self {
-        const XZ_MASK: u64 = 2u64.pow(29) - 1;
-
         let index = value.0;
-        let y = (index >> 58) as i32;
-        let x = ((index >> 29) & XZ_MASK) as i32;
-        let z = (index & XZ_MASK) as i32;
+        let y = RegionIndex::sign_extend(index >> (2 * RegionIndex::XZ_BITS), RegionIndex::Y_BITS);
+        let x = RegionIndex::sign_extend(index >> RegionIndex::XZ_BITS, RegionIndex::XZ_BITS);
+        let z = RegionIndex::sign_extend(index, RegionIndex::XZ_BITS);
 
         Vector::from([x, y, z])
     }
 }
 
 /// A subchunk with an added index into its owning region.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IndexedSubChunk {
     /// The region index.
     pub index: RegionIndex,
+    /// The dimension the subchunk is located in.
+    pub dimension: Dimension,
     /// The subchunk data.
     pub data: SubChunk,
 }
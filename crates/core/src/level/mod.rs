@@ -1,5 +1,6 @@
 //! Implements basic Minecraft level functionality.
 
+pub mod breaking;
 pub mod io;
 pub mod net;
 pub mod rule;
@@ -1,48 +1,55 @@
 use std::ops::Range;
 
-use level::{BlockStates, SubChunk};
-use util::BinaryWrite;
+use level::SubChunk;
+use proto::types::Dimension;
 
 use crate::level::viewer::ChunkOffset;
 
+/// A set of subchunks in the same chunk column that are being sent to a client.
 pub struct ChunkColumn {
+    /// The loaded subchunks together with their offset in the request.
+    /// Subchunks that do not exist on disk are `None`.
     pub subchunks: Vec<(ChunkOffset, Option<SubChunk>)>,
+    /// Vertical range of the column in block coordinates.
     pub range: Range<i16>,
     heightmap: Box<[[i16; 16]; 16]>,
 }
 
 impl ChunkColumn {
-    pub fn empty() -> ChunkColumn {
+    /// Creates an empty column in the given dimension.
+    pub fn empty(dimension: Dimension) -> ChunkColumn {
         ChunkColumn {
             subchunks: Vec::new(),
-            range: 0..0,
+            range: Self::dimension_range(dimension),
             heightmap: Box::new([[0; 16]; 16]),
         }
     }
 
-    pub fn heightmap(&self) -> &Box<[[i16; 16]; 16]> {
+    /// The vertical range in block coordinates of the given dimension.
+    pub const fn dimension_range(dimension: Dimension) -> Range<i16> {
+        match dimension {
+            Dimension::Overworld => -64..320,
+            Dimension::Nether => 0..128,
+            Dimension::End => 0..256,
+        }
+    }
+
+    /// Returns the heightmap of this column.
+    ///
+    /// Every value is the Y-coordinate of the highest non-air block in that column.
+    /// If a column only contains air, the value is one block below the bottom of the column.
+    pub fn heightmap(&self) -> &[[i16; 16]; 16] {
         &self.heightmap
     }
 
+    /// Computes the heightmap of this column using the loaded subchunks.
     pub fn generate_heightmap(&mut self) {
-        for x in 0..16 {
-            for z in 0..16 {
-                let mut top_found = false;
-
-                for sub in self.subchunks.iter().rev().filter_map(|(_, sub)| sub.as_ref()) {
-                    if top_found {
-                        break;
-                    }
-
-                    for y in (0..16).rev() {
-                        let block = sub.layer(0).unwrap().get((x, y, z));
-                        dbg!(block);
-                    }
-                }
+        let highest = level::highest_blocks(self.subchunks.iter().filter_map(|(_, sub)| sub.as_ref()));
+        for (heights, tops) in self.heightmap.iter_mut().zip(highest.iter()) {
+            for (height, top) in heights.iter_mut().zip(tops) {
+                *height = top.unwrap_or(self.range.start - 1);
             }
         }
-
-        // todo!()
     }
 
     /// Converts a vertical coordinate to a subchunk index in this column.
@@ -50,22 +57,8 @@ impl ChunkColumn {
         ((y - self.range.start) / 16) as u16
     }
 
+    /// Converts a subchunk index in this column to the vertical coordinate of its lowest block.
     pub fn index_to_y(&self, index: u16) -> i16 {
         (index * 16) as i16 + self.range.start
     }
-
-    fn serialize_network_in<W>(&self, states: &BlockStates, mut writer: W) -> anyhow::Result<()>
-    where
-        W: BinaryWrite,
-    {
-        // let entries = Vec::with_capacity(self.subchunks.len());
-        // for subchunk in &self.subchunks {
-        //     if let Some(subchunk) = subchunk {
-        //     } else {
-        //         entries.push(SubChunkEntry { result: SubChunkResult::NotFound });
-        //     }
-        // }
-
-        todo!()
-    }
 }
@@ -2,9 +2,14 @@ use proto::bedrock::HeightmapType;
 
 use super::column::ChunkColumn;
 
+/// Heightmap of a single subchunk as sent in a [`SubChunkResponse`](proto::bedrock::SubChunkResponse).
 #[derive(Debug, Clone)]
 pub struct Heightmap {
+    /// Height of the highest block in every column, relative to the bottom of this subchunk.
+    ///
+    /// This is `None` unless `map_type` is [`HeightmapType::WithData`].
     pub data: Option<Box<[i8; 256]>>,
+    /// Type of the heightmap.
     pub map_type: HeightmapType,
 }
 
@@ -14,46 +19,46 @@ impl Heightmap {
         let mut heightmap = Box::new([0; 256]);
 
         // Whether at least one of the columns has a topmost block that lies below this subchunk.
-        let mut above_top = false;
+        let mut lower = false;
         // Whether at least one of the columns has a topmost block that lies above this subchunk.
-        let mut below_top = false;
+        let mut higher = false;
 
+        let bottom = chunk_column.index_to_y(subchunk_idx);
         for x in 0..16 {
             for z in 0..16 {
                 // Index of coordinate in current subchunk.
                 let block_idx = ((z as u16) << 4 | (x as u16)) as usize;
                 // Y-coordinate of highest block in column.
                 let y = chunk_column.heightmap()[x][z];
-                // Index of subchunk that the highest block is located in.
-                let other_idx = chunk_column.y_to_index(y);
 
-                if subchunk_idx > other_idx {
+                if y < bottom {
                     // Topmost block is located below current subchunk.
                     heightmap[block_idx] = -1;
-                    above_top = true;
-                } else if subchunk_idx < other_idx {
+                    lower = true;
+                } else if y >= bottom + 16 {
                     // Topmost block is located above current subchunk.
                     heightmap[block_idx] = 16;
-                    below_top = true;
+                    higher = true;
                 } else {
                     // Topmost block is located in current subchunk.
-                    heightmap[block_idx] = (y - chunk_column.index_to_y(other_idx)) as i8;
-                    above_top = true;
-                    below_top = true;
+                    heightmap[block_idx] = (y - bottom) as i8;
+                    lower = true;
+                    higher = true;
                 }
             }
         }
 
-        let mut map_type = HeightmapType::WithData;
-        if !above_top {
+        let map_type = if lower && higher {
+            HeightmapType::WithData
+        } else if higher {
             // All topmost blocks in this chunk column are located above this subchunk,
             // there is no point in sending heightmap data.
-            map_type = HeightmapType::TooLow;
-        } else if !below_top {
+            HeightmapType::TooHigh
+        } else {
             // All topmost blocks in this chunk column are located below this subchunk,
             // there is no point in sending heightmap data.
-            map_type = HeightmapType::TooHigh;
-        }
+            HeightmapType::TooLow
+        };
 
         Heightmap {
             map_type,
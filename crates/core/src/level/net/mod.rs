@@ -1,4 +1,3 @@
-mod ser;
-
 pub mod column;
 pub mod heightmap;
+pub mod ser;
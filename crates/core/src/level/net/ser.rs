@@ -1,6 +1,7 @@
-use level::{BlockStates, SubChunk, SubChunkVersion, SubStorage};
+use level::{BlockStates, PaletteEntry, SubChunk, SubChunkVersion, SubStorage};
 use util::{BinaryWrite, RVec};
 
+/// Serializes level data into the format used by the network protocol.
 pub trait NetworkChunkExt {
     /// Serialises the sub chunk into a new buffer and returns it in network format.
     fn serialize_network(&self, states: &BlockStates) -> anyhow::Result<RVec> {
@@ -9,6 +10,7 @@ pub trait NetworkChunkExt {
         Ok(buffer)
     }
 
+    /// Serialises the sub chunk into the given writer in network format.
     fn serialize_network_in<W>(&self, states: &BlockStates, writer: W) -> anyhow::Result<()>
     where
         W: BinaryWrite;
@@ -19,39 +21,48 @@ impl NetworkChunkExt for SubStorage {
     where
         W: BinaryWrite,
     {
-        level::serialize_packed_array(&mut writer, &self.indices, self.palette.len(), true)?;
+        // Storages containing a single block type (or none at all, which means the storage is filled with air)
+        // are written with 0 bits per block, followed by just the runtime ID of that block.
+        if self.palette.len() <= 1 {
+            let runtime_id = self.palette.first().map_or(states.air(), |entry| runtime_id(states, entry));
 
-        if !self.palette.is_empty() {
-            writer.write_var_i32(self.palette.len() as i32)?;
-        }
+            writer.write_u8(1)?;
+            writer.write_var_i32(runtime_id as i32)?;
 
-        for entry in &self.palette {
-            // Obtain block runtime ID of palette entry.
-            let runtime_id = states.state(entry).unwrap_or(states.air());
-            tracing::debug!("{}: {runtime_id}", entry.name);
+            return Ok(());
+        }
 
-            writer.write_var_i32(runtime_id as i32)?;
+        level::serialize_packed_array(&mut writer, &self.indices, self.palette.len(), true)?;
 
-            // https://github.com/df-mc/dragonfly/blob/master/server/world/chunk/paletted_storage.go#L35
-            // Requires new palette storage that only stores runtime IDs.
+        // In network format the palette only consists of runtime IDs.
+        // https://github.com/df-mc/dragonfly/blob/master/server/world/chunk/paletted_storage.go#L35
+        writer.write_var_i32(self.palette.len() as i32)?;
+        for entry in &self.palette {
+            writer.write_var_i32(runtime_id(states, entry) as i32)?;
         }
 
         Ok(())
     }
 }
 
+/// Obtains the runtime ID of a palette entry, falling back to air for unknown blocks.
+#[inline]
+fn runtime_id(states: &BlockStates, entry: &PaletteEntry) -> u32 {
+    states.state(entry).unwrap_or_else(|| {
+        tracing::warn!("Unknown block state {}, replacing it with air", entry.name);
+        states.air()
+    })
+}
+
 impl NetworkChunkExt for SubChunk {
-    /// Serialises the sub chunk into the given writer in network format.
     fn serialize_network_in<W>(&self, states: &BlockStates, mut writer: W) -> anyhow::Result<()>
     where
         W: BinaryWrite,
     {
-        writer.write_u8(self.version as u8)?;
+        // The client only accepts limitless subchunks, older versions are upgraded while serializing.
+        writer.write_u8(SubChunkVersion::Limitless as u8)?;
         writer.write_u8(self.layers.len() as u8)?;
-
-        if self.version == SubChunkVersion::Limitless {
-            writer.write_i8(self.index)?;
-        }
+        writer.write_i8(self.index)?;
 
         for layer in &self.layers {
             layer.serialize_network_in(states, &mut writer)?;
@@ -2,12 +2,18 @@ use super::io::sink::RegionSink;
 use super::io::stream::{IndexedSubChunk, RegionIndex};
 use std::{
     any::TypeId,
+    collections::HashSet,
     sync::{Arc, OnceLock, Weak},
 };
 
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
-use level::{provider::Provider, SubChunk};
+use futures::SinkExt;
+use level::{provider::Provider, Biomes, LevelDat, LevelSettings, PaletteEntry, PlayerSave, SubChunk};
+use parking_lot::{RwLock, RwLockReadGuard};
+use proto::bedrock::GameRule;
 use proto::types::Dimension;
+use proto::uuid::Uuid;
 use rayon::iter::ParallelIterator;
 use tokio::sync::mpsc::{self, error::SendError};
 use tokio_util::sync::CancellationToken;
@@ -15,9 +21,11 @@ use util::{Joinable, Vector};
 
 use crate::instance::Instance;
 
+use super::net::column::ChunkColumn;
+
 use super::{
     io::{region::Region, sink::Collector, stream::RegionStream},
-    rule::{Rule, RuleValue},
+    rule::*,
 };
 
 pub struct ServiceOptions {
@@ -25,6 +33,9 @@ pub struct ServiceOptions {
     pub level_path: String,
 }
 
+/// Identifies a subchunk in a specific dimension.
+type SubChunkKey = (RegionIndex, Dimension);
+
 /// Threshold for the service to switch from singular to batching mode.
 /// Any requests with more chunks than specified in this threshold will be processed
 /// with a parallel iterator and threadpool.
@@ -43,23 +54,46 @@ pub struct Service {
     pub(super) provider: Arc<level::provider::Provider>,
     /// Collects subchunk changes using sinks and writes them to disk periodically.
     collector: Collector,
+    /// Subchunks that have been modified since startup.
+    /// These take precedence over the data on disk.
+    modified: Arc<DashMap<SubChunkKey, SubChunk>>,
+    /// Queues modified subchunks to be written back to disk.
+    writeback: mpsc::UnboundedSender<SubChunkKey>,
     /// Current gamerule values.
     /// The gamerules are stored by TypeId to allow for user-defined gamerules.
     gamerules: DashMap<TypeId, RuleValue>,
+    /// Contents of the `level.dat` file, including the fields that are not part of the settings.
+    level_dat: RwLock<LevelDat>,
+    /// Settings of the world.
+    settings: RwLock<LevelSettings>,
 }
 
 impl Service {
     pub(crate) fn new(options: ServiceOptions) -> anyhow::Result<Arc<Service>> {
         let provider = Arc::new(level::provider::Provider::open(&options.level_path)?);
+        let level_dat = provider.level_dat()?;
+        let settings = level_dat.settings()?;
+
+        let collector = Collector::new(Arc::clone(&provider), options.instance_token.clone(), 100);
+
+        let modified = Arc::new(DashMap::new());
+        let (writeback, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(Service::write_back(Arc::clone(&provider), Arc::clone(&modified), collector.create_sink(), receiver));
 
         let service = Arc::new(Service {
-            collector: Collector::new(Arc::clone(&provider), options.instance_token.clone(), 100),
+            collector,
+            modified,
+            writeback,
             instance_token: options.instance_token,
             shutdown_token: CancellationToken::new(),
             instance: OnceLock::new(),
             provider,
             gamerules: DashMap::new(),
+            level_dat: RwLock::new(level_dat),
+            settings: RwLock::new(settings),
         });
+        service.load_gamerules();
+
         Ok(service)
     }
 
@@ -70,6 +104,13 @@ impl Service {
             .map_err(|_| anyhow::anyhow!("Level service instance was already set"))
     }
 
+    /// Returns the instance that owns this service.
+    pub(crate) fn instance(&self) -> Arc<Instance> {
+        // This will not panic because the instance field is initialised before any clients can connect.
+        #[allow(clippy::unwrap_used)]
+        self.instance.get().unwrap().upgrade().unwrap()
+    }
+
     /// Requests chunks using the specified region iterator.
     pub fn region<R: Region>(self: &Arc<Service>, region: R) -> RegionStream
     where
@@ -87,6 +128,160 @@ impl Service {
         self.collector.create_sink()
     }
 
+    /// Loads a single subchunk, taking any modifications made since startup into account.
+    ///
+    /// The position is given in subchunk coordinates.
+    pub fn subchunk(&self, position: Vector<i32, 3>, dimension: Dimension) -> anyhow::Result<Option<SubChunk>> {
+        if let Some(subchunk) = self.modified.get(&(RegionIndex::from(position.clone()), dimension)) {
+            return Ok(Some(subchunk.value().clone()));
+        }
+
+        self.provider.subchunk(position, dimension)
+    }
+
+    /// Loads the biomes of the chunk at the given chunk coordinates.
+    #[inline]
+    pub fn biomes(&self, coordinates: Vector<i32, 2>, dimension: Dimension) -> anyhow::Result<Option<Biomes>> {
+        self.provider.biomes(coordinates, dimension)
+    }
+
+    /// Returns the block at the given position, or `None` if the subchunk it is in does not exist.
+    pub fn block(&self, position: &Vector<i32, 3>, dimension: Dimension) -> anyhow::Result<Option<PaletteEntry>> {
+        if !Self::within_height(position, dimension) {
+            return Ok(None);
+        }
+
+        let (index, local) = Self::split_position(position);
+
+        let block = |subchunk: &SubChunk| subchunk.layer(0).and_then(|layer| layer.get(local.clone())).cloned();
+        if let Some(subchunk) = self.modified.get(&(RegionIndex::from(index.clone()), dimension)) {
+            return Ok(block(subchunk.value()));
+        }
+
+        Ok(self.provider.subchunk(index, dimension)?.and_then(|subchunk| block(&subchunk)))
+    }
+
+    /// Replaces the block at the given position.
+    ///
+    /// The change is immediately visible through [`subchunk`](Self::subchunk) and [`block`](Self::block)
+    /// and is written to disk by the region sink.
+    pub fn set_block(&self, position: &Vector<i32, 3>, dimension: Dimension, block: &PaletteEntry) -> anyhow::Result<()> {
+        if !Self::within_height(position, dimension) {
+            anyhow::bail!("Block height {} is outside of the height range of {dimension:?}", position.y);
+        }
+
+        let (index, local) = Self::split_position(position);
+        let key = (RegionIndex::from(index.clone()), dimension);
+        let y = i8::try_from(index.y)?;
+
+        let mut subchunk = match self.modified.entry(key) {
+            Entry::Occupied(entry) => entry.into_ref(),
+            Entry::Vacant(entry) => {
+                let subchunk = self.provider.subchunk(index, dimension)?.unwrap_or_else(|| SubChunk::empty(y));
+                entry.insert(subchunk)
+            }
+        };
+
+        subchunk.set(0, local, block.clone());
+        drop(subchunk);
+
+        self.writeback.send(key).map_err(|_| anyhow::anyhow!("Level write-back task has shut down"))
+    }
+
+    /// Loads the data that was saved when the player with the given UUID last left the server.
+    pub fn player(&self, uuid: &Uuid) -> anyhow::Result<Option<PlayerSave>> {
+        self.provider.player(uuid)
+    }
+
+    /// Saves the data of the player with the given UUID so that it can be restored when they rejoin.
+    pub fn save_player(&self, uuid: &Uuid, save: &PlayerSave) -> anyhow::Result<()> {
+        self.provider.save_player(uuid, save)
+    }
+
+    /// Whether the given block position lies within the height range of the dimension.
+    fn within_height(position: &Vector<i32, 3>, dimension: Dimension) -> bool {
+        let range = ChunkColumn::dimension_range(dimension);
+        (i32::from(range.start)..i32::from(range.end)).contains(&position.y)
+    }
+
+    /// Splits a block position into the position of its subchunk and the position within that subchunk.
+    fn split_position(position: &Vector<i32, 3>) -> (Vector<i32, 3>, Vector<u8, 3>) {
+        let index = Vector::from([position.x >> 4, position.y >> 4, position.z >> 4]);
+        let local = Vector::from([(position.x & 0xf) as u8, (position.y & 0xf) as u8, (position.z & 0xf) as u8]);
+
+        (index, local)
+    }
+
+    /// Sends the latest version of every modified subchunk into the region sink.
+    ///
+    /// Subchunks are read when they are written rather than when they are queued,
+    /// so that an older version can never overwrite a newer one.
+    ///
+    /// Once no more changes are queued, the sink is flushed, the heightmaps of the changed chunks are updated
+    /// and the subchunks that are now on disk are removed from the modified map, unless they were changed again in the meantime.
+    async fn write_back(
+        provider: Arc<Provider>,
+        modified: Arc<DashMap<SubChunkKey, SubChunk>>,
+        mut sink: RegionSink,
+        mut receiver: mpsc::UnboundedReceiver<SubChunkKey>,
+    ) {
+        // Subchunks that have been fed into the sink but have not been flushed yet.
+        let mut written = Vec::new();
+        while let Some(key) = receiver.recv().await {
+            if let Some(data) = modified.get(&key).map(|entry| entry.value().clone()) {
+                let mut compacted = data.clone();
+                compacted.compact();
+
+                let indexed = IndexedSubChunk { index: key.0, dimension: key.1, data: compacted };
+                match sink.feed(indexed).await {
+                    Ok(()) => written.push((key, data)),
+                    Err(err) => tracing::error!("Failed to write back modified subchunk: {err:#}"),
+                }
+            }
+
+            if !receiver.is_empty() || written.is_empty() {
+                continue;
+            }
+
+            if let Err(err) = sink.flush().await {
+                // The subchunks stay in the modified map so that the changes are not lost while the server is running.
+                tracing::error!("Failed to flush modified subchunks: {err:#}");
+                written.clear();
+                continue;
+            }
+
+            let mut chunks = HashSet::new();
+            for (key, data) in std::mem::take(&mut written) {
+                let index = Vector::from(key.0);
+                chunks.insert((Vector::from([index.x, index.z]), key.1));
+
+                modified.remove_if(&key, |_, current| *current == data);
+            }
+
+            for (coordinates, dimension) in chunks {
+                if let Err(err) = Self::update_heightmap(&provider, coordinates.clone(), dimension) {
+                    tracing::error!("Failed to update heightmap of chunk {coordinates:?}: {err:#}");
+                }
+            }
+        }
+    }
+
+    /// Recomputes the heightmap that is stored together with the biomes of a chunk from the blocks on disk.
+    fn update_heightmap(provider: &Provider, coordinates: Vector<i32, 2>, dimension: Dimension) -> anyhow::Result<()> {
+        let range = ChunkColumn::dimension_range(dimension);
+
+        let mut subchunks = Vec::new();
+        for y in (range.start >> 4)..(range.end >> 4) {
+            if let Some(subchunk) = provider.subchunk([coordinates.x, i32::from(y), coordinates.y], dimension)? {
+                subchunks.push(subchunk);
+            }
+        }
+
+        let mut biomes = provider.biomes(coordinates.clone(), dimension)?.unwrap_or_else(|| Biomes::uniform(level::PLAINS));
+        biomes.generate_heightmap(&subchunks, range.start);
+        provider.set_biomes(coordinates, dimension, &biomes)
+    }
+
     /// Loads a region using a sequential iterator.
     ///
     /// This function is used for smaller regions that do not benefit from
@@ -158,6 +353,7 @@ impl Service {
 
         IndexedSubChunk {
             index: RegionIndex::from(item),
+            dimension,
             data: subchunk,
         }
     }
@@ -184,6 +380,146 @@ impl Service {
         old.into()
     }
 
+    /// The settings of the world as loaded from `level.dat`.
+    ///
+    /// Gamerules are kept up to date by [`gamerule`](Self::gamerule) instead.
+    pub fn settings(&self) -> RwLockReadGuard<'_, LevelSettings> {
+        self.settings.read()
+    }
+
+    /// The experiments that are enabled in this world.
+    pub fn experiments(&self) -> Vec<(String, bool)> {
+        self.level_dat.read().experiments().into_iter().map(|(name, enabled)| (name.to_owned(), enabled)).collect()
+    }
+
+    /// The values of all vanilla gamerules that are known to the client.
+    pub fn game_rules(&self) -> Vec<GameRule> {
+        vec![
+            GameRule::CommandBlocksEnabled(self.gamerule::<CommandBlocksEnabled>()),
+            GameRule::CommandBlockOutput(self.gamerule::<CommandBlockOutput>()),
+            GameRule::DaylightCycle(self.gamerule::<DaylightCycle>()),
+            GameRule::EntityDrops(self.gamerule::<EntityDrops>()),
+            GameRule::FireTick(self.gamerule::<FireTick>()),
+            GameRule::Insomnia(self.gamerule::<Insomnia>()),
+            GameRule::ImmediateRespawn(self.gamerule::<ImmediateRespawn>()),
+            GameRule::MobLoot(self.gamerule::<MobLoot>()),
+            GameRule::MobSpawning(self.gamerule::<MobSpawning>()),
+            GameRule::TileDrops(self.gamerule::<TileDrops>()),
+            GameRule::WeatherCycle(self.gamerule::<WeatherCycle>()),
+            GameRule::DrowningDamage(self.gamerule::<DrowningDamage>()),
+            GameRule::FallDamage(self.gamerule::<FallDamage>()),
+            GameRule::FireDamage(self.gamerule::<FireDamage>()),
+            GameRule::FreezeDamage(self.gamerule::<FreezeDamage>()),
+            GameRule::FunctionCommandLimit(self.gamerule::<FunctionCommandLimit>()),
+            GameRule::KeepInventory(self.gamerule::<KeepInventory>()),
+            GameRule::MaxCommandChainLength(self.gamerule::<MaxCommandChainLength>()),
+            GameRule::MobGriefing(self.gamerule::<MobGriefing>()),
+            GameRule::NaturalRegeneration(self.gamerule::<NaturalRegeneration>()),
+            GameRule::Pvp(self.gamerule::<Pvp>()),
+            GameRule::RandomTickSpeed(self.gamerule::<RandomTickSpeed>()),
+            GameRule::RespawnBlocksExplode(self.gamerule::<RespawnBlocksExplode>()),
+            GameRule::SendCommandFeedback(self.gamerule::<SendCommandFeedback>()),
+            GameRule::ShowBorderEffect(self.gamerule::<ShowBorderEffect>()),
+            GameRule::ShowCoordinates(self.gamerule::<ShowCoordinates>()),
+            GameRule::ShowDeathMessages(self.gamerule::<ShowDeathMessages>()),
+            GameRule::ShowTags(self.gamerule::<ShowTags>()),
+            GameRule::SpawnRadius(self.gamerule::<SpawnRadius>()),
+            GameRule::TntExplodes(self.gamerule::<TntExplodes>()),
+        ]
+    }
+
+    /// Writes the settings, world time and current gamerule values back to `level.dat`.
+    pub fn save_settings(&self) -> anyhow::Result<()> {
+        let mut settings = self.settings.write();
+        self.store_gamerules(&mut settings);
+
+        if let Some(instance) = self.instance.get().and_then(Weak::upgrade) {
+            settings.time = instance.ticker().time();
+            settings.current_tick = instance.ticker().current() as i64;
+        }
+
+        let mut level_dat = self.level_dat.write();
+        level_dat.set_settings(&settings)?;
+
+        self.provider.save_level_dat(&level_dat)
+    }
+
+    /// Initialises the vanilla gamerules with the values stored in the settings.
+    fn load_gamerules(&self) {
+        let settings = self.settings.read();
+
+        self.set_gamerule::<CommandBlocksEnabled>(settings.command_blocks_enabled);
+        self.set_gamerule::<CommandBlockOutput>(settings.command_block_output);
+        self.set_gamerule::<DaylightCycle>(settings.daylight_lock);
+        self.set_gamerule::<EntityDrops>(settings.entity_drops);
+        self.set_gamerule::<FireTick>(settings.fire_tick);
+        self.set_gamerule::<Insomnia>(settings.insomnia);
+        self.set_gamerule::<ImmediateRespawn>(settings.immediate_respawn);
+        self.set_gamerule::<LimitedCrafting>(settings.limited_crafting);
+        self.set_gamerule::<MobLoot>(settings.mob_loot);
+        self.set_gamerule::<MobSpawning>(settings.mob_spawning);
+        self.set_gamerule::<TileDrops>(settings.tile_drops);
+        self.set_gamerule::<WeatherCycle>(settings.weather_cycle);
+        self.set_gamerule::<DrowningDamage>(settings.drowning_damage);
+        self.set_gamerule::<FallDamage>(settings.fall_damage);
+        self.set_gamerule::<FireDamage>(settings.fire_damage);
+        self.set_gamerule::<FreezeDamage>(settings.freeze_damage);
+        self.set_gamerule::<FunctionCommandLimit>(settings.function_command_limit);
+        self.set_gamerule::<KeepInventory>(settings.keep_inventory);
+        self.set_gamerule::<MaxCommandChainLength>(settings.max_command_chain_length);
+        self.set_gamerule::<MobGriefing>(settings.mob_griefing);
+        self.set_gamerule::<NaturalRegeneration>(settings.natural_regeneration);
+        self.set_gamerule::<PlayersSleepingPercentage>(settings.sleeping_percentage);
+        self.set_gamerule::<Pvp>(settings.pvp);
+        self.set_gamerule::<RandomTickSpeed>(settings.random_tick_speed);
+        self.set_gamerule::<RecipesUnlock>(settings.recipes_unlock);
+        self.set_gamerule::<RespawnBlocksExplode>(settings.respawn_blocks_explode);
+        self.set_gamerule::<SendCommandFeedback>(settings.send_command_feedback);
+        self.set_gamerule::<ShowBorderEffect>(settings.show_border_effect);
+        self.set_gamerule::<ShowCoordinates>(settings.show_coordinates);
+        self.set_gamerule::<ShowDeathMessages>(settings.show_death_messages);
+        self.set_gamerule::<ShowTags>(settings.show_tags);
+        self.set_gamerule::<SpawnRadius>(settings.spawn_radius);
+        self.set_gamerule::<TntExplodes>(settings.tnt_explodes);
+    }
+
+    /// Copies the current values of the vanilla gamerules into the settings.
+    fn store_gamerules(&self, settings: &mut LevelSettings) {
+        settings.command_blocks_enabled = self.gamerule::<CommandBlocksEnabled>();
+        settings.command_block_output = self.gamerule::<CommandBlockOutput>();
+        settings.daylight_lock = self.gamerule::<DaylightCycle>();
+        settings.entity_drops = self.gamerule::<EntityDrops>();
+        settings.fire_tick = self.gamerule::<FireTick>();
+        settings.insomnia = self.gamerule::<Insomnia>();
+        settings.immediate_respawn = self.gamerule::<ImmediateRespawn>();
+        settings.limited_crafting = self.gamerule::<LimitedCrafting>();
+        settings.mob_loot = self.gamerule::<MobLoot>();
+        settings.mob_spawning = self.gamerule::<MobSpawning>();
+        settings.tile_drops = self.gamerule::<TileDrops>();
+        settings.weather_cycle = self.gamerule::<WeatherCycle>();
+        settings.drowning_damage = self.gamerule::<DrowningDamage>();
+        settings.fall_damage = self.gamerule::<FallDamage>();
+        settings.fire_damage = self.gamerule::<FireDamage>();
+        settings.freeze_damage = self.gamerule::<FreezeDamage>();
+        settings.function_command_limit = self.gamerule::<FunctionCommandLimit>();
+        settings.keep_inventory = self.gamerule::<KeepInventory>();
+        settings.max_command_chain_length = self.gamerule::<MaxCommandChainLength>();
+        settings.mob_griefing = self.gamerule::<MobGriefing>();
+        settings.natural_regeneration = self.gamerule::<NaturalRegeneration>();
+        settings.sleeping_percentage = self.gamerule::<PlayersSleepingPercentage>();
+        settings.pvp = self.gamerule::<Pvp>();
+        settings.random_tick_speed = self.gamerule::<RandomTickSpeed>();
+        settings.recipes_unlock = self.gamerule::<RecipesUnlock>();
+        settings.respawn_blocks_explode = self.gamerule::<RespawnBlocksExplode>();
+        settings.send_command_feedback = self.gamerule::<SendCommandFeedback>();
+        settings.show_border_effect = self.gamerule::<ShowBorderEffect>();
+        settings.show_coordinates = self.gamerule::<ShowCoordinates>();
+        settings.show_death_messages = self.gamerule::<ShowDeathMessages>();
+        settings.show_tags = self.gamerule::<ShowTags>();
+        settings.spawn_radius = self.gamerule::<SpawnRadius>();
+        settings.tnt_explodes = self.gamerule::<TntExplodes>();
+    }
+
     /// Returns the value of the given gamerule.
     ///
     /// Instead of referring to the gamerules by name, I decided to use generics instead.
@@ -209,6 +545,7 @@ impl Service {
 impl Joinable for Service {
     async fn join(&self) -> anyhow::Result<()> {
         self.collector.join().await?;
+        self.save_settings()?;
 
         Ok(())
     }
@@ -1,25 +1,25 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{
         atomic::{AtomicI32, AtomicU16, Ordering},
         Arc,
     },
 };
 
-use futures::{future, StreamExt};
-use level::SubChunk;
+use level::{BlockStates, SubChunk};
 use nohash_hasher::BuildNoHashHasher;
+use parking_lot::Mutex;
 use proto::{
-    bedrock::{HeightmapType, SubChunkEntry, SubChunkResponse, SubChunkResult},
+    bedrock::{LevelChunk, SubChunkEntry, SubChunkRequestMode, SubChunkResponse, SubChunkResult},
     types::Dimension,
 };
-use util::Vector;
+use util::{BinaryWrite, RVec, Vector};
 
-use super::io::point::PointRegion;
-use super::io::r#box::BoxRegion;
 use super::net::column::ChunkColumn;
 use super::net::heightmap::Heightmap;
+use super::net::ser::NetworkChunkExt;
 use super::Service;
+use crate::net::BlobLedger;
 
 pub type ChunkOffset = Vector<i8, 3>;
 
@@ -30,29 +30,54 @@ pub struct Viewer {
     // The current position of this viewer in chunk coordinates.
     current_x: AtomicI32,
     current_z: AtomicI32,
+
+    /// Chunks that have been sent to this viewer and are still within its render distance.
+    sent: Mutex<HashSet<(i32, i32)>>,
 }
 
 impl Viewer {
-    pub const fn new(service: Arc<Service>) -> Viewer {
+    pub fn new(service: Arc<Service>) -> Viewer {
         Viewer {
             service,
             radius: AtomicU16::new(0),
             current_x: AtomicI32::new(0),
             current_z: AtomicI32::new(0),
+            sent: Mutex::new(HashSet::new()),
         }
     }
 
     /// Updates the position of this viewer.
-    pub fn update_position(&self, position: Vector<f32, 2>) {
+    ///
+    /// This returns whether the viewer has moved into a different chunk.
+    pub fn update_position(&self, position: Vector<f32, 2>) -> bool {
         // Transform player coordinates to chunk coordinates.
-        let chunk_x = (position.x / 16.0).ceil() as i32;
-        let chunk_z = (position.y / 16.0).ceil() as i32;
+        let chunk_x = (position.x / 16.0).floor() as i32;
+        let chunk_z = (position.y / 16.0).floor() as i32;
 
-        self.current_x.store(chunk_x, Ordering::Relaxed);
-        self.current_z.store(chunk_z, Ordering::Relaxed);
+        let previous_x = self.current_x.swap(chunk_x, Ordering::Relaxed);
+        let previous_z = self.current_z.swap(chunk_z, Ordering::Relaxed);
+        if previous_x == chunk_x && previous_z == chunk_z {
+            return false
+        }
 
         // Update view if required
         self.on_view_update();
+        true
+    }
+
+    /// Whether the given position is within the render distance of this viewer.
+    pub fn in_range(&self, position: Vector<f32, 2>) -> bool {
+        let dx = i64::from((position.x / 16.0).floor() as i32 - self.current_x.load(Ordering::Relaxed));
+        let dz = i64::from((position.y / 16.0).floor() as i32 - self.current_z.load(Ordering::Relaxed));
+        let radius = i64::from(self.radius());
+
+        dx * dx + dz * dz <= radius * radius
+    }
+
+    /// The render distance of this viewer in chunks.
+    #[inline]
+    pub fn radius(&self) -> u16 {
+        self.radius.load(Ordering::Relaxed)
     }
 
     /// Updates the render distance of this viewer
@@ -62,39 +87,179 @@ impl Viewer {
         self.on_view_update();
     }
 
-    fn create_entry(&self, base: Vector<i32, 3>, offset: ChunkOffset, full_chunk: &ChunkColumn) -> anyhow::Result<SubChunkEntry> {
-        let absolute_y = base.y + offset.y as i32;
-        let subchunk_index = full_chunk.y_to_index(absolute_y as i16);
+    /// Returns the chunks within render distance that have not been sent to this viewer yet,
+    /// ordered from closest to furthest.
+    ///
+    /// The returned chunks are marked as sent. Chunks that are now out of range are forgotten
+    /// so that they will be sent again when the viewer returns.
+    pub fn unsent_chunks(&self) -> Vec<Vector<i32, 2>> {
+        let center_x = self.current_x.load(Ordering::Relaxed);
+        let center_z = self.current_z.load(Ordering::Relaxed);
+        let radius = i32::from(self.radius());
+
+        let mut sent = self.sent.lock();
+        sent.retain(|&(x, z)| {
+            let (dx, dz) = (x - center_x, z - center_z);
+            dx * dx + dz * dz <= radius * radius
+        });
+
+        let mut unsent = Vec::new();
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                if dx * dx + dz * dz > radius * radius {
+                    continue
+                }
+
+                if sent.insert((center_x + dx, center_z + dz)) {
+                    unsent.push((dx, dz));
+                }
+            }
+        }
+
+        unsent.sort_unstable_by_key(|&(dx, dz)| dx * dx + dz * dz);
+        unsent.into_iter().map(|(dx, dz)| Vector::from([center_x + dx, center_z + dz])).collect()
+    }
+
+    /// Creates the [`LevelChunk`] packet for the given chunk.
+    ///
+    /// The packet only contains the biomes of the chunk, the client requests the subchunks
+    /// separately using [`SubChunkRequest`](proto::bedrock::SubChunkRequest)s.
+    ///
+    /// If a blob ledger is given, the biomes are sent as a blob and stored in the ledger until the client reports on it.
+    pub fn level_chunk(&self, coordinates: Vector<i32, 2>, dimension: Dimension, cache: Option<&BlobLedger>) -> anyhow::Result<LevelChunk> {
+        let range = ChunkColumn::dimension_range(dimension);
+        let count = (range.end - range.start) as usize / 16;
+
+        let biomes = self.service.biomes(coordinates.clone(), dimension)?.unwrap_or_else(|| level::Biomes::uniform(level::PLAINS));
+
+        let mut payload = RVec::alloc();
+        biomes.serialize_network(&mut payload, count)?;
+
+        let mut blob_hashes = None;
+        if let Some(cache) = cache {
+            match cache.insert(payload) {
+                Ok(hash) => {
+                    blob_hashes = Some(vec![hash]);
+                    payload = RVec::alloc();
+                }
+                Err(biomes) => payload = biomes,
+            }
+        }
 
-        let heightmap = Heightmap::new(subchunk_index, full_chunk);
-        let entry = SubChunkEntry {
+        // Border block count, these only exist in education edition.
+        payload.write_u8(0)?;
+
+        Ok(LevelChunk {
+            coordinates,
+            dimension,
+            request_mode: SubChunkRequestMode::Limitless,
+            highest_sub_chunk: 0,
+            sub_chunk_count: 0,
+            blob_hashes,
+            raw_payload: payload,
+        })
+    }
+
+    /// Creates the response entry of a single subchunk.
+    fn create_entry(
+        offset: ChunkOffset,
+        subchunk: Option<&SubChunk>,
+        column: &ChunkColumn,
+        states: &BlockStates,
+        cache: Option<&BlobLedger>,
+    ) -> anyhow::Result<SubChunkEntry> {
+        let Some(subchunk) = subchunk.filter(|sub| !Self::is_air(sub)) else {
+            return Ok(SubChunkEntry {
+                result: SubChunkResult::AllAir,
+                offset,
+                ..Default::default()
+            });
+        };
+
+        let heightmap = Heightmap::new(column.y_to_index(subchunk.index() as i16 * 16), column);
+        let mut payload = subchunk.serialize_network(states)?;
+        let mut blob_hash = 0;
+
+        if let Some(cache) = cache {
+            // The subchunk data itself is sent as a blob, the payload only contains block entities.
+            // Room for the blobs has been reserved by the caller, since the response cannot mix blobs and inline data.
+            blob_hash = cache.insert(payload).map_err(|_| anyhow::anyhow!("Blob cache is full"))?;
+            payload = RVec::alloc();
+        }
+
+        Ok(SubChunkEntry {
             result: SubChunkResult::Success,
             offset,
             heightmap_type: heightmap.map_type,
             heightmap: heightmap.data,
-            blob_hash: todo!(),
-            payload: todo!(),
-        };
+            blob_hash,
+            payload,
+        })
+    }
 
-        Ok(entry)
+    /// Whether the given subchunk consists entirely of air.
+    fn is_air(subchunk: &SubChunk) -> bool {
+        subchunk.layer(0).map_or(true, |layer| layer.palette().iter().all(|entry| entry.name == "minecraft:air"))
     }
 
-    pub fn load_offsets(&self, base: Vector<i32, 3>, offsets: &[ChunkOffset], dimension: Dimension) -> anyhow::Result<SubChunkResponse> {
+    /// Loads the subchunks at the given offsets and creates a response for the client.
+    ///
+    /// Subchunks are reported as [`NotFound`](SubChunkResult::NotFound) if the chunk they are in
+    /// does not exist and as [`AllAir`](SubChunkResult::AllAir) if they do not contain any blocks.
+    ///
+    /// If a blob ledger is given, subchunk data is not included in the response and referred to by
+    /// its blob hash instead. The data is then stored in the ledger until the client reports on it.
+    pub fn load_offsets(
+        &self,
+        base: Vector<i32, 3>,
+        offsets: &[ChunkOffset],
+        dimension: Dimension,
+        cache: Option<&BlobLedger>,
+    ) -> anyhow::Result<SubChunkResponse> {
+        let instance = self.service.instance();
+        let range = ChunkColumn::dimension_range(dimension);
+
+        // Send the data directly if the client has too many blobs it has not reported on yet.
+        let cache = cache.filter(|cache| cache.has_room(offsets.len()));
+
+        // Entries are filled in per column, but have to be returned in the order of the request.
+        let mut entries: Vec<Option<SubChunkEntry>> = offsets.iter().map(|_| None).collect();
+
         // Group all subchunks into chunk columns,
         // with the map indices being two concatenated 32-bit integers representing X and Z coords.
-        let mut col_map: HashMap<i64, ChunkColumn, BuildNoHashHasher<i64>> = HashMap::with_hasher(std::hash::BuildHasherDefault::default());
-        for offset in offsets {
-            let abs_coord: Vector<i32, 3> = (base.x + offset.x as i32, base.y + offset.y as i32, base.z + offset.z as i32).into();
+        // Every column also stores the indices of its subchunks in the request.
+        let mut col_map: HashMap<u64, (bool, ChunkColumn, Vec<usize>), BuildNoHashHasher<u64>> =
+            HashMap::with_hasher(std::hash::BuildHasherDefault::default());
+        for (index, offset) in offsets.iter().enumerate() {
+            let Some(abs_coord) = Self::absolute_position(&base, offset, &range) else {
+                entries[index] = Some(SubChunkEntry {
+                    result: SubChunkResult::OutOfBounds,
+                    offset: offset.clone(),
+                    ..Default::default()
+                });
+                continue;
+            };
+
+            let xz = (abs_coord.x as u32 as u64) << 32 | abs_coord.z as u32 as u64;
+            let (_, col, indices) = col_map.entry(xz).or_insert_with(|| {
+                let exists = match self.service.provider.version((abs_coord.x, abs_coord.z), dimension) {
+                    Ok(version) => version.is_some(),
+                    Err(e) => {
+                        tracing::error!("Failed to load chunk version at {abs_coord:?}: {e:#}");
+                        false
+                    }
+                };
 
-            let xz = (abs_coord.x as i64) | (abs_coord.z as i64) >> 32;
-            let col = col_map.entry(xz).or_insert_with(ChunkColumn::empty);
+                (exists, ChunkColumn::empty(dimension), Vec::new())
+            });
 
+            indices.push(index);
             match self.load(abs_coord.clone(), dimension) {
                 Ok(opt) => {
                     col.subchunks.push((offset.clone(), opt));
                 }
                 Err(e) => {
-                    tracing::error!("Failed to load subchunk at {abs_coord:?}: {e}");
+                    tracing::error!("Failed to load subchunk at {abs_coord:?}: {e:#}");
                     col.subchunks.push((offset.clone(), None));
                 }
             }
@@ -102,42 +267,52 @@ impl Viewer {
 
         // Generate all heightmaps now that the columns are finalised.
         // TODO: Could maybe benefit from parallelisation depending on the offset count?
-        col_map.values_mut().for_each(ChunkColumn::generate_heightmap);
-
-        let mut entries = Vec::with_capacity(offsets.len());
-        for col in col_map.values() {
-            for (offset, opt) in &col.subchunks {
-                if let Some(sub) = opt {
-                    let subchunk_idx = base.y + offset.y as i32 - col.range.start as i32;
-                    dbg!(subchunk_idx);
-                    let heightmap = Heightmap::new(subchunk_idx as u16, col);
-                    dbg!(&heightmap);
-
-                    let payload = todo!();
-                    entries.push(SubChunkEntry {
-                        offset: offset.clone(),
-                        result: SubChunkResult::Success,
-                        heightmap_type: heightmap.map_type,
-                        heightmap: heightmap.data,
-                        blob_hash: 0,
-                        payload,
-                    });
+        col_map.values_mut().for_each(|(_, col, _)| col.generate_heightmap());
+
+        for (exists, col, indices) in col_map.values() {
+            for (&index, (offset, opt)) in indices.iter().zip(&col.subchunks) {
+                entries[index] = Some(if *exists {
+                    Self::create_entry(offset.clone(), opt.as_ref(), col, &instance.block_states, cache)?
                 } else {
-                    entries.push(SubChunkEntry {
-                        result: SubChunkResult::AllAir,
+                    SubChunkEntry {
+                        result: SubChunkResult::NotFound,
                         offset: offset.clone(),
                         ..Default::default()
-                    });
-                }
+                    }
+                });
             }
         }
 
-        todo!()
+        // Every offset has been assigned exactly one entry above.
+        let entries = entries.into_iter().flatten().collect();
+
+        Ok(SubChunkResponse {
+            cache_enabled: cache.is_some(),
+            dimension,
+            position: base,
+            entries,
+        })
+    }
+
+    /// Computes the absolute subchunk position of an offset in a request.
+    ///
+    /// Returns `None` if the position does not fit in the world or lies outside of the vertical range of the dimension.
+    fn absolute_position(base: &Vector<i32, 3>, offset: &ChunkOffset, range: &std::ops::Range<i16>) -> Option<Vector<i32, 3>> {
+        let x = base.x.checked_add(i32::from(offset.x))?;
+        let y = base.y.checked_add(i32::from(offset.y))?;
+        let z = base.z.checked_add(i32::from(offset.z))?;
+
+        let block_y = y.checked_mul(16)?;
+        if block_y < i32::from(range.start) || block_y >= i32::from(range.end) {
+            return None
+        }
+
+        Some(Vector::from([x, y, z]))
     }
 
     #[inline]
     pub fn load(&self, pos: Vector<i32, 3>, dimension: Dimension) -> anyhow::Result<Option<SubChunk>> {
-        self.service.provider.subchunk(pos, dimension)
+        self.service.subchunk(pos, dimension)
     }
 
     fn on_view_update(&self) {
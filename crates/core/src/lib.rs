@@ -48,11 +48,14 @@
 
 pub mod command;
 pub mod config;
+pub mod entity;
 pub mod forms;
 pub mod instance;
 pub mod item;
 pub mod level;
 pub mod net;
+pub mod pack;
+pub mod tick;
 
 #[cfg(test)]
 mod test;
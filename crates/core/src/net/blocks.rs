@@ -0,0 +1,227 @@
+use proto::bedrock::{GameMode, ItemInstance, PlayerActionType, PlayerBlockAction, UpdateBlock, UpdateBlockFlags};
+use proto::types::Dimension;
+use util::{BlockPosition, Vector};
+
+use super::{BedrockClient, PlayerData};
+use crate::item::InventoryWindow;
+use crate::level::breaking::{efficiency_level, Hardness, Tool};
+
+/// Maximum distance from the player's eyes at which blocks can be broken or placed.
+const MAX_REACH: f32 = 8.0;
+
+/// Ticks that a block may be broken earlier than expected, on top of [`BREAK_TIME_FRACTION`].
+///
+/// This accounts for latency and for the rounding of the break time.
+const BREAK_TIME_LENIENCY: u64 = 2;
+
+/// Fraction of the expected break time that must have passed before a block can be broken.
+const BREAK_TIME_FRACTION: f32 = 0.8;
+
+/// Blocks that are replaced when a block is placed into them.
+const REPLACEABLE_BLOCKS: &[&str] = &[
+    "minecraft:air",
+    "minecraft:water",
+    "minecraft:flowing_water",
+    "minecraft:lava",
+    "minecraft:flowing_lava",
+    "minecraft:short_grass",
+    "minecraft:tall_grass",
+    "minecraft:fern",
+    "minecraft:large_fern",
+    "minecraft:deadbush",
+    "minecraft:snow_layer",
+    "minecraft:vine",
+    "minecraft:fire",
+];
+
+/// Converts a block face to the offset of the block that is adjacent to that face.
+fn face_offset(face: i32) -> Option<Vector<i32, 3>> {
+    Some(Vector::from(match face {
+        0 => [0, -1, 0],
+        1 => [0, 1, 0],
+        2 => [0, 0, -1],
+        3 => [0, 0, 1],
+        4 => [-1, 0, 0],
+        5 => [1, 0, 0],
+        _ => return None,
+    }))
+}
+
+impl BedrockClient {
+    /// Handles the block actions that were sent in a [`PlayerAuthInput`](proto::bedrock::PlayerAuthInput) packet.
+    ///
+    /// With server authoritative block breaking, the client only predicts when a block is broken.
+    pub(crate) fn handle_block_actions(&self, actions: &[PlayerBlockAction]) -> anyhow::Result<()> {
+        let player = self.player()?;
+        for action in actions {
+            match action.action {
+                PlayerActionType::StartBreak => {
+                    *player.breaking.lock() = Some((action.position.clone(), self.instance().ticker().current()));
+                }
+                PlayerActionType::ContinueBreak => {
+                    // The client continues breaking when it moves on to the next block while holding the button.
+                    let mut breaking = player.breaking.lock();
+                    if breaking.as_ref().map_or(true, |(position, _)| *position != action.position) {
+                        *breaking = Some((action.position.clone(), self.instance().ticker().current()));
+                    }
+                }
+                PlayerActionType::AbortBreak => *player.breaking.lock() = None,
+                PlayerActionType::PredictBreak => self.break_block(&action.position)?,
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Breaks the block at the given position.
+    ///
+    /// Outside of creative mode, the player must have started breaking the block long enough ago.
+    /// If the player is not allowed to break the block, the block is resent to the player.
+    pub fn break_block(&self, position: &Vector<i32, 3>) -> anyhow::Result<()> {
+        let instance = self.instance();
+        let level = instance.level();
+        let player = self.player()?;
+
+        let started = player.breaking.lock().take();
+        let Some(current) = level.block(position, Dimension::Overworld)? else {
+            // There is nothing to break in subchunks that do not exist.
+            return Ok(());
+        };
+
+        let gamemode = player.gamemode();
+        let allowed = match gamemode {
+            GameMode::Creative => true,
+            GameMode::Adventure | GameMode::Spectator => false,
+            _ => match (started, Hardness::of(&current.name)) {
+                (Some((start, tick)), Some(hardness)) if start == *position => {
+                    let elapsed = instance.ticker().current().saturating_sub(tick);
+                    elapsed + BREAK_TIME_LENIENCY >= self.required_break_ticks(player, &hardness)
+                }
+                // The player did not start breaking this block, or the block is unbreakable.
+                _ => false,
+            },
+        };
+
+        if !allowed || !self.within_reach(position)? {
+            tracing::debug!("Rejected breaking block at {position:?}");
+            let current_id = instance.block_states.state(&current).unwrap_or_else(|| instance.block_states.air());
+            return self.resend_block(position, current_id);
+        }
+
+        let Some(air) = instance.block_states.entry(instance.block_states.air()) else {
+            anyhow::bail!("Air block state is not registered");
+        };
+
+        level.set_block(position, Dimension::Overworld, air)?;
+        self.broadcast_block(position, instance.block_states.air())
+    }
+
+    /// Places the held item against the given face of the clicked block.
+    ///
+    /// Nothing happens if the held item is not a block.
+    pub fn place_block(&self, clicked: &Vector<i32, 3>, face: i32) -> anyhow::Result<()> {
+        let Some(offset) = face_offset(face) else {
+            anyhow::bail!("Invalid block face {face}");
+        };
+
+        let position = Vector::from([clicked.x + offset.x, clicked.y + offset.y, clicked.z + offset.z]);
+        let player = self.player()?;
+        let instance = self.instance();
+        let level = instance.level();
+
+        let (held_slot, block_runtime_id) = {
+            let inventory = player.inventory.read();
+            (inventory.held_slot(), inventory.held_item().block_runtime_id)
+        };
+
+        let Some(block) = u32::try_from(block_runtime_id).ok().filter(|id| *id != 0).and_then(|id| instance.block_states.entry(id)) else {
+            return Ok(());
+        };
+
+        // Subchunks that do not exist yet only contain air.
+        let current = level.block(&position, Dimension::Overworld)?;
+        let replaceable = current.as_ref().map_or(true, |current| REPLACEABLE_BLOCKS.contains(&current.name.as_str()));
+
+        let gamemode = player.gamemode();
+        if !replaceable || matches!(gamemode, GameMode::Adventure | GameMode::Spectator) || !self.within_reach(&position)? {
+            tracing::debug!("Rejected placing block at {position:?}");
+
+            let current_id = current.and_then(|current| instance.block_states.state(&current)).unwrap_or_else(|| instance.block_states.air());
+            self.resend_block(&position, current_id)?;
+            return self.send_inventory_slot(InventoryWindow::Main, usize::from(held_slot));
+        }
+
+        level.set_block(&position, Dimension::Overworld, block)?;
+        self.broadcast_block(&position, block_runtime_id as u32)?;
+
+        if gamemode != GameMode::Creative {
+            let slot = usize::from(held_slot);
+            let mut inventory = player.inventory.write();
+
+            let mut item = inventory.held_item().clone();
+            item.count = item.count.saturating_sub(1);
+            let item = if item.count == 0 { ItemInstance::air() } else { item };
+
+            inventory.set(InventoryWindow::Main, slot, item)?;
+            drop(inventory);
+
+            self.send_inventory_slot(InventoryWindow::Main, slot)?;
+        }
+
+        Ok(())
+    }
+
+    /// Minimum amount of ticks it takes this player to break a block with the held item.
+    fn required_break_ticks(&self, player: &PlayerData, hardness: &Hardness) -> u64 {
+        let inventory = player.inventory.read();
+        let held = inventory.held_item();
+        let tool = self.instance().item_network_ids.get_name(held.network_id).and_then(Tool::from_item);
+        let efficiency = efficiency_level(&held.nbt);
+        drop(inventory);
+
+        (hardness.break_ticks(tool, efficiency) as f32 * BREAK_TIME_FRACTION) as u64
+    }
+
+    /// Sends a block update to every player that has the block loaded.
+    pub fn broadcast_block(&self, position: &Vector<i32, 3>, block_runtime_id: u32) -> anyhow::Result<()> {
+        let packet = UpdateBlock {
+            position: BlockPosition::new(position.x, position.y as u32, position.z),
+            block_runtime_id,
+            flags: UpdateBlockFlags::UpdateNetwork as u32 | UpdateBlockFlags::UpdateNeighbors as u32,
+            layer: 0,
+        };
+
+        let xz = Vector::from([position.x as f32, position.z as f32]);
+        for client in self.instance().clients().connected() {
+            if client.viewer.in_range(xz.clone()) {
+                if let Err(err) = client.send(packet.clone()) {
+                    tracing::warn!("Failed to send block update to {}: {err:#}", client.name().unwrap_or("<unknown>"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends the server's version of a block to this player to undo a rejected change.
+    fn resend_block(&self, position: &Vector<i32, 3>, block_runtime_id: u32) -> anyhow::Result<()> {
+        self.send(UpdateBlock {
+            position: BlockPosition::new(position.x, position.y as u32, position.z),
+            block_runtime_id,
+            flags: UpdateBlockFlags::UpdateNetwork as u32,
+            layer: 0,
+        })
+    }
+
+    /// Whether the given block is within reach of the player.
+    fn within_reach(&self, position: &Vector<i32, 3>) -> anyhow::Result<bool> {
+        let eyes = self.player()?.position();
+
+        let dx = position.x as f32 + 0.5 - eyes.x;
+        let dy = position.y as f32 + 0.5 - eyes.y;
+        let dz = position.z as f32 + 0.5 - eyes.z;
+
+        Ok(dx * dx + dy * dy + dz * dz <= MAX_REACH * MAX_REACH)
+    }
+}
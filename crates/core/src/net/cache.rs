@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use std::time::{Duration, Instant};
+
+use nohash_hasher::NoHashHasher;
+use parking_lot::Mutex;
+use proto::bedrock::{CacheBlob, CacheBlobStatus, CacheMissResponse};
+use util::{Deserialize, RVec};
+use xxhash_rust::xxh64::xxh64;
+
+use super::BedrockClient;
+
+/// Blob hashes are already uniformly distributed and do not have to be hashed again.
+type BlobHasher = BuildHasherDefault<NoHashHasher<u64>>;
+
+/// Maximum amount of blobs that can wait for a status report at the same time.
+const MAX_PENDING_BLOBS: usize = 4096;
+/// Time after which a blob that the client has not reported on may be dropped to make room for new blobs.
+const PENDING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A blob that has been referenced in a packet, but whose status has not been reported by the client yet.
+struct PendingBlob {
+    payload: RVec,
+    /// Amount of sent references that the client has not reported on yet.
+    references: usize,
+    /// When this blob was last referenced.
+    referenced_at: Instant,
+}
+
+/// Keeps track of the blobs that have been sent to a client supporting the blob cache.
+///
+/// Whenever the server sends a blob hash instead of a payload, it stores the payload here until the client
+/// has reported whether it has the blob in its cache. Misses are answered with the stored payloads.
+///
+/// At most [`MAX_PENDING_BLOBS`] blobs are stored, so that a client that never reports on its blobs
+/// cannot make the server run out of memory.
+#[derive(Default)]
+pub struct BlobLedger {
+    /// Blobs that the client has not reported on yet.
+    pending: Mutex<HashMap<u64, PendingBlob, BlobHasher>>,
+}
+
+impl BlobLedger {
+    /// Creates an empty ledger.
+    pub fn new() -> BlobLedger {
+        BlobLedger::default()
+    }
+
+    /// Computes the hash that identifies the given payload in the blob cache.
+    #[inline]
+    pub fn hash(payload: &[u8]) -> u64 {
+        xxh64(payload, 0)
+    }
+
+    /// Registers a payload that is about to be referenced by hash and returns its hash.
+    ///
+    /// The payload is kept until the client has reported the status of the blob.
+    /// If the ledger is full, this returns the payload instead and it should be sent directly.
+    pub fn insert(&self, payload: RVec) -> Result<u64, RVec> {
+        let hash = Self::hash(payload.as_ref());
+
+        let mut pending = self.pending.lock();
+        if !pending.contains_key(&hash) && !Self::make_room(&mut pending, 1) {
+            return Err(payload)
+        }
+
+        let blob = pending.entry(hash).or_insert_with(|| PendingBlob { payload, references: 0, referenced_at: Instant::now() });
+        blob.references += 1;
+        blob.referenced_at = Instant::now();
+        drop(pending);
+
+        Ok(hash)
+    }
+
+    /// Whether the given amount of new blobs can be inserted.
+    pub fn has_room(&self, count: usize) -> bool {
+        Self::make_room(&mut self.pending.lock(), count)
+    }
+
+    /// Makes room for the given amount of new blobs by dropping blobs that have timed out.
+    ///
+    /// Returns whether there is enough room afterwards.
+    fn make_room(pending: &mut HashMap<u64, PendingBlob, BlobHasher>, count: usize) -> bool {
+        if pending.len() + count > MAX_PENDING_BLOBS {
+            // Drop blobs that the client has most likely forgotten about.
+            pending.retain(|_, blob| blob.referenced_at.elapsed() < PENDING_TIMEOUT);
+        }
+
+        pending.len() + count <= MAX_PENDING_BLOBS
+    }
+
+    /// Amount of blobs that are waiting for a status report from the client.
+    pub fn pending(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    /// Processes a status report sent by the client.
+    ///
+    /// This returns the hashes and payloads of all reported misses that still have to be sent.
+    pub fn process(&self, status: &CacheBlobStatus) -> Vec<(u64, RVec)> {
+        let mut missing = Vec::with_capacity(status.misses.len());
+
+        let mut pending = self.pending.lock();
+        for hash in &status.hits {
+            Self::release(&mut pending, *hash);
+        }
+
+        for hash in &status.misses {
+            if let Some(blob) = pending.get(hash) {
+                missing.push((*hash, blob.payload.clone()));
+            } else {
+                tracing::warn!("Client reported a miss for unknown blob {hash:#x}");
+            }
+
+            Self::release(&mut pending, *hash);
+        }
+        drop(pending);
+
+        missing
+    }
+
+    /// Removes a single reference to the given blob, dropping it when the client has reported on all of them.
+    fn release(pending: &mut HashMap<u64, PendingBlob, BlobHasher>, hash: u64) {
+        if let Some(blob) = pending.get_mut(&hash) {
+            blob.references -= 1;
+            if blob.references == 0 {
+                pending.remove(&hash);
+            }
+        }
+    }
+}
+
+impl BedrockClient {
+    /// Handles a [`CacheBlobStatus`] packet by sending the blobs the client does not have.
+    #[tracing::instrument(
+        skip_all,
+        name = "BedrockUser::handle_cache_blob_status",
+        fields(
+            username = %self.name().unwrap_or("<unknown>")
+        )
+    )]
+    pub fn handle_cache_blob_status(&self, packet: RVec) -> anyhow::Result<()> {
+        let status = CacheBlobStatus::deserialize(packet.as_ref())?;
+        let missing = self.blobs.process(&status);
+
+        tracing::trace!("Client reported {} hits and {} misses", status.hits.len(), status.misses.len());
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let blobs = missing
+            .iter()
+            .map(|(hash, payload)| CacheBlob { hash: *hash, payload: payload.as_ref() })
+            .collect::<Vec<_>>();
+
+        self.send(CacheMissResponse { blobs: &blobs })
+    }
+}
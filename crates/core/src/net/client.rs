@@ -1,4 +1,5 @@
-use std::io::{Read, Write};
+use std::collections::HashSet;
+use std::io::Write;
 
 use std::sync::{Arc, OnceLock, Weak};
 use std::sync::atomic::{
@@ -7,21 +8,22 @@ use std::sync::atomic::{
 use std::time::{Instant, Duration};
 
 use anyhow::Context;
-use flate2::Compression;
-use flate2::write::DeflateEncoder;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use raknet::{BroadcastPacket, Frame, FrameBatch, RakNetClient, RakNetCommand, SendConfig, DEFAULT_SEND_CONFIG};
 use tokio::sync::{broadcast, mpsc};
-use proto::bedrock::{Animate, CacheStatus, ChunkRadiusRequest, ClientToServerHandshake, CommandPermissionLevel, CommandRequest, CompressionAlgorithm, ConnectedPacket, ContainerClose, Disconnect, DisconnectReason, FormResponseData, GameMode, Header, Interact, InventoryTransaction, Login, MobEquipment, MovePlayer, PermissionLevel, PlayerAction, PlayerAuthInput, RequestAbility, RequestNetworkSettings, ResourcePackClientResponse, SetInventoryOptions, SetLocalPlayerAsInitialized, SettingsCommand, Skin, TextMessage, TickSync, UpdateSkin, ViolationWarning, CONNECTED_PACKET_ID};
+use proto::bedrock::{AbilityData, AbilityLayer, AbilityType, ActorData, ActorFlag, Animate, CacheBlobStatus, CacheStatus, ChunkRadiusRequest, ClientToServerHandshake, CommandPermissionLevel, CommandRequest, ConnectedPacket, ContainerClose, Disconnect, DisconnectReason, FormResponseData, GameMode, Header, Interact, InventoryTransaction, ItemStackRequest, Login, MobEquipment, MovePlayer, PermissionLevel, PlayerAction, PlayerAuthInput, RequestAbility, RequestNetworkSettings, ResourcePackChunkRequest, ResourcePackClientResponse, SetInventoryOptions, SetLocalPlayerAsInitialized, SettingsCommand, Skin, SubChunkRequest, TextData, TextMessage, TickSync, UpdateAbilities, UpdateSkin, ViolationWarning, ABILITY_FLAG_END, ABILITY_FLYING, CONNECTED_PACKET_ID};
 use proto::crypto::{Encryptor, BedrockIdentity, BedrockClientInfo};
 use proto::uuid::Uuid;
 
 use tokio_util::sync::CancellationToken;
 use util::{AtomicFlag, BinaryRead, BinaryWrite, Deserialize, Joinable, RVec, pool, Serialize, Vector};
 
+use crate::config::Compression;
 use crate::forms;
 use crate::instance::Instance;
+use crate::item::PlayerInventory;
 use crate::level::Viewer;
+use crate::net::{BlobLedger, MovementState};
 
 const REQUEST_TIMEOUT: Duration = Duration::from_millis(50);
 
@@ -38,6 +40,14 @@ pub struct BedrockClient {
     pub(crate) should_decompress: AtomicFlag,
     /// Whether the client supports the blob cache.
     pub(crate) supports_cache: AtomicBool,
+    /// Blobs that have been sent to the client if it supports the blob cache.
+    pub(crate) blobs: BlobLedger,
+    /// Whether the player has spawned and is visible to other players.
+    pub(crate) spawned: AtomicBool,
+    /// Runtime IDs of the entities that have been added to this client's world.
+    pub(crate) visible_entities: Mutex<HashSet<u64>>,
+    /// Runtime IDs of the other players that have been added to this client's world.
+    pub(crate) visible_players: Mutex<HashSet<u64>>,
     pub(crate) raknet: Arc<RakNetClient>,
     pub(crate) player: OnceLock<PlayerData>,
 
@@ -68,6 +78,10 @@ impl BedrockClient {
             expected: AtomicU32::new(RequestNetworkSettings::ID),
             should_decompress: AtomicFlag::new(),
             supports_cache: AtomicBool::new(false),
+            blobs: BlobLedger::new(),
+            spawned: AtomicBool::new(false),
+            visible_entities: Mutex::new(HashSet::new()),
+            visible_players: Mutex::new(HashSet::new()),
             raknet,
             player: OnceLock::new(),
             forms: forms::Subscriber::new(),
@@ -138,6 +152,7 @@ impl BedrockClient {
         }
 
         tracing::info!("{} has disconnected", self.name().unwrap_or("<unknown>"));
+        self.leave();
 
         tracing::info!(
             "Requests: {} | Returns: {} | Allocations: {}",
@@ -147,6 +162,40 @@ impl BedrockClient {
         self.shutdown_token.cancel();
     }
 
+    /// Removes the player from the world after it disconnected, was kicked or timed out.
+    ///
+    /// Clients that disconnect before they have spawned are not announced to other players.
+    fn leave(&self) {
+        let was_spawned = self.spawned();
+        if let Err(err) = self.despawn() {
+            tracing::error!("Failed to remove player from other clients: {err:#}");
+        }
+
+        if was_spawned {
+            let name = self.name().unwrap_or("<unknown>");
+            let result = self.broadcast_others(TextMessage {
+                data: TextData::Translation {
+                    parameters: vec![&format!("§e{name}")],
+                    message: "multiplayer.player.left",
+                },
+                needs_translation: true,
+                xuid: 0,
+                platform_chat_id: "",
+            });
+
+            if let Err(err) = result {
+                tracing::error!("Failed to broadcast leave message: {err:#}");
+            }
+        }
+
+        // Players that never finished logging in have no data to save.
+        if self.player.get().is_some() {
+            if let Err(err) = self.save_player_data() {
+                tracing::error!("Failed to save player data: {err:#}");
+            }
+        }
+    }
+
     /// Returns the instance this client belongs to.
     pub(crate) fn instance(&self) -> Arc<Instance> {
         // Instance should always exist while a client is active.
@@ -271,47 +320,17 @@ impl BedrockClient {
         where
             B: AsRef<[u8]>
     {
-        let mut out;
-        if self.should_decompress.get() {
-            let (algorithm, threshold) = {
-                let instance = self.instance();
-                let compression = instance.config().compression();
-                (compression.algorithm, compression.threshold)
-            };
+        // Also reserve capacity for the compression header and checksum even if these are not used,
+        // preventing allocations.
+        let mut out = RVec::alloc_with_capacity(1 + 1 + packet.as_ref().len() + 8);
+        out.write_u8(CONNECTED_PACKET_ID)?;
 
-            if packet.as_ref().len() > threshold as usize {
-                // Compress packet
-                match algorithm {
-                    CompressionAlgorithm::Snappy => {
-                        unimplemented!("Snappy compression");
-                    }
-                    CompressionAlgorithm::Flate => {
-                        let writer_inner = RVec::alloc_with_capacity(packet.as_ref().len());
-                        let mut writer = DeflateEncoder::new(writer_inner, Compression::best());
-
-                        writer.write_all(packet.as_ref())?;
-                        let compressed_body = writer.finish()?;
-
-                        out = RVec::alloc_with_capacity(1 + 1 + compressed_body.len());
-                        out.write_u8(CONNECTED_PACKET_ID)?;
-                        out.write_u8(algorithm as u8)?;
-                        out.write_all(&compressed_body)?;
-                    }
-                }
-            } else {
-                // Also reserve capacity for checksum even if encryption is disabled,
-                // preventing allocations.
-                out = RVec::alloc_with_capacity(1 + packet.as_ref().len() + 8);
-                out.write_u8(CONNECTED_PACKET_ID)?;
-                out.write_all(packet.as_ref())?;
-            }
+        if self.should_decompress.get() {
+            let instance = self.instance();
+            instance.config().compression().compress(packet.as_ref(), &mut out)?;
         } else {
-            // Also reserve capacity for checksum even if encryption is disabled,
-            // preventing allocations.
-            out = RVec::alloc_with_capacity(1 + packet.as_ref().len() + 8);
-            out.write_u8(CONNECTED_PACKET_ID)?;
             out.write_all(packet.as_ref())?;
-        };
+        }
 
         let chunk_max_size = self.raknet.mtu as usize
             - std::mem::size_of::<Frame>()
@@ -346,32 +365,11 @@ impl BedrockClient {
             encryptor.decrypt(&mut packet).context("Failed to decrypt packet")?;
         }
 
-        let out = if self.should_decompress.get() {
-            if packet[0] == 0xff {
-                packet.remove(0);
-                self.handle_frame_body(packet).await
-            } else {
-                let algorithm = CompressionAlgorithm::try_from(packet[0])?;
-                packet.remove(0);
-
-                match algorithm {
-                    CompressionAlgorithm::Flate => {
-                        let mut reader = flate2::read::DeflateDecoder::new(packet.as_slice());
-                        let mut decompressed = RVec::alloc_with_capacity(packet.len() * 2);
-                        
-                        reader.read_to_end(&mut decompressed)?;
-                        self.handle_frame_body(decompressed).await
-                    },
-                    CompressionAlgorithm::Snappy => {
-                        anyhow::bail!("Snappy compression is not implemented");
-                    }
-                }
-            }
-        } else {
-            self.handle_frame_body(packet).await
-        };
+        if self.should_decompress.get() {
+            packet = Compression::decompress(packet)?;
+        }
 
-        out
+        self.handle_frame_body(packet).await
     }
 
     /// Handles the body of a frame.
@@ -410,6 +408,7 @@ impl BedrockClient {
                 SetInventoryOptions::ID => this.handle_inventory_options(packet).context("while handling SetInventoryOptions"),
                 MobEquipment::ID => this.handle_mob_equipment(packet).context("while handling MobEquipment"),
                 InventoryTransaction::ID => this.handle_inventory_transaction(packet).context("while handling InventoryTransaction"),
+                ItemStackRequest::ID => this.handle_item_stack_request(packet).context("while handling ItemStackRequest"),
                 PlayerAuthInput::ID => this.handle_auth_input(packet).context("while handling PlayerAuthInput"),
                 RequestNetworkSettings::ID => {
                     this.handle_network_settings_request(packet).context("while handling RequestNetworkSettings")
@@ -422,6 +421,7 @@ impl BedrockClient {
                 ResourcePackClientResponse::ID => {
                     this.handle_resource_client_response(packet).context("while handling ResourcePackClientResponse")
                 }
+                ResourcePackChunkRequest::ID => this.handle_pack_chunk_request(packet).context("while handling ResourcePackChunkRequest"),
                 ViolationWarning::ID => this.handle_violation_warning(packet).context("while handling ViolationWarning"),
                 ChunkRadiusRequest::ID => this.handle_chunk_radius_request(packet).context("while handling ChunkRadiusRequest"),
                 Interact::ID => this.handle_interaction(packet).context("while handling Interact"),
@@ -443,6 +443,8 @@ impl BedrockClient {
                 ContainerClose::ID => this.handle_container_close(packet),
                 FormResponseData::ID => this.handle_form_response(packet),
                 TickSync::ID => this.handle_tick_sync(packet),
+                CacheBlobStatus::ID => this.handle_cache_blob_status(packet).context("while handling CacheBlobStatus"),
+                SubChunkRequest::ID => this.handle_subchunk_request(packet).context("while handling SubChunkRequest"),
                 id => anyhow::bail!("Invalid game packet: {id:#04x}"),
             }
         };
@@ -468,6 +470,12 @@ impl BedrockClient {
         self.identity.get().ok_or_else(|| anyhow::anyhow!("Identity unknown: user has not logged in yet"))
     }
 
+    /// Returns the client info that was sent during login.
+    #[inline]
+    pub fn client_info(&self) -> anyhow::Result<&BedrockClientInfo> {
+        self.client_info.get().ok_or_else(|| anyhow::anyhow!("Client info unknown: user has not logged in yet"))
+    }
+
     /// This function panics if the name was not set.
     #[inline]
     pub fn name(&self) -> anyhow::Result<&str> {
@@ -480,6 +488,12 @@ impl BedrockClient {
         Ok(self.player()?.runtime_id)
     }
 
+    /// This function panics if the player data was not set.
+    #[inline]
+    pub fn unique_id(&self) -> anyhow::Result<i64> {
+        Ok(self.player()?.unique_id)
+    }
+
     /// This function panics if the XUID was not set.
     #[inline]
     pub fn xuid(&self) -> anyhow::Result<u64> {
@@ -516,6 +530,18 @@ impl BedrockClient {
     pub fn player(&self) -> anyhow::Result<&PlayerData> {
         self.player.get().ok_or_else(|| anyhow::anyhow!("Player data unavailable"))
     }
+
+    /// Changes the permission levels of this player.
+    ///
+    /// The client is sent its updated abilities and the list of commands it now has access to.
+    pub fn set_permissions(&self, permission_level: PermissionLevel, command_permission_level: CommandPermissionLevel) -> anyhow::Result<()> {
+        let player = self.player()?;
+        *player.permission_level.write() = permission_level;
+        *player.command_permission_level.write() = command_permission_level;
+
+        self.send(UpdateAbilities(player.ability_data()))?;
+        self.send(self.commands.available_commands_for(command_permission_level))
+    }
 }
 
 impl Joinable for BedrockClient {
@@ -535,39 +561,71 @@ impl Joinable for BedrockClient {
 pub struct PlayerData {
     /// Whether the player's inventory is currently open.
     pub is_inventory_open: AtomicBool,
-    /// Position of the player.
-    pub position: Vector<f32, 3>,
-    /// Rotation of the player.
-    /// x and y components are general rotation.
-    /// z component is head yaw.
-    pub rotation: Vector<f32, 3>,
+    /// Position, rotation and velocity of the player.
+    pub movement: RwLock<MovementState>,
     /// Game mode.
     pub game_mode: GameMode,
+    /// Whether the player is currently flying.
+    pub is_flying: AtomicBool,
     /// General permission level.
-    pub permission_level: PermissionLevel,
+    pub permission_level: RwLock<PermissionLevel>,
     /// Command permission level
-    pub command_permission_level: CommandPermissionLevel,
+    pub command_permission_level: RwLock<CommandPermissionLevel>,
     /// The client's skin.
     pub skin: RwLock<Skin>,
+    /// Metadata that is sent to other players, such as the name tag.
+    pub metadata: RwLock<ActorData>,
+    /// Items in the player's inventory.
+    pub inventory: RwLock<PlayerInventory>,
     /// Runtime ID.
     pub runtime_id: u64,
+    /// Unique ID.
+    pub unique_id: i64,
+    /// Position of the block that the player is breaking and the tick at which they started.
+    pub breaking: Mutex<Option<(Vector<i32, 3>, u64)>>,
 }
 
 impl PlayerData {
     /// Creates a new player data struct.
-    pub fn new(skin: Skin) -> Self {
+    pub fn new(skin: Skin, runtime_id: u64, unique_id: i64, name: &str) -> Self {
+        let mut metadata = ActorData::new();
+        metadata.set_name(name);
+        metadata.set_always_show_name(true);
+        metadata.set_scale(1.0);
+        metadata.set_bounding_box(0.6, 1.8);
+        metadata.set_air_supply(300, 300);
+        for flag in [ActorFlag::ShowName, ActorFlag::HasGravity, ActorFlag::HasCollision, ActorFlag::Breathing, ActorFlag::CanClimb] {
+            metadata.set_flag(flag, true);
+        }
+
         Self {
             is_inventory_open: AtomicBool::new(false),
-            position: Vector::from([0.0, 50.0, 0.0]),
-            rotation: Vector::from([0.0; 3]),
+            movement: RwLock::new(MovementState::new(Vector::from([0.0, 6.0, 0.0]))),
             game_mode: GameMode::Creative,
-            permission_level: PermissionLevel::Member,
-            command_permission_level: CommandPermissionLevel::Owner,
+            is_flying: AtomicBool::new(false),
+            permission_level: RwLock::new(PermissionLevel::Member),
+            command_permission_level: RwLock::new(CommandPermissionLevel::Normal),
             skin: RwLock::new(skin),
-            runtime_id: 1
+            metadata: RwLock::new(metadata),
+            inventory: RwLock::new(PlayerInventory::new()),
+            runtime_id,
+            unique_id,
+            breaking: Mutex::new(None)
         }
     }
 
+    /// The current position of the player.
+    pub fn position(&self) -> Vector<f32, 3> {
+        self.movement.read().position.clone()
+    }
+
+    /// The current rotation of the player.
+    ///
+    /// The x and y components are the pitch and yaw, the z component is the head yaw.
+    pub fn rotation(&self) -> Vector<f32, 3> {
+        self.movement.read().rotation.clone()
+    }
+
     /// The gamemode the player is currently in.
     pub const fn gamemode(&self) -> GameMode {
         self.game_mode
@@ -578,13 +636,36 @@ impl PlayerData {
         self.runtime_id
     }
 
+    /// The unique ID of the player.
+    pub const fn unique_id(&self) -> i64 {
+        self.unique_id
+    }
+
     /// The permission level of the player.
-    pub const fn permission_level(&self) -> PermissionLevel {
-        self.permission_level
+    pub fn permission_level(&self) -> PermissionLevel {
+        *self.permission_level.read()
     }
 
     /// The command permission level of the player.
-    pub const fn command_permission_level(&self) -> CommandPermissionLevel {
-        self.command_permission_level
+    pub fn command_permission_level(&self) -> CommandPermissionLevel {
+        *self.command_permission_level.read()
+    }
+
+    /// Creates the ability data that describes the current state of the player.
+    pub fn ability_data(&self) -> AbilityData {
+        AbilityData {
+            command_permission_level: self.command_permission_level(),
+            permission_level: self.permission_level(),
+            unique_id: self.unique_id() as u64,
+            layers: vec![
+                AbilityLayer {
+                    fly_speed: 0.05,
+                    walk_speed: 0.1,
+                    values: if self.is_flying.load(Ordering::Relaxed) { ABILITY_FLYING } else { 0 },
+                    abilities: ABILITY_FLAG_END - 1,
+                    ability_type: AbilityType::Base
+                }
+            ]
+        }
     }
 }
\ No newline at end of file
@@ -7,7 +7,7 @@ use dashmap::DashMap;
 
 use proto::uuid::Uuid;
 use raknet::{BroadcastPacket, RakNetCreateDescription, RakNetClient};
-use proto::bedrock::{ConnectedPacket, Disconnect, DisconnectReason};
+use proto::bedrock::{CommandPermissionLevel, ConnectedPacket, Disconnect, DisconnectReason, PermissionLevel};
 use util::{RVec, Joinable, Serialize};
 
 use tokio::sync::{broadcast, mpsc};
@@ -21,6 +21,24 @@ use super::{ForwardablePacket, BedrockClient};
 const BROADCAST_CHANNEL_CAPACITY: usize = 5;
 const FORWARD_TIMEOUT: Duration = Duration::from_millis(10);
 
+/// Maps the identity of a logged in client to its address.
+#[derive(Default)]
+struct IdentityIndex {
+    xuids: DashMap<u64, SocketAddr>,
+    uuids: DashMap<Uuid, SocketAddr>,
+    /// Usernames are stored in lowercase to allow case-insensitive lookups.
+    names: DashMap<String, SocketAddr>,
+}
+
+impl IdentityIndex {
+    /// Removes all identities that point to the given address.
+    fn remove(&self, address: &SocketAddr) {
+        self.xuids.retain(|_, indexed| indexed != address);
+        self.uuids.retain(|_, indexed| indexed != address);
+        self.names.retain(|_, indexed| indexed != address);
+    }
+}
+
 /// Contains the user state itself and a method to contact the user.
 pub struct UserMapEntry<T> {
     channel: mpsc::Sender<RVec>,
@@ -47,6 +65,8 @@ pub struct Clients {
     
     connecting_map: Arc<DashMap<SocketAddr, UserMapEntry<RakNetClient>>>,
     connected_map: Arc<DashMap<SocketAddr, UserMapEntry<BedrockClient>>>,
+    /// Addresses of logged in clients, indexed by their identity.
+    identities: Arc<IdentityIndex>,
     /// Channel that sends a packet to all connected sessions.
     broadcast: broadcast::Sender<BroadcastPacket>,
 
@@ -67,6 +87,7 @@ impl Clients {
             shutdown_token: CancellationToken::new(),
             connecting_map, 
             connected_map, 
+            identities: Arc::new(IdentityIndex::default()),
             broadcast, 
             commands, 
             level,
@@ -116,10 +137,12 @@ impl Clients {
 
         let connecting_map = Arc::clone(&self.connecting_map);
         let connected_map = Arc::clone(&self.connected_map);
+        let identities = Arc::clone(&self.identities);
         let state_clone = Arc::clone(&state);
 
         tokio::spawn(async move {
             state_clone.active.cancelled().await;
+            identities.remove(&state_clone.address);
             connected_map.remove(&state_clone.address);
             connecting_map.remove(&state_clone.address);
         });
@@ -143,14 +166,29 @@ impl Clients {
         self.instance.get().unwrap().upgrade().unwrap()
     }
 
+    /// Makes a client that has just logged in available through its XUID, UUID and username.
+    ///
+    /// The client is removed from the index again when it disconnects.
+    pub(crate) fn register(&self, client: &BedrockClient) -> anyhow::Result<()> {
+        let address = client.raknet.address;
+
+        self.identities.xuids.insert(client.xuid()?, address);
+        self.identities.uuids.insert(*client.uuid()?, address);
+        self.identities.names.insert(client.name()?.to_lowercase(), address);
+
+        Ok(())
+    }
+
     /// Attempts to retrieve the user with the given XUID.
     pub fn by_xuid(&self, xuid: u64) -> Option<Arc<BedrockClient>> {
-        todo!()
+        let address = *self.identities.xuids.get(&xuid)?;
+        self.by_address(&address)
     }
 
     /// Attempts to retrieve the user with the given UUID.
     pub fn by_uuid(&self, uuid: Uuid) -> Option<Arc<BedrockClient>> {
-        todo!()
+        let address = *self.identities.uuids.get(&uuid)?;
+        self.by_address(&address)
     }
 
     /// Attempts to retrieve the user with the given IP address.
@@ -161,8 +199,33 @@ impl Clients {
     }
 
     /// Attempts to retrieve the user with the given username.
+    ///
+    /// Usernames are compared case-insensitively.
     pub fn by_username<S: AsRef<str>>(&self, username: S) -> Option<Arc<BedrockClient>> {
-        todo!()
+        let address = *self.identities.names.get(&username.as_ref().to_lowercase())?;
+        self.by_address(&address)
+    }
+
+    /// Returns all users that are fully connected to the server.
+    pub fn connected(&self) -> Vec<Arc<BedrockClient>> {
+        self.connected_map
+            .iter()
+            .map(|r| Arc::clone(&r.value().state))
+            .collect()
+    }
+
+    /// Changes the permission levels of the player with the given username.
+    ///
+    /// This returns an error if the player is not connected or if sending the updated abilities fails.
+    pub fn set_permissions<S: AsRef<str>>(
+        &self, username: S, permission_level: PermissionLevel, command_permission_level: CommandPermissionLevel
+    ) -> anyhow::Result<()> {
+        let username = username.as_ref();
+        let Some(client) = self.by_username(username) else {
+            anyhow::bail!("Player {username} is not connected")
+        };
+
+        client.set_permissions(permission_level, command_permission_level)
     }
 
     /// Forwards a packet to a user within the map.
@@ -1,19 +1,21 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
 use futures::{future, StreamExt};
 use level::{BiomeEncoding, BiomeStorage, Biomes, SubChunk, SubStorage};
 use proto::{
     bedrock::{
         Animate, CommandOutput, CommandOutputMessage, CommandOutputType, CommandRequest, DisconnectReason, FormResponseData, HeightmapType,
-        HudElement, HudVisibility, InventoryTransaction, ItemInstance, LevelChunk, MobEquipment, NetworkChunkPublisherUpdate, PlayerAuthInput,
+        HudElement, HudVisibility, LevelChunk, MobEquipment, NetworkChunkPublisherUpdate,
         RequestAbility, SetHud, SetInventoryOptions, SettingsCommand, SubChunkEntry, SubChunkRequestMode, SubChunkResponse, SubChunkResult, TextData,
-        TextMessage, TickSync, TransactionAction, TransactionSourceType, TransactionType, UpdateSkin, WindowId,
+        TextMessage, TickSync, UpdateSkin, WindowId,
     },
     types::Dimension,
 };
 
 use util::{BinaryRead, BinaryWrite, CowSlice, Deserialize, RVec};
 
+use crate::command::CommandSender;
+use crate::item::same_item;
 use crate::level::io::r#box::BoxRegion;
 use crate::level::io::stream::IndexedSubChunk;
 
@@ -28,10 +30,25 @@ impl BedrockClient {
         // Clients only send this packet to modify themselves.
         if equipment.runtime_id != self.runtime_id()? {
             // Illegal packet modifications
-            self.kick_with_reason("Illegal packets", DisconnectReason::BadPacket)?;
+            return self.kick_with_reason("Illegal packets", DisconnectReason::BadPacket);
         }
 
-        self.broadcast_others(equipment)
+        if equipment.window_id != WindowId::Inventory {
+            return Ok(())
+        }
+
+        // Other players are shown the item that the server thinks the player is holding.
+        let mut inventory = self.player()?.inventory.write();
+        inventory.set_held_slot(equipment.hotbar_slot)?;
+        let held_item = inventory.held_item().clone();
+        drop(inventory);
+
+        if !same_item(&held_item, &equipment.new_item) {
+            tracing::debug!("Client is holding an item that is not in hotbar slot {}", equipment.hotbar_slot);
+            self.send_inventory()?;
+        }
+
+        self.relay(MobEquipment { new_item: held_item, ..equipment })
     }
 
     pub fn handle_inventory_options(&self, packet: RVec) -> anyhow::Result<()> {
@@ -41,56 +58,6 @@ impl BedrockClient {
         Ok(())
     }
 
-    pub fn handle_inventory_transaction(&self, packet: RVec) -> anyhow::Result<()> {
-        let transaction = InventoryTransaction::deserialize(packet.as_ref())?;
-        tracing::debug!("{transaction:?}");
-        // let action = &transaction.actions[0];
-        // let item = &action.new_item;
-
-        // let transaction = InventoryTransaction {
-        //     legacy_request_id: 0,
-        //     legacy_transactions: vec![],
-        //     transaction_type: TransactionType::Normal,
-        //     actions: vec![
-        //         TransactionAction {
-        //             slot: 0,
-        //             source_type: TransactionSourceType::Container {
-        //                 inventory_id: WindowId::Ui
-        //             },
-        //             new_item: ItemInstance::air(),
-        //             old_item: item.clone()
-        //         },
-        //         TransactionAction {
-        //             slot: 2,
-        //             source_type: TransactionSourceType::Container {
-        //                 inventory_id: WindowId::Hotbar
-        //             },
-        //             old_item: ItemInstance::air(),
-        //             new_item: item.clone()
-        //         }
-        //     ]
-        // };
-        // self.send(transaction)?;
-
-        // for action in transaction.actions {
-        //     let instance = self.instance();
-
-        //     let new = instance.item_network_ids.get_name(action.new_item.network_id);
-        //     // let old = instance.item_network_ids.get_name(action.old_item.network_id);
-
-        //     let mut buf = Vec::with_capacity(5);
-        //     buf.write_var_i32(action.new_item.network_id)?;
-
-        //     let mut var = buf.as_slice();
-        //     let var = var.read_var_u32()?;
-        //     println!("{var}")
-
-        //     // println!("Switch from {old:?} to {new:?}");
-        // }
-
-        Ok(())
-    }
-
     /// Handles a [`SettingsCommand`] packet used to adjust a world setting.
     pub fn handle_settings_command(&self, packet: RVec) -> anyhow::Result<()> {
         let request = SettingsCommand::deserialize(packet.as_ref())?;
@@ -101,14 +68,13 @@ impl BedrockClient {
 
     /// Handles a [`TickSync`] packet used to synchronise ticks between the client and server.
     pub fn handle_tick_sync(&self, packet: RVec) -> anyhow::Result<()> {
-        let _request = TickSync::deserialize(packet.as_ref())?;
-        // TODO: Implement tick synchronisation
-        Ok(())
-        // let response = TickSync {
-        //     request_tick: request.request_tick,
-        //     response_tick: self.level.
-        // };
-        // self.send(response)
+        let request = TickSync::deserialize(packet.as_ref())?;
+        let response = TickSync {
+            request_tick: request.request_tick,
+            response_tick: self.instance().ticker().current()
+        };
+
+        self.send(response)
     }
 
     /// Handles a [`TextMessage`] packet sent when a client wants to send a chat message.
@@ -142,17 +108,6 @@ impl BedrockClient {
         }
     }
 
-    /// Handles a [`PlayerAuthInput`] packet. These are sent every tick and are used
-    /// for server authoritative player movement.
-    pub fn handle_auth_input(&self, packet: RVec) -> anyhow::Result<()> {
-        let input = PlayerAuthInput::deserialize(packet.as_ref())?;
-        if input.input_data.0 != 0 {
-            // tracing::debug!("{:?}", input.input_data);
-        }
-        
-        Ok(())
-    }
-
     /// Handles an [`UpdateSkin`] packet.
     pub fn handle_skin_update(&self, packet: RVec) -> anyhow::Result<()> {
         let request = UpdateSkin::deserialize(packet.as_ref())?;
@@ -171,89 +126,6 @@ impl BedrockClient {
     /// Handles an [`Animation`] packet.
     pub fn handle_animation(&self, packet: RVec) -> anyhow::Result<()> {
         let request = Animate::deserialize(packet.as_ref())?;
-
-        let transaction = InventoryTransaction {
-            legacy_request_id: 0,
-            legacy_transactions: vec![],
-            transaction_type: TransactionType::Normal,
-            actions: vec![
-                TransactionAction {
-                    slot: 0,
-                    source_type: TransactionSourceType::Container { inventory_id: WindowId::Creative },
-                    new_item: ItemInstance::air(),
-                    old_item: ItemInstance {
-                        block_runtime_id: 13256,
-                        network_id: 5,
-                        blocking_tick: 0,
-                        can_destroy: vec![],
-                        can_place_on: vec![],
-                        count: 12,
-                        metadata: 0,
-                        nbt: HashMap::new(),
-                        stack_id: None,
-                    },
-                },
-                TransactionAction {
-                    slot: 0,
-                    source_type: TransactionSourceType::Container { inventory_id: WindowId::Ui },
-                    old_item: ItemInstance::air(),
-                    new_item: ItemInstance {
-                        block_runtime_id: 13256,
-                        network_id: 5,
-                        blocking_tick: 0,
-                        can_destroy: vec![],
-                        can_place_on: vec![],
-                        count: 12,
-                        metadata: 0,
-                        nbt: HashMap::new(),
-                        stack_id: None,
-                    },
-                },
-            ],
-        };
-        self.send(transaction)?;
-
-        let transaction = InventoryTransaction {
-            legacy_request_id: 0,
-            legacy_transactions: vec![],
-            transaction_type: TransactionType::Normal,
-            actions: vec![
-                TransactionAction {
-                    slot: 0,
-                    source_type: TransactionSourceType::Container { inventory_id: WindowId::Ui },
-                    new_item: ItemInstance::air(),
-                    old_item: ItemInstance {
-                        block_runtime_id: 13256,
-                        network_id: 5,
-                        blocking_tick: 0,
-                        can_destroy: vec![],
-                        can_place_on: vec![],
-                        count: 12,
-                        metadata: 0,
-                        nbt: HashMap::new(),
-                        stack_id: None,
-                    },
-                },
-                TransactionAction {
-                    slot: 0,
-                    source_type: TransactionSourceType::Container { inventory_id: WindowId::Inventory },
-                    old_item: ItemInstance::air(),
-                    new_item: ItemInstance {
-                        block_runtime_id: 13256,
-                        network_id: 5,
-                        blocking_tick: 0,
-                        can_destroy: vec![],
-                        can_place_on: vec![],
-                        count: 12,
-                        metadata: 0,
-                        nbt: HashMap::new(),
-                        stack_id: None,
-                    },
-                },
-            ],
-        };
-        self.send(transaction)?;
-
         tracing::debug!("{request:?}");
 
         Ok(())
@@ -376,7 +248,7 @@ impl BedrockClient {
             };
             tracing::Span::current().record("command", request.command);
 
-            let receiver = match self.commands.execute(Arc::clone(&self), request.command.to_owned()).await {
+            let receiver = match self.commands.execute(CommandSender::Player(Arc::clone(&self)), request.command.to_owned()).await {
                 Ok(r) => r,
                 Err(e) => {
                     tracing::error!("{e:#}");
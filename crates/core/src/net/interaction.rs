@@ -1,6 +1,6 @@
 use std::sync::atomic::Ordering;
 
-use proto::bedrock::{ABILITY_FLYING, AbilityData, AbilityLayer, AbilityType, ContainerClose, ContainerOpen, ContainerType, GameMode, Interact, InteractAction, INVENTORY_WINDOW_ID, MovePlayer, PlayerAction, PlayerActionType, UpdateAbilities, ABILITY_FLAG_END};
+use proto::bedrock::{ContainerClose, ContainerOpen, ContainerType, GameMode, Interact, InteractAction, INVENTORY_WINDOW_ID, MovePlayer, PlayerAction, PlayerActionType, UpdateAbilities};
 use util::{RVec, Deserialize};
 
 use super::BedrockClient;
@@ -39,14 +39,12 @@ impl BedrockClient {
 
     /// Handles a [`MovePlayer`] packet.
     pub fn handle_move_player(&self, packet: RVec) -> anyhow::Result<()> {
-        let _request = MovePlayer::deserialize(packet.as_ref())?;
+        let request = MovePlayer::deserialize(packet.as_ref())?;
 
-        Ok(())
-        // self.replicator.move_player(self.xuid(), &request).await?;
-
-        // request.mode = MovementMode::Normal;
+        // Movement is server authoritative, positions are only accepted through `PlayerAuthInput`.
+        tracing::trace!("Ignoring client-authoritative movement: {request:?}");
 
-        // self.broadcast(request)
+        Ok(())
     }
     
     /// Handles a [`PlayerAction`] packet.
@@ -70,22 +68,8 @@ impl BedrockClient {
         // Only allow flying if the player is in the correct gamemode.
         let gamemode = player.gamemode();
         if gamemode == GameMode::Creative || gamemode == GameMode::SurvivalSpectator {
-            self.send(UpdateAbilities(
-                AbilityData {
-                    command_permission_level: player.command_permission_level(),
-                    permission_level: player.permission_level(),
-                    unique_id: player.runtime_id(),
-                    layers: vec![
-                        AbilityLayer {
-                            fly_speed: 0.05,
-                            walk_speed: 0.1,
-                            values: ABILITY_FLYING,
-                            abilities: ABILITY_FLAG_END - 1,
-                            ability_type: AbilityType::Base
-                        }
-                    ]
-                },
-            ))?;
+            player.is_flying.store(true, Ordering::Relaxed);
+            self.send(UpdateAbilities(player.ability_data()))?;
         }
 
         Ok(())
@@ -95,22 +79,8 @@ impl BedrockClient {
     fn action_stop_flying(&self, _action: PlayerAction) -> anyhow::Result<()> {
         let player = self.player()?;
 
-        self.send(UpdateAbilities(
-            AbilityData {
-                command_permission_level: player.command_permission_level,
-                permission_level: player.permission_level(),
-                unique_id: player.runtime_id(),
-                layers: vec![
-                    AbilityLayer {
-                        fly_speed: 0.05,
-                        walk_speed: 0.1,
-                        values: 0,
-                        abilities: ABILITY_FLAG_END - 1,
-                        ability_type: AbilityType::Base
-                    }
-                ]
-            }
-        ))?;
+        player.is_flying.store(false, Ordering::Relaxed);
+        self.send(UpdateAbilities(player.ability_data()))?;
 
         Ok(())
     }
@@ -0,0 +1,140 @@
+use proto::bedrock::{
+    GameMode, InventoryContent, InventorySlot, InventoryTransaction, ItemInstance, ItemStackRequest, ItemStackResponse, StackRequest,
+    StackResponse, StackResponseContainerInfo, StackResponseSlotInfo, StackResponseStatus, TransactionType, UseItemAction,
+};
+use util::{Deserialize, RVec, Vector};
+
+use super::BedrockClient;
+use crate::item::{same_item, InventoryWindow};
+
+impl BedrockClient {
+    /// Handles an [`InventoryTransaction`] packet.
+    ///
+    /// Transactions that do not match the server's view of the inventory are rejected and the inventory is resent.
+    pub fn handle_inventory_transaction(&self, packet: RVec) -> anyhow::Result<()> {
+        let transaction = InventoryTransaction::deserialize(packet.as_ref())?;
+        let player = self.player()?;
+
+        let result = match &transaction.transaction_type {
+            TransactionType::Normal => {
+                let creative = player.gamemode() == GameMode::Creative;
+                player.inventory.write().apply_transaction(&transaction.actions, &self.instance().creative_items.stacks, creative)
+            }
+            // The client requests the inventory to be resent when it detects a mismatch.
+            TransactionType::Mismatch => return self.send_inventory(),
+            TransactionType::Use { action_type, block_position, face, hotbar_slot, held_item, .. } => {
+                self.verify_held_item(*hotbar_slot, held_item).and_then(|()| {
+                    let position = Vector::from([block_position.x, block_position.y as i32, block_position.z]);
+                    match action_type {
+                        UseItemAction::ClickBlock => self.place_block(&position, *face),
+                        UseItemAction::BreakBlock => self.break_block(&position),
+                        UseItemAction::ClickAir => Ok(()),
+                    }
+                })
+            }
+            TransactionType::UseOnEntity { hotbar_slot, held_item, .. }
+            | TransactionType::Release { hotbar_slot, held_item, .. } => self.verify_held_item(*hotbar_slot, held_item),
+        };
+
+        if let Err(err) = result {
+            tracing::debug!("Rejected inventory transaction: {err:#}");
+            return self.send_inventory();
+        }
+
+        Ok(())
+    }
+
+    /// Handles an [`ItemStackRequest`] packet.
+    pub fn handle_item_stack_request(&self, packet: RVec) -> anyhow::Result<()> {
+        let request = ItemStackRequest::deserialize(packet.as_ref())?;
+        self.apply_stack_requests(&request.requests)
+    }
+
+    /// Applies item stack requests to the player's inventory and lets the client know which ones were accepted.
+    pub(crate) fn apply_stack_requests(&self, requests: &[StackRequest]) -> anyhow::Result<()> {
+        let player = self.player()?;
+        let instance = self.instance();
+        let creative = player.gamemode() == GameMode::Creative;
+
+        let mut inventory = player.inventory.write();
+        let mut responses = Vec::with_capacity(requests.len());
+
+        for request in requests {
+            let changed = match inventory.apply_request(request, &instance.creative_items.stacks, creative) {
+                Ok(changed) => changed,
+                Err(err) => {
+                    tracing::debug!("Rejected item stack request {}: {err:#}", request.request_id);
+                    responses.push(StackResponse {
+                        status: StackResponseStatus::Error,
+                        request_id: request.request_id,
+                        containers: Vec::new(),
+                    });
+                    continue
+                }
+            };
+
+            let mut containers: Vec<StackResponseContainerInfo> = Vec::new();
+            for info in changed {
+                let Some(window) = InventoryWindow::from_container(info.container.container) else { continue };
+                let item = inventory.get(window, window.index(info.slot)).cloned().unwrap_or_else(ItemInstance::air);
+
+                let slot = StackResponseSlotInfo {
+                    slot: info.slot,
+                    hotbar_slot: info.slot,
+                    count: item.count as u8,
+                    stack_network_id: item.stack_id.unwrap_or(0),
+                    custom_name: "",
+                    durability_correction: 0,
+                };
+
+                match containers.iter_mut().find(|container| container.container == info.container) {
+                    Some(container) => container.slots.push(slot),
+                    None => containers.push(StackResponseContainerInfo { container: info.container, slots: vec![slot] }),
+                }
+            }
+
+            responses.push(StackResponse {
+                status: StackResponseStatus::Ok,
+                request_id: request.request_id,
+                containers,
+            });
+        }
+        drop(inventory);
+
+        self.send(ItemStackResponse { responses: &responses })
+    }
+
+    /// Sends the full content of the player's inventory.
+    pub fn send_inventory(&self) -> anyhow::Result<()> {
+        let inventory = self.player()?.inventory.read();
+        for window in [InventoryWindow::Main, InventoryWindow::Armor, InventoryWindow::Offhand] {
+            self.send(InventoryContent { window_id: window.window_id(), content: inventory.window(window) })?;
+        }
+
+        self.send_inventory_slot(InventoryWindow::Cursor, 0)
+    }
+
+    /// Sends the content of a single slot of the player's inventory.
+    pub fn send_inventory_slot(&self, window: InventoryWindow, slot: usize) -> anyhow::Result<()> {
+        let inventory = self.player()?.inventory.read();
+        let Some(item) = inventory.get(window, slot) else {
+            anyhow::bail!("Slot {slot} is out of range for {window:?}");
+        };
+
+        self.send(InventorySlot { window_id: window.window_id(), slot: slot as u32, item })
+    }
+
+    /// Verifies that the player is holding the item that the client claims it is holding.
+    fn verify_held_item(&self, hotbar_slot: i32, item: &ItemInstance) -> anyhow::Result<()> {
+        let inventory = self.player()?.inventory.read();
+        if hotbar_slot != i32::from(inventory.held_slot()) {
+            anyhow::bail!("Client used hotbar slot {hotbar_slot} but slot {} is selected", inventory.held_slot());
+        }
+
+        if !same_item(inventory.held_item(), item) {
+            anyhow::bail!("Client used an item that it is not holding");
+        }
+
+        Ok(())
+    }
+}
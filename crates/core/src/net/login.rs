@@ -1,10 +1,10 @@
 use level::PaletteEntry;
 use proto::bedrock::{
-    BiomeDefinitionList, BroadcastIntent, CacheStatus, ChatRestrictionLevel, ChunkRadiusReply, ChunkRadiusRequest, ClientToServerHandshake,
-    ConnectedPacket, CreativeContent, Difficulty, DisconnectReason, EditorWorldType, ExperimentData, GameMode, GameRule, HeightmapType,
+    BiomeDefinitionList, BroadcastIntent, CacheStatus, ChatRestrictionLevel, ChunkRadiusReply, ChunkRadiusRequest, ClientToServerHandshake, CommandPermissionLevel,
+    ConnectedPacket, CreativeContent, Difficulty, DisconnectReason, EditorWorldType, ExperimentData, GameMode, HeightmapType,
     InventoryTransaction, ItemInstance, LevelChunk, Login, NetworkChunkPublisherUpdate, NetworkSettings, PermissionLevel, PlayStatus,
-    PlayerMovementSettings, PlayerMovementType, PropertyData, RequestNetworkSettings, ResourcePackClientResponse, ResourcePackStack,
-    ResourcePacksInfo, ServerToClientHandshake, SetLocalPlayerAsInitialized, SpawnBiomeType, StartGame, Status, SubChunkEntry, SubChunkRequestMode,
+    PlayerMovementSettings, PlayerMovementType, PropertyData, RequestNetworkSettings, ResourcePackClientResponse, ResourcePackStatus,
+    ServerToClientHandshake, SetLocalPlayerAsInitialized, SetTime, SpawnBiomeType, StartGame, Status, SubChunkEntry, SubChunkRequest, SubChunkRequestMode,
     SubChunkResponse, SubChunkResult, TextData, TextMessage, TransactionAction, TransactionSourceType, TransactionType, UpdateBlock,
     UpdateBlockFlags, ViolationWarning, WindowId, WorldGenerator, CLIENT_VERSION_STRING, PROTOCOL_VERSION,
 };
@@ -75,9 +75,6 @@ impl BedrockClient {
 
         self.send(NetworkChunkPublisherUpdate { position: (0, 0, 0).into(), radius: 12 })?;
 
-        let res = self.viewer.load_offsets((0, 0, 0).into(), &[(0, 0, 0).into()], Dimension::Overworld)?;
-        tracing::debug!("res {res:?}");
-
         // self.send(LevelChunk {
         //     blob_hashes: None,
         //     coordinates: (0, 0).into(),
@@ -95,32 +92,8 @@ impl BedrockClient {
         //     block_runtime_id: 13256
         // })?;
 
-        // Add player to other's player lists
-
         // Tell rest of server that this client has joined...
         {
-            // let identity_data = self.get_identity_data()?;
-            // let _user_data = self.get_user_data()?;
-
-            // self.broadcast_others(PlayerListAdd {
-            //     entries: &[PlayerListAddEntry {
-            //         uuid: identity_data.uuid,
-            //         entity_id: self.player.read().runtime_id as i64,
-            //         username: &identity_data.display_name,
-            //         xuid: identity_data.xuid,
-            //         device_os: user_data.build_platform,
-            //         skin: self.player.read().skin.as_ref().ok_or_else(
-            //             || {
-            //                 error!(
-            //                     NotInitialized,
-            //                     "Skin data has not been initialised"
-            //                 )
-            //             },
-            //         )?,
-            //         host: false,
-            //     }],
-            // })?;
-
             // let level_chunk = self.level_manager.request_biomes(Vector::from([0, 0]), Dimension::Overworld)?;
             // dbg!(level_chunk);
 
@@ -139,10 +112,8 @@ impl BedrockClient {
             tracing::debug!("stack: {stack:?}");
         }   
 
-        // ...then tell the client about all the other players.
-        // TODO
-
-        Ok(())
+        // ...then add the player to the world of everyone else and vice versa.
+        self.spawn()
     }
 
     /// Handles a [`ChunkRadiusRequest`] packet by returning the maximum allowed render distance.
@@ -163,8 +134,37 @@ impl BedrockClient {
         self.send(ChunkRadiusReply { allowed_radius })?;
 
         self.viewer.update_radius(allowed_radius as u16);
+        self.send_chunks()?;
+
+        // The render distance can also be changed after the player has spawned.
+        self.sync_entities()
+    }
+
+    /// Handles a [`SubChunkRequest`] packet by loading the requested subchunks from the level.
+    #[tracing::instrument(
+        skip_all,
+        name = "BedrockUser::handle_subchunk_request",
+        fields(
+            username = %self.name().unwrap_or("<unknown>")
+        )
+    )]
+    pub fn handle_subchunk_request(&self, packet: RVec) -> anyhow::Result<()> {
+        let request = SubChunkRequest::deserialize(packet.as_ref())?;
+
+        let cache = self.supports_cache.load(Ordering::Relaxed).then_some(&self.blobs);
+        let response = self.viewer.load_offsets(request.position, &request.offsets, request.dimension, cache)?;
 
-        // self.player().viewer.set_radius(allowed_radius);
+        self.send(response)
+    }
+
+    /// Sends the biomes of all chunks that came within render distance since the last call.
+    ///
+    /// The client requests the block data of these chunks itself using [`SubChunkRequest`]s.
+    pub(crate) fn send_chunks(&self) -> anyhow::Result<()> {
+        let cache = self.supports_cache.load(Ordering::Relaxed).then_some(&self.blobs);
+        for coordinates in self.viewer.unsent_chunks() {
+            self.send(self.viewer.level_chunk(coordinates, Dimension::Overworld, cache)?)?;
+        }
 
         Ok(())
     }
@@ -173,73 +173,88 @@ impl BedrockClient {
     pub fn handle_resource_client_response(&self, packet: RVec) -> anyhow::Result<()> {
         self.expected.store(u32::MAX, Ordering::SeqCst);
 
-        let _request = ResourcePackClientResponse::deserialize(packet.as_ref())?;
-        tracing::debug!("Received resource pack client response");
+        let request = ResourcePackClientResponse::deserialize(packet.as_ref())?;
+        tracing::debug!("Received resource pack client response: {:?}", request.status);
+
+        match request.status {
+            ResourcePackStatus::SendPacks => return self.send_pack_data_info(&request.pack_ids),
+            ResourcePackStatus::Refused if self.instance().config().packs().required => {
+                return self.kick_with_reason("You must accept the resource packs to join this server", DisconnectReason::ResourcePackProblem);
+            }
+            ResourcePackStatus::HaveAllPacks | ResourcePackStatus::Refused => return self.send_pack_stack(),
+            ResourcePackStatus::None => return Ok(()),
+            ResourcePackStatus::Completed => (),
+        }
+
+        let instance = self.instance();
+        let level = instance.level();
+        let game_rules = level.game_rules();
+        let level_settings = level.settings();
 
-        // TODO: Implement resource packs.
+        let experiments = level.experiments();
+        let experiments: Vec<ExperimentData> = experiments.iter().map(|(name, enabled)| ExperimentData { name: name.as_str(), enabled: *enabled }).collect();
 
         let start_game = StartGame {
-            entity_id: 1,
-            runtime_id: 1,
+            entity_id: self.unique_id()?,
+            runtime_id: self.runtime_id()?,
             game_mode: self.player()?.gamemode(),
-            position: Vector::from([0.0, 6.0, 0.0]),
+            position: self.player()?.position(),
             rotation: Vector::from([0.0, 0.0]),
-            world_seed: 0,
+            world_seed: level_settings.random_seed as u64,
             spawn_biome_type: SpawnBiomeType::Default,
             custom_biome_name: "plains",
             dimension: Dimension::Overworld,
-            generator: WorldGenerator::Infinite,
-            world_game_mode: GameMode::Survival,
-            hardcore: false,
-            difficulty: Difficulty::Normal,
-            world_spawn: BlockPosition::new(0, 60, 0),
+            generator: WorldGenerator::try_from(level_settings.generator).unwrap_or(WorldGenerator::Infinite),
+            world_game_mode: GameMode::try_from(level_settings.game_mode).unwrap_or(GameMode::Survival),
+            hardcore: level_settings.hardcore.unwrap_or(false),
+            difficulty: Difficulty::try_from(level_settings.difficulty).unwrap_or(Difficulty::Normal),
+            // The spawn height is unsigned in the protocol, so spawn points below zero are moved up.
+            world_spawn: BlockPosition::new(level_settings.spawn_x, u32::try_from(level_settings.spawn_y).unwrap_or(0), level_settings.spawn_z),
             achievements_disabled: true,
             editor_world_type: EditorWorldType::NotEditor,
-            created_in_editor: false,
-            exported_from_editor: false,
-            day_cycle_lock_time: 0,
-            education_features_enabled: true,
-            rain_level: 0.0,
-            lightning_level: 0.0,
-            confirmed_platform_locked_content: false,
-            broadcast_to_lan: true,
+            created_in_editor: level_settings.created_in_editor,
+            exported_from_editor: level_settings.exported_from_editor,
+            day_cycle_lock_time: level_settings.daylight_cycle,
+            education_features_enabled: level_settings.education_features_enabled,
+            rain_level: level_settings.rain_level,
+            lightning_level: level_settings.lightning_level,
+            confirmed_platform_locked_content: level_settings.confirmed_platform_locked_content,
+            broadcast_to_lan: level_settings.lan_broadcast,
             xbox_broadcast_intent: BroadcastIntent::Public,
             platform_broadcast_intent: BroadcastIntent::Public,
-            enable_commands: true,
-            texture_packs_required: true,
-            // FIXME: Reimplement with new level interface.
-            // game_rules: &self.level.get_game_rules(),
-            game_rules: &[GameRule::ShowCoordinates(true)],
-            experiments: &[],
-            experiments_previously_enabled: false,
-            bonus_chest_enabled: false,
-            starter_map_enabled: false,
-            permission_level: PermissionLevel::Operator,
-            server_chunk_tick_range: 12,
-            has_locked_behavior_pack: false,
-            has_locked_resource_pack: false,
-            is_from_locked_world_template: false,
-            use_msa_gamertags_only: false,
-            is_from_world_template: false,
-            is_world_template_option_locked: false,
-            only_spawn_v1_villagers: false,
+            enable_commands: level_settings.commands_enabled,
+            texture_packs_required: self.instance().config().packs().required,
+            game_rules: &game_rules,
+            experiments: &experiments,
+            experiments_previously_enabled: level_settings.experiments.experiments_ever_used,
+            bonus_chest_enabled: level_settings.bonus_chest_enabled,
+            starter_map_enabled: level_settings.start_with_map_enabled,
+            permission_level: self.player()?.permission_level(),
+            server_chunk_tick_range: level_settings.server_chunk_tick_range,
+            has_locked_behavior_pack: level_settings.has_locked_behavior_pack,
+            has_locked_resource_pack: level_settings.has_locked_resource_pack,
+            is_from_locked_world_template: level_settings.is_from_locked_template,
+            use_msa_gamertags_only: level_settings.use_msa_gamertags_only,
+            is_from_world_template: level_settings.is_from_world_template,
+            is_world_template_option_locked: level_settings.is_world_template_option_locked,
+            only_spawn_v1_villagers: level_settings.spawn_v1_villagers,
             persona_disabled: false,
             custom_skins_disabled: false,
             emote_chat_muted: false,
-            limited_world_width: 0,
-            limited_world_height: 0,
+            limited_world_width: level_settings.limited_world_width,
+            limited_world_height: level_settings.limited_world_depth,
             force_experimental_gameplay: false,
             chat_restriction_level: ChatRestrictionLevel::None,
             disable_player_interactions: false,
             level_id: "",
-            level_name: "Mirai Dedicated Server",
+            level_name: &level_settings.level_name,
             template_content_identity: "",
             movement_settings: PlayerMovementSettings {
                 movement_type: PlayerMovementType::ServerAuthoritative,
                 rewind_history_size: 0,
                 server_authoritative_breaking: true,
             },
-            time: 0,
+            time: self.instance().ticker().time(),
             enchantment_seed: 0,
             // block_properties: &[BlockEntry {
             //     name: "minecraft:bedrock".to_owned(),
@@ -248,7 +263,8 @@ impl BedrockClient {
             block_properties: &[],
             item_properties: &[],
             property_data: PropertyData {},
-            server_authoritative_inventory: false,
+            // Requests that involve crafting are answered with a failed response until crafting is implemented.
+            server_authoritative_inventory: true,
             game_version: CLIENT_VERSION_STRING,
             // property_data: nbt::Value::Compound(HashMap::new()),
             server_block_state_checksum: 0,
@@ -258,10 +274,12 @@ impl BedrockClient {
             server_authoritative_sounds: true,
         };
         self.send(start_game)?;
+        drop(level_settings);
+        self.send(SetTime { time: self.instance().ticker().time() as i32 })?;
 
         self.send(BiomeDefinitionList)?;
 
-        let available_commands = self.commands.available_commands();
+        let available_commands = self.commands.available_commands_for(self.player()?.command_permission_level());
         self.send(available_commands)?;
 
         tracing::debug!("{:?}", self.instance().creative_items.stacks);
@@ -270,6 +288,7 @@ impl BedrockClient {
             items: &self.instance().creative_items.stacks,
         };
         self.send(creative_content)?;
+        self.send_inventory()?;
 
         let play_status = PlayStatus { status: Status::PlayerSpawn };
         self.send(play_status)?;
@@ -295,29 +314,7 @@ impl BedrockClient {
         let response = PlayStatus { status: Status::LoginSuccess };
         self.send(response)?;
 
-        // TODO: Implement resource packs
-        // FIXME: Sometimes these two packets might be sent in different ticks instead of being batched together.
-        // This will result in the client sending a resource pack response which we do not want.
-        let pack_info = ResourcePacksInfo {
-            required: false,
-            scripting_enabled: false,
-            forcing_server_packs: false,
-            has_addons: false,
-            behavior_info: &[],
-            resource_info: &[],
-        };
-        self.send(pack_info)?;
-
-        let pack_stack = ResourcePackStack {
-            forced_to_accept: false,
-            resource_packs: &[],
-            behavior_packs: &[],
-            game_version: CLIENT_VERSION_STRING,
-            experiments: &[],
-            experiments_previously_toggled: false,
-            includes_editor_packs: false,
-        };
-        self.send(pack_stack)?;
+        self.send_pack_info()?;
 
         Ok(())
     }
@@ -369,11 +366,20 @@ impl BedrockClient {
             return self.kick_with_reason("Unexpected login", DisconnectReason::UnexpectedPacket);
         }
 
-        if self.player.set(PlayerData::new(request.skin)).is_err() {
+        let runtime_id = self.instance().entities().allocate_id();
+        let unique_id = self.instance().entities().allocate_unique_id();
+        let mut player = PlayerData::new(request.skin, runtime_id, unique_id, self.name()?);
+        if self.instance().config().is_operator(self.name()?) {
+            *player.permission_level.get_mut() = PermissionLevel::Operator;
+            *player.command_permission_level.get_mut() = CommandPermissionLevel::Admin;
+        }
+
+        if self.player.set(player).is_err() {
             anyhow::bail!("Player data was already set");
         };
 
-        Ok(())
+        self.instance().clients().register(self)?;
+        self.load_player_data()
     }
 
     /// Handles a [`RequestNetworkSettings`] packet.
@@ -9,6 +9,13 @@ glob_export!(level);
 glob_export!(client);
 glob_export!(clients);
 glob_export!(login);
+glob_export!(packs);
 glob_export!(interaction);
+glob_export!(inventory);
+glob_export!(blocks);
+glob_export!(movement);
+glob_export!(replication);
+glob_export!(persistence);
 glob_export!(handlers);
 glob_export!(forwardable);
+glob_export!(cache);
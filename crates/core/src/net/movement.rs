@@ -0,0 +1,339 @@
+use std::sync::atomic::Ordering;
+
+use proto::bedrock::{ActorFlag, GameMode, InputData, MovePlayer, MovementMode, NetworkChunkPublisherUpdate, PlayerAuthInput, SetActorData, TeleportCause};
+use proto::types::Dimension;
+use util::{Deserialize, RVec, Vector};
+
+use super::BedrockClient;
+
+/// Height of the eyes of a player above its feet.
+/// Positions in movement packets are given at eye height.
+pub const PLAYER_EYE_HEIGHT: f32 = 1.62;
+
+/// Maximum horizontal distance in blocks a player can walk or sprint in a single tick.
+///
+/// Sprint-jumping reaches about 0.6 blocks per tick, the remainder accounts for effects and latency.
+const MAX_WALK_SPEED: f32 = 1.0;
+/// Maximum horizontal distance in blocks a flying player can travel in a single tick.
+const MAX_FLY_SPEED: f32 = 2.5;
+/// Maximum distance in blocks a gliding player can travel in a single tick.
+const MAX_GLIDE_SPEED: f32 = 4.0;
+/// Maximum height in blocks a player that is not allowed to fly can ascend in a single tick.
+///
+/// A jump moves the player up by roughly 0.42 blocks in the first tick.
+const MAX_ASCENT: f32 = 0.6;
+/// Maximum distance in blocks that a player can move in a single update, regardless of the amount of ticks elapsed.
+/// Anything further than this is considered a teleport.
+const MAX_TELEPORT_DISTANCE: f32 = 10.0;
+/// Maximum amount of ticks that the movement limits are scaled by when the client skips ticks.
+const MAX_ELAPSED_TICKS: u64 = 20;
+/// Maximum amount of ticks a player that is not allowed to fly can stay in the air without descending.
+///
+/// A jump reaches its highest point after about 6 ticks, the remainder accounts for jump boost and latency.
+const MAX_AIRBORNE_TICKS: u64 = 20;
+/// Half of the width of a player's hitbox.
+const PLAYER_HALF_WIDTH: f32 = 0.3;
+
+/// Movement of a player as last accepted by the server.
+#[derive(Debug, Clone)]
+pub struct MovementState {
+    /// Position of the player.
+    pub position: Vector<f32, 3>,
+    /// Rotation of the player.
+    /// x and y components are general rotation.
+    /// z component is head yaw.
+    pub rotation: Vector<f32, 3>,
+    /// Change in position during the last accepted update.
+    pub velocity: Vector<f32, 3>,
+    /// Client tick of the last accepted update.
+    pub tick: u64,
+    /// Server tick at which the last update was accepted.
+    pub server_tick: u64,
+    /// Amount of ticks the player has not descended since it was last known to stand on a block.
+    pub airborne_ticks: u64,
+    /// Whether the server corrected the player's position and is waiting for the client to acknowledge it.
+    pub awaiting_teleport: bool,
+    /// Whether the player is gliding with an elytra.
+    pub gliding: bool,
+}
+
+impl MovementState {
+    /// Creates a new movement state for a player standing still at the given position.
+    pub fn new(position: Vector<f32, 3>) -> MovementState {
+        MovementState {
+            position,
+            rotation: Vector::from([0.0; 3]),
+            velocity: Vector::from([0.0; 3]),
+            tick: 0,
+            server_tick: 0,
+            airborne_ticks: 0,
+            awaiting_teleport: false,
+            gliding: false,
+        }
+    }
+}
+
+/// Reason why a movement update was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MovementViolation {
+    /// The position contained NaN or infinite values.
+    InvalidPosition,
+    /// The player moved faster than allowed.
+    Speed,
+    /// The player ascended without being allowed to fly.
+    Flight,
+    /// The player moved too far in a single update.
+    Teleport,
+}
+
+/// Limits that a movement update is checked against.
+#[derive(Debug, Copy, Clone)]
+pub struct MovementLimits {
+    /// Whether the player is allowed to fly.
+    pub may_fly: bool,
+    /// Whether the player is gliding with an elytra.
+    pub gliding: bool,
+    /// Amount of ticks the player has been in the air without descending, including this update.
+    pub airborne_ticks: u64,
+}
+
+impl MovementLimits {
+    /// Verifies a movement from `from` to `to` that took the given amount of ticks.
+    pub fn check(&self, from: &Vector<f32, 3>, to: &Vector<f32, 3>, ticks: u64) -> Result<(), MovementViolation> {
+        if !to.x.is_finite() || !to.y.is_finite() || !to.z.is_finite() {
+            return Err(MovementViolation::InvalidPosition);
+        }
+
+        let (dx, dy, dz) = (to.x - from.x, to.y - from.y, to.z - from.z);
+        if (dx * dx + dy * dy + dz * dz).sqrt() > MAX_TELEPORT_DISTANCE {
+            return Err(MovementViolation::Teleport);
+        }
+
+        let ticks = ticks.clamp(1, MAX_ELAPSED_TICKS) as f32;
+        let max_speed = if self.gliding {
+            MAX_GLIDE_SPEED
+        } else if self.may_fly {
+            MAX_FLY_SPEED
+        } else {
+            MAX_WALK_SPEED
+        };
+
+        if (dx * dx + dz * dz).sqrt() > max_speed * ticks {
+            return Err(MovementViolation::Speed);
+        }
+
+        if !self.may_fly && !self.gliding && (dy > MAX_ASCENT * ticks || self.airborne_ticks > MAX_AIRBORNE_TICKS) {
+            return Err(MovementViolation::Flight);
+        }
+
+        Ok(())
+    }
+}
+
+impl BedrockClient {
+    /// Handles a [`PlayerAuthInput`] packet. These are sent every tick and are used
+    /// for server authoritative player movement.
+    pub fn handle_auth_input(&self, packet: RVec) -> anyhow::Result<()> {
+        let input = PlayerAuthInput::deserialize(packet.as_ref())?;
+        let player = self.player()?;
+
+        self.update_input_flags(input.input_data)?;
+        if let Some(request) = &input.item_stack {
+            self.apply_stack_requests(std::slice::from_ref(request))?;
+        }
+        if let Some(actions) = &input.block_actions {
+            self.handle_block_actions(actions)?;
+        }
+
+        let mut movement = player.movement.write();
+        if input.input_data.start_gliding() {
+            movement.gliding = true;
+        } else if input.input_data.stop_gliding() {
+            movement.gliding = false;
+        }
+
+        if movement.awaiting_teleport {
+            // Ignore any movement until the client has processed the correction.
+            if !input.input_data.handled_teleport() {
+                return Ok(());
+            }
+
+            movement.awaiting_teleport = false;
+        }
+
+        // Client ticks must increase, anything else is a replayed or reordered packet.
+        if input.tick <= movement.tick {
+            tracing::debug!("Ignored movement with tick {} after tick {}", input.tick, movement.tick);
+            return Ok(());
+        }
+
+        // The client tick is only trusted as far as the server has advanced since the last update,
+        // otherwise a client could claim to have skipped ticks to move further.
+        let server_tick = self.instance().ticker().current();
+        let elapsed = (input.tick - movement.tick).min(server_tick.saturating_sub(movement.server_tick).max(1));
+
+        let gamemode = player.gamemode();
+        let may_fly = player.is_flying.load(Ordering::Relaxed)
+            || matches!(gamemode, GameMode::Creative | GameMode::Spectator | GameMode::SurvivalSpectator | GameMode::CreativeSpectator);
+
+        let mut airborne_ticks = if input.position.y < movement.position.y { 0 } else { movement.airborne_ticks + elapsed };
+        if airborne_ticks > MAX_AIRBORNE_TICKS && !may_fly && !movement.gliding && self.is_supported(&input.position)? {
+            // Only look up the blocks below the player once it has not descended for a while,
+            // to avoid reading the level on every update.
+            airborne_ticks = 0;
+        }
+        movement.airborne_ticks = airborne_ticks;
+
+        let limits = MovementLimits { may_fly, gliding: movement.gliding, airborne_ticks };
+        if let Err(violation) = limits.check(&movement.position, &input.position, elapsed) {
+            tracing::debug!("Rejected movement to {:?}: {violation:?}", input.position);
+
+            movement.awaiting_teleport = true;
+            movement.velocity = Vector::from([0.0; 3]);
+
+            let correction = MovePlayer {
+                runtime_id: player.runtime_id(),
+                translation: movement.position.clone(),
+                pitch: movement.rotation.x,
+                yaw: movement.rotation.y,
+                head_yaw: movement.rotation.z,
+                mode: MovementMode::Reset,
+                on_ground: false,
+                ridden_runtime_id: 0,
+                teleport_cause: TeleportCause::Unknown,
+                teleport_source_type: 0,
+                tick: input.tick,
+            };
+            drop(movement);
+
+            return self.send(correction);
+        }
+
+        let rotation = Vector::from([input.pitch, input.yaw, input.head_yaw]);
+        let moved = movement.position != input.position || movement.rotation != rotation;
+
+        movement.position = input.position.clone();
+        movement.rotation = rotation;
+        movement.velocity = input.delta.clone();
+        movement.tick = input.tick;
+        movement.server_tick = server_tick;
+        drop(movement);
+
+        if moved {
+            self.relay_movement(MovePlayer {
+                runtime_id: player.runtime_id(),
+                translation: input.position.clone(),
+                pitch: input.pitch,
+                yaw: input.yaw,
+                head_yaw: input.head_yaw,
+                mode: MovementMode::Normal,
+                on_ground: false,
+                ridden_runtime_id: 0,
+                teleport_cause: TeleportCause::Unknown,
+                teleport_source_type: 0,
+                tick: input.tick,
+            })?;
+        }
+
+        if self.viewer.update_position(Vector::from([input.position.x, input.position.z])) {
+            // Let the client know that it should request the chunks around its new position.
+            self.send(NetworkChunkPublisherUpdate {
+                position: Vector::from([input.position.x.floor() as i32, input.position.y.floor() as i32, input.position.z.floor() as i32]),
+                radius: u32::from(self.viewer.radius()) * 16,
+            })?;
+
+            self.send_chunks()?;
+            self.sync_entities()?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether a player with its eyes at the given position is standing on or inside a block.
+    ///
+    /// This checks the blocks below every corner of the player's hitbox, so that players standing on the edge of a block
+    /// are considered supported. Blocks such as water and ladders are found because the feet are checked as well.
+    fn is_supported(&self, position: &Vector<f32, 3>) -> anyhow::Result<bool> {
+        let instance = self.instance();
+        let feet = position.y - PLAYER_EYE_HEIGHT;
+
+        let corners = [-PLAYER_HALF_WIDTH, PLAYER_HALF_WIDTH];
+        for y in [(feet - 0.05).floor() as i32, feet.floor() as i32] {
+            for dx in corners {
+                for dz in corners {
+                    let x = (position.x + dx).floor() as i32;
+                    let z = (position.z + dz).floor() as i32;
+
+                    let block = instance.level().block(&Vector::from([x, y, z]), Dimension::Overworld)?;
+                    if block.is_some_and(|block| block.name != "minecraft:air") {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Updates the metadata flags that are toggled by player input, such as sneaking,
+    /// and notifies nearby players if any of them changed.
+    fn update_input_flags(&self, input: InputData) -> anyhow::Result<()> {
+        let toggles = [
+            (ActorFlag::Sneaking, input.start_sneaking(), input.stop_sneaking()),
+            (ActorFlag::Sprinting, input.start_sprinting(), input.stop_sprinting()),
+            (ActorFlag::Swimming, input.start_swimming(), input.stop_swimming()),
+            (ActorFlag::Gliding, input.start_gliding(), input.stop_gliding()),
+            (ActorFlag::Crawling, input.start_crawling(), input.stop_crawling()),
+        ];
+
+        let player = self.player()?;
+        let mut metadata = player.metadata.write();
+
+        let mut changed = false;
+        for (flag, start, stop) in toggles {
+            if (start || stop) && metadata.flag(flag) != start {
+                metadata.set_flag(flag, start);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return Ok(())
+        }
+
+        let metadata = parking_lot::RwLockWriteGuard::downgrade(metadata);
+        self.relay(SetActorData {
+            runtime_id: player.runtime_id(),
+            metadata: &metadata,
+            tick: self.instance().ticker().current(),
+        })
+    }
+
+    /// Teleports the player to the given position.
+    pub fn teleport(&self, position: Vector<f32, 3>) -> anyhow::Result<()> {
+        let player = self.player()?;
+
+        let mut movement = player.movement.write();
+        movement.position = position.clone();
+        movement.velocity = Vector::from([0.0; 3]);
+        movement.awaiting_teleport = true;
+
+        let packet = MovePlayer {
+            runtime_id: player.runtime_id(),
+            translation: position,
+            pitch: movement.rotation.x,
+            yaw: movement.rotation.y,
+            head_yaw: movement.rotation.z,
+            mode: MovementMode::Teleport,
+            on_ground: false,
+            ridden_runtime_id: 0,
+            teleport_cause: TeleportCause::Command,
+            teleport_source_type: 0,
+            tick: movement.tick,
+        };
+        drop(movement);
+
+        self.relay_movement(packet.clone())?;
+        self.send(packet)
+    }
+}
@@ -0,0 +1,92 @@
+use proto::bedrock::{
+    ResourcePackChunkData, ResourcePackChunkRequest, ResourcePackDataInfo, ResourcePackStack, ResourcePackStackEntry,
+    ResourcePacksInfo, CLIENT_VERSION_STRING,
+};
+use util::{Deserialize, RVec};
+
+use super::BedrockClient;
+use crate::pack::{PackKind, PACK_CHUNK_SIZE};
+
+impl BedrockClient {
+    /// Announces the server's resource and behavior packs.
+    ///
+    /// The client responds with the packs it has yet to download.
+    pub(crate) fn send_pack_info(&self) -> anyhow::Result<()> {
+        let instance = self.instance();
+        let packs = instance.packs();
+
+        let behavior_info = packs.behavior_info();
+        let resource_info = packs.resource_info();
+
+        self.send(ResourcePacksInfo {
+            required: instance.config().packs().required,
+            scripting_enabled: behavior_info.iter().any(|pack| pack.has_scripts),
+            forcing_server_packs: false,
+            has_addons: !behavior_info.is_empty(),
+            behavior_info: &behavior_info,
+            resource_info: &resource_info,
+        })
+    }
+
+    /// Sends the order in which the client should apply the packs.
+    pub(crate) fn send_pack_stack(&self) -> anyhow::Result<()> {
+        let instance = self.instance();
+        let packs = instance.packs();
+
+        let resource_packs: Vec<ResourcePackStackEntry<'_>> = packs.of_kind(PackKind::Resources).map(|pack| pack.stack_entry()).collect();
+        let behavior_packs: Vec<ResourcePackStackEntry<'_>> = packs.of_kind(PackKind::Behavior).map(|pack| pack.stack_entry()).collect();
+
+        self.send(ResourcePackStack {
+            forced_to_accept: instance.config().packs().required,
+            resource_packs: &resource_packs,
+            behavior_packs: &behavior_packs,
+            game_version: CLIENT_VERSION_STRING,
+            experiments: &[],
+            experiments_previously_toggled: false,
+            includes_editor_packs: false,
+        })
+    }
+
+    /// Tells the client how the requested packs will be split up into chunks.
+    pub(crate) fn send_pack_data_info(&self, pack_ids: &[&str]) -> anyhow::Result<()> {
+        let instance = self.instance();
+        for id in pack_ids {
+            let Some(pack) = instance.packs().get(id) else {
+                anyhow::bail!("Client requested unknown pack {id}");
+            };
+
+            self.send(ResourcePackDataInfo {
+                uuid: id,
+                chunk_size: PACK_CHUNK_SIZE as u32,
+                chunk_count: pack.chunk_count(),
+                size: pack.size(),
+                hash: pack.hash(),
+                premium: false,
+                pack_type: pack.pack_type(),
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Handles a [`ResourcePackChunkRequest`] packet.
+    pub fn handle_pack_chunk_request(&self, packet: RVec) -> anyhow::Result<()> {
+        let request = ResourcePackChunkRequest::deserialize(packet.as_ref())?;
+
+        let instance = self.instance();
+        let Some(pack) = instance.packs().get(request.uuid) else {
+            anyhow::bail!("Client requested chunk of unknown pack {}", request.uuid);
+        };
+
+        let Some(data) = pack.chunk(request.chunk_index) else {
+            anyhow::bail!("Client requested chunk {} of pack {} which only has {} chunks", request.chunk_index, request.uuid, pack.chunk_count());
+        };
+
+        self.send(ResourcePackChunkData {
+            uuid: request.uuid,
+            chunk_index: request.chunk_index,
+            offset: u64::from(request.chunk_index) * PACK_CHUNK_SIZE as u64,
+            data,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use level::{PlayerSave, SavedItem};
+use proto::bedrock::ItemInstance;
+use util::Vector;
+
+use super::{BedrockClient, PlayerData, PLAYER_EYE_HEIGHT};
+use crate::item::{creative_item, InventoryWindow};
+
+impl BedrockClient {
+    /// Restores the position and inventory that the player had when they last left the server.
+    ///
+    /// Nothing happens if the player has not joined before.
+    pub(crate) fn load_player_data(&self) -> anyhow::Result<()> {
+        let instance = self.instance();
+        let Some(save) = instance.level().player(self.uuid()?)? else {
+            return Ok(());
+        };
+
+        let player = self.player()?;
+        {
+            let mut movement = player.movement.write();
+            movement.position = Vector::from([save.position[0], save.position[1] + PLAYER_EYE_HEIGHT, save.position[2]]);
+            movement.rotation = Vector::from([save.rotation[0], save.rotation[1], save.rotation[1]]);
+        }
+
+        let mut inventory = player.inventory.write();
+        for (window, items) in [(InventoryWindow::Main, &save.inventory), (InventoryWindow::Armor, &save.armor), (InventoryWindow::Offhand, &save.offhand)] {
+            for saved in items {
+                let Some(item) = self.restore_item(saved)? else {
+                    continue
+                };
+
+                let restored = usize::try_from(saved.slot).map_err(anyhow::Error::from).and_then(|slot| inventory.set(window, slot, item));
+                if let Err(err) = restored {
+                    tracing::warn!("Dropping item {} in slot {} from the saved inventory of {}: {err:#}", saved.name, saved.slot, self.name()?);
+                }
+            }
+        }
+
+        inventory.set_held_slot(u8::try_from(save.selected_slot).unwrap_or(0))
+    }
+
+    /// Converts a saved item back into an item instance.
+    ///
+    /// Returns `None` if the item is unknown or has an invalid count.
+    fn restore_item(&self, saved: &SavedItem) -> anyhow::Result<Option<ItemInstance<'static>>> {
+        let instance = self.instance();
+        let Some(network_id) = instance.item_network_ids.get_id(&saved.name) else {
+            tracing::warn!("Dropping unknown item {} from the saved inventory of {}", saved.name, self.name()?);
+            return Ok(None)
+        };
+
+        let Some(count) = u16::try_from(saved.count).ok().filter(|count| *count > 0) else {
+            tracing::warn!("Dropping item {} with invalid count {} from the saved inventory of {}", saved.name, saved.count, self.name()?);
+            return Ok(None)
+        };
+
+        let metadata = saved.damage as u32;
+
+        // Creative items carry the block runtime ID that is required to place the item.
+        let mut item = instance.creative_items.stacks
+            .iter()
+            .find(|stack| stack.item_type.network_id == network_id && stack.item_type.meta == metadata)
+            .map_or_else(|| ItemInstance { network_id, count, metadata, ..ItemInstance::air() }, |stack| creative_item(stack, count));
+
+        if let Some(tag) = &saved.tag {
+            item.nbt = tag.clone();
+        }
+
+        Ok(Some(item))
+    }
+
+    /// Saves the position and inventory of the player so that they can be restored when they rejoin.
+    pub(crate) fn save_player_data(&self) -> anyhow::Result<()> {
+        let player = self.player()?;
+
+        let (position, rotation) = {
+            let movement = player.movement.read();
+            (movement.position.clone(), movement.rotation.clone())
+        };
+
+        let save = PlayerSave {
+            position: [position.x, position.y - PLAYER_EYE_HEIGHT, position.z],
+            rotation: [rotation.x, rotation.y],
+            game_mode: player.gamemode() as i32,
+            selected_slot: i32::from(player.inventory.read().held_slot()),
+            inventory: self.saved_items(player, InventoryWindow::Main),
+            armor: self.saved_items(player, InventoryWindow::Armor),
+            offhand: self.saved_items(player, InventoryWindow::Offhand),
+        };
+
+        self.instance().level().save_player(self.uuid()?, &save)
+    }
+
+    /// Converts the non-empty slots of an inventory window into their saved form.
+    fn saved_items(&self, player: &PlayerData, window: InventoryWindow) -> Vec<SavedItem> {
+        let instance = self.instance();
+        let inventory = player.inventory.read();
+
+        let items = inventory
+            .window(window)
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.network_id != 0)
+            .filter_map(|(slot, item)| {
+                Some(SavedItem {
+                    slot: i8::try_from(slot).ok()?,
+                    name: instance.item_network_ids.get_name(item.network_id)?.to_owned(),
+                    count: i8::try_from(item.count).ok()?,
+                    damage: item.metadata as i16,
+                    tag: (!item.nbt.is_empty()).then(|| item.nbt.clone()),
+                })
+            })
+            .collect();
+        drop(inventory);
+
+        items
+    }
+}
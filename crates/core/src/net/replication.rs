@@ -0,0 +1,279 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use parking_lot::RwLockReadGuard;
+use proto::bedrock::{AddPlayer, ConnectedPacket, ItemInstance, MovePlayer, PlayerListAdd, PlayerListAddEntry, PlayerListRemove, RemoveActor, Skin};
+use util::{Serialize, Vector};
+
+use super::{BedrockClient, PLAYER_EYE_HEIGHT};
+use crate::entity::Entity;
+
+impl BedrockClient {
+    /// Whether this client has spawned in the world and is visible to other players.
+    #[inline]
+    pub fn spawned(&self) -> bool {
+        self.spawned.load(Ordering::Relaxed)
+    }
+
+    /// Whether the given position is within the render distance of this client.
+    #[inline]
+    pub fn can_see(&self, position: &Vector<f32, 3>) -> bool {
+        self.viewer.in_range(Vector::from([position.x, position.z]))
+    }
+
+    /// Returns all other clients that have spawned in the world.
+    fn spawned_others(&self) -> Vec<Arc<BedrockClient>> {
+        self.instance()
+            .clients()
+            .connected()
+            .into_iter()
+            .filter(|client| !std::ptr::eq(Arc::as_ptr(client), self) && client.spawned())
+            .collect()
+    }
+
+    /// Sends a packet to all other spawned clients that have this player in their world.
+    ///
+    /// A failure to send to one client does not prevent the packet from being sent to the others.
+    pub fn relay<P: ConnectedPacket + Serialize + Clone>(&self, packet: P) -> anyhow::Result<()> {
+        let runtime_id = self.runtime_id()?;
+        for client in self.spawned_others() {
+            if !client.sees_player(runtime_id) {
+                continue
+            }
+
+            if let Err(err) = client.send(packet.clone()) {
+                tracing::warn!("Failed to relay packet to {}: {err:#}", client.name().unwrap_or("<unknown>"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds this player to the player list and world of every other player and vice versa.
+    ///
+    /// This should only be called once, when the client has finished loading the world.
+    pub(crate) fn spawn(&self) -> anyhow::Result<()> {
+        if self.spawned.swap(true, Ordering::Relaxed) {
+            anyhow::bail!("Player has already been spawned");
+        }
+
+        let others = self.spawned_others();
+
+        // The skins are locked for as long as the entries referencing them exist.
+        let skins = std::iter::once(self)
+            .chain(others.iter().map(Arc::as_ref))
+            .map(|client| Ok(client.player()?.skin.read()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let entries = std::iter::once(self)
+            .chain(others.iter().map(Arc::as_ref))
+            .zip(&skins)
+            .map(|(client, skin)| client.list_entry(skin))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // The new player receives the entries of every player, including its own.
+        self.send(PlayerListAdd { entries: &entries })?;
+        for client in &others {
+            let result = client
+                .send(PlayerListAdd { entries: &entries[..1] })
+                .and_then(|()| client.sync_player(self, None))
+                .and_then(|()| self.sync_player(client, None));
+
+            if let Err(err) = result {
+                tracing::warn!("Failed to spawn player for {}: {err:#}", client.name().unwrap_or("<unknown>"));
+            }
+        }
+
+        self.sync_entities()
+    }
+
+    /// Removes this player from the player list and world of every other player.
+    pub(crate) fn despawn(&self) -> anyhow::Result<()> {
+        if !self.spawned.swap(false, Ordering::Relaxed) {
+            return Ok(())
+        }
+
+        let uuid = *self.uuid()?;
+        let runtime_id = self.runtime_id()?;
+        let unique_id = self.unique_id()?;
+
+        for client in self.spawned_others() {
+            let result = client.send(PlayerListRemove { entries: &[uuid] }).and_then(|()| client.hide_player(runtime_id, unique_id));
+            if let Err(err) = result {
+                tracing::warn!("Failed to despawn player for {}: {err:#}", client.name().unwrap_or("<unknown>"));
+            }
+        }
+        self.visible_players.lock().clear();
+
+        Ok(())
+    }
+
+    /// Whether the player with the given runtime ID has been added to this client's world.
+    pub fn sees_player(&self, runtime_id: u64) -> bool {
+        self.visible_players.lock().contains(&runtime_id)
+    }
+
+    /// Adds or removes the given player from this client's world depending on whether it is in range.
+    ///
+    /// If the player was already visible and a movement is given, the movement is sent instead.
+    pub(crate) fn sync_player(&self, other: &BedrockClient, movement: Option<&MovePlayer>) -> anyhow::Result<()> {
+        if !self.spawned() || !other.spawned() {
+            return Ok(())
+        }
+
+        let runtime_id = other.runtime_id()?;
+        let in_range = self.can_see(&other.player()?.position());
+        let mut visible = self.visible_players.lock();
+
+        // The set is updated before sending so that concurrent updates cannot add or remove the player twice.
+        match (visible.contains(&runtime_id), in_range) {
+            (false, true) => {
+                visible.insert(runtime_id);
+                drop(visible);
+
+                other.spawn_to(self)
+            }
+            (true, false) => {
+                visible.remove(&runtime_id);
+                drop(visible);
+
+                self.send(RemoveActor { unique_id: other.unique_id()? })
+            }
+            (true, true) => {
+                drop(visible);
+                movement.map_or(Ok(()), |movement| self.send(movement.clone()))
+            }
+            (false, false) => Ok(())
+        }
+    }
+
+    /// Removes the player with the given IDs from this client's world if it was visible.
+    fn hide_player(&self, runtime_id: u64, unique_id: i64) -> anyhow::Result<()> {
+        if !self.visible_players.lock().remove(&runtime_id) {
+            return Ok(())
+        }
+
+        self.send(RemoveActor { unique_id })
+    }
+
+    /// Whether the entity with the given runtime ID has been added to this client's world.
+    pub fn sees_entity(&self, runtime_id: u64) -> bool {
+        self.visible_entities.lock().contains(&runtime_id)
+    }
+
+    /// Adds or removes the given entity from this client's world depending on whether it is in range.
+    ///
+    /// If the entity was already visible and `moved` is set, its new position is sent instead.
+    pub(crate) fn sync_entity(&self, entity: &Entity, moved: bool) -> anyhow::Result<()> {
+        if !self.spawned() {
+            return Ok(())
+        }
+
+        let in_range = self.can_see(&entity.position());
+        let mut visible = self.visible_entities.lock();
+
+        match (visible.contains(&entity.runtime_id()), in_range) {
+            (false, true) => {
+                visible.insert(entity.runtime_id());
+                drop(visible);
+
+                let metadata = entity.metadata();
+                self.send(entity.add_packet(&metadata))
+            }
+            (true, false) => {
+                visible.remove(&entity.runtime_id());
+                drop(visible);
+
+                self.send(RemoveActor { unique_id: entity.unique_id() })
+            }
+            (true, true) if moved => {
+                drop(visible);
+                self.send(entity.move_packet())
+            }
+            _ => Ok(())
+        }
+    }
+
+    /// Removes the given entity from this client's world if it was visible.
+    pub(crate) fn hide_entity(&self, entity: &Entity) -> anyhow::Result<()> {
+        if !self.visible_entities.lock().remove(&entity.runtime_id()) {
+            return Ok(())
+        }
+
+        self.send(RemoveActor { unique_id: entity.unique_id() })
+    }
+
+    /// Updates which entities and players are visible after this client has moved or changed its render distance.
+    pub(crate) fn sync_entities(&self) -> anyhow::Result<()> {
+        for entity in self.instance().entities().all() {
+            self.sync_entity(&entity, false)?;
+        }
+
+        for client in self.spawned_others() {
+            self.sync_player(&client, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Relays a change in position or rotation of this player to all nearby players.
+    ///
+    /// Players that this player moved into or out of range of are sent a spawn or despawn instead.
+    pub(crate) fn relay_movement(&self, packet: MovePlayer) -> anyhow::Result<()> {
+        if !self.spawned() {
+            return Ok(())
+        }
+
+        for client in self.spawned_others() {
+            if let Err(err) = client.sync_player(self, Some(&packet)) {
+                tracing::warn!("Failed to relay movement to {}: {err:#}", client.name().unwrap_or("<unknown>"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the player list entry of this player.
+    fn list_entry<'a>(&'a self, skin: &'a RwLockReadGuard<'_, Skin>) -> anyhow::Result<PlayerListAddEntry<'a>> {
+        let identity = self.identity()?;
+        Ok(PlayerListAddEntry {
+            uuid: identity.uuid,
+            entity_id: self.unique_id()?,
+            username: &identity.name,
+            xuid: identity.xuid,
+            device_os: self.client_info()?.build_platform,
+            skin,
+            host: false,
+        })
+    }
+
+    /// Adds this player to the world of the given client.
+    fn spawn_to(&self, client: &BedrockClient) -> anyhow::Result<()> {
+        let player = self.player()?;
+        let identity = self.identity()?;
+        let client_info = self.client_info()?;
+
+        let metadata = player.metadata.read();
+        let movement = player.movement.read();
+        let position = Vector::from([movement.position.x, movement.position.y - PLAYER_EYE_HEIGHT, movement.position.z]);
+
+        let packet = AddPlayer {
+            uuid: identity.uuid,
+            username: &identity.name,
+            runtime_id: player.runtime_id(),
+            position,
+            velocity: movement.velocity.clone(),
+            rotation: movement.rotation.clone(),
+            game_mode: player.gamemode(),
+            held_item: ItemInstance::air(),
+            metadata: &metadata,
+            ability_data: player.ability_data(),
+            links: &[],
+            device_id: &client_info.device_id,
+            device_os: client_info.build_platform,
+        };
+        drop(movement);
+
+        client.send(packet)
+    }
+}
@@ -0,0 +1,239 @@
+//! Resource and behavior packs that are sent to clients when they join.
+
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use anyhow::Context;
+use proto::bedrock::{BehaviorPack, PackType, ResourcePack, ResourcePackStackEntry};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Maximum size of a single chunk of a pack that is sent to the client.
+pub const PACK_CHUNK_SIZE: usize = 128 * 1024;
+
+/// Whether a pack contains resources or behaviors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PackKind {
+    /// A resource pack, containing textures, models and sounds.
+    Resources,
+    /// A behavior pack, containing data and scripts.
+    Behavior,
+}
+
+/// A pack that has been loaded from disk.
+pub struct Pack {
+    /// UUID of the pack.
+    uuid: String,
+    /// Version of the pack in `major.minor.patch` format.
+    version: String,
+    /// The pack UUID and version in the format that the client uses to refer to the pack.
+    id: String,
+    kind: PackKind,
+    /// Whether the pack contains scripts.
+    has_scripts: bool,
+    /// The compressed pack archive.
+    data: Vec<u8>,
+    /// SHA-256 hash of the pack archive.
+    hash: [u8; 32],
+}
+
+impl Pack {
+    /// Loads a pack from a `.mcpack` or `.zip` archive.
+    pub fn from_archive(data: Vec<u8>) -> anyhow::Result<Pack> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data.as_slice()))?;
+
+        // The manifest can either be in the root of the archive or in a single top-level directory.
+        let manifest_index = (0..archive.len())
+            .filter_map(|i| {
+                let name = archive.name_for_index(i)?;
+                (name == "manifest.json" || name.ends_with("/manifest.json")).then(|| (name.matches('/').count(), i))
+            })
+            .min()
+            .map(|(_, i)| i)
+            .context("Pack does not contain a manifest.json")?;
+
+        let mut manifest = String::new();
+        archive.by_index(manifest_index)?.read_to_string(&mut manifest)?;
+        let manifest: Value = serde_json::from_str(&manifest).context("Pack contains an invalid manifest.json")?;
+
+        let header = &manifest["header"];
+        let uuid = header["uuid"].as_str().context("Pack manifest is missing a UUID")?.to_owned();
+        let version = header["version"]
+            .as_array()
+            .filter(|version| version.len() == 3)
+            .and_then(|version| version.iter().map(Value::as_u64).collect::<Option<Vec<_>>>())
+            .context("Pack manifest has an invalid version")?;
+        let version = format!("{}.{}.{}", version[0], version[1], version[2]);
+
+        let modules = manifest["modules"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let has_module = |kinds: &[&str]| modules.iter().any(|module| module["type"].as_str().is_some_and(|kind| kinds.contains(&kind)));
+
+        let kind = if has_module(&["data", "script"]) { PackKind::Behavior } else { PackKind::Resources };
+        let has_scripts = has_module(&["script"]);
+
+        Ok(Pack {
+            id: format!("{uuid}_{version}"),
+            uuid,
+            version,
+            kind,
+            has_scripts,
+            hash: Sha256::digest(&data).into(),
+            data,
+        })
+    }
+
+    /// UUID of the pack.
+    #[inline]
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// Version of the pack in `major.minor.patch` format.
+    #[inline]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Whether this is a resource or behavior pack.
+    #[inline]
+    pub const fn kind(&self) -> PackKind {
+        self.kind
+    }
+
+    /// Size of the pack archive in bytes.
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    /// SHA-256 hash of the pack archive.
+    #[inline]
+    pub const fn hash(&self) -> &[u8; 32] {
+        &self.hash
+    }
+
+    /// Amount of chunks the pack is split into when it is sent to a client.
+    #[inline]
+    pub fn chunk_count(&self) -> u32 {
+        self.data.len().div_ceil(PACK_CHUNK_SIZE) as u32
+    }
+
+    /// Returns the chunk with the given index, or `None` if it is out of range.
+    pub fn chunk(&self, index: u32) -> Option<&[u8]> {
+        self.data.chunks(PACK_CHUNK_SIZE).nth(index as usize)
+    }
+
+    /// The type of this pack as used by the protocol.
+    pub const fn pack_type(&self) -> PackType {
+        match self.kind {
+            PackKind::Resources => PackType::Resources,
+            PackKind::Behavior => PackType::Behavior,
+        }
+    }
+
+    /// Whether the given identifier refers to this pack.
+    ///
+    /// Clients refer to packs either using only their UUID or using their UUID followed by an underscore and the version.
+    pub fn matches(&self, id: &str) -> bool {
+        id == self.uuid || id == self.id
+    }
+
+    /// Entry of this pack in the pack stack.
+    pub fn stack_entry(&self) -> ResourcePackStackEntry<'_> {
+        ResourcePackStackEntry {
+            pack_id: &self.uuid,
+            pack_version: &self.version,
+            subpack_name: "",
+        }
+    }
+}
+
+/// All packs that are offered to clients.
+#[derive(Default)]
+pub struct Packs {
+    packs: Vec<Pack>,
+}
+
+impl Packs {
+    /// Loads every `.mcpack` and `.zip` archive in the given directory.
+    ///
+    /// A directory that does not exist is treated as containing no packs.
+    /// Archives that fail to load are skipped.
+    pub fn load<P: AsRef<Path>>(directory: P) -> anyhow::Result<Packs> {
+        let directory = directory.as_ref();
+        if !directory.exists() {
+            return Ok(Packs::default());
+        }
+
+        let mut packs = Vec::new();
+        for entry in std::fs::read_dir(directory).with_context(|| format!("Unable to read pack directory {}", directory.display()))? {
+            let path = entry?.path();
+            let is_archive = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mcpack") || ext.eq_ignore_ascii_case("zip"));
+            if !is_archive {
+                continue;
+            }
+
+            match std::fs::read(&path).map_err(anyhow::Error::from).and_then(Pack::from_archive) {
+                Ok(pack) => {
+                    tracing::info!("Loaded pack {} v{} from {}", pack.uuid, pack.version, path.display());
+                    packs.push(pack);
+                }
+                Err(err) => tracing::error!("Failed to load pack {}: {err:#}", path.display()),
+            }
+        }
+
+        Ok(Packs { packs })
+    }
+
+    /// Returns the pack that the given identifier refers to.
+    pub fn get(&self, id: &str) -> Option<&Pack> {
+        self.packs.iter().find(|pack| pack.matches(id))
+    }
+
+    /// Iterates over all packs.
+    pub fn iter(&self) -> std::slice::Iter<'_, Pack> {
+        self.packs.iter()
+    }
+
+    /// Whether there are no packs.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.packs.is_empty()
+    }
+
+    /// Resource packs in the format used by [`ResourcePacksInfo`](proto::bedrock::ResourcePacksInfo).
+    pub fn resource_info(&self) -> Vec<ResourcePack> {
+        self.of_kind(PackKind::Resources)
+            .map(|pack| ResourcePack {
+                uuid: pack.uuid.clone(),
+                version: pack.version.clone(),
+                size: pack.size(),
+                content_key: String::new(),
+                subpack_name: String::new(),
+                content_identity: String::new(),
+                has_scripts: pack.has_scripts,
+                rtx_enabled: false,
+            })
+            .collect()
+    }
+
+    /// Behavior packs in the format used by [`ResourcePacksInfo`](proto::bedrock::ResourcePacksInfo).
+    pub fn behavior_info(&self) -> Vec<BehaviorPack> {
+        self.of_kind(PackKind::Behavior)
+            .map(|pack| BehaviorPack {
+                uuid: pack.uuid.clone(),
+                version: pack.version.clone(),
+                size: pack.size(),
+                content_key: String::new(),
+                subpack_name: String::new(),
+                content_identity: String::new(),
+                has_scripts: pack.has_scripts,
+            })
+            .collect()
+    }
+
+    /// Iterates over all packs of the given kind.
+    pub fn of_kind(&self, kind: PackKind) -> impl Iterator<Item = &Pack> {
+        self.packs.iter().filter(move |pack| pack.kind == kind)
+    }
+}
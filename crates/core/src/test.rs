@@ -23,3 +23,719 @@ fn header() {
 
     assert_eq!(Header::deserialize(buffer.as_ref()).unwrap(), header);
 }
+
+#[test]
+fn region_index() {
+    use crate::level::io::stream::RegionIndex;
+    use util::Vector;
+
+    for coord in [[0, 0, 0], [-1, -4, -1], [12, 19, -300], [-(1 << 28), -32, (1 << 28) - 1]] {
+        let coord = Vector::from(coord);
+        let index = RegionIndex::from(coord.clone());
+
+        assert_eq!(Vector::<i32, 3>::from(index), coord);
+    }
+}
+
+#[test]
+fn blob_ledger() {
+    use crate::net::BlobLedger;
+    use proto::bedrock::CacheBlobStatus;
+    use util::RVec;
+
+    let ledger = BlobLedger::new();
+    let first = ledger.insert(RVec::from([1, 2, 3].as_slice())).unwrap();
+    let second = ledger.insert(RVec::from([4, 5, 6].as_slice())).unwrap();
+    assert_eq!(ledger.pending(), 2);
+
+    let missing = ledger.process(&CacheBlobStatus { misses: vec![second], hits: vec![first] });
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].0, second);
+    assert_eq!(missing[0].1.as_ref(), &[4, 5, 6]);
+
+    assert_eq!(ledger.pending(), 0);
+
+    // A client that never reports on its blobs cannot fill up the ledger indefinitely.
+    let mut rejected = 0;
+    for i in 0..5000u32 {
+        if ledger.insert(RVec::from(i.to_le_bytes().as_slice())).is_err() {
+            rejected += 1;
+        }
+    }
+    assert_eq!(ledger.pending(), 4096);
+    assert_eq!(rejected, 5000 - 4096);
+
+    // Hash counts are checked before anything is allocated.
+    assert!(CacheBlobStatus::deserialize([0xff, 0xff, 0xff, 0xff, 0x0f, 0x00].as_slice()).is_err());
+    assert!(CacheBlobStatus::deserialize([0x02, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08].as_slice()).is_err());
+}
+
+#[test]
+fn compression_round_trip() {
+    use crate::config::Compression;
+    use proto::bedrock::CompressionAlgorithm;
+    use util::RVec;
+
+    let packet: Vec<u8> = (0..512u32).map(|i| (i % 7) as u8).collect();
+
+    for algorithm in [CompressionAlgorithm::Flate, CompressionAlgorithm::Snappy] {
+        for threshold in [0, 511, 512, 1024] {
+            let compression = Compression { algorithm, threshold };
+
+            let mut compressed = RVec::alloc();
+            compression.compress(&packet, &mut compressed).unwrap();
+
+            if packet.len() > threshold as usize {
+                assert_eq!(compressed[0], algorithm as u8);
+                assert!(compressed.len() < packet.len());
+            } else {
+                assert_eq!(compressed[0], Compression::UNCOMPRESSED);
+                assert_eq!(&compressed[1..], packet.as_slice());
+            }
+
+            let decompressed = Compression::decompress(compressed).unwrap();
+            assert_eq!(decompressed.as_ref(), packet.as_slice());
+        }
+    }
+
+    // The decompressed size is checked before anything is allocated.
+    let oversized = RVec::from([CompressionAlgorithm::Snappy as u8, 0x80, 0x80, 0x80, 0x08].as_slice());
+    assert!(Compression::decompress(oversized).is_err());
+}
+
+#[tokio::test]
+async fn raknet_loopback() {
+    use std::sync::Arc;
+
+    use proto::raknet::ConnectionRequestAccepted;
+    use raknet::{RakNetClient, RakNetCreateDescription};
+    use tokio::net::UdpSocket;
+    use tokio::sync::{broadcast, mpsc};
+    use util::BinaryRead;
+
+    for local in ["127.0.0.1:0", "[::1]:0"] {
+        let server = Arc::new(UdpSocket::bind(local).await.unwrap());
+        let peer = UdpSocket::bind(local).await.unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+
+        let (broadcast, _) = broadcast::channel(1);
+        let (_forward, forward_rx) = mpsc::channel(1);
+        let (client, _output) = RakNetClient::new(
+            RakNetCreateDescription { address: peer_addr, mtu: 1400, guid: 0, socket: Arc::clone(&server) },
+            broadcast,
+            forward_rx,
+        );
+        assert_eq!(client.address, peer_addr);
+
+        let reply = ConnectionRequestAccepted { client_address: peer_addr, request_time: 1 };
+        let mut serialized = Vec::new();
+        reply.serialize_into(&mut serialized).unwrap();
+        assert_eq!(serialized.len(), reply.size_hint());
+
+        client.send_datagram(&serialized).await.unwrap();
+
+        let mut buffer = [0; 1500];
+        let (n, from) = peer.recv_from(&mut buffer).await.unwrap();
+        assert_eq!(from, server.local_addr().unwrap());
+
+        let mut reader = &buffer[1..n];
+        assert_eq!(reader.read_addr().unwrap(), peer_addr);
+    }
+}
+
+/// Builds and starts an instance that uses an empty level in a temporary directory.
+///
+/// The instance only listens on the loopback interface using a port chosen by the OS.
+#[cfg(feature = "rust-leveldb")]
+async fn temporary_instance<F>(name: &str, configure: F) -> (std::sync::Arc<crate::instance::Instance>, std::path::PathBuf)
+where
+    F: FnOnce(crate::instance::InstanceBuilder) -> crate::instance::InstanceBuilder,
+{
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    use crate::instance::Instance;
+
+    let directory = std::env::temp_dir().join(format!("mirai-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::copy("../../resources/level/level.dat", directory.join("level.dat")).unwrap();
+
+    let builder = Instance::builder()
+        .level_path(directory.to_str().unwrap())
+        .pack_path(directory.join("packs").to_str().unwrap())
+        .ipv4_addr(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+
+    let instance = configure(builder).build().await.unwrap();
+    instance.start().unwrap();
+
+    (instance, directory)
+}
+
+#[cfg(feature = "rust-leveldb")]
+#[tokio::test]
+async fn instance_loopback() {
+    use std::net::{Ipv6Addr, SocketAddrV6};
+    use std::time::Duration;
+
+    use proto::raknet::{OpenConnectionReply2, OFFLINE_MESSAGE_DATA};
+    use tokio::net::UdpSocket;
+    use util::{BinaryRead, BinaryWrite};
+
+    let (instance, directory) = temporary_instance("loopback", |builder| builder.ipv6_addr(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 0, 0, 0))).await;
+
+    let servers = [instance.ipv4_local_addr().unwrap(), instance.ipv6_local_addr().unwrap().unwrap()];
+    for server in servers {
+        let peer = UdpSocket::bind(if server.is_ipv4() { "127.0.0.1:0" } else { "[::1]:0" }).await.unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+
+        // Opening a connection makes the instance create a session on the socket of the matching family.
+        let mut request = vec![0x07];
+        request.extend_from_slice(OFFLINE_MESSAGE_DATA);
+        request.write_addr(&server).unwrap();
+        request.write_u16_be(1400).unwrap();
+        request.write_u64_be(1).unwrap();
+        peer.send_to(&request, server).await.unwrap();
+
+        let mut buffer = [0; 1500];
+        let (n, from) = tokio::time::timeout(Duration::from_secs(5), peer.recv_from(&mut buffer)).await.unwrap().unwrap();
+        assert_eq!(from, server);
+        assert_eq!(buffer[0], OpenConnectionReply2::ID);
+
+        let mut reader = &buffer[1 + OFFLINE_MESSAGE_DATA.len() + 8..n];
+        assert_eq!(reader.read_addr().unwrap(), peer_addr);
+        assert_eq!(reader.read_u16_be().unwrap(), 1400);
+    }
+
+    instance.shutdown().unwrap().await.unwrap().unwrap();
+    drop(instance);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "rust-leveldb")]
+#[tokio::test]
+async fn command_permissions() {
+    use proto::bedrock::CommandPermissionLevel;
+
+    use util::Joinable;
+
+    use crate::command::CommandSender;
+
+    let (instance, directory) = temporary_instance("permissions", |builder| builder.operator("Operator")).await;
+    assert!(instance.config().is_operator("operator"));
+    assert!(!instance.config().is_operator("player"));
+
+    let sender = |permission_level| CommandSender::Programmatic { name: "test".to_owned(), permission_level };
+
+    // Players without operator permissions must not be able to stop the server.
+    let receiver = instance.commands().execute(sender(CommandPermissionLevel::Normal), "/shutdown".to_owned()).await.unwrap();
+    let output = receiver.await.unwrap().unwrap_err();
+    assert!(output.message.as_str().contains("permission"));
+
+    let receiver = instance.commands().execute(sender(CommandPermissionLevel::Admin), "/shutdown".to_owned()).await.unwrap();
+    assert!(receiver.await.unwrap().is_ok());
+
+    // The command has already started shutting down the server.
+    assert!(instance.shutdown().is_none());
+    instance.join().await.unwrap();
+    drop(instance);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "rust-leveldb")]
+#[tokio::test]
+async fn block_write_back() {
+    use std::time::Duration;
+
+    use level::PaletteEntry;
+    use proto::types::Dimension;
+    use util::Vector;
+
+    let (instance, directory) = temporary_instance("write-back", |builder| builder).await;
+    let level = instance.level();
+
+    let stone = PaletteEntry { name: "minecraft:stone".to_owned(), version: None, states: Default::default() };
+    let position = Vector::from([3, 70, -5]);
+    level.set_block(&position, Dimension::Overworld, &stone).unwrap();
+    assert_eq!(level.block(&position, Dimension::Overworld).unwrap(), Some(stone.clone()));
+
+    // Blocks outside of the height range of the dimension do not exist.
+    assert!(level.set_block(&Vector::from([0, 320, 0]), Dimension::Overworld, &stone).is_err());
+    assert!(level.set_block(&Vector::from([0, 128, 0]), Dimension::Nether, &stone).is_err());
+    assert_eq!(level.block(&Vector::from([0, -65, 0]), Dimension::Overworld).unwrap(), None);
+
+    // The heightmap of the chunk is updated once the block has been written to disk.
+    let heightmap = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let biomes = level.biomes(Vector::from([0, -1]), Dimension::Overworld).unwrap();
+            if let Some(biomes) = biomes.filter(|biomes| biomes.heightmap[3][11] != 0) {
+                break biomes.heightmap;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(heightmap[3][11], 70 + 64 + 1);
+    assert_eq!(heightmap[0][0], 0);
+    assert_eq!(level.block(&position, Dimension::Overworld).unwrap(), Some(stone));
+
+    instance.shutdown().unwrap().await.unwrap().unwrap();
+    drop(instance);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn command_tokenizer() {
+    use crate::command::Tokenizer;
+
+    let mut tokens = Tokenizer::new(r#"say "hello \"world\"" @a[r=10, name="Steve Doe"] {"a": [1, 2]}"#);
+    assert_eq!(tokens.next_token().unwrap().as_deref(), Some("say"));
+    assert_eq!(tokens.next_token().unwrap().as_deref(), Some(r#"hello "world""#));
+    assert_eq!(tokens.next_raw().unwrap(), Some(r#"@a[r=10, name="Steve Doe"]"#));
+    assert_eq!(tokens.next_raw().unwrap(), Some(r#"{"a": [1, 2]}"#));
+    assert!(tokens.is_empty());
+
+    assert!(Tokenizer::new(r#""unterminated"#).next_raw().is_err());
+    assert!(Tokenizer::new("@a[r=1").next_raw().is_err());
+}
+
+#[test]
+fn command_arguments() {
+    use crate::command::{Coordinate, CommandTarget, ParsedCommand, SelectorKind};
+    use proto::bedrock::{Command, CommandDataType, CommandOverload, CommandParameter, CommandPermissionLevel};
+    use util::Vector;
+
+    let parameter = |name: &str, data_type| CommandParameter {
+        name: name.to_owned(),
+        data_type,
+        optional: false,
+        options: 0,
+        command_enum: None,
+        suffix: String::new(),
+    };
+
+    let syntax = Command {
+        name: "test".to_owned(),
+        description: String::new(),
+        permission_level: CommandPermissionLevel::Normal,
+        aliases: Vec::new(),
+        overloads: vec![CommandOverload {
+            parameters: vec![
+                parameter("target", CommandDataType::Target),
+                parameter("position", CommandDataType::Position),
+                parameter("range", CommandDataType::IntegerRange),
+                parameter("message", CommandDataType::Message),
+            ],
+        }],
+    };
+
+    let parsed = ParsedCommand::default_parser(&syntax, "/test @p[c=2,m=!creative] ~~1~-2 !1..5 hello there").unwrap();
+    assert_eq!(parsed.name, "test");
+
+    let Some(CommandTarget::Selector { kind, arguments }) = parsed.parameters["target"].as_target() else {
+        panic!("expected a selector");
+    };
+    assert_eq!(*kind, SelectorKind::ClosestPlayer);
+    assert_eq!(arguments.count, Some(2));
+    assert!(arguments.game_mode.as_ref().unwrap().negated);
+
+    let position = parsed.parameters["position"].as_position().unwrap();
+    assert_eq!(position.x, Coordinate::Relative(0.0));
+    assert_eq!(position.z, Coordinate::Relative(-2.0));
+    let resolved = position.resolve(&Vector::from([1.0, 2.0, 3.0]), &Vector::from([0.0, 0.0]));
+    assert_eq!((resolved.x, resolved.y, resolved.z), (1.0, 3.0, 1.0));
+
+    let crate::command::ParsedArgument::IntegerRange(range) = &parsed.parameters["range"] else {
+        panic!("expected an integer range");
+    };
+    assert!(!range.contains(3) && range.contains(6));
+    assert_eq!(parsed.parameters["message"].as_string(), Some("hello there"));
+
+    // Local coordinates cannot be mixed with other kinds.
+    assert!(ParsedCommand::default_parser(&syntax, "/test @s ^ ~ ^ 1 hi").is_err());
+    assert!(CommandTarget::parse("*", false).is_err());
+
+    // Dynamic enums only accept their current options.
+    let mut option = parameter("option", CommandDataType::String);
+    option.command_enum = Some(proto::bedrock::CommandEnum {
+        enum_id: "dynamic".to_owned(),
+        options: vec!["first".to_owned()],
+        dynamic: true,
+    });
+    let syntax = Command { overloads: vec![CommandOverload { parameters: vec![option] }], ..syntax };
+
+    assert!(ParsedCommand::default_parser(&syntax, "/test first").is_ok());
+    assert!(ParsedCommand::default_parser(&syntax, "/test second").is_err());
+
+    let current = |_: &str| Some(vec!["second".to_owned()]);
+    assert!(ParsedCommand::parse_with_enums(&syntax, "/test second", &current).is_ok());
+    assert!(ParsedCommand::parse_with_enums(&syntax, "/test first", &current).is_err());
+}
+
+#[test]
+fn ticker_callbacks() {
+    use crate::tick::Ticker;
+
+    let ticker = Ticker::new(0, 0);
+    let first = ticker.register(|_, _| ());
+    let second = ticker.register(|_, _| ());
+
+    assert_ne!(first, second);
+    assert!(ticker.unregister(first));
+    assert!(!ticker.unregister(first));
+    assert_eq!(ticker.current(), 0);
+}
+
+#[test]
+fn movement_limits() {
+    use crate::net::{MovementLimits, MovementViolation};
+    use util::Vector;
+
+    let walking = MovementLimits { may_fly: false, gliding: false, airborne_ticks: 0 };
+    let origin = Vector::from([0.0, 64.0, 0.0]);
+
+    assert_eq!(walking.check(&origin, &Vector::from([0.3, 64.0, 0.2]), 1), Ok(()));
+    assert_eq!(walking.check(&origin, &Vector::from([2.0, 64.0, 0.0]), 1), Err(MovementViolation::Speed));
+    // Skipped ticks allow for a larger distance.
+    assert_eq!(walking.check(&origin, &Vector::from([2.0, 64.0, 0.0]), 3), Ok(()));
+    assert_eq!(walking.check(&origin, &Vector::from([0.0, 65.0, 0.0]), 1), Err(MovementViolation::Flight));
+    assert_eq!(walking.check(&origin, &Vector::from([0.0, 80.0, 0.0]), 20), Err(MovementViolation::Teleport));
+    assert_eq!(walking.check(&origin, &Vector::from([f32::NAN, 64.0, 0.0]), 1), Err(MovementViolation::InvalidPosition));
+
+    // Hovering without descending is flagged once the player has been in the air for too long.
+    let hovering = MovementLimits { airborne_ticks: 40, ..walking };
+    assert_eq!(hovering.check(&origin, &Vector::from([0.3, 64.0, 0.0]), 1), Err(MovementViolation::Flight));
+
+    let flying = MovementLimits { may_fly: true, gliding: false, airborne_ticks: 40 };
+    assert_eq!(flying.check(&origin, &Vector::from([0.0, 65.0, 1.5]), 1), Ok(()));
+}
+
+#[test]
+fn block_break_time() {
+    use crate::level::breaking::{Hardness, Tool};
+
+    assert_eq!(Hardness::of("minecraft:bedrock"), None);
+    assert_eq!(Hardness::of("minecraft:torch"), Some(Hardness::INSTANT));
+
+    // Stone takes 7.5 seconds by hand and 1.15 seconds with a wooden pickaxe.
+    let stone = Hardness::of("minecraft:stone").expect("Stone is breakable");
+    assert_eq!(stone.break_ticks(None, 0), 150);
+    assert_eq!(stone.break_ticks(Tool::from_item("minecraft:wooden_pickaxe"), 0), 23);
+    assert_eq!(stone.break_ticks(Tool::from_item("minecraft:diamond_shovel"), 0), 150);
+    assert_eq!(stone.break_ticks(Tool::from_item("minecraft:diamond_pickaxe"), 5), 2);
+
+    // Dirt does not require a tool.
+    let dirt = Hardness::of("minecraft:dirt").expect("Dirt is breakable");
+    assert_eq!(dirt.break_ticks(None, 0), 15);
+    assert_eq!(Tool::from_item("minecraft:stick"), None);
+}
+
+#[test]
+fn auth_input_block_actions() {
+    use proto::bedrock::{PlayerActionType, PlayerAuthInput, StackRequestAction};
+
+    #[rustfmt::skip]
+    let bytes: &[u8] = &[
+        0x00, 0x00, 0x20, 0x41, // Pitch
+        0x00, 0x00, 0xb4, 0x42, // Yaw
+        0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x82, 0x42, 0x00, 0x00, 0x00, 0x3f, // Position
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Move vector
+        0x00, 0x00, 0xb4, 0x42, // Head yaw
+        0x80, 0x80, 0x80, 0x80, 0x80, 0x03, // Input data (block actions and item stack request)
+        0x01, 0x00, 0x01, // Input mode, play mode, interaction model
+        0xd2, 0x09, // Tick
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Delta
+        // Item stack request with a single mine block action.
+        0x09, 0x01, 0x0b, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // Block actions: start break and continue break at (1, 64, -3).
+        0x04, 0x00, 0x02, 0x80, 0x01, 0x05, 0x02, 0x36, 0x02, 0x80, 0x01, 0x05, 0x02,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Analogue move vector
+    ];
+
+    let input = PlayerAuthInput::deserialize(bytes).unwrap();
+    assert_eq!(input.tick, 1234);
+    assert!(input.item_transaction.is_none());
+
+    let request = input.item_stack.as_ref().unwrap();
+    assert_eq!(request.request_id, -5);
+    assert!(matches!(request.actions[..], [StackRequestAction::MineBlock { predicted_durability: 10, .. }]));
+
+    let actions = input.block_actions.as_ref().unwrap();
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[0].action, PlayerActionType::StartBreak);
+    assert_eq!(actions[1].action, PlayerActionType::ContinueBreak);
+    assert_eq!(actions[1].position, util::Vector::from([1, 64, -3]));
+    assert_eq!(actions[1].face, 1);
+
+    let mut buffer = Vec::new();
+    input.serialize_into(&mut buffer).unwrap();
+    assert_eq!(buffer, bytes);
+}
+
+#[test]
+fn auth_input_item_interaction() {
+    use proto::bedrock::{PlayerAuthInput, UseItemAction};
+
+    #[rustfmt::skip]
+    let bytes: &[u8] = &[
+        0x00, 0x00, 0x20, 0x41, // Pitch
+        0x00, 0x00, 0xb4, 0x42, // Yaw
+        0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x82, 0x42, 0x00, 0x00, 0x00, 0x3f, // Position
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Move vector
+        0x00, 0x00, 0xb4, 0x42, // Head yaw
+        0x80, 0x80, 0x80, 0x80, 0x40, // Input data (item interaction)
+        0x01, 0x00, 0x01, // Input mode, play mode, interaction model
+        0xd2, 0x09, // Tick
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Delta
+        0x00, 0x00, // Legacy request ID and inventory actions
+        0x00, 0x02, 0x40, 0x05, 0x02, 0x00, // Click block at (1, 64, -3) on face 1 with hotbar slot 0
+        // Held item with stack network ID 3 and empty extra data.
+        0x0a, 0x01, 0x00, 0x00, 0x01, 0x06, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x82, 0x42, 0x00, 0x00, 0x00, 0x3f, // Player position
+        0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x3f, // Clicked position
+        0xac, 0x02, // Block runtime ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Analogue move vector
+    ];
+
+    let input = PlayerAuthInput::deserialize(bytes).unwrap();
+    let transaction = input.item_transaction.as_ref().unwrap();
+    assert_eq!(transaction.action_type, UseItemAction::ClickBlock);
+    assert_eq!((transaction.block_position.x, transaction.block_position.y, transaction.block_position.z), (1, 64, -3));
+    assert_eq!(transaction.block_face, 1);
+    assert_eq!(transaction.held_item.network_id, 5);
+    assert_eq!(transaction.held_item.stack_id, Some(3));
+    assert_eq!(transaction.block_runtime_id, 300);
+
+    let mut buffer = Vec::new();
+    input.serialize_into(&mut buffer).unwrap();
+    assert_eq!(buffer, bytes);
+}
+
+#[test]
+fn auth_input_protocol_686() {
+    use proto::bedrock::{InputMode, InteractionModel, PlayerActionType, PlayerAuthInput, PlayMode};
+
+    // Sent by a 1.21.0 client that is sprinting while breaking a block.
+    #[rustfmt::skip]
+    let bytes: &[u8] = &[
+        0x00, 0x00, 0x96, 0x41, // Pitch
+        0x00, 0x00, 0xbb, 0xc2, // Yaw
+        0x00, 0x10, 0xcf, 0x42, 0x71, 0x3d, 0x83, 0x42, 0x00, 0xc0, 0x20, 0xc2, // Position
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x3f, // Move vector
+        0x00, 0x00, 0xbb, 0xc2, // Head yaw
+        0x90, 0x88, 0xc0, 0x80, 0x80, 0x81, 0x40, // Input data (sprinting, block actions, block breaking delay)
+        0x01, 0x00, 0x02, // Input mode, play mode, interaction model
+        0xd5, 0xf8, 0x02, // Tick
+        0x00, 0x00, 0x90, 0xbe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x3c, // Delta
+        0x02, 0x36, 0xce, 0x01, 0x80, 0x01, 0x51, 0x02, // Block actions: continue break at (103, 64, -41)
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x3f, // Analogue move vector
+    ];
+
+    let input = PlayerAuthInput::deserialize(bytes).unwrap();
+    assert_eq!(input.position, util::Vector::from([103.53125, 65.62, -40.1875]));
+    assert_eq!(input.input_mode, InputMode::Mouse);
+    assert_eq!(input.play_mode, PlayMode::Normal);
+    assert_eq!(input.interaction_model, InteractionModel::Classic);
+    assert_eq!(input.tick, 48213);
+    assert!(input.input_data.sprinting() && input.input_data.block_breaking_delay_enabled());
+
+    let actions = input.block_actions.as_ref().unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].action, PlayerActionType::ContinueBreak);
+    assert_eq!(actions[0].position, util::Vector::from([103, 64, -41]));
+
+    let mut buffer = Vec::new();
+    input.serialize_into(&mut buffer).unwrap();
+    assert_eq!(buffer, bytes);
+
+    // Lists longer than the limit are rejected before anything is allocated.
+    let mut oversized = bytes[..bytes.len() - 16].to_vec();
+    oversized.extend_from_slice(&[0xfe, 0xff, 0xff, 0xff, 0x0f]);
+    assert!(PlayerAuthInput::deserialize(oversized.as_slice()).is_err());
+}
+
+#[test]
+fn actor_data() {
+    use std::collections::HashMap;
+
+    use proto::bedrock::{ActorData, ActorDataKey, ActorDataValue, ActorFlag};
+    use util::Vector;
+
+    let mut data = ActorData::new();
+    data.set_name("Steve");
+    data.set_scale(1.5);
+    data.set_bounding_box(0.6, 1.8);
+    data.set_air_supply(300, 300);
+    data.set_flag(ActorFlag::Sneaking, true);
+    data.set_flag(ActorFlag::Crawling, true);
+    data.insert(ActorDataKey::BedPosition, ActorDataValue::BlockPosition(Vector::from([1, -64, 3])));
+    data.insert(ActorDataKey::SeatOffset, ActorDataValue::Vector(Vector::from([0.0, 1.5, 0.0])));
+    data.insert(ActorDataKey::NpcData, ActorDataValue::Compound(HashMap::from([("id".to_owned(), nbt::Value::Int(5))])));
+
+    assert!(data.flag(ActorFlag::Sneaking));
+    assert!(!data.flag(ActorFlag::Sprinting));
+    // Flags past the 64th are stored in the second flags entry.
+    assert!(matches!(data.get(ActorDataKey::Flags2), Some(ActorDataValue::Long(_))));
+
+    data.set_flag(ActorFlag::Sneaking, false);
+    assert!(!data.flag(ActorFlag::Sneaking));
+
+    let mut buffer = Vec::new();
+    data.serialize_into(&mut buffer).unwrap();
+    assert_eq!(ActorData::deserialize(buffer.as_ref()).unwrap(), data);
+}
+
+#[test]
+fn entity_ids() {
+    use crate::entity::Entities;
+    use util::Vector;
+
+    let entities = Entities::new();
+    let player = entities.allocate_id();
+    let player_unique = entities.allocate_unique_id();
+    let entity = entities.create("minecraft:pig", Vector::from([0.0, 64.0, 0.0]), Vector::from([0.0, 90.0, 90.0]));
+
+    // Players and entities share the same ID spaces.
+    assert_ne!(player, 0);
+    assert_ne!(entity.runtime_id(), player);
+    assert_ne!(entity.unique_id(), player_unique);
+    assert_eq!(entity.identifier(), "minecraft:pig");
+    assert!(entities.is_empty());
+}
+
+#[test]
+fn inventory_stack_requests() {
+    use proto::bedrock::{
+        ContainerSlotType, FilterCause, FullContainerName, ItemInstance, StackRequest, StackRequestAction, StackRequestSlotInfo,
+        TransactionAction, TransactionSourceType, WindowId,
+    };
+
+    use crate::item::{InventoryWindow, PlayerInventory};
+
+    let slot = |container, slot, stack_network_id| StackRequestSlotInfo {
+        container: FullContainerName::new(container),
+        slot,
+        stack_network_id,
+    };
+    let request = |actions| StackRequest { request_id: -1, actions, filters: vec![], filter_cause: FilterCause::ServerChatPublic };
+
+    let mut inventory = PlayerInventory::new();
+    inventory.set(InventoryWindow::Main, 0, ItemInstance { network_id: 5, count: 32, ..ItemInstance::air() }).unwrap();
+    let stack_id = inventory.held_item().stack_id.unwrap();
+
+    // Split the stack into the main inventory.
+    let split = request(vec![StackRequestAction::Place {
+        count: 12,
+        source: slot(ContainerSlotType::Hotbar, 0, stack_id),
+        destination: slot(ContainerSlotType::Inventory, 9, 0),
+    }]);
+    let changed = inventory.apply_request(&split, &[], false).unwrap();
+    assert_eq!(changed.len(), 2);
+    assert_eq!(inventory.held_item().count, 20);
+
+    let moved = inventory.get(InventoryWindow::Main, 9).unwrap();
+    assert_eq!(moved.count, 12);
+    assert_ne!(moved.stack_id, Some(stack_id));
+
+    // The same request is now outdated and must be rejected without changing anything.
+    assert!(inventory.apply_request(&split, &[], false).is_err());
+    assert_eq!(inventory.held_item().count, 20);
+
+    // Destroying items is only allowed in creative mode.
+    let destroy = request(vec![StackRequestAction::Destroy { count: 20, source: slot(ContainerSlotType::Hotbar, 0, stack_id) }]);
+    assert!(inventory.apply_request(&destroy, &[], false).is_err());
+
+    // Crafting is rejected so that the client reverts its prediction.
+    let craft = request(vec![
+        StackRequestAction::CraftRecipe { recipe_network_id: 1 },
+        StackRequestAction::Consume { count: 1, source: slot(ContainerSlotType::Hotbar, 0, stack_id) },
+    ]);
+    assert!(inventory.apply_request(&craft, &[], false).is_err());
+    assert_eq!(inventory.held_item().count, 20);
+
+    // Moving half of the stack to another slot uses a legacy transaction.
+    let container = |slot, old_item, new_item| TransactionAction {
+        source_type: TransactionSourceType::Container { inventory_id: WindowId::Inventory },
+        slot,
+        old_item,
+        new_item,
+    };
+    let stack = |count| ItemInstance { network_id: 5, count, ..ItemInstance::air() };
+
+    let split = [container(0, stack(20), stack(10)), container(1, ItemInstance::air(), stack(10))];
+    inventory.apply_transaction(&split, &[], false).unwrap();
+    assert_eq!(inventory.held_item().count, 10);
+    assert_eq!(inventory.get(InventoryWindow::Main, 1).unwrap().count, 10);
+    // The old item no longer matches.
+    assert!(inventory.apply_transaction(&split, &[], false).is_err());
+
+    // Transactions cannot create items.
+    let duplicate = [container(0, stack(10), stack(10)), container(2, ItemInstance::air(), stack(10))];
+    assert!(inventory.apply_transaction(&duplicate, &[], false).is_err());
+
+    // Dropped items would vanish, so drops are refused without changing anything.
+    let drop = [
+        container(0, stack(10), stack(9)),
+        TransactionAction {
+            source_type: TransactionSourceType::WorldInteraction { flags: 0 },
+            slot: 0,
+            old_item: ItemInstance::air(),
+            new_item: stack(1),
+        },
+    ];
+    assert!(inventory.apply_transaction(&drop, &[], false).is_err());
+    assert_eq!(inventory.held_item().count, 10);
+}
+
+#[test]
+fn block_state_lookup() {
+    use level::BlockStates;
+
+    let states = BlockStates::new().unwrap();
+    let air = states.entry(states.air()).unwrap();
+    assert_eq!(air.name, "minecraft:air");
+
+    // Runtime IDs and block states convert back and forth.
+    for runtime_id in [states.air(), 1, 1000] {
+        let state = states.entry(runtime_id).unwrap();
+        assert_eq!(states.state(state), Some(runtime_id));
+    }
+}
+
+#[test]
+fn pack_archive() {
+    use std::io::{Cursor, Write};
+
+    use zip::write::SimpleFileOptions;
+
+    use crate::pack::{Pack, PackKind, PACK_CHUNK_SIZE};
+
+    let manifest = r#"{
+        "format_version": 2,
+        "header": { "uuid": "5c8c5a36-3b67-4c26-8fd5-2f4d1a1b0e6f", "version": [1, 2, 3] },
+        "modules": [{ "type": "resources", "uuid": "0c4b1f53-1f7a-4f5c-9d0e-4b8f1c2b6a7d", "version": [1, 2, 3] }]
+    }"#;
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    writer.start_file("textures/blocks/stone.png", SimpleFileOptions::default()).unwrap();
+    writer.write_all(&[0xaa; PACK_CHUNK_SIZE]).unwrap();
+    writer.start_file("custom/manifest.json", SimpleFileOptions::default()).unwrap();
+    writer.write_all(manifest.as_bytes()).unwrap();
+    let archive = writer.finish().unwrap().into_inner();
+
+    let pack = Pack::from_archive(archive.clone()).unwrap();
+    assert_eq!(pack.uuid(), "5c8c5a36-3b67-4c26-8fd5-2f4d1a1b0e6f");
+    assert_eq!(pack.version(), "1.2.3");
+    assert_eq!(pack.kind(), PackKind::Resources);
+    assert!(pack.matches("5c8c5a36-3b67-4c26-8fd5-2f4d1a1b0e6f_1.2.3"));
+
+    // Chunks cover the entire archive.
+    assert_eq!(pack.size(), archive.len() as u64);
+    let chunks: Vec<u8> = (0..pack.chunk_count()).flat_map(|i| pack.chunk(i).unwrap().to_vec()).collect();
+    assert_eq!(chunks, archive);
+    assert!(pack.chunk(pack.chunk_count()).is_none());
+
+    assert!(Pack::from_archive(vec![0; 16]).is_err());
+}
@@ -0,0 +1,189 @@
+//! Drives the global server tick.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+use proto::bedrock::SetTime;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+
+use crate::instance::Instance;
+use crate::level::rule::DaylightCycle;
+
+/// Amount of ticks the server performs every second.
+pub const TICKS_PER_SECOND: u64 = 20;
+/// Time between two consecutive ticks.
+pub const TICK_INTERVAL: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND);
+/// Amount of ticks in a single day-night cycle.
+pub const TICKS_PER_DAY: i64 = 24000;
+/// Amount of ticks between two [`SetTime`] broadcasts.
+///
+/// Clients advance the time themselves, the broadcast only corrects any drift.
+const TIME_SYNC_INTERVAL: u64 = 10 * TICKS_PER_SECOND;
+
+/// Identifies a callback registered with the [`Ticker`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TickCallbackId(u64);
+
+/// A function that is called every tick.
+type TickCallback = Arc<dyn Fn(&Arc<Instance>, u64) + Send + Sync>;
+
+/// Metrics describing the performance of the tick loop.
+pub struct TickMetrics {
+    /// Time it took to run a tick, in seconds.
+    pub duration: Histogram,
+    /// Amount of ticks that took longer than [`TICK_INTERVAL`].
+    pub overruns: Counter,
+}
+
+impl TickMetrics {
+    fn new() -> TickMetrics {
+        TickMetrics {
+            // Buckets range from 1 ms to 128 ms.
+            duration: Histogram::new(exponential_buckets(0.001, 2.0, 8)),
+            overruns: Counter::default(),
+        }
+    }
+
+    /// Registers the tick metrics with the given registry.
+    pub fn register(&self, registry: &mut Registry) {
+        registry.register("tick_duration_seconds", "Time it took to run a server tick", self.duration.clone());
+        registry.register("tick_overruns", "Ticks that took longer than the tick interval", self.overruns.clone());
+    }
+}
+
+/// Runs the server at a fixed rate of [`TICKS_PER_SECOND`] ticks per second.
+///
+/// Gameplay systems can register callbacks that are executed every tick.
+pub struct Ticker {
+    /// The amount of ticks that have been performed since startup.
+    current: AtomicU64,
+    /// The time of day in ticks.
+    time: AtomicI64,
+    /// Callbacks executed on every tick.
+    callbacks: RwLock<Vec<(TickCallbackId, TickCallback)>>,
+    /// ID given to the next registered callback.
+    next_id: AtomicU64,
+    metrics: TickMetrics,
+}
+
+impl Ticker {
+    /// Creates a new ticker that continues from the given tick and world time.
+    pub(crate) fn new(current: u64, time: i64) -> Ticker {
+        Ticker {
+            current: AtomicU64::new(current),
+            time: AtomicI64::new(time),
+            callbacks: RwLock::new(Vec::new()),
+            next_id: AtomicU64::new(0),
+            metrics: TickMetrics::new(),
+        }
+    }
+
+    /// The current server tick.
+    #[inline]
+    pub fn current(&self) -> u64 {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// The current world time in ticks.
+    #[inline]
+    pub fn time(&self) -> i64 {
+        self.time.load(Ordering::Relaxed)
+    }
+
+    /// Sets the time of day and notifies all clients.
+    pub fn set_time(&self, instance: &Instance, time: i64) -> anyhow::Result<()> {
+        let time = time.rem_euclid(TICKS_PER_DAY);
+        self.time.store(time, Ordering::Relaxed);
+
+        Self::broadcast_time(instance, time)
+    }
+
+    /// Metrics of the tick loop.
+    #[inline]
+    pub const fn metrics(&self) -> &TickMetrics {
+        &self.metrics
+    }
+
+    /// Registers a callback that is executed every tick.
+    ///
+    /// The callback receives the current tick. Callbacks run on the tick loop and should therefore
+    /// finish quickly, long-running work should be moved to a separate task.
+    pub fn register<F>(&self, callback: F) -> TickCallbackId
+    where
+        F: Fn(&Arc<Instance>, u64) + Send + Sync + 'static,
+    {
+        let id = TickCallbackId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.callbacks.write().push((id, Arc::new(callback)));
+
+        id
+    }
+
+    /// Removes a callback from the ticker.
+    ///
+    /// This returns whether the callback existed.
+    pub fn unregister(&self, id: TickCallbackId) -> bool {
+        let mut callbacks = self.callbacks.write();
+        let len = callbacks.len();
+        callbacks.retain(|(other, _)| *other != id);
+
+        callbacks.len() != len
+    }
+
+    /// Runs the tick loop until the token is cancelled.
+    pub(crate) async fn run(instance: Arc<Instance>, token: CancellationToken) {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => instance.ticker().tick(&instance),
+                _ = token.cancelled() => break
+            }
+        }
+
+        tracing::info!("Tick loop stopped");
+    }
+
+    /// Performs a single tick.
+    fn tick(&self, instance: &Arc<Instance>) {
+        let start = Instant::now();
+        let tick = self.current.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let time = if instance.level().gamerule::<DaylightCycle>() {
+            self.time.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            self.time.load(Ordering::Relaxed)
+        };
+
+        if tick % TIME_SYNC_INTERVAL == 0 {
+            if let Err(err) = Self::broadcast_time(instance, time) {
+                tracing::error!("Failed to broadcast time: {err:#}");
+            }
+        }
+
+        // Clone the callbacks so that they are able to register other callbacks.
+        let callbacks = self.callbacks.read().iter().map(|(_, callback)| Arc::clone(callback)).collect::<Vec<_>>();
+        for callback in callbacks {
+            callback(instance, tick);
+        }
+
+        let elapsed = start.elapsed();
+        self.metrics.duration.observe(elapsed.as_secs_f64());
+
+        if elapsed > TICK_INTERVAL {
+            self.metrics.overruns.inc();
+            tracing::warn!("Tick {tick} took {elapsed:?}, the server is falling behind");
+        }
+    }
+
+    /// Sends the given time to all clients.
+    fn broadcast_time(instance: &Instance, time: i64) -> anyhow::Result<()> {
+        instance.clients().broadcast(SetTime { time: time as i32 })
+    }
+}
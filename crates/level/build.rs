@@ -3,6 +3,11 @@ fn main() {
     println!("cargo:rerun-if-env-changed=skip-leveldb");
     println!("cargo:rerun-if-changed=leveldb");
 
+    if std::env::var_os("CARGO_FEATURE_RUST_LEVELDB").is_some() {
+        // The pure Rust implementation is used instead.
+        return;
+    }
+
     let dst = cmake::Config::new("leveldb").profile("Release").build();
 
     println!("cargo:rustc-link-search=native={}/build/out", dst.display());
@@ -240,6 +240,12 @@ void iter_next(void *iter_raw)
     iter->Next();
 }
 
+void iter_seek(void *iter_raw, const char *key, int key_size)
+{
+    auto iter = reinterpret_cast<leveldb::Iterator *>(iter_raw);
+    iter->Seek(leveldb::Slice(key, key_size));
+}
+
 bool iter_valid(const void *iter_raw)
 {
     auto iter = reinterpret_cast<const leveldb::Iterator *>(iter_raw);
@@ -68,6 +68,10 @@ bool iter_valid(const void *iter);
 // This position could be invalid.
 void iter_next(void *iter);
 
+// Moves the iterator to the first key that is greater than or equal to the given key.
+// This position could be invalid.
+void iter_seek(void *iter, const char *key, int key_size);
+
 // Batched writes
 // //////////////////////////////////////
 
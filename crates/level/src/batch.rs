@@ -3,7 +3,9 @@ use std::{
     ptr::NonNull,
 };
 
-use crate::ffi;
+use util::RVec;
+
+use crate::{ffi, DataKey};
 
 /// Combines multiple operations into one large batch.
 pub struct WriteBatch {
@@ -40,6 +42,27 @@ impl WriteBatch {
         }
     }
 
+    /// Adds a put operation with a typed database key to the batch.
+    pub fn put_key<V>(&mut self, key: &DataKey, val: V) -> anyhow::Result<()>
+    where
+        V: AsRef<[u8]>,
+    {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.put(raw_key, val);
+        Ok(())
+    }
+
+    /// Adds a delete operation with a typed database key to the batch.
+    pub fn delete_key(&mut self, key: &DataKey) -> anyhow::Result<()> {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.delete(raw_key);
+        Ok(())
+    }
+
     /// Adds a delete operation to the batch.
     pub fn delete<K>(&mut self, key: K)
     where
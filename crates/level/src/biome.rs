@@ -1,9 +1,15 @@
 use util::{BinaryRead, BinaryWrite};
 
-use crate::PackedArrayReturn;
+use crate::{PackedArrayReturn, SubChunk};
 
 /// Size in bytes of the heightmap.
 const HEIGHTMAP_SIZE: usize = 512;
+/// Header of a fragment that inherits the biomes of the fragment below it.
+const INHERIT_HEADER: u8 = 0x7f << 1;
+/// Name of the air block, which is ignored when computing heightmaps.
+const AIR: &str = "minecraft:air";
+/// ID of the plains biome, used for chunks and subchunks that have no biome data.
+pub const PLAINS: u32 = 1;
 
 /// A paletted biome.
 ///
@@ -42,18 +48,56 @@ pub enum BiomeEncoding {
     Paletted(BiomeStorage),
 }
 
+/// Finds the vertical coordinate of the highest block that is not air in every column of a chunk.
+///
+/// The result is indexed by X and then Z coordinate. Columns that only contain air are `None`.
+/// Subchunks that are not included are considered to be empty.
+pub fn highest_blocks<'a, I>(subchunks: I) -> Box<[[Option<i16>; 16]; 16]>
+where
+    I: IntoIterator<Item = &'a SubChunk>,
+{
+    let mut sorted: Vec<&SubChunk> = subchunks.into_iter().collect();
+    // Search from top to bottom.
+    sorted.sort_unstable_by_key(|sub| std::cmp::Reverse(sub.index()));
+
+    let mut highest = Box::new([[None; 16]; 16]);
+    for x in 0..16u8 {
+        for z in 0..16u8 {
+            highest[x as usize][z as usize] = sorted.iter().find_map(|sub| {
+                let layer = sub.layer(0)?;
+                (0..16u8)
+                    .rev()
+                    .find(|&y| layer.get((x, y, z)).map_or(false, |block| block.name != AIR))
+                    .map(|y| i16::from(sub.index()) * 16 + i16::from(y))
+            });
+        }
+    }
+
+    highest
+}
+
 /// Describes the biomes contained in a single full size chunk.
 ///
 /// The biome consists of a heightmap and a biome fragment for each sub chunk.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Biomes {
-    /// Highest blocks in the chunk.
+    /// Highest blocks in the chunk, indexed by X and then Z coordinate.
+    ///
+    /// Every value is the height above the bottom of the world of the first air block above the highest block in that column.
     pub heightmap: Box<[[u16; 16]; 16]>,
     /// The biomes in each sub chunk.
     pub fragments: Vec<BiomeEncoding>,
 }
 
 impl Biomes {
+    /// Creates a chunk that consists of a single biome and has an empty heightmap.
+    pub fn uniform(biome: u32) -> Biomes {
+        Biomes {
+            heightmap: Box::new([[0; 16]; 16]),
+            fragments: vec![BiomeEncoding::Single(biome)],
+        }
+    }
+
     /// Heightmap of this biome.
     #[inline]
     pub const fn heightmap(&self) -> &[[u16; 16]; 16] {
@@ -66,8 +110,26 @@ impl Biomes {
         &self.fragments
     }
 
+    /// Recomputes the heightmap using the blocks in the given subchunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `subchunks` - Subchunks of this chunk. Subchunks that are not included are considered to be empty.
+    /// * `bottom` - Vertical coordinate of the lowest block in the dimension.
+    pub fn generate_heightmap<'a, I>(&mut self, subchunks: I, bottom: i16)
+    where
+        I: IntoIterator<Item = &'a SubChunk>,
+    {
+        let highest = highest_blocks(subchunks);
+        for (heights, tops) in self.heightmap.iter_mut().zip(highest.iter()) {
+            for (height, top) in heights.iter_mut().zip(tops) {
+                *height = top.map_or(0, |top| (top - bottom + 1) as u16);
+            }
+        }
+    }
+
     /// Reads a chunk biome from a raw buffer.
-    pub(crate) fn deserialize<'a, R>(mut reader: R) -> anyhow::Result<Self>
+    pub fn deserialize_disk<'a, R>(mut reader: R) -> anyhow::Result<Self>
     where
         R: BinaryRead<'a>,
     {
@@ -96,8 +158,8 @@ impl Biomes {
         Ok(Self { heightmap, fragments })
     }
 
-    /// Serializes the current chunk biome.
-    pub fn serialize<W>(&self, mut writer: W) -> anyhow::Result<()>
+    /// Serializes the current chunk biome in the format used by the `Data3D` database key.
+    pub fn serialize_disk<W>(&self, mut writer: W) -> anyhow::Result<()>
     where
         W: BinaryWrite,
     {
@@ -106,7 +168,7 @@ impl Biomes {
 
         for fragment in &self.fragments {
             match fragment {
-                BiomeEncoding::Inherit => writer.write_u8(0x7f << 1)?,
+                BiomeEncoding::Inherit => writer.write_u8(INHERIT_HEADER)?,
                 BiomeEncoding::Single(single) => {
                     writer.write_u8(0)?;
                     writer.write_u32_le(*single)?;
@@ -124,4 +186,47 @@ impl Biomes {
 
         Ok(())
     }
+
+    /// Serializes the biomes in the format used by the [`LevelChunk`](proto::bedrock::LevelChunk) packet.
+    ///
+    /// The client expects a fragment for every subchunk in the dimension.
+    /// Missing fragments are written as inheriting from the fragment below them and surplus fragments are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writer to serialize into.
+    /// * `count` - Amount of subchunks in the dimension.
+    pub fn serialize_network<W>(&self, mut writer: W, count: usize) -> anyhow::Result<()>
+    where
+        W: BinaryWrite,
+    {
+        for index in 0..count {
+            match self.fragments.get(index) {
+                // The lowest fragment has nothing to inherit from.
+                None | Some(BiomeEncoding::Inherit) if index == 0 => {
+                    writer.write_u8(1)?;
+                    writer.write_var_i32(PLAINS as i32)?;
+                }
+                None | Some(BiomeEncoding::Inherit) => writer.write_u8(INHERIT_HEADER | 1)?,
+                Some(BiomeEncoding::Single(single)) => {
+                    writer.write_u8(1)?;
+                    writer.write_var_i32(*single as i32)?;
+                }
+                Some(BiomeEncoding::Paletted(biome)) if biome.palette.len() <= 1 => {
+                    writer.write_u8(1)?;
+                    writer.write_var_i32(biome.palette.first().copied().unwrap_or(0) as i32)?;
+                }
+                Some(BiomeEncoding::Paletted(biome)) => {
+                    crate::serialize_packed_array(&mut writer, &biome.indices, biome.palette.len(), true)?;
+
+                    writer.write_var_i32(biome.palette.len() as i32)?;
+                    for entry in &biome.palette {
+                        writer.write_var_i32(*entry as i32)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
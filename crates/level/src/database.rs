@@ -125,6 +125,19 @@ impl<'a> Keys<'a> {
             _marker: PhantomData,
         }
     }
+
+    /// Moves the iterator to the first key that is greater than or equal to the given key.
+    ///
+    /// Iteration continues from that key, even if it was already passed before.
+    pub fn seek(&mut self, key: &[u8]) {
+        // SAFETY: `level_iter_seek` is safe to call, as long as the iterator has not been destroyed.
+        // The only code able to destroy the iter is the `Drop` implementation of `Self`.
+        // The key is only read for the duration of the call.
+        unsafe { ffi::iter_seek(self.iter.as_ptr(), key.as_ptr() as *const c_char, key.len() as c_int) };
+
+        // The iterator is now positioned on the next key, which should not be skipped.
+        self.index = 0;
+    }
 }
 
 impl<'a> Iterator for Keys<'a> {
@@ -208,6 +221,11 @@ impl Database {
         let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
         key.serialize(&mut raw_key)?;
 
+        self.get_raw(&raw_key)
+    }
+
+    /// Loads the value stored at a key that is not associated with a chunk, such as `~local_player`.
+    pub fn get_raw(&self, raw_key: &[u8]) -> anyhow::Result<Option<Guard>> {
         // SAFETY: This function is guaranteed to not modify any arguments.
         // It also does not throw exceptions and returns a valid struct.
         //
@@ -248,6 +266,14 @@ impl Database {
         let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
         key.serialize(&mut raw_key)?;
 
+        self.put_raw(&raw_key, value)
+    }
+
+    /// Inserts a value at a key that is not associated with a chunk, such as `~local_player`.
+    pub fn put_raw<V>(&self, raw_key: &[u8], value: V) -> anyhow::Result<()>
+    where
+        V: AsRef<[u8]>,
+    {
         let value = value.as_ref();
 
         // SAFETY: This is safe because the data and lengths come from properly allocated vecs.
@@ -59,6 +59,8 @@ extern "C" {
     pub fn iter_value(iter: *const c_void) -> SizedData;
     /// Moves the iterator to the next position.
     pub fn iter_next(iter: *mut c_void);
+    /// Moves the iterator to the first key that is greater than or equal to the given key.
+    pub fn iter_seek(iter: *mut c_void, key: *const c_char, key_size: c_int);
     /// Creates a new reusable batch.
     pub fn batch_new() -> *mut c_void;
     /// Adds a delete operation to the batch.
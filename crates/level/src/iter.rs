@@ -0,0 +1,108 @@
+use util::RVec;
+
+use crate::database::{Database, Keys, KvRef};
+use crate::DataKey;
+
+/// Returns the smallest key that is greater than every key starting with the given prefix.
+///
+/// This returns `None` if the prefix only consists of `0xff` bytes, in which case no such key exists.
+pub(crate) fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+    while let Some(last) = successor.pop() {
+        if last != u8::MAX {
+            successor.push(last + 1);
+            return Some(successor)
+        }
+    }
+
+    None
+}
+
+/// Iterator over a range of keys, created by [`Database::range`] and [`Database::prefix`].
+pub struct KeyRange<'a> {
+    /// Iterator positioned at the start of the range.
+    keys: Keys<'a>,
+    /// Exclusive end of the range, or `None` if the range is unbounded.
+    end: Option<Vec<u8>>,
+    /// Whether the end of the range has been reached.
+    done: bool,
+}
+
+impl<'a> Iterator for KeyRange<'a> {
+    type Item = KvRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None
+        }
+
+        let kv = self.keys.next()?;
+        if let Some(end) = &self.end {
+            if &*kv.key() >= end.as_slice() {
+                self.done = true;
+                return None
+            }
+        }
+
+        Some(kv)
+    }
+}
+
+/// Iterator over the chunk data in the database, created by [`Database::data_keys`].
+///
+/// Keys that are not associated with a chunk, such as `~local_player`, are skipped.
+pub struct DataKeys<'a> {
+    /// Iterator over the raw keys.
+    keys: Keys<'a>,
+}
+
+impl<'a> DataKeys<'a> {
+    /// Moves the iterator to the first key that is greater than or equal to the given key.
+    ///
+    /// Keys are sorted by their serialised form, so the chunk coordinates do not follow numeric order.
+    pub fn seek(&mut self, key: &DataKey) -> anyhow::Result<()> {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.keys.seek(&raw_key);
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for DataKeys<'a> {
+    type Item = (DataKey, KvRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let kv = self.keys.next()?;
+            let key = DataKey::deserialize(&*kv.key());
+
+            if let Ok(key) = key {
+                return Some((key, kv))
+            }
+        }
+    }
+}
+
+impl Database {
+    /// Creates an iterator over all keys in the half-open range from `start` to `end`.
+    pub fn range(&self, start: &[u8], end: &[u8]) -> KeyRange<'_> {
+        let mut keys = self.iter();
+        keys.seek(start);
+
+        KeyRange { keys, end: Some(end.to_vec()), done: false }
+    }
+
+    /// Creates an iterator over all keys that start with the given prefix.
+    pub fn prefix(&self, prefix: &[u8]) -> KeyRange<'_> {
+        let mut keys = self.iter();
+        keys.seek(prefix);
+
+        KeyRange { keys, end: prefix_successor(prefix), done: false }
+    }
+
+    /// Creates an iterator over all chunk data in the database that decodes the keys into [`DataKey`]s.
+    pub fn data_keys(&self) -> DataKeys<'_> {
+        DataKeys { keys: self.iter() }
+    }
+}
@@ -17,6 +17,39 @@ pub const SCOREBOARD: &[u8] = b"scoreboard";
 pub const SCHEDULER: &[u8] = b"schedulerWT";
 /// The `~local_player` database key.
 pub const LOCAL_PLAYER: &[u8] = b"~local_player";
+/// Prefix of the keys that list the entities in a chunk.
+pub const DIGP_PREFIX: &[u8] = b"digp";
+/// Prefix of the keys that store a single entity.
+pub const ACTOR_PREFIX: &[u8] = b"actorprefix";
+
+/// Length of the entity identifiers stored in a `digp` record.
+pub const ACTOR_ID_SIZE: usize = 8;
+
+/// Creates the key of the `digp` record that lists the entities in the given chunk.
+///
+/// The layout is the same as a [`DataKey`], except that the prefix comes first and there is no key type.
+pub fn digp_key(coordinates: &Vector<i32, 2>, dimension: Dimension) -> Vec<u8> {
+    let mut key = Vec::with_capacity(DIGP_PREFIX.len() + 12);
+    key.extend_from_slice(DIGP_PREFIX);
+    key.extend_from_slice(&coordinates.x.to_le_bytes());
+    key.extend_from_slice(&coordinates.y.to_le_bytes());
+
+    if dimension != Dimension::Overworld {
+        key.extend_from_slice(&(dimension as i32).to_le_bytes());
+    }
+
+    key
+}
+
+/// Creates the key that the entity with the given identifier is stored at.
+///
+/// The identifier is one of the entries of a `digp` record.
+pub fn actor_key(id: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(ACTOR_PREFIX.len() + id.len());
+    key.extend_from_slice(ACTOR_PREFIX);
+    key.extend_from_slice(id);
+    key
+}
 
 /// Database key prefixes.
 ///
@@ -67,7 +100,7 @@ impl KeyType {
 }
 
 /// A key that can be loaded from the database.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DataKey {
     /// X and Z coordinates of the requested chunk.
     pub coordinates: Vector<i32, 2>,
@@ -104,6 +137,8 @@ impl DataKey {
     }
 
     /// Deserialises a key from the given reader.
+    ///
+    /// The reader must contain exactly one key, so that keys that are not associated with a chunk are rejected.
     pub(crate) fn deserialize<'a, R>(mut reader: R) -> anyhow::Result<Self>
     where
         R: BinaryRead<'a> + 'a,
@@ -111,7 +146,8 @@ impl DataKey {
         let x = reader.read_i32_le()?;
         let z = reader.read_i32_le()?;
 
-        let dimension = if reader.remaining() > 6 {
+        // Overworld keys only contain the key type and an optional subchunk index after the coordinates.
+        let dimension = if reader.remaining() > 2 {
             Dimension::try_from(reader.read_u32_le()?)?
         } else {
             Dimension::Overworld
@@ -135,6 +171,10 @@ impl DataKey {
             _ => anyhow::bail!(format!("Invalid key type: {key_ty:x?}")),
         };
 
+        if !reader.eof() {
+            anyhow::bail!("Data key contains trailing bytes");
+        }
+
         Ok(Self {
             coordinates: Vector::from([x, z]),
             dimension,
@@ -66,21 +66,29 @@ pub enum PackedArrayReturn {
 
 /// Serializes a packed array into binary format.
 ///
+/// The smallest index size that can hold every index in the array is used,
+/// so arrays whose palette contains unused entries are not written with more bits than necessary.
+///
 /// # Arguments
 /// * `writer` - Write to serialize into.
 /// * `array` - Array to serialize into packed form.
-/// * `max_index` - Amount of unique elements of the array.
+/// * `max_index` - Amount of unique elements of the array. Every index in the array must be lower than this.
 /// * `is_network` - Serialize into network format.
 pub fn serialize_packed_array<W>(writer: &mut W, array: &[u16; 4096], max_index: usize, is_network: bool) -> anyhow::Result<()>
 where
     W: BinaryWrite,
 {
+    let highest = array.iter().copied().max().unwrap_or(0) as usize;
+    if highest >= max_index.max(1) {
+        anyhow::bail!("Packed array index {highest} is out of range for {max_index} unique elements");
+    }
+
     // Determine the required bits per index
     let index_size = {
-        let mut bits_per_block = 0;
+        let mut bits_per_block = 16;
         // Loop over allowed values.
         for b in [1, 2, 3, 4, 5, 6, 8, 16] {
-            if 2usize.pow(b) >= max_index {
+            if highest < 1 << b {
                 bits_per_block = b;
                 break;
             }
@@ -163,21 +171,39 @@ where
 #[cfg(test)]
 mod test;
 
+#[cfg(not(feature = "rust-leveldb"))]
 mod batch;
 mod biome;
+#[cfg(not(feature = "rust-leveldb"))]
 mod ffi;
+mod iter;
 mod key;
+mod player;
 mod settings;
 mod states;
 mod subchunk;
 
 /// Direct access to the LevelDB database.
+#[cfg(not(feature = "rust-leveldb"))]
+pub mod database;
+/// Direct access to the LevelDB database.
+///
+/// This is a pure Rust implementation of the LevelDB format used by Minecraft,
+/// enabled with the `rust-leveldb` feature.
+#[cfg(feature = "rust-leveldb")]
+#[path = "lsm/mod.rs"]
 pub mod database;
 /// Implements serialization and deserialization for important types.
 pub mod provider;
 
+#[cfg(not(feature = "rust-leveldb"))]
 pub use batch::*;
+#[cfg(feature = "rust-leveldb")]
+pub use database::WriteBatch;
 pub use biome::*;
+pub use iter::*;
 pub use key::*;
+pub use player::*;
+pub use settings::*;
 pub use states::*;
 pub use subchunk::*;
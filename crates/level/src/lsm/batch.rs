@@ -0,0 +1,123 @@
+use util::{BinaryRead, RVec};
+
+use super::coding::{put_prefixed, ValueType};
+use crate::DataKey;
+
+/// Combines multiple operations into one large batch.
+#[derive(Debug, Default, Clone)]
+pub struct WriteBatch {
+    /// Keys to write together with their new value, or `None` if the key should be deleted.
+    pub(super) operations: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl WriteBatch {
+    /// Creates a new batch.
+    /// This batch can be reused by calling [`clear`](Self::clear) and executed using [`execute`](super::Database::execute).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a put operation to the batch.
+    pub fn put<K, V>(&mut self, key: K, val: V)
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.operations.push((key.as_ref().to_vec(), Some(val.as_ref().to_vec())));
+    }
+
+    /// Adds a put operation with a typed database key to the batch.
+    pub fn put_key<V>(&mut self, key: &DataKey, val: V) -> anyhow::Result<()>
+    where
+        V: AsRef<[u8]>,
+    {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.put(raw_key, val);
+        Ok(())
+    }
+
+    /// Adds a delete operation with a typed database key to the batch.
+    pub fn delete_key(&mut self, key: &DataKey) -> anyhow::Result<()> {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.delete(raw_key);
+        Ok(())
+    }
+
+    /// Adds a delete operation to the batch.
+    pub fn delete<K>(&mut self, key: K)
+    where
+        K: AsRef<[u8]>,
+    {
+        self.operations.push((key.as_ref().to_vec(), None));
+    }
+
+    /// Clears the batch, removing all stored operations.
+    pub fn clear(&mut self) {
+        self.operations.clear();
+    }
+
+    /// Amount of operations in this batch.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Whether this batch contains no operations.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Encodes the batch as a write-ahead log record.
+    ///
+    /// The operations are assigned consecutive sequence numbers, starting at `sequence`.
+    pub(super) fn encode(&self, sequence: u64) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(12);
+        buffer.extend_from_slice(&sequence.to_le_bytes());
+        buffer.extend_from_slice(&(self.operations.len() as u32).to_le_bytes());
+
+        for (key, value) in &self.operations {
+            if let Some(value) = value {
+                buffer.push(ValueType::Value as u8);
+                put_prefixed(&mut buffer, key);
+                put_prefixed(&mut buffer, value);
+            } else {
+                buffer.push(ValueType::Deletion as u8);
+                put_prefixed(&mut buffer, key);
+            }
+        }
+
+        buffer
+    }
+
+    /// Decodes a batch from a write-ahead log record.
+    ///
+    /// Returns the sequence number of the first operation together with the batch.
+    pub(super) fn decode(mut reader: &[u8]) -> anyhow::Result<(u64, WriteBatch)> {
+        let sequence = reader.read_u64_le()?;
+        let count = reader.read_u32_le()?;
+
+        let mut operations = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let kind = reader.read_u8()?;
+
+            let length = reader.read_var_u32()?;
+            let key = reader.take_n(length as usize)?.to_vec();
+
+            let value = match kind {
+                0 => None,
+                1 => {
+                    let length = reader.read_var_u32()?;
+                    Some(reader.take_n(length as usize)?.to_vec())
+                }
+                _ => anyhow::bail!("Write batch contains unknown operation type {kind}"),
+            };
+
+            operations.push((key, value));
+        }
+
+        Ok((sequence, WriteBatch { operations }))
+    }
+}
@@ -0,0 +1,154 @@
+use util::BinaryRead;
+
+use super::coding::put_varint;
+
+/// Amount of keys between restart points, where a key is stored in full instead of sharing a prefix with the previous key.
+const RESTART_INTERVAL: usize = 16;
+
+/// Builds a single sorted block of key-value pairs.
+pub struct BlockBuilder {
+    /// Encoded entries.
+    buffer: Vec<u8>,
+    /// Offsets of the restart points in the buffer.
+    restarts: Vec<u32>,
+    /// Amount of entries since the last restart point.
+    counter: usize,
+    /// Key of the last entry that was added.
+    last_key: Vec<u8>,
+}
+
+impl BlockBuilder {
+    /// Creates an empty block.
+    pub fn new() -> BlockBuilder {
+        BlockBuilder { buffer: Vec::new(), restarts: vec![0], counter: 0, last_key: Vec::new() }
+    }
+
+    /// Adds an entry to the block.
+    ///
+    /// Keys must be added in sorted order.
+    pub fn add(&mut self, key: &[u8], value: &[u8]) {
+        let shared = if self.counter < RESTART_INTERVAL {
+            self.last_key.iter().zip(key).take_while(|(lhs, rhs)| lhs == rhs).count()
+        } else {
+            self.restarts.push(self.buffer.len() as u32);
+            self.counter = 0;
+            0
+        };
+
+        put_varint(&mut self.buffer, shared as u64);
+        put_varint(&mut self.buffer, (key.len() - shared) as u64);
+        put_varint(&mut self.buffer, value.len() as u64);
+        self.buffer.extend_from_slice(&key[shared..]);
+        self.buffer.extend_from_slice(value);
+
+        self.last_key.clear();
+        self.last_key.extend_from_slice(key);
+        self.counter += 1;
+    }
+
+    /// Whether no entries have been added to this block.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Estimated size of the block once it is finished.
+    pub fn size_estimate(&self) -> usize {
+        self.buffer.len() + self.restarts.len() * 4 + 4
+    }
+
+    /// Key of the last entry that was added.
+    pub fn last_key(&self) -> &[u8] {
+        &self.last_key
+    }
+
+    /// Returns the encoded block and resets the builder.
+    pub fn finish(&mut self) -> Vec<u8> {
+        let mut block = std::mem::take(&mut self.buffer);
+        for restart in &self.restarts {
+            block.extend_from_slice(&restart.to_le_bytes());
+        }
+        block.extend_from_slice(&(self.restarts.len() as u32).to_le_bytes());
+
+        self.restarts = vec![0];
+        self.counter = 0;
+        self.last_key.clear();
+
+        block
+    }
+}
+
+/// A decoded block read from a table.
+pub struct Block {
+    /// Uncompressed contents of the block.
+    data: Vec<u8>,
+    /// Offset of the restart array, which is also the end of the entries.
+    restarts: usize,
+}
+
+impl Block {
+    /// Parses the uncompressed contents of a block.
+    pub fn new(data: Vec<u8>) -> anyhow::Result<Block> {
+        let Some(count) = data.len().checked_sub(4).map(|offset| &data[offset..]) else {
+            anyhow::bail!("Table block is too small to contain a restart count");
+        };
+
+        let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+        let Some(restarts) = count.checked_mul(4).and_then(|size| data.len().checked_sub(4 + size)) else {
+            anyhow::bail!("Table block restart count is larger than the block");
+        };
+
+        Ok(Block { data, restarts })
+    }
+
+    /// Iterates over the entries in this block.
+    pub fn iter(&self) -> BlockIter<'_> {
+        BlockIter { data: &self.data[..self.restarts], key: Vec::new() }
+    }
+}
+
+/// Iterator over the entries in a block.
+pub struct BlockIter<'a> {
+    /// Remaining encoded entries.
+    data: &'a [u8],
+    /// Key of the previous entry, which following keys share a prefix with.
+    key: Vec<u8>,
+}
+
+impl<'a> BlockIter<'a> {
+    /// Decodes the next entry.
+    fn read_entry(&mut self) -> anyhow::Result<(Vec<u8>, &'a [u8])> {
+        let shared = self.data.read_var_u32()? as usize;
+        let non_shared = self.data.read_var_u32()? as usize;
+        let value_size = self.data.read_var_u32()? as usize;
+
+        if shared > self.key.len() {
+            anyhow::bail!("Table block entry shares more bytes than the previous key contains");
+        }
+
+        let delta = self.data.take_n(non_shared)?;
+        let value = self.data.take_n(value_size)?;
+
+        self.key.truncate(shared);
+        self.key.extend_from_slice(delta);
+
+        Ok((self.key.clone(), value))
+    }
+}
+
+impl<'a> Iterator for BlockIter<'a> {
+    type Item = anyhow::Result<(Vec<u8>, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+
+        let entry = self.read_entry();
+        if entry.is_err() {
+            // Do not attempt to decode anything after a corrupted entry.
+            self.data = &[];
+        }
+
+        Some(entry)
+    }
+}
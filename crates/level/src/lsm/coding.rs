@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+
+/// Polynomial of the CRC-32C (Castagnoli) checksum, in reversed bit order.
+const CRC_POLYNOMIAL: u32 = 0x82f6_3b78;
+/// Lookup table used to compute CRC-32C checksums a byte at a time.
+const CRC_TABLE: [u32; 256] = crc_table();
+/// Added to masked checksums.
+///
+/// LevelDB masks the checksums it stores, because computing the checksum of data that
+/// itself contains embedded checksums is problematic.
+const CRC_MASK_DELTA: u32 = 0xa282_ead8;
+
+/// Size in bytes of the sequence number and value type that are appended to every key.
+pub const KEY_TRAILER_SIZE: usize = 8;
+
+/// Generates the CRC-32C lookup table.
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ CRC_POLYNOMIAL } else { crc >> 1 };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+/// Computes the masked CRC-32C checksum of the concatenation of the given buffers.
+pub fn masked_crc(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for part in parts {
+        for &byte in *part {
+            crc = CRC_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8);
+        }
+    }
+
+    (!crc).rotate_right(15).wrapping_add(CRC_MASK_DELTA)
+}
+
+/// Appends a variable-length integer to the buffer.
+pub fn put_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Appends a buffer prefixed with its length to the buffer.
+pub fn put_prefixed(buffer: &mut Vec<u8>, data: &[u8]) {
+    put_varint(buffer, data.len() as u64);
+    buffer.extend_from_slice(data);
+}
+
+/// Type of an entry in the database.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ValueType {
+    /// The key has been deleted.
+    Deletion = 0,
+    /// The key holds a value.
+    Value = 1,
+}
+
+/// A single version of a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Key as given by the user.
+    pub key: Vec<u8>,
+    /// Sequence number of the write that created this version.
+    pub sequence: u64,
+    /// Value of the key or `None` if this version deletes the key.
+    pub value: Option<Vec<u8>>,
+}
+
+impl Entry {
+    /// Key of this entry with the sequence number and value type appended,
+    /// as stored in tables.
+    pub fn internal_key(&self) -> Vec<u8> {
+        let kind = if self.value.is_some() { ValueType::Value } else { ValueType::Deletion };
+
+        let mut key = Vec::with_capacity(self.key.len() + KEY_TRAILER_SIZE);
+        key.extend_from_slice(&self.key);
+        key.extend_from_slice(&(self.sequence << 8 | kind as u64).to_le_bytes());
+        key
+    }
+
+    /// Creates an entry from a key and value read from a table.
+    pub fn from_internal(internal_key: &[u8], value: Vec<u8>) -> anyhow::Result<Entry> {
+        let (key, trailer) = split_internal_key(internal_key)?;
+        let kind = trailer as u8;
+
+        let value = match kind {
+            0 => None,
+            1 => Some(value),
+            _ => anyhow::bail!("Invalid value type {kind} in table key"),
+        };
+
+        Ok(Entry { key: key.to_vec(), sequence: trailer >> 8, value })
+    }
+}
+
+/// Splits a table key into the user key and the packed sequence number and value type.
+pub fn split_internal_key(key: &[u8]) -> anyhow::Result<(&[u8], u64)> {
+    if key.len() < KEY_TRAILER_SIZE {
+        anyhow::bail!("Table key is too short to contain a sequence number");
+    }
+
+    let (user, trailer) = key.split_at(key.len() - KEY_TRAILER_SIZE);
+    let mut bytes = [0; KEY_TRAILER_SIZE];
+    bytes.copy_from_slice(trailer);
+
+    Ok((user, u64::from_le_bytes(bytes)))
+}
+
+/// Returns the user key part of a table key.
+///
+/// Keys that are too short to contain a trailer are returned as is.
+pub fn user_key(key: &[u8]) -> &[u8] {
+    &key[..key.len().saturating_sub(KEY_TRAILER_SIZE)]
+}
+
+/// Compares two table keys.
+///
+/// Keys are sorted by user key in ascending order and then by sequence number in descending order,
+/// so that the most recent version of a key comes first.
+pub fn compare_internal(lhs: &[u8], rhs: &[u8]) -> Ordering {
+    match (split_internal_key(lhs), split_internal_key(rhs)) {
+        (Ok((lhs_key, lhs_trailer)), Ok((rhs_key, rhs_trailer))) => lhs_key.cmp(rhs_key).then(rhs_trailer.cmp(&lhs_trailer)),
+        _ => lhs.cmp(rhs),
+    }
+}
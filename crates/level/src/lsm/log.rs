@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use super::coding::masked_crc;
+
+/// Logs are split into blocks of this size. Records never cross the header of a block.
+const BLOCK_SIZE: usize = 32 * 1024;
+/// Size of the checksum, length and type that precede every record fragment.
+const HEADER_SIZE: usize = 7;
+
+/// Describes which part of a record a fragment contains.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+enum RecordType {
+    /// The fragment contains the entire record.
+    Full = 1,
+    /// The first fragment of a record.
+    First = 2,
+    /// A fragment that is neither the first nor the last of a record.
+    Middle = 3,
+    /// The last fragment of a record.
+    Last = 4,
+}
+
+/// Appends records to a log file, such as the write-ahead log or the manifest.
+pub struct LogWriter {
+    /// File the records are written to.
+    file: File,
+    /// Offset of the writer in the current block.
+    offset: usize,
+}
+
+impl LogWriter {
+    /// Creates a new log file, replacing any existing file.
+    pub fn create(path: &Path) -> anyhow::Result<LogWriter> {
+        Ok(LogWriter { file: File::create(path)?, offset: 0 })
+    }
+
+    /// Appends a single record to the log.
+    pub fn add_record(&mut self, mut data: &[u8]) -> anyhow::Result<()> {
+        let mut buffer = Vec::with_capacity(data.len() + HEADER_SIZE);
+        let mut first = true;
+
+        loop {
+            let left = BLOCK_SIZE - self.offset;
+            if left < HEADER_SIZE {
+                // Pad the block with zeroes, a header does not fit in it anymore.
+                buffer.resize(buffer.len() + left, 0);
+                self.offset = 0;
+                continue
+            }
+
+            let length = data.len().min(left - HEADER_SIZE);
+            let last = length == data.len();
+            let kind = match (first, last) {
+                (true, true) => RecordType::Full,
+                (true, false) => RecordType::First,
+                (false, true) => RecordType::Last,
+                (false, false) => RecordType::Middle,
+            };
+
+            let (fragment, rest) = data.split_at(length);
+            buffer.extend_from_slice(&masked_crc(&[&[kind as u8], fragment]).to_le_bytes());
+            buffer.extend_from_slice(&(length as u16).to_le_bytes());
+            buffer.push(kind as u8);
+            buffer.extend_from_slice(fragment);
+
+            self.offset += HEADER_SIZE + length;
+            data = rest;
+            first = false;
+
+            if last {
+                break
+            }
+        }
+
+        self.file.write_all(&buffer)?;
+        Ok(())
+    }
+
+    /// Flushes the log to disk.
+    pub fn sync(&self) -> anyhow::Result<()> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Reads all records from the contents of a log file.
+///
+/// A corrupted or incomplete record, which can be left behind by a crash, ends the log.
+/// All records before it are still returned.
+pub fn read_log(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut records = Vec::new();
+    let mut pending: Option<Vec<u8>> = None;
+    let mut offset = 0;
+
+    while offset + HEADER_SIZE <= data.len() {
+        let left = BLOCK_SIZE - offset % BLOCK_SIZE;
+        if left < HEADER_SIZE {
+            offset += left;
+            continue
+        }
+
+        let header = &data[offset..offset + HEADER_SIZE];
+        let checksum = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let length = u16::from_le_bytes([header[4], header[5]]) as usize;
+        let kind = header[6];
+
+        if kind == 0 && length == 0 {
+            // Zeroed space at the end of a block or preallocated file.
+            offset += left;
+            continue
+        }
+
+        let start = offset + HEADER_SIZE;
+        let end = start + length;
+        if length > left - HEADER_SIZE || end > data.len() {
+            tracing::warn!("Log ends with an incomplete record, it will be ignored");
+            break
+        }
+
+        let fragment = &data[start..end];
+        if masked_crc(&[&[kind], fragment]) != checksum {
+            tracing::warn!("Log record at offset {offset} is corrupted, the remainder of the log will be ignored");
+            break
+        }
+        offset = end;
+
+        match kind {
+            1 => records.push(fragment.to_vec()),
+            2 => pending = Some(fragment.to_vec()),
+            3 => {
+                if let Some(pending) = &mut pending {
+                    pending.extend_from_slice(fragment);
+                }
+            }
+            4 => {
+                if let Some(mut record) = pending.take() {
+                    record.extend_from_slice(fragment);
+                    records.push(record);
+                }
+            }
+            _ => {
+                tracing::warn!("Log record at offset {offset} has unknown type {kind}, the remainder of the log will be ignored");
+                break
+            }
+        }
+    }
+
+    records
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use super::coding::{user_key, Entry};
+use super::table::{TableCache, TableIter};
+use super::version::FileMeta;
+
+/// A sorted sequence of entries that can be repositioned.
+pub trait SeekIter: Iterator<Item = anyhow::Result<Entry>> {
+    /// Moves the iterator to the first entry with a key that is greater than or equal to the given key.
+    fn seek(&mut self, key: &[u8]);
+}
+
+/// A sorted sequence of entries, such as the memtable or a level.
+pub type Source<'a> = Box<dyn SeekIter + Send + 'a>;
+
+/// Iterates over a snapshot of the memtable.
+pub struct MemIter {
+    /// Entries sorted by key.
+    entries: Vec<Entry>,
+    /// Index of the next entry.
+    position: usize,
+}
+
+impl MemIter {
+    /// Creates an iterator over the given sorted entries.
+    pub fn new(entries: Vec<Entry>) -> MemIter {
+        MemIter { entries, position: 0 }
+    }
+}
+
+impl Iterator for MemIter {
+    type Item = anyhow::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.get(self.position)?.clone();
+        self.position += 1;
+
+        Some(Ok(entry))
+    }
+}
+
+impl SeekIter for MemIter {
+    fn seek(&mut self, key: &[u8]) {
+        self.position = self.entries.partition_point(|entry| entry.key.as_slice() < key);
+    }
+}
+
+/// Iterates over a list of non-overlapping tables, opening them one at a time.
+pub struct LevelIter<'a> {
+    /// Cache used to open the tables.
+    cache: &'a TableCache,
+    /// Tables sorted by key.
+    files: Vec<Arc<FileMeta>>,
+    /// Index of the next table to open.
+    next_file: usize,
+    /// Iterator over the current table.
+    current: Option<TableIter>,
+    /// Key that the next opened table should seek to.
+    target: Option<Vec<u8>>,
+}
+
+impl<'a> LevelIter<'a> {
+    /// Creates an iterator over the given tables.
+    ///
+    /// The tables must be sorted by key and must not overlap.
+    pub fn new<I>(cache: &'a TableCache, files: I) -> LevelIter<'a>
+    where
+        I: IntoIterator<Item = Arc<FileMeta>>,
+    {
+        LevelIter { cache, files: files.into_iter().collect(), next_file: 0, current: None, target: None }
+    }
+}
+
+impl Iterator for LevelIter<'_> {
+    type Item = anyhow::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(entry)
+            }
+
+            let meta = self.files.get(self.next_file)?;
+            self.next_file += 1;
+
+            match self.cache.get(meta) {
+                Ok(table) => {
+                    let mut iter = table.iter();
+                    if let Some(target) = self.target.take() {
+                        iter.seek(&target);
+                    }
+
+                    self.current = Some(iter);
+                }
+                Err(err) => {
+                    self.next_file = self.files.len();
+                    self.current = None;
+                    return Some(Err(err))
+                }
+            }
+        }
+    }
+}
+
+impl SeekIter for LevelIter<'_> {
+    fn seek(&mut self, key: &[u8]) {
+        // The first table that ends at or after the key is the only one that can contain it.
+        self.next_file = self.files.partition_point(|meta| user_key(&meta.largest) < key);
+        self.current = None;
+        self.target = Some(key.to_vec());
+    }
+}
+
+/// Merges several sorted sources into a single sorted sequence.
+///
+/// Only the most recent version of every key is returned. Sources are given from newest to oldest,
+/// so when multiple sources contain the same key, the version in the earliest source is used.
+/// Deletions are returned as well so that compactions can preserve them.
+pub struct MergingIter<'a> {
+    /// Sources ordered from newest to oldest, together with their next entry.
+    sources: Vec<(Source<'a>, Option<anyhow::Result<Entry>>)>,
+}
+
+impl<'a> MergingIter<'a> {
+    /// Creates an iterator that merges the given sources.
+    pub fn new(sources: Vec<Source<'a>>) -> MergingIter<'a> {
+        MergingIter {
+            sources: sources
+                .into_iter()
+                .map(|mut source| {
+                    let head = source.next();
+                    (source, head)
+                })
+                .collect(),
+        }
+    }
+
+    /// Moves the iterator to the first key that is greater than or equal to the given key.
+    pub fn seek(&mut self, key: &[u8]) {
+        for (source, head) in &mut self.sources {
+            source.seek(key);
+            *head = source.next();
+        }
+    }
+}
+
+impl Iterator for MergingIter<'_> {
+    type Item = anyhow::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut smallest: Option<(usize, &[u8])> = None;
+        for (index, (_, head)) in self.sources.iter().enumerate() {
+            match head {
+                None => (),
+                Some(Err(_)) => {
+                    smallest = Some((index, &[]));
+                    break
+                }
+                Some(Ok(entry)) => {
+                    // Ties are won by the earlier, newer source.
+                    if smallest.map_or(true, |(_, key)| entry.key.as_slice() < key) {
+                        smallest = Some((index, &entry.key));
+                    }
+                }
+            }
+        }
+
+        let (index, _) = smallest?;
+        let (source, head) = &mut self.sources[index];
+        let entry = match std::mem::replace(head, source.next())? {
+            Ok(entry) => entry,
+            Err(err) => return Some(Err(err)),
+        };
+
+        // Skip older versions of the same key.
+        for (source, head) in &mut self.sources {
+            while matches!(head, Some(Ok(next)) if next.key == entry.key) {
+                *head = source.next();
+            }
+        }
+
+        Some(Ok(entry))
+    }
+}
@@ -0,0 +1,715 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use util::RVec;
+
+use crate::DataKey;
+use coding::{compare_internal, user_key, Entry};
+use log::LogWriter;
+use merge::{LevelIter, MemIter, MergingIter, Source};
+use table::{table_path, TableBuilder, TableCache};
+use version::{FileMeta, Version, VersionEdit, COMPARATOR, NUM_LEVELS};
+
+mod batch;
+mod block;
+mod coding;
+mod log;
+mod merge;
+mod table;
+mod version;
+
+pub use batch::WriteBatch;
+
+/// Size of the memtable at which it is written to a table.
+const WRITE_BUFFER_SIZE: usize = 4 * 1024 * 1024;
+/// Size at which tables created by compactions are split.
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+/// Approximate memory overhead of a single memtable entry.
+const ENTRY_OVERHEAD: usize = 16;
+
+/// Kinds of files that can be found in the database directory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FileType {
+    /// Write-ahead log containing writes that have not been written to a table yet.
+    Log,
+    /// Sorted table.
+    Table,
+    /// Manifest describing which tables are part of the database.
+    Manifest,
+    /// Temporary file used to atomically replace `CURRENT`.
+    Temp,
+}
+
+/// Parses the name of a file in the database directory.
+fn parse_file_name(name: &str) -> Option<(u64, FileType)> {
+    if let Some(number) = name.strip_prefix("MANIFEST-") {
+        return Some((number.parse().ok()?, FileType::Manifest))
+    }
+
+    let (number, extension) = name.split_once('.')?;
+    let kind = match extension {
+        "log" => FileType::Log,
+        "ldb" | "sst" => FileType::Table,
+        "dbtmp" => FileType::Temp,
+        _ => return None,
+    };
+
+    Some((number.parse().ok()?, kind))
+}
+
+/// Path of the write-ahead log with the given file number.
+fn log_path(directory: &Path, number: u64) -> PathBuf {
+    directory.join(format!("{number:06}.log"))
+}
+
+/// Creates an error for a lock that was poisoned by a panicking thread.
+fn poisoned<T>(_: PoisonError<T>) -> anyhow::Error {
+    anyhow::anyhow!("Database lock is poisoned")
+}
+
+/// Wraps a value read from the database.
+#[derive(Debug)]
+pub struct Guard<'a>(Cow<'a, [u8]>);
+
+impl<'a> Deref for Guard<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> AsRef<[u8]> for Guard<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Reference to a key-value pair returned by the [`Keys`] iterator.
+pub struct KvRef<'a> {
+    /// Key of the pair.
+    key: Vec<u8>,
+    /// Value of the pair.
+    value: Vec<u8>,
+    /// Ensures that [`KvRef`] does not outlive the parent [`Keys`] iterator.
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> KvRef<'a> {
+    /// The key associated with this pair.
+    pub fn key(&self) -> Guard<'_> {
+        Guard(Cow::Borrowed(&self.key))
+    }
+
+    /// The data associated with this pair.
+    pub fn value(&self) -> Guard<'_> {
+        Guard(Cow::Borrowed(&self.value))
+    }
+}
+
+/// Iterator over keys in a LevelDB database.
+///
+/// The iterator sees the database as it was when the iterator was created.
+pub struct Keys<'a> {
+    /// Database that is iterated over.
+    db: &'a Database,
+    /// Merged view of the memtable and all tables.
+    merged: MergingIter<'a>,
+    /// Set when a table could not be read, which ends the iteration.
+    failed: bool,
+}
+
+impl<'a> Keys<'a> {
+    /// Creates a new iterator for the given database.
+    pub fn new(db: &'a Database) -> Keys<'a> {
+        // Tables that are removed by compactions are kept on disk until all iterators are done with them.
+        db.obsolete.lock().unwrap_or_else(PoisonError::into_inner).0 += 1;
+
+        let inner = db.inner.read().unwrap_or_else(PoisonError::into_inner);
+        let memtable: Vec<_> = inner
+            .memtable
+            .iter()
+            .map(|(key, (sequence, value))| Entry { key: key.clone(), sequence: *sequence, value: value.clone() })
+            .collect();
+
+        let mut sources: Vec<Source<'a>> = vec![Box::new(MemIter::new(memtable))];
+        for meta in &inner.version.levels[0] {
+            sources.push(Box::new(LevelIter::new(&db.tables, [Arc::clone(meta)])));
+        }
+        for level in &inner.version.levels[1..] {
+            sources.push(Box::new(LevelIter::new(&db.tables, level.iter().cloned())));
+        }
+
+        Keys { db, merged: MergingIter::new(sources), failed: false }
+    }
+
+    /// Moves the iterator to the first key that is greater than or equal to the given key.
+    ///
+    /// Iteration continues from that key, even if it was already passed before.
+    pub fn seek(&mut self, key: &[u8]) {
+        self.merged.seek(key);
+        self.failed = false;
+    }
+}
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = KvRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            match self.merged.next()? {
+                Ok(Entry { key, value: Some(value), .. }) => return Some(KvRef { key, value, _marker: PhantomData }),
+                // Deleted keys are skipped.
+                Ok(_) => (),
+                Err(err) => {
+                    tracing::error!("Failed to read database entry, iteration has been aborted: {err:#}");
+                    self.failed = true;
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> Drop for Keys<'a> {
+    fn drop(&mut self) {
+        self.db.release_iterator();
+    }
+}
+
+/// Mutable state of the database.
+struct Inner {
+    /// Writes that have not been written to a table yet, indexed by key.
+    memtable: BTreeMap<Vec<u8>, (u64, Option<Vec<u8>>)>,
+    /// Approximate size of the memtable in bytes.
+    memtable_size: usize,
+    /// Tables that make up the database.
+    version: Version,
+    /// Write-ahead log that contains the contents of the memtable.
+    log: LogWriter,
+    /// File number of the write-ahead log.
+    log_number: u64,
+    /// Manifest that all changes to the version are recorded in.
+    manifest: LogWriter,
+    /// Next unused file number.
+    next_file_number: u64,
+    /// Sequence number of the last write.
+    last_sequence: u64,
+    /// Largest key of the last compaction in every level, where the next compaction continues.
+    compact_pointers: [Vec<u8>; NUM_LEVELS],
+}
+
+impl Inner {
+    /// Allocates a new file number.
+    fn new_file_number(&mut self) -> u64 {
+        self.next_file_number += 1;
+        self.next_file_number - 1
+    }
+
+    /// Inserts the operations of a batch into the memtable.
+    fn insert(&mut self, batch: &WriteBatch, sequence: u64) {
+        for ((key, value), sequence) in batch.operations.iter().zip(sequence..) {
+            self.memtable_size += key.len() + value.as_ref().map_or(0, Vec::len) + ENTRY_OVERHEAD;
+            self.memtable.insert(key.clone(), (sequence, value.clone()));
+        }
+    }
+}
+
+/// State read from the manifest and write-ahead logs when the database is opened.
+struct Recovered {
+    /// Tables that make up the database.
+    version: Version,
+    /// Writes recovered from the write-ahead logs.
+    memtable: BTreeMap<Vec<u8>, (u64, Option<Vec<u8>>)>,
+    /// Next unused file number.
+    next_file_number: u64,
+    /// Sequence number of the last write.
+    last_sequence: u64,
+    /// Compaction progress of every level.
+    compact_pointers: [Vec<u8>; NUM_LEVELS],
+}
+
+impl Recovered {
+    /// Reads the manifest and replays the write-ahead logs of the database in the given directory.
+    ///
+    /// An empty state is returned if the directory does not contain a database yet.
+    fn read(directory: &Path) -> anyhow::Result<Recovered> {
+        let mut recovered = Recovered {
+            version: Version::default(),
+            memtable: BTreeMap::new(),
+            // File number 1 is used by the manifest of a new database.
+            next_file_number: 2,
+            last_sequence: 0,
+            compact_pointers: Default::default(),
+        };
+
+        let mut log_number = 0;
+        let mut prev_log_number = 0;
+
+        let current = directory.join("CURRENT");
+        if current.exists() {
+            let name = std::fs::read_to_string(&current)?;
+            let manifest = std::fs::read(directory.join(name.trim_end()))?;
+
+            for record in log::read_log(&manifest) {
+                let edit = VersionEdit::decode(&record)?;
+                if let Some(comparator) = &edit.comparator {
+                    if comparator != COMPARATOR {
+                        anyhow::bail!("Database uses unsupported comparator {comparator}");
+                    }
+                }
+
+                log_number = edit.log_number.unwrap_or(log_number);
+                prev_log_number = edit.prev_log_number.unwrap_or(prev_log_number);
+                recovered.next_file_number = edit.next_file_number.unwrap_or(recovered.next_file_number);
+                recovered.last_sequence = edit.last_sequence.unwrap_or(recovered.last_sequence);
+                for (level, key) in &edit.compact_pointers {
+                    recovered.compact_pointers[*level].clone_from(key);
+                }
+
+                recovered.version.apply(&edit);
+            }
+        }
+
+        let mut logs = Vec::new();
+        for file in std::fs::read_dir(directory)? {
+            let name = file?.file_name();
+            if let Some((number, FileType::Log)) = name.to_str().and_then(parse_file_name) {
+                if number >= log_number || number == prev_log_number {
+                    logs.push(number);
+                }
+            }
+        }
+        logs.sort_unstable();
+
+        for number in logs {
+            recovered.next_file_number = recovered.next_file_number.max(number + 1);
+
+            let data = std::fs::read(log_path(directory, number))?;
+            for record in log::read_log(&data) {
+                let (sequence, batch) = WriteBatch::decode(&record)?;
+                for ((key, value), sequence) in batch.operations.into_iter().zip(sequence..) {
+                    recovered.last_sequence = recovered.last_sequence.max(sequence);
+                    recovered.memtable.insert(key, (sequence, value));
+                }
+            }
+        }
+
+        Ok(recovered)
+    }
+}
+
+/// A LevelDB database, implemented without any native dependencies.
+///
+/// Tables are written using the raw zlib compression that Minecraft uses, so that databases
+/// written by this implementation can be opened by the game.
+pub struct Database {
+    /// Directory containing the database files.
+    directory: PathBuf,
+    /// Mutable state of the database.
+    inner: RwLock<Inner>,
+    /// Recently used tables.
+    tables: TableCache,
+    /// Amount of active iterators together with the files that can be deleted once they are done.
+    obsolete: Mutex<(usize, Vec<PathBuf>)>,
+}
+
+impl Database {
+    /// Opens the database at the specified path.
+    ///
+    /// A new database is created if the directory does not contain one.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the database could not be opened.
+    pub fn open<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<str>,
+    {
+        let directory = PathBuf::from(path.as_ref());
+        std::fs::create_dir_all(&directory)?;
+
+        let mut recovered = Recovered::read(&directory)?;
+        let mut next_file_number = || {
+            recovered.next_file_number += 1;
+            recovered.next_file_number - 1
+        };
+
+        let log_number = next_file_number();
+        let table_number = next_file_number();
+        let manifest_number = next_file_number();
+
+        // Write the recovered writes to a table so that the old logs can be removed.
+        if !recovered.memtable.is_empty() {
+            let mut table = TableBuilder::create(&table_path(&directory, table_number))?;
+            for (key, (sequence, value)) in std::mem::take(&mut recovered.memtable) {
+                table.add(&Entry { key, sequence, value })?;
+            }
+
+            if let Some(meta) = table.finish(table_number)? {
+                recovered.version.apply(&VersionEdit { added: vec![(0, meta)], ..VersionEdit::default() });
+            }
+        }
+
+        // Start a new manifest containing the current state of the database.
+        let mut manifest = LogWriter::create(&directory.join(format!("MANIFEST-{manifest_number:06}")))?;
+        let mut edit = recovered.version.snapshot();
+        edit.comparator = Some(COMPARATOR.to_owned());
+        edit.log_number = Some(log_number);
+        edit.next_file_number = Some(recovered.next_file_number);
+        edit.last_sequence = Some(recovered.last_sequence);
+        edit.compact_pointers = recovered
+            .compact_pointers
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| !key.is_empty())
+            .map(|(level, key)| (level, key.clone()))
+            .collect();
+        manifest.add_record(&edit.encode())?;
+        manifest.sync()?;
+
+        let temp = directory.join(format!("{manifest_number:06}.dbtmp"));
+        std::fs::write(&temp, format!("MANIFEST-{manifest_number:06}\n"))?;
+        std::fs::rename(&temp, directory.join("CURRENT"))?;
+
+        let inner = Inner {
+            memtable: BTreeMap::new(),
+            memtable_size: 0,
+            log: LogWriter::create(&log_path(&directory, log_number))?,
+            log_number,
+            manifest,
+            version: recovered.version,
+            next_file_number: recovered.next_file_number,
+            last_sequence: recovered.last_sequence,
+            compact_pointers: recovered.compact_pointers,
+        };
+
+        let database = Database {
+            tables: TableCache::new(directory.clone()),
+            directory,
+            inner: RwLock::new(inner),
+            obsolete: Mutex::new((0, Vec::new())),
+        };
+
+        database.remove_obsolete_files(manifest_number)?;
+        {
+            let mut inner = database.inner.write().map_err(poisoned)?;
+            database.compact(&mut inner)?;
+        }
+
+        Ok(database)
+    }
+
+    /// Creates a new [`Keys`] iterator.
+    #[inline]
+    pub fn iter(&self) -> Keys<'_> {
+        Keys::new(self)
+    }
+
+    /// Loads the specified value from the database.
+    pub fn get(&self, key: DataKey) -> anyhow::Result<Option<Guard<'_>>> {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.get_raw(&raw_key)
+    }
+
+    /// Loads the value stored at a key that is not associated with a chunk, such as `~local_player`.
+    pub fn get_raw(&self, raw_key: &[u8]) -> anyhow::Result<Option<Guard<'_>>> {
+        let inner = self.inner.read().map_err(poisoned)?;
+        if let Some((_, value)) = inner.memtable.get(raw_key) {
+            return Ok(value.clone().map(|value| Guard(Cow::Owned(value))))
+        }
+
+        // Tables in level 0 may overlap, all of them have to be searched from newest to oldest.
+        let level0 = inner.version.levels[0].iter().filter(|meta| meta.contains(raw_key));
+
+        // Other levels contain at most one table that could contain the key.
+        let deeper = inner.version.levels[1..].iter().filter_map(|files| {
+            let index = files.partition_point(|meta| user_key(&meta.largest) < raw_key);
+            files.get(index).filter(|meta| meta.contains(raw_key))
+        });
+
+        for meta in level0.chain(deeper) {
+            if let Some(entry) = self.tables.get(meta)?.get(raw_key)? {
+                return Ok(entry.value.map(|value| Guard(Cow::Owned(value))))
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Inserts a new value into the database.
+    ///
+    /// # Arguments
+    /// * `key` - Key to store the value at.
+    /// * `value` - Value to store at the specified key.
+    pub fn put<V>(&self, key: DataKey, value: V) -> anyhow::Result<()>
+    where
+        V: AsRef<[u8]>,
+    {
+        let mut raw_key = RVec::alloc_with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key)?;
+
+        self.put_raw(&raw_key, value)
+    }
+
+    /// Inserts a value at a key that is not associated with a chunk, such as `~local_player`.
+    pub fn put_raw<V>(&self, raw_key: &[u8], value: V) -> anyhow::Result<()>
+    where
+        V: AsRef<[u8]>,
+    {
+        let mut batch = WriteBatch::new();
+        batch.put(raw_key, value);
+
+        self.execute(&batch)
+    }
+
+    /// Removes the given key from the database.
+    pub fn delete(&self, key: DataKey) -> anyhow::Result<()> {
+        let mut batch = WriteBatch::new();
+        batch.delete_key(&key)?;
+
+        self.execute(&batch)
+    }
+
+    /// Executes a batch.
+    pub fn execute(&self, batch: &WriteBatch) -> anyhow::Result<()> {
+        if batch.is_empty() {
+            return Ok(())
+        }
+
+        let mut inner = self.inner.write().map_err(poisoned)?;
+        let sequence = inner.last_sequence + 1;
+
+        inner.log.add_record(&batch.encode(sequence))?;
+        inner.insert(batch, sequence);
+        inner.last_sequence += batch.len() as u64;
+
+        if inner.memtable_size >= WRITE_BUFFER_SIZE {
+            self.flush(&mut inner)?;
+            self.compact(&mut inner)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the memtable to a new table in level 0 and starts a new write-ahead log.
+    fn flush(&self, inner: &mut Inner) -> anyhow::Result<()> {
+        let log_number = inner.new_file_number();
+        let log = LogWriter::create(&log_path(&self.directory, log_number))?;
+
+        let memtable = std::mem::take(&mut inner.memtable);
+        let entries = memtable.into_iter().map(|(key, (sequence, value))| Ok(Entry { key, sequence, value }));
+        let tables = self.write_tables(inner, entries, false)?;
+
+        let edit = VersionEdit {
+            log_number: Some(log_number),
+            added: tables.into_iter().map(|meta| (0, meta)).collect(),
+            ..VersionEdit::default()
+        };
+        self.log_and_apply(inner, edit)?;
+
+        inner.log = log;
+        inner.memtable_size = 0;
+        let old_log = std::mem::replace(&mut inner.log_number, log_number);
+
+        self.remove_files(vec![log_path(&self.directory, old_log)])
+    }
+
+    /// Compacts levels until all of them are within their limits.
+    fn compact(&self, inner: &mut Inner) -> anyhow::Result<()> {
+        while let Some(level) = inner.version.compaction_level() {
+            self.compact_level(inner, level)?;
+        }
+
+        Ok(())
+    }
+
+    /// Merges tables from the given level into the next level.
+    ///
+    /// All tables are compacted out of level 0, while other levels compact a single table at a time,
+    /// cycling through the key space.
+    fn compact_level(&self, inner: &mut Inner, level: usize) -> anyhow::Result<()> {
+        let inputs: Vec<Arc<FileMeta>> = if level == 0 {
+            inner.version.levels[0].clone()
+        } else {
+            let files = &inner.version.levels[level];
+            let pointer = &inner.compact_pointers[level];
+
+            files
+                .iter()
+                .find(|meta| pointer.is_empty() || compare_internal(&meta.largest, pointer).is_gt())
+                .or_else(|| files.first())
+                .cloned()
+                .into_iter()
+                .collect()
+        };
+
+        let (Some(smallest), Some(largest)) = (
+            inputs.iter().map(|meta| user_key(&meta.smallest)).min().map(<[u8]>::to_vec),
+            inputs.iter().map(|meta| user_key(&meta.largest)).max().map(<[u8]>::to_vec),
+        ) else {
+            return Ok(())
+        };
+
+        let next = level + 1;
+        let overlapping = inner.version.overlapping(next, &smallest, &largest);
+
+        let mut edit = VersionEdit::default();
+        if let Some(meta) = inputs.iter().max_by(|lhs, rhs| compare_internal(&lhs.largest, &rhs.largest)) {
+            edit.compact_pointers.push((level, meta.largest.clone()));
+        }
+        edit.deleted.extend(inputs.iter().map(|meta| (level, meta.number)));
+
+        if let ([input], []) = (inputs.as_slice(), overlapping.as_slice()) {
+            // Nothing to merge with, the table can be moved without rewriting it.
+            edit.added.push((next, FileMeta::clone(input)));
+            return self.log_and_apply(inner, edit)
+        }
+
+        edit.deleted.extend(overlapping.iter().map(|meta| (next, meta.number)));
+
+        // Deletions can only be dropped if no deeper level contains an older version of the key.
+        let drop_deletions = inner.version.levels[next + 1..].iter().flatten().all(|meta| !meta.overlaps(&smallest, &largest));
+
+        let mut sources: Vec<Source> = Vec::with_capacity(inputs.len() + 1);
+        for meta in &inputs {
+            sources.push(Box::new(LevelIter::new(&self.tables, [Arc::clone(meta)])));
+        }
+        sources.push(Box::new(LevelIter::new(&self.tables, overlapping.iter().cloned())));
+
+        let tables = self.write_tables(inner, MergingIter::new(sources), drop_deletions)?;
+        edit.added.extend(tables.into_iter().map(|meta| (next, meta)));
+        self.log_and_apply(inner, edit)?;
+
+        let removed = inputs.iter().chain(&overlapping).map(|meta| {
+            self.tables.evict(meta.number);
+            table_path(&self.directory, meta.number)
+        });
+
+        self.remove_files(removed.collect())
+    }
+
+    /// Writes sorted entries to new tables, starting a new table whenever one becomes too large.
+    fn write_tables<I>(&self, inner: &mut Inner, entries: I, drop_deletions: bool) -> anyhow::Result<Vec<FileMeta>>
+    where
+        I: Iterator<Item = anyhow::Result<Entry>>,
+    {
+        let mut tables = Vec::new();
+        let mut current: Option<(u64, TableBuilder)> = None;
+
+        for entry in entries {
+            let entry = entry?;
+            if drop_deletions && entry.value.is_none() {
+                continue
+            }
+
+            let (_, builder) = match &mut current {
+                Some(current) => current,
+                None => {
+                    let number = inner.new_file_number();
+                    current.insert((number, TableBuilder::create(&table_path(&self.directory, number))?))
+                }
+            };
+
+            builder.add(&entry)?;
+            if builder.file_size() >= MAX_FILE_SIZE {
+                if let Some((number, builder)) = current.take() {
+                    tables.extend(builder.finish(number)?);
+                }
+            }
+        }
+
+        if let Some((number, builder)) = current {
+            tables.extend(builder.finish(number)?);
+        }
+
+        Ok(tables)
+    }
+
+    /// Records an edit in the manifest and applies it to the current version.
+    fn log_and_apply(&self, inner: &mut Inner, mut edit: VersionEdit) -> anyhow::Result<()> {
+        edit.next_file_number = Some(inner.next_file_number);
+        edit.last_sequence = Some(inner.last_sequence);
+
+        inner.manifest.add_record(&edit.encode())?;
+        inner.manifest.sync()?;
+
+        for (level, key) in &edit.compact_pointers {
+            inner.compact_pointers[*level].clone_from(key);
+        }
+        inner.version.apply(&edit);
+
+        Ok(())
+    }
+
+    /// Removes all files that are no longer part of the database.
+    fn remove_obsolete_files(&self, manifest_number: u64) -> anyhow::Result<()> {
+        let inner = self.inner.read().map_err(poisoned)?;
+        let live: std::collections::HashSet<u64> = inner.version.live_files().collect();
+
+        let mut obsolete = Vec::new();
+        for file in std::fs::read_dir(&self.directory)? {
+            let file = file?;
+            let keep = match file.file_name().to_str().and_then(parse_file_name) {
+                Some((number, FileType::Log)) => number == inner.log_number,
+                Some((number, FileType::Manifest)) => number == manifest_number,
+                Some((number, FileType::Table)) => live.contains(&number),
+                Some((_, FileType::Temp)) => false,
+                None => true,
+            };
+
+            if !keep {
+                obsolete.push(file.path());
+            }
+        }
+        drop(inner);
+
+        self.remove_files(obsolete)
+    }
+
+    /// Deletes the given files, or defers deletion until all active iterators have finished.
+    fn remove_files(&self, files: Vec<PathBuf>) -> anyhow::Result<()> {
+        let mut obsolete = self.obsolete.lock().map_err(poisoned)?;
+        if obsolete.0 > 0 {
+            obsolete.1.extend(files);
+            return Ok(())
+        }
+        drop(obsolete);
+
+        for file in files {
+            if let Err(err) = std::fs::remove_file(&file) {
+                tracing::warn!("Failed to remove obsolete database file {}: {err}", file.display());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unregisters an iterator and removes any files that were waiting for it.
+    fn release_iterator(&self) {
+        let files = {
+            let mut obsolete = self.obsolete.lock().unwrap_or_else(PoisonError::into_inner);
+            obsolete.0 -= 1;
+            if obsolete.0 > 0 {
+                return
+            }
+
+            std::mem::take(&mut obsolete.1)
+        };
+
+        for file in files {
+            if let Err(err) = std::fs::remove_file(&file) {
+                tracing::warn!("Failed to remove obsolete database file {}: {err}", file.display());
+            }
+        }
+    }
+}
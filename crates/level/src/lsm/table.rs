@@ -0,0 +1,407 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use flate2::{read::DeflateDecoder, read::ZlibDecoder, write::DeflateEncoder, Compression};
+use util::BinaryRead;
+
+use super::block::{Block, BlockBuilder};
+use super::coding::{masked_crc, put_varint, user_key, Entry};
+use super::version::FileMeta;
+
+/// Uncompressed size at which a data block is written to the table.
+const BLOCK_SIZE: usize = 4096;
+/// Size of the footer at the end of every table.
+const FOOTER_SIZE: usize = 48;
+/// Size of the compression type and checksum that follow every block.
+const BLOCK_TRAILER_SIZE: usize = 5;
+/// Magic number at the end of every table.
+const TABLE_MAGIC: u64 = 0xdb47_7524_8b80_fb57;
+/// Maximum amount of tables that are kept open at the same time.
+const MAX_OPEN_TABLES: usize = 512;
+
+/// Compression algorithms used for table blocks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+enum BlockCompression {
+    /// The block is stored as is.
+    None = 0,
+    /// Snappy compression, which is used by upstream LevelDB but not by Minecraft.
+    Snappy = 1,
+    /// Zlib compression, used by older versions of Minecraft.
+    Zlib = 2,
+    /// Zlib compression without the zlib header, used by Minecraft.
+    ZlibRaw = 4,
+}
+
+/// Location of a block in a table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BlockHandle {
+    /// Offset of the block from the start of the file.
+    pub offset: u64,
+    /// Size of the block, excluding its trailer.
+    pub size: u64,
+}
+
+impl BlockHandle {
+    /// Reads an encoded block handle.
+    fn decode(reader: &mut &[u8]) -> anyhow::Result<BlockHandle> {
+        Ok(BlockHandle { offset: reader.read_var_u64()?, size: reader.read_var_u64()? })
+    }
+
+    /// Appends the encoded block handle to the buffer.
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        put_varint(buffer, self.offset);
+        put_varint(buffer, self.size);
+    }
+}
+
+/// An immutable sorted table stored on disk.
+pub struct Table {
+    /// The opened table file.
+    file: Mutex<File>,
+    /// Last key of every data block in the table together with the location of the block.
+    index: Vec<(Vec<u8>, BlockHandle)>,
+}
+
+impl Table {
+    /// Opens the table at the given path and loads its index.
+    pub fn open(path: &Path, size: u64) -> anyhow::Result<Table> {
+        if size < FOOTER_SIZE as u64 {
+            anyhow::bail!("Table {} is too small to contain a footer", path.display());
+        }
+
+        let mut file = File::open(path)?;
+        let mut footer = [0; FOOTER_SIZE];
+        file.seek(SeekFrom::Start(size - FOOTER_SIZE as u64))?;
+        file.read_exact(&mut footer)?;
+
+        let mut magic = [0; 8];
+        magic.copy_from_slice(&footer[FOOTER_SIZE - 8..]);
+        if u64::from_le_bytes(magic) != TABLE_MAGIC {
+            anyhow::bail!("Table {} has an invalid magic number", path.display());
+        }
+
+        let mut reader = &footer[..FOOTER_SIZE - 8];
+        let _metaindex = BlockHandle::decode(&mut reader)?;
+        let index_handle = BlockHandle::decode(&mut reader)?;
+
+        let mut table = Table { file: Mutex::new(file), index: Vec::new() };
+        let index_block = table.read_block(index_handle)?;
+
+        let mut index = Vec::new();
+        for entry in index_block.iter() {
+            let (key, mut value) = entry?;
+            index.push((key, BlockHandle::decode(&mut value)?));
+        }
+        table.index = index;
+
+        Ok(table)
+    }
+
+    /// Reads, verifies and decompresses a block.
+    fn read_block(&self, handle: BlockHandle) -> anyhow::Result<Block> {
+        let mut raw = vec![0; handle.size as usize + BLOCK_TRAILER_SIZE];
+        {
+            let mut file = self.file.lock().map_err(|_| anyhow::anyhow!("Table file lock is poisoned"))?;
+            file.seek(SeekFrom::Start(handle.offset))?;
+            file.read_exact(&mut raw)?;
+        }
+
+        let (contents, trailer) = raw.split_at(handle.size as usize);
+        let checksum = u32::from_le_bytes([trailer[1], trailer[2], trailer[3], trailer[4]]);
+        if masked_crc(&[contents, &trailer[..1]]) != checksum {
+            anyhow::bail!("Table block at offset {} is corrupted", handle.offset);
+        }
+
+        let data = match trailer[0] {
+            c if c == BlockCompression::None as u8 => {
+                raw.truncate(handle.size as usize);
+                raw
+            }
+            c if c == BlockCompression::ZlibRaw as u8 => {
+                let mut data = Vec::new();
+                DeflateDecoder::new(contents).read_to_end(&mut data)?;
+                data
+            }
+            c if c == BlockCompression::Zlib as u8 => {
+                let mut data = Vec::new();
+                ZlibDecoder::new(contents).read_to_end(&mut data)?;
+                data
+            }
+            c if c == BlockCompression::Snappy as u8 => anyhow::bail!("Snappy compressed tables are not supported"),
+            c => anyhow::bail!("Table block uses unknown compression type {c}"),
+        };
+
+        Block::new(data)
+    }
+
+    /// Looks up the most recent version of the given key in this table.
+    pub fn get(&self, key: &[u8]) -> anyhow::Result<Option<Entry>> {
+        let start = self.index.partition_point(|(last, _)| user_key(last) < key);
+        for (_, handle) in &self.index[start..] {
+            let block = self.read_block(*handle)?;
+            for entry in block.iter() {
+                let (internal, value) = entry?;
+                match user_key(&internal).cmp(key) {
+                    std::cmp::Ordering::Less => continue,
+                    std::cmp::Ordering::Equal => return Entry::from_internal(&internal, value.to_vec()).map(Some),
+                    std::cmp::Ordering::Greater => return Ok(None),
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Iterates over all entries in this table.
+    pub fn iter(self: &Arc<Self>) -> TableIter {
+        TableIter { table: Arc::clone(self), block: 0, entries: VecDeque::new(), target: None }
+    }
+}
+
+/// Iterator over the entries of a table.
+///
+/// Blocks are read one at a time as the iterator advances.
+pub struct TableIter {
+    /// Table that is iterated over.
+    table: Arc<Table>,
+    /// Index of the next block to read.
+    block: usize,
+    /// Remaining entries of the current block.
+    entries: VecDeque<(Vec<u8>, Vec<u8>)>,
+    /// Entries with a key smaller than this are skipped.
+    target: Option<Vec<u8>>,
+}
+
+impl TableIter {
+    /// Moves the iterator to the first entry with a key that is greater than or equal to the given key.
+    pub fn seek(&mut self, key: &[u8]) {
+        self.block = self.table.index.partition_point(|(last, _)| user_key(last) < key);
+        self.entries.clear();
+        self.target = Some(key.to_vec());
+    }
+}
+
+impl Iterator for TableIter {
+    type Item = anyhow::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.entries.pop_front() {
+                if let Some(target) = &self.target {
+                    if user_key(&key) < target.as_slice() {
+                        continue
+                    }
+                    self.target = None;
+                }
+
+                return Some(Entry::from_internal(&key, value))
+            }
+
+            let (_, handle) = self.table.index.get(self.block)?;
+            self.block += 1;
+
+            let entries = self
+                .table
+                .read_block(*handle)
+                .and_then(|block| block.iter().map(|entry| entry.map(|(key, value)| (key, value.to_vec()))).collect());
+
+            match entries {
+                Ok(entries) => self.entries = entries,
+                Err(err) => {
+                    self.block = self.table.index.len();
+                    return Some(Err(err))
+                }
+            }
+        }
+    }
+}
+
+/// Writes a new table to disk.
+pub struct TableBuilder {
+    /// File the table is written to.
+    file: BufWriter<File>,
+    /// Amount of bytes written so far.
+    offset: u64,
+    /// The data block that is currently being built.
+    data: BlockBuilder,
+    /// Index of all data blocks that have been written.
+    index: BlockBuilder,
+    /// Smallest key in the table.
+    smallest: Option<Vec<u8>>,
+}
+
+impl TableBuilder {
+    /// Creates a new table file at the given path.
+    pub fn create(path: &Path) -> anyhow::Result<TableBuilder> {
+        Ok(TableBuilder {
+            file: BufWriter::new(File::create(path)?),
+            offset: 0,
+            data: BlockBuilder::new(),
+            index: BlockBuilder::new(),
+            smallest: None,
+        })
+    }
+
+    /// Adds an entry to the table.
+    ///
+    /// Entries must be added in sorted order.
+    pub fn add(&mut self, entry: &Entry) -> anyhow::Result<()> {
+        let key = entry.internal_key();
+        if self.smallest.is_none() {
+            self.smallest = Some(key.clone());
+        }
+
+        self.data.add(&key, entry.value.as_deref().unwrap_or_default());
+        if self.data.size_estimate() >= BLOCK_SIZE {
+            self.flush_block()?;
+        }
+
+        Ok(())
+    }
+
+    /// Approximate size of the table file.
+    pub fn file_size(&self) -> u64 {
+        self.offset + self.data.size_estimate() as u64
+    }
+
+    /// Writes the current data block and adds it to the index.
+    fn flush_block(&mut self) -> anyhow::Result<()> {
+        if self.data.is_empty() {
+            return Ok(())
+        }
+
+        let last = self.data.last_key().to_vec();
+        let contents = self.data.finish();
+        let handle = self.write_block(&contents, true)?;
+
+        let mut encoded = Vec::new();
+        handle.encode(&mut encoded);
+        self.index.add(&last, &encoded);
+
+        Ok(())
+    }
+
+    /// Writes a block followed by its trailer.
+    ///
+    /// Blocks are only stored compressed if that saves at least an eighth of their size.
+    fn write_block(&mut self, contents: &[u8], compress: bool) -> anyhow::Result<BlockHandle> {
+        let mut compressed = None;
+        if compress {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(contents)?;
+
+            let data = encoder.finish()?;
+            if data.len() < contents.len() - contents.len() / 8 {
+                compressed = Some(data);
+            }
+        }
+
+        let (data, compression) = match &compressed {
+            Some(data) => (data.as_slice(), BlockCompression::ZlibRaw),
+            None => (contents, BlockCompression::None),
+        };
+
+        let handle = BlockHandle { offset: self.offset, size: data.len() as u64 };
+        self.file.write_all(data)?;
+        self.file.write_all(&[compression as u8])?;
+        self.file.write_all(&masked_crc(&[data, &[compression as u8]]).to_le_bytes())?;
+        self.offset += (data.len() + BLOCK_TRAILER_SIZE) as u64;
+
+        Ok(handle)
+    }
+
+    /// Writes the index and footer and flushes the table to disk.
+    ///
+    /// Returns `None` if no entries were added to the table.
+    pub fn finish(mut self, number: u64) -> anyhow::Result<Option<FileMeta>> {
+        let Some(smallest) = self.smallest.take() else {
+            return Ok(None)
+        };
+
+        self.flush_block()?;
+        let largest = self.index.last_key().to_vec();
+
+        // Filters are optional, the metaindex block is left empty.
+        let metaindex = BlockBuilder::new().finish();
+        let metaindex = self.write_block(&metaindex, false)?;
+        let index = self.index.finish();
+        let index = self.write_block(&index, false)?;
+
+        let mut footer = Vec::with_capacity(FOOTER_SIZE);
+        metaindex.encode(&mut footer);
+        index.encode(&mut footer);
+        footer.resize(FOOTER_SIZE - 8, 0);
+        footer.extend_from_slice(&TABLE_MAGIC.to_le_bytes());
+
+        self.file.write_all(&footer)?;
+        self.offset += FOOTER_SIZE as u64;
+
+        let file = self.file.into_inner().map_err(|err| err.into_error())?;
+        file.sync_all()?;
+
+        Ok(Some(FileMeta { number, size: self.offset, smallest, largest }))
+    }
+}
+
+/// Path of the table with the given file number.
+///
+/// Tables written by older versions of LevelDB use the `.sst` extension.
+pub fn table_path(directory: &Path, number: u64) -> PathBuf {
+    let path = directory.join(format!("{number:06}.ldb"));
+    if path.exists() {
+        return path
+    }
+
+    let legacy = directory.join(format!("{number:06}.sst"));
+    if legacy.exists() {
+        legacy
+    } else {
+        path
+    }
+}
+
+/// Keeps recently used tables open.
+pub struct TableCache {
+    /// Directory of the database.
+    directory: PathBuf,
+    /// Opened tables indexed by file number.
+    tables: Mutex<HashMap<u64, Arc<Table>>>,
+}
+
+impl TableCache {
+    /// Creates an empty cache for the database in the given directory.
+    pub fn new(directory: PathBuf) -> TableCache {
+        TableCache { directory, tables: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the table described by the given metadata, opening it if necessary.
+    pub fn get(&self, meta: &FileMeta) -> anyhow::Result<Arc<Table>> {
+        let mut tables = self.tables.lock().map_err(|_| anyhow::anyhow!("Table cache lock is poisoned"))?;
+        if let Some(table) = tables.get(&meta.number) {
+            return Ok(Arc::clone(table))
+        }
+
+        if tables.len() >= MAX_OPEN_TABLES {
+            // Close an arbitrary table to stay below the file descriptor limit.
+            if let Some(&number) = tables.keys().next() {
+                tables.remove(&number);
+            }
+        }
+
+        let table = Arc::new(Table::open(&table_path(&self.directory, meta.number), meta.size)?);
+        tables.insert(meta.number, Arc::clone(&table));
+
+        Ok(table)
+    }
+
+    /// Closes the table with the given number if it is open.
+    pub fn evict(&self, number: u64) {
+        if let Ok(mut tables) = self.tables.lock() {
+            tables.remove(&number);
+        }
+    }
+}
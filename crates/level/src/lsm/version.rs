@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use util::BinaryRead;
+
+use super::coding::{compare_internal, put_prefixed, put_varint, user_key};
+
+/// Amount of levels in the database.
+pub const NUM_LEVELS: usize = 7;
+/// Amount of tables in level 0 that triggers a compaction into level 1.
+const LEVEL0_COMPACTION_TRIGGER: usize = 4;
+/// Name of the only key comparator that is supported.
+pub const COMPARATOR: &str = "leveldb.BytewiseComparator";
+
+/// Tags of the fields in a version edit.
+mod tag {
+    pub const COMPARATOR: u32 = 1;
+    pub const LOG_NUMBER: u32 = 2;
+    pub const NEXT_FILE_NUMBER: u32 = 3;
+    pub const LAST_SEQUENCE: u32 = 4;
+    pub const COMPACT_POINTER: u32 = 5;
+    pub const DELETED_FILE: u32 = 6;
+    pub const NEW_FILE: u32 = 7;
+    pub const PREV_LOG_NUMBER: u32 = 9;
+}
+
+/// Describes a single table in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMeta {
+    /// File number of the table.
+    pub number: u64,
+    /// Size of the table in bytes.
+    pub size: u64,
+    /// Smallest table key in the table.
+    pub smallest: Vec<u8>,
+    /// Largest table key in the table.
+    pub largest: Vec<u8>,
+}
+
+impl FileMeta {
+    /// Whether the given user key falls within the key range of this table.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        user_key(&self.smallest) <= key && key <= user_key(&self.largest)
+    }
+
+    /// Whether the key range of this table overlaps with the given range of user keys.
+    pub fn overlaps(&self, smallest: &[u8], largest: &[u8]) -> bool {
+        user_key(&self.smallest) <= largest && smallest <= user_key(&self.largest)
+    }
+}
+
+/// A change to the set of tables in the database, as recorded in the manifest.
+#[derive(Debug, Default)]
+pub struct VersionEdit {
+    /// Name of the comparator used to sort keys.
+    pub comparator: Option<String>,
+    /// Logs with a number lower than this have been written to tables.
+    pub log_number: Option<u64>,
+    /// Log that was still in use when the log number was last changed, used by old versions of LevelDB.
+    pub prev_log_number: Option<u64>,
+    /// Next unused file number.
+    pub next_file_number: Option<u64>,
+    /// Sequence number of the last write.
+    pub last_sequence: Option<u64>,
+    /// Key at which the next compaction of a level should start.
+    pub compact_pointers: Vec<(usize, Vec<u8>)>,
+    /// Tables that have been removed, given as level and file number.
+    pub deleted: Vec<(usize, u64)>,
+    /// Tables that have been added.
+    pub added: Vec<(usize, FileMeta)>,
+}
+
+impl VersionEdit {
+    /// Encodes the edit into a manifest record.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        if let Some(comparator) = &self.comparator {
+            put_varint(&mut buffer, u64::from(tag::COMPARATOR));
+            put_prefixed(&mut buffer, comparator.as_bytes());
+        }
+
+        let numbers = [
+            (tag::LOG_NUMBER, self.log_number),
+            (tag::PREV_LOG_NUMBER, self.prev_log_number),
+            (tag::NEXT_FILE_NUMBER, self.next_file_number),
+            (tag::LAST_SEQUENCE, self.last_sequence),
+        ];
+        for (tag, number) in numbers {
+            if let Some(number) = number {
+                put_varint(&mut buffer, u64::from(tag));
+                put_varint(&mut buffer, number);
+            }
+        }
+
+        for (level, key) in &self.compact_pointers {
+            put_varint(&mut buffer, u64::from(tag::COMPACT_POINTER));
+            put_varint(&mut buffer, *level as u64);
+            put_prefixed(&mut buffer, key);
+        }
+
+        for (level, number) in &self.deleted {
+            put_varint(&mut buffer, u64::from(tag::DELETED_FILE));
+            put_varint(&mut buffer, *level as u64);
+            put_varint(&mut buffer, *number);
+        }
+
+        for (level, meta) in &self.added {
+            put_varint(&mut buffer, u64::from(tag::NEW_FILE));
+            put_varint(&mut buffer, *level as u64);
+            put_varint(&mut buffer, meta.number);
+            put_varint(&mut buffer, meta.size);
+            put_prefixed(&mut buffer, &meta.smallest);
+            put_prefixed(&mut buffer, &meta.largest);
+        }
+
+        buffer
+    }
+
+    /// Decodes an edit from a manifest record.
+    pub fn decode(mut reader: &[u8]) -> anyhow::Result<VersionEdit> {
+        /// Reads a buffer prefixed with its length.
+        fn read_prefixed(reader: &mut &[u8]) -> anyhow::Result<Vec<u8>> {
+            let length = reader.read_var_u32()?;
+            Ok(reader.take_n(length as usize)?.to_vec())
+        }
+
+        /// Reads a level number and ensures it is in range.
+        fn read_level(reader: &mut &[u8]) -> anyhow::Result<usize> {
+            let level = reader.read_var_u32()? as usize;
+            if level >= NUM_LEVELS {
+                anyhow::bail!("Manifest refers to level {level}, which does not exist");
+            }
+
+            Ok(level)
+        }
+
+        let mut edit = VersionEdit::default();
+        while !reader.is_empty() {
+            match reader.read_var_u32()? {
+                tag::COMPARATOR => edit.comparator = Some(String::from_utf8(read_prefixed(&mut reader)?)?),
+                tag::LOG_NUMBER => edit.log_number = Some(reader.read_var_u64()?),
+                tag::PREV_LOG_NUMBER => edit.prev_log_number = Some(reader.read_var_u64()?),
+                tag::NEXT_FILE_NUMBER => edit.next_file_number = Some(reader.read_var_u64()?),
+                tag::LAST_SEQUENCE => edit.last_sequence = Some(reader.read_var_u64()?),
+                tag::COMPACT_POINTER => {
+                    let level = read_level(&mut reader)?;
+                    edit.compact_pointers.push((level, read_prefixed(&mut reader)?));
+                }
+                tag::DELETED_FILE => {
+                    let level = read_level(&mut reader)?;
+                    edit.deleted.push((level, reader.read_var_u64()?));
+                }
+                tag::NEW_FILE => {
+                    let level = read_level(&mut reader)?;
+                    let meta = FileMeta {
+                        number: reader.read_var_u64()?,
+                        size: reader.read_var_u64()?,
+                        smallest: read_prefixed(&mut reader)?,
+                        largest: read_prefixed(&mut reader)?,
+                    };
+                    edit.added.push((level, meta));
+                }
+                tag => anyhow::bail!("Manifest contains unknown tag {tag}"),
+            }
+        }
+
+        Ok(edit)
+    }
+}
+
+/// The set of tables that make up the database.
+#[derive(Debug, Clone, Default)]
+pub struct Version {
+    /// Tables in every level.
+    ///
+    /// Tables in level 0 may overlap and are sorted from newest to oldest.
+    /// Tables in other levels do not overlap and are sorted by key.
+    pub levels: [Vec<Arc<FileMeta>>; NUM_LEVELS],
+}
+
+impl Version {
+    /// Applies the table changes in an edit.
+    pub fn apply(&mut self, edit: &VersionEdit) {
+        for (level, number) in &edit.deleted {
+            self.levels[*level].retain(|meta| meta.number != *number);
+        }
+
+        for (level, meta) in &edit.added {
+            self.levels[*level].push(Arc::new(meta.clone()));
+        }
+
+        self.levels[0].sort_unstable_by(|lhs, rhs| rhs.number.cmp(&lhs.number));
+        for level in &mut self.levels[1..] {
+            level.sort_unstable_by(|lhs, rhs| compare_internal(&lhs.smallest, &rhs.smallest));
+        }
+    }
+
+    /// Creates an edit that adds all tables in this version.
+    pub fn snapshot(&self) -> VersionEdit {
+        let added = self
+            .levels
+            .iter()
+            .enumerate()
+            .flat_map(|(level, files)| files.iter().map(move |meta| (level, FileMeta::clone(meta))))
+            .collect();
+
+        VersionEdit { added, ..VersionEdit::default() }
+    }
+
+    /// File numbers of all tables in this version.
+    pub fn live_files(&self) -> impl Iterator<Item = u64> + '_ {
+        self.levels.iter().flatten().map(|meta| meta.number)
+    }
+
+    /// Tables in the given level that overlap with the given range of user keys.
+    pub fn overlapping(&self, level: usize, smallest: &[u8], largest: &[u8]) -> Vec<Arc<FileMeta>> {
+        self.levels[level].iter().filter(|meta| meta.overlaps(smallest, largest)).cloned().collect()
+    }
+
+    /// Returns the level that should be compacted next, if any.
+    ///
+    /// Level 0 is limited by its amount of tables, because every table in it has to be searched.
+    /// Other levels are limited by their total size, which grows tenfold for every level.
+    pub fn compaction_level(&self) -> Option<usize> {
+        if self.levels[0].len() >= LEVEL0_COMPACTION_TRIGGER {
+            return Some(0)
+        }
+
+        let mut max_bytes = 10 * 1024 * 1024;
+        for level in 1..NUM_LEVELS - 1 {
+            let size: u64 = self.levels[level].iter().map(|meta| meta.size).sum();
+            if size > max_bytes {
+                return Some(level)
+            }
+
+            max_bytes *= 10;
+        }
+
+        None
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// An item stored in a player's inventory on disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedItem {
+    /// Slot in the inventory that the item occupies.
+    #[serde(rename = "Slot")]
+    pub slot: i8,
+    /// Identifier of the item, such as `minecraft:stone`.
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// Amount of items in the stack.
+    #[serde(rename = "Count")]
+    pub count: i8,
+    /// Damage or variant of the item.
+    #[serde(rename = "Damage")]
+    pub damage: i16,
+    /// Additional data of the item, such as its enchantments and custom name.
+    #[serde(rename = "tag", default)]
+    pub tag: Option<HashMap<String, nbt::Value>>,
+}
+
+/// Player data that is kept when a player leaves the server.
+///
+/// This uses the same field names as the player data stored by vanilla servers,
+/// but only contains the fields that are used by the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "")]
+pub struct PlayerSave {
+    /// Position of the player's feet.
+    #[serde(rename = "Pos")]
+    pub position: [f32; 3],
+    /// Pitch and yaw of the player.
+    #[serde(rename = "Rotation")]
+    pub rotation: [f32; 2],
+    /// Game mode of the player.
+    #[serde(rename = "PlayerGameType")]
+    pub game_mode: i32,
+    /// Hotbar slot that was selected.
+    #[serde(rename = "SelectedInventorySlot")]
+    pub selected_slot: i32,
+    /// Non-empty slots of the main inventory.
+    #[serde(rename = "Inventory")]
+    pub inventory: Vec<SavedItem>,
+    /// Non-empty armour slots, from helmet to boots.
+    #[serde(rename = "Armor", default)]
+    pub armor: Vec<SavedItem>,
+    /// The off-hand item, if there is one.
+    #[serde(rename = "Offhand", default)]
+    pub offhand: Vec<SavedItem>,
+}
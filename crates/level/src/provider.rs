@@ -1,15 +1,69 @@
 // Special keys
 
 use crate::biome::Biomes;
-use crate::database::Database;
-use crate::settings::LevelSettings;
-use crate::{DataKey, KeyType, SubChunk, WriteBatch};
+use crate::database::{Database, Keys};
+use crate::settings::{LevelDat, LevelSettings};
+use crate::{actor_key, digp_key, prefix_successor, DataKey, KeyType, PlayerSave, SubChunk, WriteBatch, ACTOR_ID_SIZE};
 use anyhow::anyhow;
+use nbt::Value;
 use proto::types::Dimension;
+use proto::uuid::Uuid;
 use std::path::{Path, PathBuf};
-use util::BinaryRead;
 use util::Vector;
 
+/// Size of the chunk coordinates at the start of every [`DataKey`].
+const COORDINATES_SIZE: usize = 8;
+
+/// Iterator over the chunks that exist in a dimension, created by [`Provider::chunks`].
+pub struct Chunks<'a> {
+    /// Iterator over the raw database keys.
+    keys: Keys<'a>,
+    /// Dimension to list the chunks of.
+    dimension: Dimension,
+    /// Whether the last possible chunk has been visited.
+    done: bool,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = anyhow::Result<Vector<i32, 2>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let (coordinates, prefix) = {
+                let kv = self.keys.next()?;
+                let key = kv.key();
+                let Ok(data_key) = DataKey::deserialize(&*key) else {
+                    continue
+                };
+
+                (data_key.coordinates, key[..COORDINATES_SIZE].to_vec())
+            };
+
+            // The data of a chunk in all dimensions shares the same coordinate prefix.
+            // Only the version key has to be checked, the remaining keys of the chunk can be skipped.
+            let version = DataKey { coordinates: coordinates.clone(), dimension: self.dimension, data: KeyType::ChunkVersion };
+            let mut raw_key = Vec::with_capacity(version.serialized_size());
+            if let Err(err) = version.serialize(&mut raw_key) {
+                return Some(Err(err))
+            }
+
+            self.keys.seek(&raw_key);
+            let exists = self.keys.next().is_some_and(|kv| *kv.key() == *raw_key);
+
+            match prefix_successor(&prefix) {
+                Some(next) => self.keys.seek(&next),
+                None => self.done = true,
+            }
+
+            if exists {
+                return Some(Ok(coordinates))
+            }
+        }
+
+        None
+    }
+}
+
 /// Provides world data.
 ///
 /// This is a wrapper around a database that also deserialises and serialises data.
@@ -42,20 +96,36 @@ impl Provider {
     /// This method returns an error if the content does not match what is specified in the header.
     #[tracing::instrument(skip_all, name = "Provider::settings")]
     pub fn settings(&self) -> anyhow::Result<LevelSettings> {
+        self.level_dat()?.settings()
+    }
+
+    /// Loads the full contents of the `level.dat` file, including fields that are unknown to the server.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the file could not be read or if the content does not match what is specified in the header.
+    pub fn level_dat(&self) -> anyhow::Result<LevelDat> {
         let raw = std::fs::read(self.path.join("level.dat"))?;
+        LevelDat::deserialize(&raw)
+    }
 
-        let mut reader = raw.as_slice();
-        let _file_version = reader.read_u32_le()?;
-        let file_size = reader.read_u32_le()?;
+    /// Writes the `level.dat` file.
+    ///
+    /// Like vanilla, the previous version of the file is kept as `level.dat_old`.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the data could not be serialised or written to disk.
+    pub fn save_level_dat(&self, level_dat: &LevelDat) -> anyhow::Result<()> {
+        let data = level_dat.serialize()?;
 
-        let remaining = reader.remaining();
-        if remaining != file_size as usize {
-            tracing::error!("Invalid `level.dat` file: header specified length of {file_size}, but found {remaining}");
-            anyhow::bail!("Invalid `level.dat` file: header specified length of {file_size} bytes, but found {remaining}");
+        let path = self.path.join("level.dat");
+        if path.exists() {
+            std::fs::copy(&path, self.path.join("level.dat_old"))?;
         }
 
-        let (settings, _) = nbt::from_le_bytes(&mut reader)?;
-        Ok(settings)
+        std::fs::write(path, data)?;
+        Ok(())
     }
 
     /// Load the version of the specified chunk.
@@ -108,13 +178,34 @@ impl Provider {
         };
 
         if let Some(data) = self.database.get(key)? {
-            let biome = Biomes::deserialize(&*data)?;
+            let biome = Biomes::deserialize_disk(&*data)?;
             Ok(Some(biome))
         } else {
             Ok(None)
         }
     }
 
+    /// Replaces the biomes and heightmap of the specified chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the biomes could not be serialised or written to the database.
+    pub fn set_biomes<I>(&self, coordinates: I, dimension: Dimension, biomes: &Biomes) -> anyhow::Result<()>
+    where
+        I: Into<Vector<i32, 2>>,
+    {
+        let key = DataKey {
+            coordinates: coordinates.into(),
+            dimension,
+            data: KeyType::Biome3d,
+        };
+
+        let mut data = Vec::new();
+        biomes.serialize_disk(&mut data)?;
+
+        self.database.put(key, data)
+    }
+
     /// Load the specified sub chunk from the database.
     ///
     /// See [`SubChunk`] for more information.
@@ -141,16 +232,255 @@ impl Provider {
         };
 
         if let Some(data) = self.database.get(key)? {
-            let sub_chunk = SubChunk::deserialize_disk(&*data)?;
+            let mut sub_chunk = SubChunk::deserialize_disk(&*data)?;
+            // Older sub chunks do not store their own index.
+            sub_chunk.index = coordinates.y as i8;
+
             Ok(Some(sub_chunk))
         } else {
             Ok(None)
         }
     }
 
+    /// Load the block entities in the specified chunk, such as chests and signs.
+    ///
+    /// Every block entity is an NBT compound that contains at least its identifier and position.
+    ///
+    /// # Arguments
+    ///
+    /// * `coordinates` - X and Z coordinates of the chunk.
+    /// * `dimension` - Dimension the chunk should be retrieved from.
+    ///
+    /// # Returns
+    ///
+    /// This method returns an empty list if the chunk has no block entities
+    /// and an error if the data could not be loaded.
+    pub fn block_entities<I>(&self, coordinates: I, dimension: Dimension) -> anyhow::Result<Vec<Value>>
+    where
+        I: Into<Vector<i32, 2>>,
+    {
+        let key = DataKey {
+            coordinates: coordinates.into(),
+            dimension,
+            data: KeyType::BlockEntity,
+        };
+
+        match self.database.get(key)? {
+            Some(data) => Self::read_compounds(&data),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Replaces all block entities in the specified chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if one of the block entities could not be serialised
+    /// or if the data could not be written to the database.
+    pub fn set_block_entities<I>(&self, coordinates: I, dimension: Dimension, block_entities: &[Value]) -> anyhow::Result<()>
+    where
+        I: Into<Vector<i32, 2>>,
+    {
+        let key = DataKey {
+            coordinates: coordinates.into(),
+            dimension,
+            data: KeyType::BlockEntity,
+        };
+
+        if block_entities.is_empty() {
+            return self.database.delete(key);
+        }
+
+        let mut data = Vec::new();
+        for block_entity in block_entities {
+            nbt::to_le_bytes_in(&mut data, block_entity)?;
+        }
+
+        self.database.put(key, data)
+    }
+
+    /// Load the entities in the specified chunk.
+    ///
+    /// Newer worlds store a list of entity identifiers in a `digp` record, with every entity in its own `actorprefix` record.
+    /// Older worlds store all entities of a chunk in a single record. Entities from both formats are returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `coordinates` - X and Z coordinates of the chunk.
+    /// * `dimension` - Dimension the chunk should be retrieved from.
+    ///
+    /// # Returns
+    ///
+    /// This method returns an empty list if the chunk has no entities
+    /// and an error if the data could not be loaded.
+    pub fn entities<I>(&self, coordinates: I, dimension: Dimension) -> anyhow::Result<Vec<Value>>
+    where
+        I: Into<Vector<i32, 2>>,
+    {
+        let coordinates = coordinates.into();
+
+        let mut entities = Vec::new();
+        for id in self.entity_ids(&coordinates, dimension)? {
+            let Some(data) = self.database.get_raw(&actor_key(&id))? else {
+                tracing::warn!("Chunk {coordinates:?} refers to entity {id:x?} which does not exist");
+                continue
+            };
+
+            let (entity, _) = nbt::from_le_bytes(&mut &*data)?;
+            entities.push(entity);
+        }
+
+        let legacy_key = DataKey {
+            coordinates,
+            dimension,
+            data: KeyType::Entity,
+        };
+
+        if let Some(data) = self.database.get(legacy_key)? {
+            entities.extend(Self::read_compounds(&data)?);
+        }
+
+        Ok(entities)
+    }
+
+    /// Replaces all entities in the specified chunk.
+    ///
+    /// The entities are always written in the `digp` and `actorprefix` format.
+    /// Entities that were stored in the legacy format are removed.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if an entity does not have a `UniqueID` field, if it could not be serialised
+    /// or if the data could not be written to the database. In that case the stored entities are left untouched.
+    pub fn set_entities<I>(&self, coordinates: I, dimension: Dimension, entities: &[Value]) -> anyhow::Result<()>
+    where
+        I: Into<Vector<i32, 2>>,
+    {
+        let coordinates = coordinates.into();
+        let digp = digp_key(&coordinates, dimension);
+
+        let mut batch = WriteBatch::new();
+        for id in self.entity_ids(&coordinates, dimension)? {
+            batch.delete(actor_key(&id));
+        }
+
+        batch.delete_key(&DataKey {
+            coordinates,
+            dimension,
+            data: KeyType::Entity,
+        })?;
+
+        let mut ids = Vec::with_capacity(entities.len() * ACTOR_ID_SIZE);
+        for entity in entities {
+            let Value::Compound(fields) = entity else {
+                anyhow::bail!("Entity must be a compound");
+            };
+
+            let Some(Value::Long(unique_id)) = fields.get("UniqueID") else {
+                anyhow::bail!("Entity is missing a UniqueID field");
+            };
+
+            let id = unique_id.to_le_bytes();
+            batch.put(actor_key(&id), nbt::to_le_bytes(entity)?);
+            ids.extend_from_slice(&id);
+        }
+
+        if ids.is_empty() {
+            batch.delete(digp);
+        } else {
+            batch.put(digp, ids);
+        }
+
+        self.database.execute(&batch)
+    }
+
+    /// Loads the identifiers of the entities that are listed in the `digp` record of the given chunk.
+    fn entity_ids(&self, coordinates: &Vector<i32, 2>, dimension: Dimension) -> anyhow::Result<Vec<[u8; ACTOR_ID_SIZE]>> {
+        let Some(data) = self.database.get_raw(&digp_key(coordinates, dimension))? else {
+            return Ok(Vec::new());
+        };
+
+        if data.len() % ACTOR_ID_SIZE != 0 {
+            anyhow::bail!("Entity list of chunk {coordinates:?} has an invalid length of {} bytes", data.len());
+        }
+
+        Ok(data
+            .chunks_exact(ACTOR_ID_SIZE)
+            .map(|id| {
+                let mut buffer = [0; ACTOR_ID_SIZE];
+                buffer.copy_from_slice(id);
+                buffer
+            })
+            .collect())
+    }
+
+    /// Reads NBT compounds that have been stored back to back.
+    fn read_compounds(mut data: &[u8]) -> anyhow::Result<Vec<Value>> {
+        let mut compounds = Vec::new();
+        while !data.is_empty() {
+            let (compound, _) = nbt::from_le_bytes(&mut data)?;
+            compounds.push(compound);
+        }
+
+        Ok(compounds)
+    }
+
+    /// Loads the saved data of the player with the given UUID.
+    ///
+    /// # Returns
+    ///
+    /// This method returns `None` if the player has never been saved
+    /// and an error if the data could not be loaded.
+    pub fn player(&self, uuid: &Uuid) -> anyhow::Result<Option<PlayerSave>> {
+        let key = Self::player_key(uuid);
+        if let Some(data) = self.database.get_raw(key.as_bytes())? {
+            let (save, _) = nbt::from_le_bytes(&mut &*data)?;
+            Ok(Some(save))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Saves the data of the player with the given UUID, overwriting any previous data.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the data could not be serialised or written to the database.
+    pub fn save_player(&self, uuid: &Uuid, save: &PlayerSave) -> anyhow::Result<()> {
+        let data = nbt::to_le_bytes(save)?;
+        self.database.put_raw(Self::player_key(uuid).as_bytes(), data)
+    }
+
+    /// The database key that the player with the given UUID is stored at.
+    ///
+    /// This is not the key used by vanilla servers, since those expect a complete player compound
+    /// and would fail to load the reduced data that is saved here.
+    fn player_key(uuid: &Uuid) -> String {
+        format!("mirai_player_{uuid}")
+    }
+
+    /// Lists the coordinates of all chunks that exist in the given dimension.
+    ///
+    /// Chunks are not returned in any particular order. Only a few keys are read for every chunk,
+    /// the chunk data itself is skipped.
+    pub fn chunks(&self, dimension: Dimension) -> Chunks<'_> {
+        Chunks { keys: self.database.iter(), dimension, done: false }
+    }
+
     /// Create a new write batch that can optionally be used in write operations.
     #[inline]
     pub fn batch() -> WriteBatch {
         WriteBatch::new()
     }
+
+    /// Atomically commits all operations in the given batch to the database.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the database failed to apply the batch.
+    /// In that case none of the operations have been applied.
+    #[inline]
+    pub fn execute(&self, batch: &WriteBatch) -> anyhow::Result<()> {
+        self.database.execute(batch)
+    }
 }
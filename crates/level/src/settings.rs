@@ -1,220 +1,461 @@
-#[derive(serde::Deserialize, Debug, PartialEq)]
+use std::collections::HashMap;
+
+use nbt::Value;
+use util::BinaryRead;
+
+/// Abilities that players have by default.
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
 pub struct Abilities {
+    /// Whether players can attack mobs.
     #[serde(rename = "attackmobs")]
     pub attack_mobs: bool,
+    /// Whether players can attack other players.
     #[serde(rename = "attackplayers")]
     pub attack_players: bool,
+    /// Whether players can place blocks.
     pub build: bool,
+    /// Whether players can use doors and switches.
     #[serde(rename = "doorsandswitches")]
     pub doors_and_switches: bool,
+    /// Whether players are flying.
     pub flying: bool,
+    /// Whether players can instantly break blocks.
     #[serde(rename = "instabuild")]
     pub instant_build: bool,
+    /// Whether players are invulnerable.
     pub invulnerable: bool,
+    /// Whether players are struck by lightning.
     pub lightning: bool,
+    /// Whether players are allowed to fly.
     pub mayfly: bool,
+    /// Whether players can break blocks.
     pub mine: bool,
+    /// Whether players are operators.
     pub op: bool,
+    /// Whether players can open containers.
     #[serde(rename = "opencontainers")]
     pub open_containers: bool,
+    /// Whether players can teleport.
     pub teleport: bool,
+    /// Flying speed of players.
     #[serde(rename = "flySpeed")]
     pub fly_speed: f32,
+    /// Walking speed of players.
     #[serde(rename = "walkSpeed")]
     pub walk_speed: f32,
 }
 
-#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
+/// Experimental features of the world.
+///
+/// The experiments that are enabled are stored next to these fields and can be accessed through [`LevelDat::experiments`].
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq)]
 pub struct Experiments {
+    /// Whether experiments have ever been enabled in this world.
     pub experiments_ever_used: bool,
+    /// Whether the world was saved with experiments enabled.
     pub saved_with_toggled_experiments: bool,
 }
 
-#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
+/// World policies.
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq)]
 pub struct Policies {
     // Not sure what is supposed to be in here
 }
 
-#[derive(serde::Deserialize, Debug, PartialEq)]
+/// The settings of a world, as stored in the `level.dat` file.
+///
+/// This only contains the fields known to the server. Use [`LevelDat`] to keep the other fields intact when saving.
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
-#[serde(deny_unknown_fields)]
 pub struct LevelSettings {
+    /// Type of Editor world.
     pub editor_world_type: i32,
+    /// Whether the world was created in Editor.
     #[serde(rename = "isCreatedInEditor")]
     pub created_in_editor: bool,
+    /// Whether the world was exported from Editor.
     #[serde(rename = "isExportedFromEditor")]
     pub exported_from_editor: bool,
+    /// Whether a random seed can be used.
     #[serde(rename = "isRandomSeedAllowed")]
     pub random_seed_allowed: bool,
+    /// Percentage of players that need to sleep to skip the night.
     #[serde(rename = "playerssleepingpercentage")]
     pub sleeping_percentage: i32,
+    /// Whether recipes are unlocked as players progress.
     #[serde(rename = "recipesunlock")]
     pub recipes_unlock: bool,
+    /// Whether cheats are enabled.
     pub cheats_enabled: bool,
+    /// Intensity of the lightning.
     pub lightning_level: f32,
+    /// Ticks until the lightning state changes.
     pub lightning_time: i32,
+    /// Intensity of the rain.
     pub rain_level: f32,
+    /// Ticks until the rain state changes.
     pub rain_time: i32,
+    /// Difficulty of the world.
     #[serde(rename = "Difficulty")]
     pub difficulty: i32,
+    /// Default game mode of the world.
     #[serde(rename = "GameType")]
     pub game_mode: i32,
+    /// World generator that is used.
     #[serde(rename = "Generator")]
     pub generator: i32,
+    /// X coordinate of the origin of a limited world.
     #[serde(rename = "LimitedWorldOriginX")]
     pub limited_world_origin_x: i32,
+    /// Y coordinate of the origin of a limited world.
     #[serde(rename = "LimitedWorldOriginY")]
     pub limited_world_origin_y: i32,
+    /// Z coordinate of the origin of a limited world.
     #[serde(rename = "LimitedWorldOriginZ")]
     pub limited_world_origin_z: i32,
+    /// Depth of a limited world.
     pub limited_world_depth: i32,
+    /// Width of a limited world.
     pub limited_world_width: i32,
+    /// Minimum client version that can open the world.
     #[serde(rename = "MinimumCompatibleClientVersion")]
     pub minimum_compatible_client_version: [i32; 5],
     // pub minimum_compatible_client_version: f32,
+    /// Scale of the nether compared to the overworld.
     #[serde(rename = "NetherScale")]
     pub nether_scale: i32,
+    /// Protocol version of the client that last saved the world.
     #[serde(rename = "NetworkVersion")]
     pub network_version: i32,
+    /// Platform that the world was created on.
     #[serde(rename = "Platform")]
     pub platform: i32,
+    /// Platform broadcast intent.
     #[serde(rename = "PlatformBroadcastIntent")]
     pub platform_broadcast_intent: i32,
+    /// Seed of the world.
     #[serde(rename = "RandomSeed")]
     pub random_seed: i64,
+    /// Whether only old villagers spawn.
     #[serde(rename = "SpawnV1Villagers")]
     pub spawn_v1_villagers: bool,
+    /// X coordinate of the world spawn.
     #[serde(rename = "SpawnX")]
     pub spawn_x: i32,
+    /// Y coordinate of the world spawn.
     #[serde(rename = "SpawnY")]
     pub spawn_y: i32,
+    /// Z coordinate of the world spawn.
     #[serde(rename = "SpawnZ")]
     pub spawn_z: i32,
+    /// Storage version of the world.
     #[serde(rename = "StorageVersion")]
     pub storage_version: i32,
+    /// Time of day in ticks.
     #[serde(rename = "Time")]
     pub time: i64,
+    /// World version.
     #[serde(rename = "WorldVersion")]
     pub world_version: i32,
+    /// Xbox Live broadcast intent.
     #[serde(rename = "XBLBroadcastIntent")]
     pub xbox_broadcast_intent: i32,
+    /// Amount of ticks that the world has been running for.
     pub current_tick: i64,
+    /// Experimental features of the world.
     pub experiments: Experiments,
+    /// Abilities that players have by default.
     pub abilities: Abilities,
+    /// Education Edition offer.
     pub edu_offer: i32,
+    /// Whether Education Edition features are enabled.
     pub education_features_enabled: bool,
+    /// Version of the game that last opened the world.
     #[serde(rename = "lastOpenedWithVersion")]
     pub last_opened_with_version: [i32; 5],
+    /// Whether the bonus chest is enabled.
     pub bonus_chest_enabled: bool,
+    /// Whether the bonus chest has been spawned.
     pub bonus_chest_spawned: bool,
+    /// The `commandblockoutput` gamerule.
     #[serde(rename = "commandblockoutput")]
     pub command_block_output: bool,
+    /// Whether maps are centered on the origin.
     #[serde(rename = "CenterMapsToOrigin")]
     pub center_maps_to_origin: bool,
+    /// The `commandblocksenabled` gamerule.
     #[serde(rename = "commandblocksenabled")]
     pub command_blocks_enabled: bool,
+    /// Whether commands are enabled.
     pub commands_enabled: bool,
+    /// Whether platform locked content has been confirmed.
     #[serde(rename = "ConfirmedPlatformLockedContent")]
     pub confirmed_platform_locked_content: bool,
+    /// Time at which the daylight cycle is locked.
     pub daylight_cycle: i32,
+    /// The `dodaylightcycle` gamerule.
     #[serde(rename = "dodaylightcycle")]
     pub daylight_lock: bool,
+    /// The `dolimitedcrafting` gamerule.
     #[serde(rename = "dolimitedcrafting")]
     pub limited_crafting: bool,
+    /// The `doentitydrops` gamerule.
     #[serde(rename = "doentitydrops")]
     pub entity_drops: bool,
+    /// The `dofiretick` gamerule.
     #[serde(rename = "dofiretick")]
     pub fire_tick: bool,
+    /// The `doimmediaterespawn` gamerule.
     #[serde(rename = "doimmediaterespawn")]
     pub immediate_respawn: bool,
+    /// The `doinsomnia` gamerule.
     #[serde(rename = "doinsomnia")]
     pub insomnia: bool,
+    /// The `domobloot` gamerule.
     #[serde(rename = "domobloot")]
     pub mob_loot: bool,
+    /// The `domobspawning` gamerule.
     #[serde(rename = "domobspawning")]
     pub mob_spawning: bool,
+    /// The `dotiledrops` gamerule.
     #[serde(rename = "dotiledrops")]
     pub tile_drops: bool,
+    /// The `doweathercycle` gamerule.
     #[serde(rename = "doweathercycle")]
     pub weather_cycle: bool,
+    /// The `drowningdamage` gamerule.
     #[serde(rename = "drowningdamage")]
     pub drowning_damage: bool,
+    /// The `falldamage` gamerule.
     #[serde(rename = "falldamage")]
     pub fall_damage: bool,
+    /// The `firedamage` gamerule.
     #[serde(rename = "firedamage")]
     pub fire_damage: bool,
+    /// The `freezedamage` gamerule.
     #[serde(rename = "freezedamage")]
     pub freeze_damage: bool,
+    /// The `keepinventory` gamerule.
     #[serde(rename = "keepinventory")]
     pub keep_inventory: bool,
+    /// The `maxcommandchainlength` gamerule.
     #[serde(rename = "maxcommandchainlength")]
     pub max_command_chain_length: i32,
+    /// The `mobgriefing` gamerule.
     #[serde(rename = "mobgriefing")]
     pub mob_griefing: bool,
+    /// The `naturalregeneration` gamerule.
     #[serde(rename = "naturalregeneration")]
     pub natural_regeneration: bool,
+    /// The `functioncommandlimit` gamerule.
     #[serde(rename = "functioncommandlimit")]
     pub function_command_limit: i32,
+    /// The `pvp` gamerule.
     pub pvp: bool,
+    /// The `randomtickspeed` gamerule.
     #[serde(rename = "randomtickspeed")]
     pub random_tick_speed: i32,
+    /// The `respawnblocksexplode` gamerule.
     #[serde(rename = "respawnblocksexplode")]
     pub respawn_blocks_explode: bool,
+    /// The `sendcommandfeedback` gamerule.
     #[serde(rename = "sendcommandfeedback")]
     pub send_command_feedback: bool,
+    /// The `showbordereffect` gamerule.
     #[serde(rename = "showbordereffect")]
     pub show_border_effect: bool,
+    /// The `showcoordinates` gamerule.
     #[serde(rename = "showcoordinates")]
     pub show_coordinates: bool,
+    /// The `showdeathmessages` gamerule.
     #[serde(rename = "showdeathmessages")]
     pub show_death_messages: bool,
+    /// The `showtags` gamerule.
     #[serde(rename = "showtags")]
     pub show_tags: bool,
+    /// The `spawnradius` gamerule.
     #[serde(rename = "spawnradius")]
     pub spawn_radius: i32,
+    /// The `tntexplodes` gamerule.
     #[serde(rename = "tntexplodes")]
     pub tnt_explodes: bool,
+    /// Whether players are forced into the default game mode.
     #[serde(rename = "ForceGameType")]
     pub force_game_mode: bool,
+    /// Whether the world is in hardcore mode.
+    ///
+    /// Older worlds do not store this field.
+    #[serde(rename = "IsHardcore")]
+    pub hardcore: Option<bool>,
+    /// Whether the world has ever been loaded in creative mode.
     pub has_been_loaded_in_creative: bool,
+    /// Whether the behavior packs of the world are locked.
     pub has_locked_behavior_pack: bool,
+    /// Whether the resource packs of the world are locked.
     pub has_locked_resource_pack: bool,
+    /// Whether the world can be modified.
     pub immutable_world: bool,
+    /// Whether the world was created from a locked template.
     pub is_from_locked_template: bool,
+    /// Whether the world was created from a template.
     pub is_from_world_template: bool,
+    /// Whether the world can only be played once.
     pub is_single_use_world: bool,
+    /// Whether the world template options are locked.
     pub is_world_template_option_locked: bool,
+    /// Whether removed packs have to be checked.
     pub requires_copied_pack_removal_check: bool,
+    /// Whether clients have to accept the resource packs.
     pub texture_packs_required: bool,
+    /// Whether the world is broadcast over LAN.
     #[serde(rename = "LANBroadcast")]
     pub lan_broadcast: bool,
+    /// LAN broadcast intent.
     #[serde(rename = "LANBroadcastIntent")]
     pub lan_broadcast_intent: i8,
+    /// Whether the world is a multiplayer game.
     #[serde(rename = "MultiplayerGame")]
     pub multiplayer_game: bool,
+    /// Multiplayer game intent.
     #[serde(rename = "MultiplayerGameIntent")]
     pub multiplayer_game_intent: i8,
+    /// Unix timestamp of when the world was last played.
     #[serde(rename = "LastPlayed")]
     pub last_played: i64,
+    /// Game version that the world is based on.
     pub base_game_version: String,
+    /// Biome that overrides all others.
     #[serde(rename = "BiomeOverride")]
     pub biome_override: String,
+    /// Layers of a flat world, encoded as JSON.
     #[serde(rename = "FlatWorldLayers")]
     pub flat_world_layers: String,
+    /// Version of the inventory format.
     #[serde(rename = "InventoryVersion")]
     pub inventory_version: String,
+    /// Name of the world.
     #[serde(rename = "LevelName")]
     pub level_name: String,
+    /// Whether only Microsoft account gamertags are shown.
     pub use_msa_gamertags_only: bool,
+    /// Amount of times the world has been started.
     pub world_start_count: i64,
+    /// Whether players start with a map.
     pub start_with_map_enabled: bool,
+    /// Whether mobs spawn.
     pub spawn_mobs: bool,
+    /// Radius in chunks around players in which chunks are ticked.
     pub server_chunk_tick_range: i32,
+    /// Permission level of operators.
     pub permissions_level: i32,
+    /// Default permission level of players.
     pub player_permissions_level: i32,
+    /// Product ID of the world.
     pub prid: String,
+    /// World policies.
     #[serde(rename = "world_policies")]
     pub world_policies: Policies,
 }
+
+/// Size of the header in front of the NBT data in a `level.dat` file.
+const LEVEL_DAT_HEADER_SIZE: usize = 8;
+
+/// The contents of a `level.dat` file.
+///
+/// Unlike [`LevelSettings`], this keeps every field in the file so that fields unknown to the server
+/// are preserved when the file is written back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelDat {
+    /// Storage version from the file header.
+    pub storage_version: u32,
+    /// All fields in the file.
+    pub fields: HashMap<String, Value>,
+}
+
+impl LevelDat {
+    /// Decodes a `level.dat` file.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the length in the header does not match the content or if the NBT data is invalid.
+    pub fn deserialize(mut reader: &[u8]) -> anyhow::Result<LevelDat> {
+        let storage_version = reader.read_u32_le()?;
+        let file_size = reader.read_u32_le()?;
+
+        let remaining = reader.remaining();
+        if remaining != file_size as usize {
+            anyhow::bail!("Invalid `level.dat` file: header specified length of {file_size} bytes, but found {remaining}");
+        }
+
+        let (fields, _) = nbt::from_le_bytes(&mut reader)?;
+        Ok(LevelDat { storage_version, fields })
+    }
+
+    /// Encodes the data in the `level.dat` format.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the fields could not be serialised.
+    pub fn serialize(&self) -> anyhow::Result<Vec<u8>> {
+        let mut data = vec![0; LEVEL_DAT_HEADER_SIZE];
+        nbt::to_le_bytes_in(&mut data, &self.fields)?;
+
+        let size = (data.len() - LEVEL_DAT_HEADER_SIZE) as u32;
+        data[..4].copy_from_slice(&self.storage_version.to_le_bytes());
+        data[4..LEVEL_DAT_HEADER_SIZE].copy_from_slice(&size.to_le_bytes());
+
+        Ok(data)
+    }
+
+    /// Reads the settings known to the server.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if a known field is missing or has the wrong type.
+    pub fn settings(&self) -> anyhow::Result<LevelSettings> {
+        let data = nbt::to_le_bytes(&self.fields)?;
+        let (settings, _) = nbt::from_le_bytes(&mut data.as_ref())?;
+
+        Ok(settings)
+    }
+
+    /// Overwrites the fields known to the server with the given settings.
+    ///
+    /// Fields that are not part of [`LevelSettings`] are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the settings could not be serialised.
+    pub fn set_settings(&mut self, settings: &LevelSettings) -> anyhow::Result<()> {
+        let data = nbt::to_le_bytes(settings)?;
+        let (fields, _): (HashMap<String, Value>, _) = nbt::from_le_bytes(&mut data.as_ref())?;
+
+        for (name, value) in fields {
+            // Merge nested compounds so that unknown fields in them, such as the enabled experiments, are kept.
+            match (self.fields.get_mut(&name), value) {
+                (Some(Value::Compound(existing)), Value::Compound(value)) => existing.extend(value),
+                (_, value) => {
+                    self.fields.insert(name, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The experiments that are enabled in this world.
+    pub fn experiments(&self) -> Vec<(&str, bool)> {
+        let Some(Value::Compound(experiments)) = self.fields.get("experiments") else {
+            return Vec::new();
+        };
+
+        experiments
+            .iter()
+            .filter(|(name, _)| *name != "experiments_ever_used" && *name != "saved_with_toggled_experiments")
+            .filter_map(|(name, value)| match value {
+                Value::Byte(enabled) => Some((name.as_str(), *enabled != 0)),
+                _ => None,
+            })
+            .collect()
+    }
+}
@@ -289,12 +289,7 @@ impl ItemNetworkIds {
     /// Convert an item network ID to a name.
     #[inline]
     pub fn get_name(&self, id: i32) -> Option<&str> {
-        let name = self.id_to_name.get(&id).map(|x| x.as_str());
-        if let Some(name) = name {
-            println!("{name}: {id}");
-        }
-
-        name
+        self.id_to_name.get(&id).map(|x| x.as_str())
     }
 }
 
@@ -305,6 +300,8 @@ const BLOCK_STATES_RAW: &[u8] = include_bytes!("../include/block_states.nbt");
 pub struct BlockStates {
     /// Converts state hashes to runtime IDs.
     runtime_hashes: HashMap<u64, u32, BuildNoHashHasher<u64>>,
+    /// Converts runtime IDs back to block states.
+    runtime_states: IntMap<u32, PaletteEntry>,
     air_id: u32,
 }
 
@@ -322,6 +319,7 @@ impl BlockStates {
 
         let mut states = Self {
             runtime_hashes: HashMap::with_capacity_and_hasher(STATE_COUNT, BuildNoHashHasher::default()),
+            runtime_states: IntMap::with_capacity_and_hasher(STATE_COUNT, BuildNoHashHasher::default()),
             air_id: 0,
         };
 
@@ -356,6 +354,11 @@ impl BlockStates {
         self.air_id
     }
 
+    /// Returns the block state with the given runtime ID.
+    pub fn entry(&self, runtime_id: u32) -> Option<&PaletteEntry> {
+        self.runtime_states.get(&runtime_id)
+    }
+
     pub fn register(&mut self, state: PaletteEntry) -> anyhow::Result<()> {
         // tracing::debug!("register {state:?}");
 
@@ -368,6 +371,7 @@ impl BlockStates {
         }
 
         self.runtime_hashes.insert(hash, new_id as u32);
+        self.runtime_states.insert(new_id as u32, state);
 
         Ok(())
     }
@@ -66,25 +66,44 @@ mod block_version {
 }
 
 /// Definition of block in the sub chunk block palette.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename = "")]
 pub struct PaletteEntry {
     /// Name of the block.
     pub name: String,
     /// Version of the block.
-    #[serde(with = "block_version")]
+    ///
+    /// Blocks without a version, such as those that were placed by the server, are stored without this field.
+    #[serde(with = "block_version", default)]
     pub version: Option<[u8; 4]>,
     /// Block-specific properties.
     pub states: HashMap<String, nbt::Value>,
 }
 
 impl PaletteEntry {
+    /// Creates an air block.
+    ///
+    /// Empty layers are implicitly filled with this block.
+    pub fn air() -> Self {
+        Self {
+            name: "minecraft:air".to_owned(),
+            version: None,
+            states: HashMap::new(),
+        }
+    }
+
     /// Hashes this block.
+    ///
+    /// The version is not included, so the same block saved by different game versions has the same hash.
+    /// States are hashed in order of their names, because the iteration order of the map is not deterministic.
     pub fn hash(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
 
         hasher.write(self.name.as_bytes());
-        for (k, v) in &self.states {
+
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_unstable_by_key(|(k, _)| *k);
+        for (k, v) in states {
             hasher.write(k.as_bytes());
             v.hash(&mut hasher);
         }
@@ -107,7 +126,7 @@ impl PaletteEntry {
 /// This is prefixed with a 32-bit little endian integer specifying the size of the palette.
 /// The rest of the palette then consists of `n` concatenated NBT compounds.
 #[doc(alias = "storage record")]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SubStorage {
     /// List of indices into the palette.
     ///
@@ -133,7 +152,7 @@ impl SubStorage {
     {
         let pos = pos.into();
 
-        if pos.x > 16 || pos.y > 16 || pos.z > 16 {
+        if pos.x >= 16 || pos.y >= 16 || pos.z >= 16 {
             return None;
         }
 
@@ -141,22 +160,135 @@ impl SubStorage {
         debug_assert!(offset < 4096, "Array offset out of range");
 
         let index = self.indices[offset] as usize;
-        Some(&self.palette[index])
+        self.palette.get(index)
     }
 
-    // FIXME: Using this method will modify every block with the same index
-    // instead of only the block at the specified position.
-    // pub fn get_mut(&mut self, pos: Vector<u8, 3>) -> Option<&mut PaletteEntry> {
-    //     if pos.x > 16 || pos.y > 16 || pos.z > 16 {
-    //         return None;
-    //     }
+    /// Replaces the block at the given position.
+    ///
+    /// The block is added to the palette if it is not in there yet.
+    /// Entries that are no longer used are kept until [`compact`](Self::compact) is called.
+    ///
+    /// Returns `false` if the position is out of range.
+    pub fn set<V>(&mut self, pos: V, entry: PaletteEntry) -> bool
+    where
+        V: Into<Vector<u8, 3>>,
+    {
+        let pos = pos.into();
+
+        if pos.x >= 16 || pos.y >= 16 || pos.z >= 16 {
+            return false;
+        }
 
-    //     let offset = to_offset(pos);
-    //     debug_assert!(offset < 4096);
+        let index = self.intern(entry);
+        self.indices[to_offset(pos)] = index;
 
-    //     let index = self.indices[offset] as usize;
-    //     Some(&mut self.palette[index])
-    // }
+        true
+    }
+
+    /// Fills the entire layer with a single block.
+    pub fn fill(&mut self, entry: PaletteEntry) {
+        self.indices.fill(0);
+        self.palette.clear();
+        self.palette.push(entry);
+    }
+
+    /// Fills all positions between `from` and `to` (both inclusive) with the given block.
+    ///
+    /// Coordinates outside of the layer are clamped to its bounds.
+    pub fn fill_area<V>(&mut self, from: V, to: V, entry: PaletteEntry)
+    where
+        V: Into<Vector<u8, 3>>,
+    {
+        let (from, to) = (from.into(), to.into());
+        let index = self.intern(entry);
+
+        for x in from.x.min(to.x)..=from.x.max(to.x).min(15) {
+            for z in from.z.min(to.z)..=from.z.max(to.z).min(15) {
+                for y in from.y.min(to.y)..=from.y.max(to.y).min(15) {
+                    self.indices[to_offset(Vector::from([x, y, z]))] = index;
+                }
+            }
+        }
+    }
+
+    /// Replaces every occurrence of `from` with `to`.
+    ///
+    /// Returns the amount of blocks that were replaced.
+    pub fn replace(&mut self, from: &PaletteEntry, to: PaletteEntry) -> usize {
+        let from_hash = from.hash();
+        let to_index = self.intern(to);
+
+        let mut replaced = 0;
+        for index in self.indices.iter_mut() {
+            if *index != to_index && self.palette.get(*index as usize).is_some_and(|entry| entry.hash() == from_hash) {
+                *index = to_index;
+                replaced += 1;
+            }
+        }
+
+        replaced
+    }
+
+    /// Returns the palette index of the given block, adding it to the palette if it does not exist yet.
+    ///
+    /// Blocks are compared using [`PaletteEntry::hash`].
+    pub fn intern(&mut self, entry: PaletteEntry) -> u16 {
+        if self.palette.is_empty() {
+            // All indices of an empty layer point to the first entry, which must therefore be air.
+            self.palette.push(PaletteEntry::air());
+        }
+
+        let hash = entry.hash();
+        if let Some(index) = self.palette.iter().position(|existing| existing.hash() == hash) {
+            return index as u16;
+        }
+
+        self.palette.push(entry);
+        (self.palette.len() - 1) as u16
+    }
+
+    /// Removes palette entries that are not used by any block and merges duplicate entries.
+    ///
+    /// The remaining entries keep their relative order.
+    pub fn compact(&mut self) {
+        if self.palette.is_empty() {
+            return;
+        }
+
+        let mut used = vec![false; self.palette.len()];
+        for &index in self.indices.iter() {
+            if let Some(used) = used.get_mut(index as usize) {
+                *used = true;
+            }
+        }
+
+        // Maps old palette indices to new ones.
+        let mut remap = vec![0u16; self.palette.len()];
+        let mut hashes = Vec::with_capacity(self.palette.len());
+        let mut palette = Vec::with_capacity(self.palette.len());
+
+        for (old, entry) in std::mem::take(&mut self.palette).into_iter().enumerate() {
+            if !used[old] {
+                continue;
+            }
+
+            let hash = entry.hash();
+            remap[old] = match hashes.iter().position(|existing| *existing == hash) {
+                Some(duplicate) => duplicate as u16,
+                None => {
+                    hashes.push(hash);
+                    palette.push(entry);
+                    (palette.len() - 1) as u16
+                }
+            };
+        }
+
+        for index in self.indices.iter_mut() {
+            *index = remap.get(*index as usize).copied().unwrap_or(0);
+        }
+
+        self.palette = palette;
+    }
 
     /// Returns a reference to the block palette.
     pub fn palette(&self) -> &[PaletteEntry] {
@@ -257,7 +389,7 @@ where
     fn index(&self, position: I) -> &Self::Output {
         let position = position.into();
         assert!(
-            position.x <= 16 && position.y <= 16 && position.z <= 16,
+            position.x < 16 && position.y < 16 && position.z < 16,
             "Block position out of sub chunk bounds"
         );
 
@@ -274,7 +406,7 @@ where
     fn index_mut(&mut self, position: I) -> &mut Self::Output {
         let position = position.into();
         assert!(
-            position.x <= 16 && position.y <= 16 && position.z <= 16,
+            position.x < 16 && position.y < 16 && position.z < 16,
             "Block position out of sub chunk bounds"
         );
 
@@ -309,7 +441,7 @@ pub fn from_offset(offset: usize) -> Vector<u8, 3> {
 /// A Minecraft sub chunk.
 ///
 /// Every world contains
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SubChunk {
     /// Version of the sub chunk.
     ///
@@ -368,6 +500,69 @@ impl SubChunk {
         self.layers.get_mut(index)
     }
 
+    /// Get a mutable reference to the layer at the specified index, creating it and any layers below it if necessary.
+    pub fn layer_or_insert(&mut self, index: usize) -> &mut SubStorage {
+        if self.layers.len() <= index {
+            self.layers.resize_with(index + 1, SubStorage::empty);
+        }
+
+        &mut self.layers[index]
+    }
+
+    /// Replaces the block at the given position in the given layer.
+    ///
+    /// Returns `false` if the position is out of range.
+    pub fn set<V>(&mut self, layer: usize, pos: V, entry: PaletteEntry) -> bool
+    where
+        V: Into<Vector<u8, 3>>,
+    {
+        self.layer_or_insert(layer).set(pos, entry)
+    }
+
+    /// Fills all positions between `from` and `to` (both inclusive) in the given layer with a single block.
+    ///
+    /// Palette entries that are no longer used afterwards are removed.
+    pub fn fill<V>(&mut self, layer: usize, from: V, to: V, entry: PaletteEntry)
+    where
+        V: Into<Vector<u8, 3>>,
+    {
+        let (from, to) = (from.into(), to.into());
+        let storage = self.layer_or_insert(layer);
+
+        let lower = [from.x.min(to.x), from.y.min(to.y), from.z.min(to.z)];
+        let upper = [from.x.max(to.x), from.y.max(to.y), from.z.max(to.z)];
+        if lower == [0; 3] && upper.iter().all(|c| *c >= 15) {
+            storage.fill(entry);
+        } else {
+            storage.fill_area(from, to, entry);
+            storage.compact();
+        }
+    }
+
+    /// Replaces every occurrence of `from` with `to` in all layers.
+    ///
+    /// Palette entries that are no longer used afterwards are removed.
+    /// Returns the amount of blocks that were replaced.
+    pub fn replace(&mut self, from: &PaletteEntry, to: &PaletteEntry) -> usize {
+        self.layers
+            .iter_mut()
+            .map(|layer| {
+                let replaced = layer.replace(from, to.clone());
+                layer.compact();
+                replaced
+            })
+            .sum()
+    }
+
+    /// Compacts the palettes of all layers.
+    ///
+    /// See [`SubStorage::compact`].
+    pub fn compact(&mut self) {
+        for layer in &mut self.layers {
+            layer.compact();
+        }
+    }
+
     /// Takes ownership of the subchunk and returns an owned list of its layers.
     #[inline]
     pub fn take_layers(self) -> Vec<SubStorage> {
@@ -157,3 +157,415 @@ fn subchunks() {
 //
 //     assert_eq!(entry, de);
 // }
+
+#[cfg(feature = "rust-leveldb")]
+#[test]
+fn rust_leveldb() {
+    use crate::WriteBatch;
+
+    let directory = std::env::temp_dir().join(format!("mirai-leveldb-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let path = directory.to_str().unwrap();
+
+    let key = |i: u32| format!("key{i:08}").into_bytes();
+    let value = |i: u32| vec![(i % 251) as u8; 1024 + (i % 7) as usize];
+
+    {
+        let database = Database::open(path).unwrap();
+
+        // Large enough to flush the memtable several times and trigger a compaction.
+        let mut batch = WriteBatch::new();
+        for i in 0..20_000 {
+            batch.put(key(i), value(i));
+            if i % 1000 == 999 {
+                database.execute(&batch).unwrap();
+                batch.clear();
+            }
+        }
+
+        for i in (0..20_000).step_by(3) {
+            batch.delete(key(i));
+        }
+        database.execute(&batch).unwrap();
+        database.put_raw(&key(1), b"overwritten").unwrap();
+
+        assert_eq!(database.get_raw(&key(0)).unwrap().as_deref(), None);
+        assert_eq!(database.get_raw(&key(1)).unwrap().as_deref(), Some(b"overwritten".as_slice()));
+        assert_eq!(database.get_raw(&key(2)).unwrap().as_deref(), Some(value(2).as_slice()));
+    }
+
+    // Writes that were still in the write-ahead log must survive reopening.
+    let database = Database::open(path).unwrap();
+    assert_eq!(database.get_raw(&key(1)).unwrap().as_deref(), Some(b"overwritten".as_slice()));
+    assert_eq!(database.get_raw(&key(19_999)).unwrap().as_deref(), Some(value(19_999).as_slice()));
+    assert_eq!(database.get_raw(&key(19_998)).unwrap().as_deref(), None);
+
+    let keys: Vec<Vec<u8>> = database.iter().map(|kv| kv.key().to_vec()).collect();
+    let expected: Vec<Vec<u8>> = (0..20_000).filter(|i| i % 3 != 0).map(key).collect();
+    assert_eq!(keys, expected);
+
+    drop(database);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "rust-leveldb")]
+#[test]
+fn rust_leveldb_minecraft() {
+    let directory = std::env::temp_dir().join(format!("mirai-leveldb-minecraft-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    for file in std::fs::read_dir("../../resources/level/db").unwrap() {
+        let file = file.unwrap();
+        std::fs::copy(file.path(), directory.join(file.file_name())).unwrap();
+    }
+
+    // Tables written by the game are compressed with raw zlib.
+    let database = Database::open(directory.to_str().unwrap()).unwrap();
+    let pairs: Vec<_> = database.iter().map(|kv| (kv.key().to_vec(), kv.value().to_vec())).collect();
+    assert!(!pairs.is_empty(), "Minecraft database is empty");
+    for (key, value) in &pairs {
+        assert_eq!(database.get_raw(key).unwrap().as_deref(), Some(value.as_slice()));
+    }
+
+    drop(database);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "rust-leveldb")]
+#[test]
+fn iterators() {
+    use crate::{DataKey, KeyType};
+
+    let directory = std::env::temp_dir().join(format!("mirai-leveldb-iterators-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let path = directory.join("db");
+
+    let serialize = |key: &DataKey| {
+        let mut raw_key = Vec::with_capacity(key.serialized_size());
+        key.serialize(&mut raw_key).unwrap();
+        raw_key
+    };
+
+    let overworld = [Vector::from([0, 0]), Vector::from([-1, 5]), Vector::from([12, -300])];
+    let nether = [Vector::from([-1, 5]), Vector::from([7, 7])];
+
+    {
+        let database = Database::open(path.to_str().unwrap()).unwrap();
+        for (coordinates, dimension) in overworld
+            .iter()
+            .map(|c| (c, Dimension::Overworld))
+            .chain(nether.iter().map(|c| (c, Dimension::Nether)))
+        {
+            for data in [KeyType::ChunkVersion, KeyType::SubChunk { index: -4 }, KeyType::SubChunk { index: 3 }, KeyType::Biome3d] {
+                let key = DataKey { coordinates: coordinates.clone(), dimension, data };
+                database.put_raw(&serialize(&key), b"data").unwrap();
+            }
+        }
+
+        // Sub chunks without a version key do not make up a chunk.
+        let orphan = DataKey { coordinates: Vector::from([100, 100]), dimension: Dimension::Overworld, data: KeyType::SubChunk { index: 0 } };
+        database.put_raw(&serialize(&orphan), b"data").unwrap();
+
+        database.put_raw(b"~local_player", b"player").unwrap();
+        database.put_raw(b"player_server_1", b"player").unwrap();
+        database.put_raw(b"player_server_2", b"player").unwrap();
+        database.put_raw(b"portals", b"portals").unwrap();
+
+        let prefixed: Vec<Vec<u8>> = database.prefix(b"player_").map(|kv| kv.key().to_vec()).collect();
+        assert_eq!(prefixed, [b"player_server_1".to_vec(), b"player_server_2".to_vec()]);
+
+        let ranged: Vec<Vec<u8>> = database.range(b"player_server_2", b"portals").map(|kv| kv.key().to_vec()).collect();
+        assert_eq!(ranged, [b"player_server_2".to_vec()]);
+
+        let mut keys = database.iter();
+        keys.seek(b"p");
+        assert_eq!(&*keys.next().unwrap().key(), b"player_server_1");
+        keys.seek(b"player_server_2");
+        assert_eq!(&*keys.next().unwrap().key(), b"player_server_2");
+        assert_eq!(&*keys.next().unwrap().key(), b"portals");
+
+        // Every chunk key must round-trip, including those with a dimension.
+        let data_keys: Vec<DataKey> = database.data_keys().map(|(key, _)| key).collect();
+        assert_eq!(data_keys.len(), (overworld.len() + nether.len()) * 4 + 1);
+        for key in &data_keys {
+            let raw_key = serialize(key);
+            assert_eq!(&DataKey::deserialize(raw_key.as_slice()).unwrap(), key);
+        }
+
+        let target = DataKey { coordinates: Vector::from([7, 7]), dimension: Dimension::Nether, data: KeyType::ChunkVersion };
+        let mut data_keys = database.data_keys();
+        data_keys.seek(&target).unwrap();
+        assert_eq!(data_keys.next().unwrap().0, target);
+    }
+
+    // Reopening writes the memtable to a table, so the chunks are listed by seeking through tables.
+    let provider = Provider::open(&directory).unwrap();
+    let chunks = |dimension| {
+        let mut chunks: Vec<(i32, i32)> = provider.chunks(dimension).map(|c| c.unwrap()).map(|c| (c.x, c.y)).collect();
+        chunks.sort_unstable();
+        chunks
+    };
+
+    assert_eq!(chunks(Dimension::Overworld), [(-1, 5), (0, 0), (12, -300)]);
+    assert_eq!(chunks(Dimension::Nether), [(-1, 5), (7, 7)]);
+    assert!(chunks(Dimension::End).is_empty());
+
+    drop(provider);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "rust-leveldb")]
+#[test]
+fn entities() {
+    use std::collections::HashMap;
+
+    use nbt::Value;
+
+    use crate::{actor_key, digp_key, DataKey, KeyType};
+
+    let directory = std::env::temp_dir().join(format!("mirai-leveldb-entities-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let path = directory.join("db");
+
+    let entity = |id: i64, identifier: &str| {
+        Value::Compound(HashMap::from([
+            ("UniqueID".to_owned(), Value::Long(id)),
+            ("identifier".to_owned(), Value::String(identifier.to_owned())),
+        ]))
+    };
+    let cow = entity(-4_294_967_295, "minecraft:cow");
+    let pig = entity(12, "minecraft:pig");
+    let sheep = entity(7, "minecraft:sheep");
+
+    let coordinates = Vector::from([3, -2]);
+    let legacy_key = DataKey { coordinates: coordinates.clone(), dimension: Dimension::Nether, data: KeyType::Entity };
+    {
+        let database = Database::open(path.to_str().unwrap()).unwrap();
+        database.put(legacy_key.clone(), nbt::to_le_bytes(&sheep).unwrap()).unwrap();
+    }
+
+    let provider = Provider::open(&directory).unwrap();
+
+    // Entities in the legacy format are loaded as well.
+    assert_eq!(provider.entities(coordinates.clone(), Dimension::Nether).unwrap(), [sheep.clone()]);
+    assert!(provider.entities(coordinates.clone(), Dimension::Overworld).unwrap().is_empty());
+
+    provider.set_entities(coordinates.clone(), Dimension::Nether, &[cow.clone(), pig.clone()]).unwrap();
+    assert_eq!(provider.entities(coordinates.clone(), Dimension::Nether).unwrap(), [cow.clone(), pig.clone()]);
+
+    // Entities without a unique ID are rejected without touching the stored entities.
+    let invalid = Value::Compound(HashMap::from([("identifier".to_owned(), Value::String("minecraft:cat".to_owned()))]));
+    assert!(provider.set_entities(coordinates.clone(), Dimension::Nether, &[sheep.clone(), invalid]).is_err());
+    assert_eq!(provider.entities(coordinates.clone(), Dimension::Nether).unwrap(), [cow.clone(), pig.clone()]);
+
+    let chest = Value::Compound(HashMap::from([
+        ("id".to_owned(), Value::String("Chest".to_owned())),
+        ("x".to_owned(), Value::Int(48)),
+        ("y".to_owned(), Value::Int(-12)),
+        ("z".to_owned(), Value::Int(-30)),
+    ]));
+    let sign = Value::Compound(HashMap::from([
+        ("id".to_owned(), Value::String("Sign".to_owned())),
+        ("x".to_owned(), Value::Int(49)),
+        ("y".to_owned(), Value::Int(70)),
+        ("z".to_owned(), Value::Int(-31)),
+    ]));
+
+    provider.set_block_entities(coordinates.clone(), Dimension::Nether, &[chest.clone(), sign.clone()]).unwrap();
+    assert_eq!(provider.block_entities(coordinates.clone(), Dimension::Nether).unwrap(), [chest.clone(), sign]);
+    provider.set_block_entities(coordinates.clone(), Dimension::Nether, &[chest.clone()]).unwrap();
+    assert_eq!(provider.block_entities(coordinates.clone(), Dimension::Nether).unwrap(), [chest]);
+    provider.set_block_entities(coordinates.clone(), Dimension::Nether, &[]).unwrap();
+    assert!(provider.block_entities(coordinates.clone(), Dimension::Nether).unwrap().is_empty());
+    drop(provider);
+
+    // The entities are listed in the digp record, each stored in its own actorprefix record,
+    // and the legacy record has been removed.
+    {
+        let database = Database::open(path.to_str().unwrap()).unwrap();
+        let ids = [(-4_294_967_295_i64).to_le_bytes(), 12_i64.to_le_bytes()].concat();
+        assert_eq!(database.get_raw(&digp_key(&coordinates, Dimension::Nether)).unwrap().as_deref(), Some(ids.as_slice()));
+        assert_eq!(
+            database.get_raw(&actor_key(&12_i64.to_le_bytes())).unwrap().as_deref(),
+            Some(nbt::to_le_bytes(&pig).unwrap().as_slice())
+        );
+        assert!(database.get(legacy_key).unwrap().is_none());
+    }
+
+    // Removing all entities also removes their records.
+    let provider = Provider::open(&directory).unwrap();
+    provider.set_entities(coordinates.clone(), Dimension::Nether, &[]).unwrap();
+    assert!(provider.entities(coordinates.clone(), Dimension::Nether).unwrap().is_empty());
+    drop(provider);
+
+    let database = Database::open(path.to_str().unwrap()).unwrap();
+    assert!(database.get_raw(&digp_key(&coordinates, Dimension::Nether)).unwrap().is_none());
+    assert!(database.get_raw(&actor_key(&12_i64.to_le_bytes())).unwrap().is_none());
+
+    drop(database);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn substorage_palette() {
+    use crate::{PaletteEntry, SubChunk, SubStorage};
+
+    let block = |name: &str| PaletteEntry { name: name.to_owned(), version: None, states: Default::default() };
+    let stone = block("minecraft:stone");
+    let dirt = block("minecraft:dirt");
+
+    // Empty layers are implicitly filled with air.
+    let mut layer = SubStorage::empty();
+    assert!(layer.set([1, 2, 3], stone.clone()));
+    assert!(!layer.set([16, 0, 0], stone.clone()));
+    assert_eq!(layer.get([1, 2, 3]), Some(&stone));
+    assert_eq!(layer.get([0, 0, 0]).unwrap().name, "minecraft:air");
+
+    // Blocks are interned by hash, regardless of version.
+    layer.set([4, 5, 6], PaletteEntry { version: Some([1, 20, 0, 0]), ..stone.clone() });
+    assert_eq!(layer.palette().len(), 2);
+
+    layer.set([1, 2, 3], dirt.clone());
+    layer.set([4, 5, 6], dirt.clone());
+    assert_eq!(layer.palette().len(), 3);
+    layer.compact();
+    assert_eq!(layer.palette().len(), 2);
+    assert_eq!(layer.get([4, 5, 6]), Some(&dirt));
+
+    let mut subchunk = SubChunk::empty(0);
+    subchunk.fill(0, [0, 0, 0], [15, 7, 15], stone.clone());
+    assert_eq!(subchunk.replace(&stone, &dirt), 16 * 16 * 8);
+    assert_eq!(subchunk[0].palette().len(), 2);
+    assert_eq!(subchunk[0].get([3, 7, 3]), Some(&dirt));
+    assert_eq!(subchunk[0].get([3, 8, 3]).unwrap().name, "minecraft:air");
+
+    subchunk.fill(1, [0, 0, 0], [15, 15, 15], stone.clone());
+    assert_eq!(subchunk[1].palette(), &[stone]);
+
+    // Unused palette entries do not increase the index size.
+    let mut buffer = Vec::new();
+    crate::serialize_packed_array(&mut buffer, subchunk[0].indices(), 100, false).unwrap();
+    assert_eq!(buffer[0] >> 1, 1);
+    assert_eq!(buffer.len(), 1 + 4096 / 32 * 4);
+    assert!(crate::serialize_packed_array(&mut buffer, subchunk[0].indices(), 1, false).is_err());
+
+    // Blocks with multiple states are interned regardless of the order of their states.
+    let states = [
+        ("direction", nbt::Value::Int(2)),
+        ("open_bit", nbt::Value::Byte(1)),
+        ("upside_down_bit", nbt::Value::Byte(0)),
+    ];
+    let trapdoor = |states: std::collections::HashMap<String, nbt::Value>| PaletteEntry {
+        name: "minecraft:trapdoor".to_owned(),
+        version: None,
+        states,
+    };
+    let forward = trapdoor(states.iter().map(|(k, v)| ((*k).to_owned(), v.clone())).collect());
+    let mut reversed = std::collections::HashMap::with_capacity(64);
+    reversed.extend(states.iter().rev().map(|(k, v)| ((*k).to_owned(), v.clone())));
+    let reversed = trapdoor(reversed);
+    assert_eq!(forward.hash(), reversed.hash());
+
+    let mut flipped = forward.clone();
+    flipped.states.insert("upside_down_bit".to_owned(), nbt::Value::Byte(1));
+    assert_ne!(forward.hash(), flipped.hash());
+
+    let mut layer = SubStorage::empty();
+    layer.set([0, 0, 0], forward.clone());
+    layer.set([0, 0, 1], reversed);
+    layer.set([0, 0, 2], flipped.clone());
+    assert_eq!(layer.palette().len(), 3);
+    assert_eq!(layer.get([0, 0, 1]), Some(&forward));
+    assert_eq!(layer.get([0, 0, 2]), Some(&flipped));
+}
+
+#[test]
+fn player_save() {
+    use crate::{PlayerSave, SavedItem};
+
+    let save = PlayerSave {
+        position: [12.5, 64.0, -3.25],
+        rotation: [10.0, 90.0],
+        game_mode: 1,
+        selected_slot: 4,
+        inventory: vec![SavedItem { slot: 4, name: "minecraft:stone".to_owned(), count: 32, damage: 0, tag: None }],
+        armor: vec![SavedItem { slot: 1, name: "minecraft:iron_chestplate".to_owned(), count: 1, damage: 12, tag: None }],
+        offhand: vec![SavedItem {
+            slot: 0,
+            name: "minecraft:diamond_pickaxe".to_owned(),
+            count: 1,
+            damage: 0,
+            tag: Some(std::collections::HashMap::from([(
+                "ench".to_owned(),
+                nbt::Value::List(vec![nbt::Value::Compound(std::collections::HashMap::from([
+                    ("id".to_owned(), nbt::Value::Short(15)),
+                    ("lvl".to_owned(), nbt::Value::Short(5)),
+                ]))]),
+            )])),
+        }],
+    };
+
+    let encoded = nbt::to_le_bytes(&save).unwrap();
+    let decoded: PlayerSave = nbt::from_le_bytes(&mut encoded.as_ref()).unwrap().0;
+    assert_eq!(decoded, save);
+
+    // Players with an empty inventory must also be stored correctly.
+    let empty = PlayerSave { inventory: Vec::new(), armor: Vec::new(), offhand: Vec::new(), ..save };
+    let encoded = nbt::to_le_bytes(&empty).unwrap();
+    let decoded: PlayerSave = nbt::from_le_bytes(&mut encoded.as_ref()).unwrap().0;
+    assert_eq!(decoded, empty);
+}
+
+#[test]
+fn level_dat() {
+    use crate::LevelDat;
+
+    let raw = std::fs::read("../../resources/level/level.dat").unwrap();
+    let level_dat = LevelDat::deserialize(&raw).unwrap();
+
+    let encoded = level_dat.serialize().unwrap();
+    assert_eq!(encoded.len(), raw.len(), "Encoded level.dat has a different size");
+    assert_eq!(LevelDat::deserialize(&encoded).unwrap(), level_dat);
+
+    // Writing the settings back must not lose or change any fields.
+    let mut settings = level_dat.settings().unwrap();
+    let mut modified = level_dat.clone();
+    modified.set_settings(&settings).unwrap();
+    assert_eq!(modified, level_dat);
+
+    settings.pvp = !settings.pvp;
+    modified.set_settings(&settings).unwrap();
+    assert_eq!(modified.settings().unwrap().pvp, settings.pvp);
+    assert_eq!(modified.fields.len(), level_dat.fields.len());
+}
+
+#[test]
+fn biomes() {
+    use crate::{Biomes, PaletteEntry, SubChunk, PLAINS};
+
+    let stone = PaletteEntry { name: "minecraft:stone".to_owned(), version: None, states: Default::default() };
+
+    let mut bottom = SubChunk::empty(-4);
+    bottom.set(0, [1, 3, 2], stone.clone());
+    bottom.set(0, [5, 0, 5], stone.clone());
+    let mut top = SubChunk::empty(2);
+    top.set(0, [1, 0, 2], stone);
+
+    let mut biomes = Biomes::uniform(PLAINS);
+    biomes.generate_heightmap(&[bottom, top], -64);
+    assert_eq!(biomes.heightmap[1][2], 2 * 16 + 64 + 1);
+    assert_eq!(biomes.heightmap[5][5], 1);
+    assert_eq!(biomes.heightmap[0][0], 0);
+
+    let mut disk = Vec::new();
+    biomes.serialize_disk(&mut disk).unwrap();
+    assert_eq!(Biomes::deserialize_disk(disk.as_slice()).unwrap(), biomes);
+
+    // Every subchunk above the first inherits its biomes.
+    let mut network = Vec::new();
+    biomes.serialize_network(&mut network, 24).unwrap();
+    // Biome IDs are written as zigzag encoded varints.
+    assert_eq!(&network[..2], &[1, PLAINS as u8 * 2]);
+    assert_eq!(network.len(), 2 + 23);
+    assert!(network[2..].iter().all(|&header| header == 0xff));
+}
@@ -267,6 +267,21 @@ where
     pub fn into_inner(self) -> W {
         self.writer
     }
+
+    /// Writes the header of an empty list.
+    ///
+    /// The element type of a list is normally written together with its first element,
+    /// which means empty lists have to be handled separately.
+    fn write_empty_list(&mut self) -> Result<(), NbtError> {
+        self.writer.write_u8(FieldType::End as u8)?;
+        match M::AS_ENUM {
+            Variant::BigEndian => self.writer.write_i32_be(0),
+            Variant::LittleEndian => self.writer.write_i32_le(0),
+            Variant::Variable => self.writer.write_var_i32(0),
+        }?;
+
+        Ok(())
+    }
 }
 
 impl<'a, W, M> ser::Serializer for &'a mut Serializer<W, M>
@@ -412,6 +427,10 @@ where
     #[inline]
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         if let Some(len) = len {
+            if len == 0 {
+                self.write_empty_list()?;
+            }
+
             self.len = len;
             Ok(self)
         } else {
@@ -421,6 +440,10 @@ where
 
     #[inline]
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        if len == 0 {
+            self.write_empty_list()?;
+        }
+
         self.len = len;
         Ok(self)
     }
@@ -174,3 +174,23 @@ fn read_write_player() {
     let value_encoded = to_be_bytes(&decoded2).unwrap();
     let _value_decoded: Value = from_be_bytes(&mut value_encoded.as_ref()).unwrap().0;
 }
+
+#[test]
+fn read_write_empty_list() {
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Lists {
+        empty: Vec<i32>,
+        filled: Vec<i32>,
+    }
+
+    let lists = Lists { empty: Vec::new(), filled: vec![1, 2, 3] };
+
+    let be = to_be_bytes(&lists).unwrap();
+    assert_eq!(from_be_bytes::<Lists, _>(&mut be.as_ref()).unwrap().0, lists);
+
+    let le = to_le_bytes(&lists).unwrap();
+    assert_eq!(from_le_bytes::<Lists, _>(&mut le.as_ref()).unwrap().0, lists);
+
+    let var = to_var_bytes(&lists).unwrap();
+    assert_eq!(from_var_bytes::<Lists, _>(&mut var.as_ref()).unwrap().0, lists);
+}
@@ -0,0 +1,757 @@
+use std::collections::{BTreeMap, HashMap};
+
+use macros::variant_count;
+use util::{BinaryRead, BinaryWrite, Deserialize, Serialize, Vector};
+
+/// Identifies a single entry of the [`ActorData`] of an entity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+#[variant_count]
+pub enum ActorDataKey {
+    /// Bit flags of the entity, see [`ActorFlag`].
+    Flags,
+    /// Health of entities such as boats and minecarts.
+    StructuralIntegrity,
+    /// Variant of the entity, such as the type of a cat.
+    Variant,
+    /// Primary colour of the entity, such as the colour of a sheep.
+    ColorIndex,
+    /// Name tag displayed above the entity.
+    Name,
+    /// Unique ID of the owner of the entity.
+    Owner,
+    /// Unique ID of the entity this entity is targeting.
+    Target,
+    /// Remaining air of the entity in ticks.
+    AirSupply,
+    /// Colour of the potion effect particles.
+    EffectColor,
+    /// Whether the potion effect particles are ambient.
+    EffectAmbience,
+    /// Duration of a horse jump.
+    JumpDuration,
+    /// Amount of ticks the entity has been hurt for.
+    Hurt,
+    /// Direction the entity was hurt from.
+    HurtDirection,
+    /// Rowing time of the left paddle of a boat.
+    RowTimeLeft,
+    /// Rowing time of the right paddle of a boat.
+    RowTimeRight,
+    /// Experience value of an experience orb.
+    Value,
+    /// Runtime ID of the block displayed in a minecart.
+    DisplayTileRuntimeId,
+    /// Offset of the block displayed in a minecart.
+    DisplayOffset,
+    /// Whether a minecart displays a custom block.
+    CustomDisplay,
+    /// Swell of a creeper.
+    Swell,
+    /// Previous swell of a creeper.
+    OldSwell,
+    /// Direction a creeper is swelling in.
+    SwellDirection,
+    /// Charge of an attack.
+    ChargeAmount,
+    /// Runtime ID of the block carried by an enderman.
+    CarryBlockRuntimeId,
+    /// Client event of the entity.
+    ClientEvent,
+    /// Whether the entity is using an item.
+    UsingItem,
+    /// Bit flags specific to players, such as sleeping.
+    PlayerFlags,
+    /// Index of the player.
+    PlayerIndex,
+    /// Position of the bed the player is sleeping in.
+    BedPosition,
+    /// Horizontal power of a fireball.
+    PowerX,
+    /// Vertical power of a fireball.
+    PowerY,
+    /// Horizontal power of a fireball.
+    PowerZ,
+    /// Auxiliary power of a fireball.
+    AuxPower,
+    /// X position of a fish hooked by a fishing hook.
+    FishX,
+    /// Z position of a fish hooked by a fishing hook.
+    FishZ,
+    /// Angle of a fish hooked by a fishing hook.
+    FishAngle,
+    /// Auxiliary value of a potion.
+    AuxValueData,
+    /// Unique ID of the entity holding the leash.
+    LeashHolder,
+    /// Scale of the entity.
+    Scale,
+    /// Whether the entity has an NPC component.
+    HasNpc,
+    /// Data of the NPC component.
+    NpcData,
+    /// Actions of the NPC component.
+    Actions,
+    /// Maximum air of the entity in ticks.
+    AirSupplyMax,
+    /// Mark variant of the entity, such as the markings of a horse.
+    MarkVariant,
+    /// Type of the container carried by the entity.
+    ContainerType,
+    /// Size of the container carried by the entity.
+    ContainerSize,
+    /// Strength modifier of the container carried by the entity.
+    ContainerStrengthModifier,
+    /// Position of the block targeted by the entity.
+    BlockTarget,
+    /// Inventory of the entity.
+    Inventory,
+    /// First target of a wither.
+    TargetA,
+    /// Second target of a wither.
+    TargetB,
+    /// Third target of a wither.
+    TargetC,
+    /// Aerial attack of a wither.
+    AerialAttack,
+    /// Width of the bounding box of the entity.
+    Width,
+    /// Height of the bounding box of the entity.
+    Height,
+    /// Fuse time of a TNT entity.
+    FuseTime,
+    /// Offset of the seat of a rideable entity.
+    SeatOffset,
+    /// Whether the rotation of passengers is locked.
+    SeatLockPassengerRotation,
+    /// Maximum rotation of passengers in degrees.
+    SeatLockPassengerRotationDegrees,
+    /// Whether the rotation of the seat is offset.
+    SeatRotationOffset,
+    /// Rotation offset of the seat in degrees.
+    SeatRotationOffsetDegrees,
+    /// Radius of an area effect cloud.
+    DataRadius,
+    /// Waiting time of an area effect cloud.
+    DataWaiting,
+    /// Particle of an area effect cloud.
+    DataParticle,
+    /// Peek ID of a shulker.
+    PeekId,
+    /// Face a shulker is attached to.
+    AttachFace,
+    /// Whether a shulker is attached.
+    Attached,
+    /// Position a shulker is attached to.
+    AttachedPosition,
+    /// Unique ID of the player trading with the entity.
+    TradeTarget,
+    /// Career of a villager.
+    Career,
+    /// Whether a minecart has a command block.
+    HasCommandBlock,
+    /// Command of a command block minecart.
+    CommandName,
+    /// Last output of a command block minecart.
+    LastCommandOutput,
+    /// Whether a command block minecart tracks its output.
+    TrackCommandOutput,
+    /// Index of the seat that controls the entity.
+    ControllingSeatIndex,
+    /// Strength of a llama.
+    Strength,
+    /// Maximum strength of a llama.
+    StrengthMax,
+    /// Colour of spell casting particles.
+    DataSpellCastingColor,
+    /// Lifetime of an evocation fang in ticks.
+    DataLifetimeTicks,
+    /// Pose of an armour stand.
+    PoseIndex,
+    /// Tick offset of an end crystal.
+    DataTickOffset,
+    /// Whether the name tag is shown even when not looking at the entity.
+    AlwaysShowNameTag,
+    /// Secondary colour of the entity.
+    ColorTwoIndex,
+    /// Author of the name of the entity.
+    NameAuthor,
+    /// Score displayed below the name tag.
+    Score,
+    /// Unique ID of the entity a balloon is attached to.
+    BalloonAnchor,
+    /// Puffed state of a pufferfish.
+    PuffedState,
+    /// Bubble time of an entity.
+    BubbleTime,
+    /// Unique ID of an agent.
+    Agent,
+    /// Sitting amount of a panda.
+    SittingAmount,
+    /// Previous sitting amount of a panda.
+    SittingAmountPrevious,
+    /// Eating counter of a panda.
+    EatingCounter,
+    /// Second set of bit flags of the entity, see [`ActorFlag`].
+    Flags2,
+    /// Laying amount of a panda.
+    LayingAmount,
+    /// Previous laying amount of a panda.
+    LayingAmountPrevious,
+    /// Duration of an area effect cloud.
+    DataDuration,
+    /// Spawn time of an area effect cloud.
+    DataSpawnTime,
+    /// Radius change rate of an area effect cloud.
+    DataChangeRate,
+    /// Radius change on pickup of an area effect cloud.
+    DataChangeOnPickup,
+    /// Pickup count of an area effect cloud.
+    DataPickupCount,
+    /// Text displayed when interacting with the entity.
+    InteractText,
+    /// Trade tier of a villager.
+    TradeTier,
+    /// Maximum trade tier of a villager.
+    MaxTradeTier,
+    /// Trade experience of a villager.
+    TradeExperience,
+    /// Skin ID of the entity.
+    SkinId,
+    /// Spawning frames of a wither.
+    SpawningFrames,
+    /// Tick delay of a command block minecart.
+    CommandBlockTickDelay,
+    /// Whether a command block minecart executes on the first tick.
+    CommandBlockExecuteOnFirstTick,
+    /// Interval between ambient sounds.
+    AmbientSoundInterval,
+    /// Range of the interval between ambient sounds.
+    AmbientSoundIntervalRange,
+    /// Name of the ambient sound event.
+    AmbientSoundEventName,
+    /// Fall damage multiplier of the entity.
+    FallDamageMultiplier,
+    /// Raw text of the name of the entity.
+    NameRawText,
+    /// Whether the entity can ride its target.
+    CanRideTarget,
+    /// Low tier trade discount after curing a zombie villager.
+    LowTierCuredTradeDiscount,
+    /// High tier trade discount after curing a zombie villager.
+    HighTierCuredTradeDiscount,
+    /// Trade discount after a nearby zombie villager was cured.
+    NearbyCuredTradeDiscount,
+    /// Time stamp of the nearby cure trade discount.
+    NearbyCuredDiscountTimeStamp,
+    /// Hit box of the entity.
+    HitBox,
+    /// Whether the entity floats.
+    IsBuoyant,
+    /// Strength of the freezing effect.
+    FreezingEffectStrength,
+    /// Buoyancy data of the entity.
+    BuoyancyData,
+    /// Amount of horns a goat has.
+    GoatHornCount,
+    /// Base runtime ID of the entity.
+    BaseRuntimeId,
+    /// Distance offset of movement sounds.
+    MovementSoundDistanceOffset,
+    /// Interval between heartbeats of a warden in ticks.
+    HeartbeatIntervalTicks,
+    /// Heartbeat sound event of a warden.
+    HeartbeatSoundEvent,
+    /// Position the player last died at.
+    PlayerLastDeathPosition,
+    /// Dimension the player last died in.
+    PlayerLastDeathDimension,
+    /// Whether the player has died before.
+    PlayerHasDied,
+    /// Collision box of the entity.
+    CollisionBox,
+    /// Mob effects that are visible on the entity.
+    VisibleMobEffects,
+}
+
+impl TryFrom<u32> for ActorDataKey {
+    type Error = anyhow::Error;
+
+    fn try_from(v: u32) -> anyhow::Result<ActorDataKey> {
+        if v < ActorDataKey::variant_count() as u32 {
+            // SAFETY: This is safe because the discriminant is in range and
+            // the representations are the same. Additionally, none of the enum members
+            // have a manually assigned value (this is ensured by the `variant_count` macro).
+            Ok(unsafe { std::mem::transmute::<u32, ActorDataKey>(v) })
+        } else {
+            anyhow::bail!("Actor data key out of range ({v} >= {})", ActorDataKey::variant_count())
+        }
+    }
+}
+
+/// A flag that is stored in the [`Flags`](ActorDataKey::Flags) and [`Flags2`](ActorDataKey::Flags2)
+/// entries of the [`ActorData`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+#[variant_count]
+pub enum ActorFlag {
+    /// The entity is on fire.
+    OnFire,
+    /// The entity is sneaking.
+    Sneaking,
+    /// The entity is riding another entity.
+    Riding,
+    /// The entity is sprinting.
+    Sprinting,
+    /// The entity is using an item.
+    UsingItem,
+    /// The entity is invisible.
+    Invisible,
+    /// The entity is tempted by an item.
+    Tempted,
+    /// The entity is in love.
+    InLove,
+    /// The entity is saddled.
+    Saddled,
+    /// The entity is powered, such as a charged creeper.
+    Powered,
+    /// The entity is ignited, such as a creeper that is about to explode.
+    Ignited,
+    /// The entity is a baby.
+    Baby,
+    /// The entity is converting, such as a zombie villager being cured.
+    Converting,
+    /// The entity's attack is critical.
+    Critical,
+    /// The name tag of the entity can be shown.
+    ShowName,
+    /// The name tag of the entity is always shown.
+    AlwaysShowName,
+    /// The entity has no AI.
+    NoAi,
+    /// The entity does not make sounds.
+    Silent,
+    /// The entity is climbing a wall.
+    WallClimbing,
+    /// The entity can climb.
+    CanClimb,
+    /// The entity can swim.
+    CanSwim,
+    /// The entity can fly.
+    CanFly,
+    /// The entity can walk.
+    CanWalk,
+    /// The entity is resting.
+    Resting,
+    /// The entity is sitting.
+    Sitting,
+    /// The entity is angry.
+    Angry,
+    /// The entity is interested.
+    Interested,
+    /// The entity is charged.
+    Charged,
+    /// The entity is tamed.
+    Tamed,
+    /// The entity is orphaned.
+    Orphaned,
+    /// The entity is leashed.
+    Leashed,
+    /// The entity is sheared.
+    Sheared,
+    /// The entity is gliding with an elytra.
+    Gliding,
+    /// The entity is an elder guardian.
+    Elder,
+    /// The entity is moving.
+    Moving,
+    /// The entity is breathing.
+    Breathing,
+    /// The entity carries a chest.
+    Chested,
+    /// The entity is stackable.
+    Stackable,
+    /// The bottom of the entity is shown.
+    ShowBottom,
+    /// The entity is standing on its hind legs.
+    Standing,
+    /// The entity is shaking.
+    Shaking,
+    /// The entity is idling.
+    Idling,
+    /// The entity is casting a spell.
+    Casting,
+    /// The entity is charging an attack.
+    Charging,
+    /// The entity is controlled with the keyboard.
+    KeyboardControlled,
+    /// The entity can power jump.
+    PowerJump,
+    /// The entity can dash.
+    Dash,
+    /// The entity is lingering.
+    Lingering,
+    /// The entity collides with blocks.
+    HasCollision,
+    /// The entity is affected by gravity.
+    HasGravity,
+    /// The entity is immune to fire.
+    FireImmune,
+    /// The entity is dancing.
+    Dancing,
+    /// The entity is enchanted.
+    Enchanted,
+    /// The trident is returning to its owner.
+    ReturnTrident,
+    /// The container of the entity is private.
+    ContainerPrivate,
+    /// The entity is transforming.
+    Transforming,
+    /// The entity damages nearby mobs.
+    DamageNearbyMobs,
+    /// The entity is swimming.
+    Swimming,
+    /// The entity is bribed.
+    Bribed,
+    /// The entity is pregnant.
+    Pregnant,
+    /// The entity is laying an egg.
+    LayingEgg,
+    /// Passengers of the entity can pick items.
+    PassengerCanPick,
+    /// The entity is transitioning to sitting.
+    TransitionSitting,
+    /// The entity is eating.
+    Eating,
+    /// The entity is laying down.
+    LayingDown,
+    /// The entity is sneezing.
+    Sneezing,
+    /// The entity is trusting.
+    Trusting,
+    /// The entity is rolling.
+    Rolling,
+    /// The entity is scared.
+    Scared,
+    /// The entity is in scaffolding.
+    InScaffolding,
+    /// The entity is over scaffolding.
+    OverScaffolding,
+    /// The entity is descending through a block.
+    DescendThroughBlock,
+    /// The entity is blocking with a shield.
+    Blocking,
+    /// The entity is transitioning to blocking.
+    TransitionBlocking,
+    /// The entity blocked an attack with a shield.
+    BlockedUsingShield,
+    /// The entity blocked an attack with a damaged shield.
+    BlockedUsingDamagedShield,
+    /// The entity is sleeping.
+    Sleeping,
+    /// The entity wants to wake up.
+    WantsToWake,
+    /// The entity is interested in trading.
+    TradeInterest,
+    /// The entity can break doors.
+    DoorBreaker,
+    /// The entity is breaking an obstruction.
+    BreakingObstruction,
+    /// The entity can open doors.
+    DoorOpener,
+    /// The entity is a raid captain.
+    Captain,
+    /// The entity is stunned.
+    Stunned,
+    /// The entity is roaring.
+    Roaring,
+    /// The entity is performing a delayed attack.
+    DelayedAttack,
+    /// The entity is avoiding mobs.
+    AvoidingMobs,
+    /// The entity is avoiding a block.
+    AvoidingBlock,
+    /// The entity is facing its target to perform a ranged attack.
+    FacingTargetToRangeAttack,
+    /// The entity is hidden when invisible.
+    HiddenWhenInvisible,
+    /// The entity is shown in a user interface.
+    InUi,
+    /// The entity is stalking.
+    Stalking,
+    /// The entity is emoting.
+    Emoting,
+    /// The entity is celebrating.
+    Celebrating,
+    /// The entity is admiring an item.
+    Admiring,
+    /// The entity is performing a special celebration.
+    CelebratingSpecial,
+    /// The entity is out of control.
+    OutOfControl,
+    /// The entity is performing a ram attack.
+    RamAttack,
+    /// The entity is playing dead.
+    PlayingDead,
+    /// The entity is in a block it can ascend.
+    InAscendingBlock,
+    /// The entity is over a block it can descend.
+    OverDescendingBlock,
+    /// The entity is croaking.
+    Croaking,
+    /// The entity is digesting a mob.
+    DigestMob,
+    /// The entity is jumping towards a goal.
+    JumpGoal,
+    /// The entity is emerging.
+    Emerging,
+    /// The entity is sniffing.
+    Sniffing,
+    /// The entity is digging.
+    Digging,
+    /// The entity is performing a sonic boom.
+    SonicBoom,
+    /// The entity has to wait before dashing again.
+    HasDashTimeout,
+    /// The entity is pushed towards the closest free space.
+    PushTowardsClosestSpace,
+    /// The entity is scenting.
+    Scenting,
+    /// The entity is rising.
+    Rising,
+    /// The entity is feeling happy.
+    FeelingHappy,
+    /// The entity is searching.
+    Searching,
+    /// The entity is crawling.
+    Crawling,
+    /// First timer flag.
+    TimerFlag1,
+    /// Second timer flag.
+    TimerFlag2,
+    /// Third timer flag.
+    TimerFlag3,
+    /// The body rotation of the entity is blocked.
+    BodyRotationBlocked,
+    /// The entity is rendered when invisible.
+    RenderWhenInvisible,
+    /// The body rotation of the entity is aligned to an axis.
+    BodyRotationAxisAligned,
+    /// Other entities collide with the entity.
+    Collidable,
+    /// The entity is controlled in the air with the movement keys.
+    WasdAirControlled,
+}
+
+impl ActorFlag {
+    /// The key of the entry that contains this flag and the bit it occupies in that entry.
+    const fn location(self) -> (ActorDataKey, u32) {
+        let index = self as u32;
+        if index < 64 {
+            (ActorDataKey::Flags, index)
+        } else {
+            (ActorDataKey::Flags2, index - 64)
+        }
+    }
+}
+
+/// The value of an [`ActorData`] entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActorDataValue {
+    /// An unsigned byte, also used for booleans.
+    Byte(u8),
+    /// A 16-bit integer.
+    Short(i16),
+    /// A 32-bit integer.
+    Int(i32),
+    /// A 32-bit float.
+    Float(f32),
+    /// A UTF-8 string.
+    String(String),
+    /// An NBT compound.
+    Compound(HashMap<String, nbt::Value>),
+    /// A block position.
+    BlockPosition(Vector<i32, 3>),
+    /// A 64-bit integer.
+    Long(i64),
+    /// A vector of three floats.
+    Vector(Vector<f32, 3>),
+}
+
+impl ActorDataValue {
+    /// The ID of the type of this value on the network.
+    pub const fn type_id(&self) -> u32 {
+        match self {
+            Self::Byte(_) => 0,
+            Self::Short(_) => 1,
+            Self::Int(_) => 2,
+            Self::Float(_) => 3,
+            Self::String(_) => 4,
+            Self::Compound(_) => 5,
+            Self::BlockPosition(_) => 6,
+            Self::Long(_) => 7,
+            Self::Vector(_) => 8,
+        }
+    }
+
+    /// Reads a value of the given type.
+    pub fn deserialize_from<'a, R: BinaryRead<'a>>(type_id: u32, reader: &mut R) -> anyhow::Result<ActorDataValue> {
+        Ok(match type_id {
+            0 => Self::Byte(reader.read_u8()?),
+            1 => Self::Short(reader.read_i16_le()?),
+            2 => Self::Int(reader.read_var_i32()?),
+            3 => Self::Float(reader.read_f32_le()?),
+            4 => Self::String(reader.read_str()?.to_owned()),
+            5 => Self::Compound(nbt::from_var_bytes(reader)?.0),
+            6 => Self::BlockPosition(reader.read_veci()?),
+            7 => Self::Long(reader.read_var_i64()?),
+            8 => Self::Vector(reader.read_vecf()?),
+            _ => anyhow::bail!("Invalid actor data type {type_id}")
+        })
+    }
+}
+
+impl Serialize for ActorDataValue {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        match self {
+            Self::Byte(v) => writer.write_u8(*v),
+            Self::Short(v) => writer.write_i16_le(*v),
+            Self::Int(v) => writer.write_var_i32(*v),
+            Self::Float(v) => writer.write_f32_le(*v),
+            Self::String(v) => writer.write_str(v),
+            Self::Compound(v) => nbt::to_var_bytes_in(writer, v),
+            Self::BlockPosition(v) => writer.write_veci(v),
+            Self::Long(v) => writer.write_var_i64(*v),
+            Self::Vector(v) => writer.write_vecf(v),
+        }
+    }
+}
+
+/// Metadata of an entity, such as its name tag, scale and flags.
+///
+/// Clients only need to be sent the entries that have changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActorData {
+    entries: BTreeMap<ActorDataKey, ActorDataValue>,
+}
+
+impl ActorData {
+    /// Creates an empty metadata dictionary.
+    pub const fn new() -> ActorData {
+        ActorData { entries: BTreeMap::new() }
+    }
+
+    /// Returns the value of the given entry.
+    pub fn get(&self, key: ActorDataKey) -> Option<&ActorDataValue> {
+        self.entries.get(&key)
+    }
+
+    /// Sets the value of an entry, returning the previous value.
+    pub fn insert(&mut self, key: ActorDataKey, value: ActorDataValue) -> Option<ActorDataValue> {
+        self.entries.insert(key, value)
+    }
+
+    /// Removes an entry, returning its value.
+    pub fn remove(&mut self, key: ActorDataKey) -> Option<ActorDataValue> {
+        self.entries.remove(&key)
+    }
+
+    /// Iterates over all entries in order of their keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&ActorDataKey, &ActorDataValue)> {
+        self.entries.iter()
+    }
+
+    /// The amount of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the given flag is set.
+    pub fn flag(&self, flag: ActorFlag) -> bool {
+        let (key, bit) = flag.location();
+        matches!(self.get(key), Some(ActorDataValue::Long(flags)) if flags & (1 << bit) != 0)
+    }
+
+    /// Sets or clears the given flag.
+    pub fn set_flag(&mut self, flag: ActorFlag, value: bool) {
+        let (key, bit) = flag.location();
+        let entry = self.entries.entry(key).or_insert(ActorDataValue::Long(0));
+
+        if let ActorDataValue::Long(flags) = entry {
+            if value {
+                *flags |= 1 << bit;
+            } else {
+                *flags &= !(1 << bit);
+            }
+        } else {
+            *entry = ActorDataValue::Long(if value { 1 << bit } else { 0 });
+        }
+    }
+
+    /// Sets the name tag that is displayed above the entity.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.insert(ActorDataKey::Name, ActorDataValue::String(name.into()));
+    }
+
+    /// Sets the scale of the entity, where 1 is the default size.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.insert(ActorDataKey::Scale, ActorDataValue::Float(scale));
+    }
+
+    /// Sets the size of the bounding box of the entity.
+    pub fn set_bounding_box(&mut self, width: f32, height: f32) {
+        self.insert(ActorDataKey::Width, ActorDataValue::Float(width));
+        self.insert(ActorDataKey::Height, ActorDataValue::Float(height));
+    }
+
+    /// Sets the variant of the entity, such as the type of a cat.
+    pub fn set_variant(&mut self, variant: i32) {
+        self.insert(ActorDataKey::Variant, ActorDataValue::Int(variant));
+    }
+
+    /// Sets the remaining and maximum amount of air of the entity in ticks.
+    pub fn set_air_supply(&mut self, air: i16, max: i16) {
+        self.insert(ActorDataKey::AirSupply, ActorDataValue::Short(air));
+        self.insert(ActorDataKey::AirSupplyMax, ActorDataValue::Short(max));
+    }
+
+    /// Sets whether the name tag is shown even when the entity is not looked at.
+    pub fn set_always_show_name(&mut self, value: bool) {
+        self.insert(ActorDataKey::AlwaysShowNameTag, ActorDataValue::Byte(u8::from(value)));
+        self.set_flag(ActorFlag::AlwaysShowName, value);
+    }
+}
+
+impl Serialize for ActorData {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u32(self.entries.len() as u32)?;
+        for (key, value) in &self.entries {
+            writer.write_var_u32(*key as u32)?;
+            writer.write_var_u32(value.type_id())?;
+            value.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Deserialize<'a> for ActorData {
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        let count = reader.read_var_u32()?;
+
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let key = ActorDataKey::try_from(reader.read_var_u32()?)?;
+            let type_id = reader.read_var_u32()?;
+
+            entries.insert(key, ActorDataValue::deserialize_from(type_id, reader)?);
+        }
+
+        Ok(ActorData { entries })
+    }
+}
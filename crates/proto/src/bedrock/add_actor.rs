@@ -0,0 +1,56 @@
+use util::{BinaryWrite, Serialize, Vector};
+
+use crate::bedrock::{ActorData, ConnectedPacket, EntityLink};
+
+/// Adds an entity other than a player to the client's world.
+///
+/// Players are added using [`AddPlayer`](crate::bedrock::AddPlayer) instead.
+#[derive(Debug, Clone)]
+pub struct AddActor<'a> {
+    /// Unique ID of the entity.
+    pub unique_id: i64,
+    /// Runtime ID of the entity.
+    pub runtime_id: u64,
+    /// Identifier of the entity type, such as `minecraft:pig`.
+    pub identifier: &'a str,
+    /// Initial position.
+    pub position: Vector<f32, 3>,
+    /// Initial velocity.
+    pub velocity: Vector<f32, 3>,
+    /// Initial rotation in degrees.
+    /// The x and y components are the pitch and yaw, the z component is the head yaw.
+    pub rotation: Vector<f32, 3>,
+    /// Yaw of the body in degrees.
+    pub body_yaw: f32,
+    /// Metadata of the entity.
+    pub metadata: &'a ActorData,
+    /// Entity links. See [`EntityLink`].
+    pub links: &'a [EntityLink],
+}
+
+impl ConnectedPacket for AddActor<'_> {
+    const ID: u32 = 0x0d;
+}
+
+impl Serialize for AddActor<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_i64(self.unique_id)?;
+        writer.write_var_u64(self.runtime_id)?;
+        writer.write_str(self.identifier)?;
+        writer.write_vecf(&self.position)?;
+        writer.write_vecf(&self.velocity)?;
+        writer.write_vecf(&self.rotation)?;
+        writer.write_f32_le(self.body_yaw)?;
+        writer.write_var_u32(0)?; // Attributes are unused.
+        self.metadata.serialize_into(writer)?;
+        writer.write_var_u32(0)?; // Entity properties are unused.
+        writer.write_var_u32(0)?; // Entity properties are unused.
+
+        writer.write_var_u32(self.links.len() as u32)?;
+        for link in self.links {
+            link.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
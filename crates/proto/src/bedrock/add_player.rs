@@ -3,7 +3,7 @@ use uuid::Uuid;
 use util::{Serialize, Vector};
 use util::{BinaryWrite};
 
-use crate::bedrock::{AbilityData, DeviceOS};
+use crate::bedrock::{AbilityData, ActorData, DeviceOS, ItemInstance};
 use crate::bedrock::{ConnectedPacket, GameMode};
 
 
@@ -65,8 +65,9 @@ pub struct AddPlayer<'a> {
     /// Game mode of the player.
     pub game_mode: GameMode,
     /// Item held by the player.
-    // pub held_item: ItemStack,
-    // pub metadata: HashMap<u32, nbt::Value>,
+    pub held_item: ItemInstance<'a>,
+    /// Metadata of the player, such as its name tag.
+    pub metadata: &'a ActorData,
     // pub properties: EntityProperties,
     /// Abilities of the player. See [`AbilityData`].
     pub ability_data: AbilityData,
@@ -91,10 +92,9 @@ impl Serialize for AddPlayer<'_> {
         writer.write_vecf(&self.position)?;
         writer.write_vecf(&self.velocity)?;
         writer.write_vecf(&self.rotation)?;
-        // self.held_item.serialize(buffer)?;
+        self.held_item.serialize_into(writer)?;
         writer.write_var_i32(self.game_mode as i32)?;
-        // buffer.put_metadata(&self.metadata);
-        writer.write_var_u32(0)?; // TODO: Entity metadata.
+        self.metadata.serialize_into(writer)?;
         writer.write_var_u32(0)?; // Entity properties are unused.
         writer.write_var_u32(0)?; // Entity properties are unused.
         self.ability_data.serialize_into(writer)?;
@@ -3,6 +3,23 @@ use util::{BinaryRead};
 
 use crate::bedrock::ConnectedPacket;
 
+/// Maximum amount of hashes in a single list of a [`CacheBlobStatus`].
+///
+/// The client never sends more than this, larger counts would only be used to reserve large amounts of memory.
+const MAX_BLOB_COUNT: u32 = 4095;
+
+/// Verifies that a hash count sent by the client is within [`MAX_BLOB_COUNT`]
+/// and that the packet is large enough to contain all of the hashes.
+fn blob_count<'a, R: BinaryRead<'a>>(count: u32, reader: &mut R, list: &str) -> anyhow::Result<usize> {
+    anyhow::ensure!(count <= MAX_BLOB_COUNT, "Too many {list} ({count} > {MAX_BLOB_COUNT})");
+
+    let count = count as usize;
+    let remaining = reader.remaining() / 8;
+    anyhow::ensure!(count <= remaining, "Packet is too short to contain {count} {list} ({remaining} remaining)");
+
+    Ok(count)
+}
+
 #[derive(Debug, Clone)]
 pub struct CacheBlobStatus {
     /// Hashes of the blobs that the client still needs.
@@ -21,12 +38,15 @@ impl<'a> Deserialize<'a> for CacheBlobStatus {
         let miss_count = reader.read_var_u32()?;
         let hit_count = reader.read_var_u32()?;
 
-        let mut misses = Vec::with_capacity(miss_count as usize);
+        let miss_count = blob_count(miss_count, reader, "blob misses")?;
+        let hit_count = blob_count(hit_count, reader, "blob hits")?;
+
+        let mut misses = Vec::with_capacity(miss_count);
         for _ in 0..miss_count {
             misses.push(reader.read_u64_le()?);
         }
 
-        let mut hits = Vec::with_capacity(hit_count as usize);
+        let mut hits = Vec::with_capacity(hit_count);
         for _ in 0..hit_count {
             hits.push(reader.read_u64_le()?);
         }
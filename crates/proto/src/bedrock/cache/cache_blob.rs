@@ -1,4 +1,4 @@
-use util::BinaryWrite;
+use util::{size_of_varint, BinaryWrite};
 
 /// A blob used in the cache protocol.
 #[derive(Debug, Clone)]
@@ -12,18 +12,19 @@ pub struct CacheBlob<'a> {
 impl<'a> CacheBlob<'a> {
     pub fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
         writer.write_u64_le(self.hash)?;
+        writer.write_var_u32(self.payload.len() as u32)?;
         writer.write_all(self.payload)?;
 
         Ok(())
     }
 
     #[inline]
-    pub const fn len(&self) -> usize {
-        8 + self.payload.len()
+    pub fn len(&self) -> usize {
+        8 + size_of_varint(self.payload.len() as u32) + self.payload.len()
     }
 
     #[inline]
-    pub const fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 }
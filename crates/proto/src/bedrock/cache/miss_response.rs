@@ -1,4 +1,4 @@
-use util::{Serialize};
+use util::{size_of_varint, Serialize};
 use util::BinaryWrite;
 
 use crate::bedrock::ConnectedPacket;
@@ -13,7 +13,7 @@ impl ConnectedPacket for CacheMissResponse<'_> {
     const ID: u32 = 0x88;
 
     fn serialized_size(&self) -> usize {
-        1 + self.blobs.iter().fold(0, |acc, blob| acc + blob.len())
+        size_of_varint(self.blobs.len() as u32) + self.blobs.iter().fold(0, |acc, blob| acc + blob.len())
     }
 }
 
@@ -2,7 +2,9 @@ use macros::variant_count;
 
 /// A permission level within the command system.
 /// Commands use permission levels separate from the standard permission levels.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+///
+/// Levels are ordered from least to most privileged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 #[variant_count]
 pub enum CommandPermissionLevel {
@@ -1,8 +1,22 @@
 use macros::variant_count;
 
-use util::{Deserialize, BinaryRead, Vector, BlockPosition};
+use util::{Deserialize, Serialize, BinaryRead, BinaryWrite, Vector, BlockPosition};
 
-use crate::bedrock::{ConnectedPacket, PlayerActionType, PlayerAction};
+use crate::bedrock::{ConnectedPacket, PlayerActionType};
+
+use super::{FullContainerName, ItemInstance, UseItemAction};
+
+/// Maximum amount of elements in a list sent by the client.
+///
+/// Lists are allocated up front using the length given by the client,
+/// this prevents a single packet from reserving large amounts of memory.
+const MAX_LIST_LENGTH: u32 = 128;
+
+/// Verifies that a list length sent by the client does not exceed [`MAX_LIST_LENGTH`].
+pub(super) fn list_length(length: u32, list: &str) -> anyhow::Result<usize> {
+    anyhow::ensure!(length <= MAX_LIST_LENGTH, "Too many {list} ({length} > {MAX_LIST_LENGTH})");
+    Ok(length as usize)
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u64)]
@@ -25,32 +39,37 @@ pub enum InputDataFlag {
     UpRight = 1 << 15,
     WantUp = 1 << 16,
     WantDown = 1 << 17,
-    WantUpSlow = 1 << 18,
-    Sprinting = 1 << 19,
-    AscendBlock = 1 << 20,
-    DescendBlock = 1 << 21,
-    SneakToggleDown = 1 << 22,
-    PersistSneak = 1 << 23,
-    StartSprinting = 1 << 24,
-    StopSprinting = 1 << 25,
-    StartSneaking = 1 << 26,
-    StopSneaking = 1 << 27,
-    StartSwimming = 1 << 28,
-    StopSwimming = 1 << 29,
-    StartJumping = 1 << 30,
-    StartGliding = 1 << 31,
-    StopGliding = 1 << 32,
-    PerformItemTransaction = 1 << 33,
-    PerformBlockActions = 1 << 34,
-    PerformItemStackRequest = 1 << 35,
-    HandledTeleport = 1 << 36,
-    Emoting = 1 << 37,
-    MissedSwing = 1 << 38,
-    StartCrawling = 1 << 39,
-    StopCrawling = 1 << 40,
-    StartFlying = 1 << 41,
-    StopFlying = 1 << 42,
-    AcknowledgeServerData = 1 << 43
+    WantDownSlow = 1 << 18,
+    WantUpSlow = 1 << 19,
+    Sprinting = 1 << 20,
+    AscendBlock = 1 << 21,
+    DescendBlock = 1 << 22,
+    SneakToggleDown = 1 << 23,
+    PersistSneak = 1 << 24,
+    StartSprinting = 1 << 25,
+    StopSprinting = 1 << 26,
+    StartSneaking = 1 << 27,
+    StopSneaking = 1 << 28,
+    StartSwimming = 1 << 29,
+    StopSwimming = 1 << 30,
+    StartJumping = 1 << 31,
+    StartGliding = 1 << 32,
+    StopGliding = 1 << 33,
+    PerformItemTransaction = 1 << 34,
+    PerformBlockActions = 1 << 35,
+    PerformItemStackRequest = 1 << 36,
+    HandledTeleport = 1 << 37,
+    Emoting = 1 << 38,
+    MissedSwing = 1 << 39,
+    StartCrawling = 1 << 40,
+    StopCrawling = 1 << 41,
+    StartFlying = 1 << 42,
+    StopFlying = 1 << 43,
+    AcknowledgeServerData = 1 << 44,
+    ClientPredictedVehicle = 1 << 45,
+    PaddlingLeft = 1 << 46,
+    PaddlingRight = 1 << 47,
+    BlockBreakingDelayEnabled = 1 << 48
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -72,11 +91,11 @@ impl TryFrom<u32> for PlayMode {
     type Error = anyhow::Error;
     
     fn try_from(v: u32) -> anyhow::Result<Self> {
-        if v <= Self::variant_count() as u32 {
+        if v < Self::variant_count() as u32 {
             // SAFETY: This is safe because the enum has a `u32` repr and the discriminant is in range.
             Ok(unsafe { std::mem::transmute::<u32, Self>(v) })
         } else {
-            anyhow::bail!("Play mode out of range ({v} > {})", Self::variant_count());
+            anyhow::bail!("Play mode out of range ({v} >= {})", Self::variant_count());
         }
     }
 }
@@ -107,7 +126,7 @@ impl TryFrom<u32> for InputMode {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-#[repr(i32)]
+#[repr(u32)]
 #[variant_count]
 pub enum InteractionModel {
     Touch,
@@ -115,15 +134,15 @@ pub enum InteractionModel {
     Classic
 }
 
-impl TryFrom<i32> for InteractionModel {
+impl TryFrom<u32> for InteractionModel {
     type Error = anyhow::Error;
 
-    fn try_from(v: i32) -> anyhow::Result<InteractionModel> {
-        if v <= InteractionModel::variant_count() as i32 {
+    fn try_from(v: u32) -> anyhow::Result<InteractionModel> {
+        if v < InteractionModel::variant_count() as u32 {
             // SAFETY: This is safe because the discriminant is in range and
             // the representations are the same. Additionally, none of the enum members
             // have a manually assigned value (this is ensured by the `variant_count` macro).
-            Ok(unsafe { std::mem::transmute::<i32, InteractionModel>(v) })
+            Ok(unsafe { std::mem::transmute::<u32, InteractionModel>(v) })
         } else {
             anyhow::bail!("Interaction model out of range")
         }
@@ -258,41 +277,83 @@ impl TryFrom<i32> for WindowId {
     }
 }
 
-#[derive(Debug)]
-pub struct InventoryAction {
+/// A change to a single inventory slot.
+#[derive(Debug, Clone)]
+pub struct InventoryAction<'a> {
+    /// Kind of inventory that was modified.
     pub source_type: InventoryActionSource,
+    /// Window that was modified. Only set for container sources.
     pub window: Option<WindowId>,
+    /// Flags of the world interaction. Only set for world sources.
     pub source_flags: u32,
-    pub inventory_slot: u32
+    /// Slot that was modified.
+    pub inventory_slot: u32,
+    /// Item in the slot before the action.
+    pub old_item: ItemInstance<'a>,
+    /// Item in the slot after the action.
+    pub new_item: ItemInstance<'a>
+}
+
+impl<'a> Serialize for InventoryAction<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u32(self.source_type as u32)?;
+
+        match self.source_type {
+            InventoryActionSource::Container | InventoryActionSource::Todo => {
+                let window = self.window.ok_or_else(|| anyhow::anyhow!("Container inventory action is missing a window"))?;
+                writer.write_var_i32(window.into())?;
+            },
+            InventoryActionSource::World => writer.write_var_u32(self.source_flags)?,
+            InventoryActionSource::Creative => {}
+        }
+
+        writer.write_var_u32(self.inventory_slot)?;
+        self.old_item.serialize_into(writer)?;
+        self.new_item.serialize_into(writer)
+    }
 }
 
-impl<'a> Deserialize<'a> for InventoryAction {
-    #[allow(clippy::collection_is_never_read)]
+impl<'a> Deserialize<'a> for InventoryAction<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
-        let source_type = InventoryActionSource::try_from(reader.read_u32_le()?)?;
-        
-        let mut _window = None;
-        let mut _source_flags = 0;
-
-        if source_type == InventoryActionSource::Container || source_type == InventoryActionSource::Todo {
-            _window = Some(WindowId::try_from(reader.read_i8()? as i32)?);
-        } else if source_type == InventoryActionSource::World {
-            _source_flags = reader.read_var_u32()?;
+        let source_type = InventoryActionSource::try_from(reader.read_var_u32()?)?;
+
+        let mut window = None;
+        let mut source_flags = 0;
+
+        match source_type {
+            InventoryActionSource::Container | InventoryActionSource::Todo => {
+                window = Some(WindowId::try_from(reader.read_var_i32()?)?);
+            },
+            InventoryActionSource::World => source_flags = reader.read_var_u32()?,
+            InventoryActionSource::Creative => {}
         }
 
-        let _inventory_slot = reader.read_var_u32()?;
+        let inventory_slot = reader.read_var_u32()?;
+        let old_item = ItemInstance::deserialize_from(reader)?;
+        let new_item = ItemInstance::deserialize_from(reader)?;
 
-        // https://github.com/Sandertv/gophertunnel/blob/36e5147307884b745b7d28d546c07ab03d4afb36/minecraft/protocol/inventory.go#L52
-        todo!("Item instance reading");
+        Ok(Self {
+            source_type, window, source_flags, inventory_slot, old_item, new_item
+        })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LegacySetItemSlot<'a> {
     pub container: u8,
     pub slots: &'a [u8]
 }
 
+impl<'a> Serialize for LegacySetItemSlot<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u8(self.container)?;
+        writer.write_var_u32(self.slots.len() as u32)?;
+        writer.write_all(self.slots)?;
+
+        Ok(())
+    }
+}
+
 impl<'a> Deserialize<'a> for LegacySetItemSlot<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let container = reader.read_u8()?;
@@ -305,43 +366,93 @@ impl<'a> Deserialize<'a> for LegacySetItemSlot<'a> {
     }
 }
 
-#[derive(Debug)]
+/// An item interaction performed in the last tick, such as using an item on a block.
+#[derive(Debug, Clone)]
 pub struct TransactionData<'a> {
+    /// ID of the legacy request. Set items are only sent when this is less than -1.
     pub legacy_request_id: i32,
+    /// Slots that were changed by the legacy request.
     pub legacy_slots: Vec<LegacySetItemSlot<'a>>,
-    pub actions: Vec<InventoryAction>,
-    pub action_type: PlayerActionType,
+    /// Inventory changes that were made by the interaction.
+    pub actions: Vec<InventoryAction<'a>>,
+    /// The kind of interaction.
+    pub action_type: UseItemAction,
+    /// Position of the block that was interacted with.
     pub block_position: BlockPosition,
+    /// Face of the block that was clicked.
     pub block_face: i32,
+    /// Hotbar slot of the item that was used.
     pub hotbar_slot: i32,
+    /// Item that was held by the player.
+    pub held_item: ItemInstance<'a>,
+    /// Position of the player.
     pub position: Vector<f32, 3>,
+    /// Position on the block that was clicked, relative to the block.
     pub clicked_position: Vector<f32, 3>,
+    /// Runtime ID of the block that was clicked.
     pub block_runtime_id: u32
 }
 
+impl<'a> Serialize for TransactionData<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_i32(self.legacy_request_id)?;
+        if self.legacy_request_id < -1 && (self.legacy_request_id & 1) == 0 {
+            writer.write_var_u32(self.legacy_slots.len() as u32)?;
+            for slot in &self.legacy_slots {
+                slot.serialize_into(writer)?;
+            }
+        }
+
+        writer.write_var_u32(self.actions.len() as u32)?;
+        for action in &self.actions {
+            action.serialize_into(writer)?;
+        }
+
+        writer.write_var_u32(self.action_type.into())?;
+        writer.write_block_pos(&self.block_position)?;
+        writer.write_var_i32(self.block_face)?;
+        writer.write_var_i32(self.hotbar_slot)?;
+        self.held_item.serialize_into(writer)?;
+        writer.write_vecf(&self.position)?;
+        writer.write_vecf(&self.clicked_position)?;
+        writer.write_var_u32(self.block_runtime_id)
+    }
+}
+
 impl<'a> Deserialize<'a> for TransactionData<'a> {
-    #[allow(clippy::collection_is_never_read)]
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let legacy_request_id = reader.read_var_i32()?;
         let mut legacy_slots = Vec::new();
 
         if legacy_request_id < -1 && (legacy_request_id & 1) == 0 {
-            let slot_count = reader.read_var_u32()?;
-            legacy_slots.reserve(slot_count as usize);
+            let slot_count = list_length(reader.read_var_u32()?, "legacy item slots")?;
+            legacy_slots.reserve(slot_count);
 
             for _ in 0..slot_count {
                 legacy_slots.push(LegacySetItemSlot::deserialize_from(reader)?);
             }
         }
 
-        let action_count = reader.read_var_u32()?;
-        let mut actions = Vec::with_capacity(action_count as usize);
+        let action_count = list_length(reader.read_var_u32()?, "inventory actions")?;
+        let mut actions = Vec::with_capacity(action_count);
 
         for _ in 0..action_count {
             actions.push(InventoryAction::deserialize_from(reader)?);
         }
 
-        todo!()
+        let action_type = UseItemAction::try_from(reader.read_var_u32()?)?;
+        let block_position = reader.read_block_pos()?;
+        let block_face = reader.read_var_i32()?;
+        let hotbar_slot = reader.read_var_i32()?;
+        let held_item = ItemInstance::deserialize_from(reader)?;
+        let position = reader.read_vecf()?;
+        let clicked_position = reader.read_vecf()?;
+        let block_runtime_id = reader.read_var_u32()?;
+
+        Ok(Self {
+            legacy_request_id, legacy_slots, actions, action_type, block_position, block_face,
+            hotbar_slot, held_item, position, clicked_position, block_runtime_id
+        })
     }
 }
 
@@ -369,7 +480,7 @@ impl TryFrom<i32> for FilterCause {
     type Error = anyhow::Error;
 
     fn try_from(v: i32) -> anyhow::Result<FilterCause> {
-        if v <= FilterCause::variant_count() as i32 {
+        if v >= 0 && v < FilterCause::variant_count() as i32 {
             // SAFETY: This is safe because the discriminant is in range and
             // the representations are the same. Additionally, none of the enum members
             // have a manually assigned value (this is ensured by the `variant_count` macro).
@@ -403,6 +514,41 @@ pub enum ItemDescriptor<'a> {
     }
 }
 
+impl<'a> Serialize for ItemDescriptor<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        match self {
+            Self::Invalid => writer.write_u8(0),
+            Self::Default { network_id, meta } => {
+                writer.write_u8(1)?;
+                writer.write_i16_le(*network_id)?;
+                if *network_id != 0 {
+                    writer.write_i16_le(*meta)?;
+                }
+
+                Ok(())
+            },
+            Self::MoLang { expression, version } => {
+                writer.write_u8(2)?;
+                writer.write_str(expression)?;
+                writer.write_u8(*version)
+            },
+            Self::ItemTag { tag } => {
+                writer.write_u8(3)?;
+                writer.write_str(tag)
+            },
+            Self::Deferred { name, meta } => {
+                writer.write_u8(4)?;
+                writer.write_str(name)?;
+                writer.write_i16_le(*meta)
+            },
+            Self::ComplexAlias { name } => {
+                writer.write_u8(5)?;
+                writer.write_str(name)
+            }
+        }
+    }
+}
+
 impl<'a> Deserialize<'a> for ItemDescriptor<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let kind = reader.read_u8()?;
@@ -449,6 +595,13 @@ pub struct ItemDescriptorCount<'a> {
     pub count: i32
 }
 
+impl<'a> Serialize for ItemDescriptorCount<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.descriptor.serialize_into(writer)?;
+        writer.write_var_i32(self.count)
+    }
+}
+
 impl<'a> Deserialize<'a> for ItemDescriptorCount<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let descriptor = ItemDescriptor::deserialize_from(reader)?;
@@ -460,21 +613,33 @@ impl<'a> Deserialize<'a> for ItemDescriptorCount<'a> {
     }
 }
 
-#[derive(Debug)]
+/// A slot referred to by an item stack request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct StackRequestSlotInfo {
-    pub container_id: u8,
+    /// Container the slot is in.
+    pub container: FullContainerName,
+    /// Index of the slot within the container.
     pub slot: u8,
+    /// Stack network ID of the item the client expects to be in the slot.
     pub stack_network_id: i32
 }
 
+impl Serialize for StackRequestSlotInfo {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.container.serialize_into(writer)?;
+        writer.write_u8(self.slot)?;
+        writer.write_var_i32(self.stack_network_id)
+    }
+}
+
 impl<'a> Deserialize<'a> for StackRequestSlotInfo {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
-        let container_id = reader.read_u8()?;
+        let container = FullContainerName::deserialize_from(reader)?;
         let slot = reader.read_u8()?;
         let stack_network_id = reader.read_var_i32()?;
 
         Ok(Self {
-            container_id, slot, stack_network_id
+            container, slot, stack_network_id
         })
     }
 }
@@ -615,12 +780,214 @@ pub enum StackRequestAction<'a> {
     CraftLoomRecipe {
         /// Pattern to craft with the loom.
         pattern: &'a str
+    },
+    /// Sent for crafting recipes that are not implemented by the client's stack requests.
+    CraftNonImplemented,
+    /// Sent after a crafting action with the items that the client expects to be crafted.
+    CraftResults {
+        /// Items that the client expects to receive.
+        result_items: Vec<ItemInstance<'a>>,
+        /// How many times the recipe was crafted.
+        times_crafted: u8
+    }
+}
+
+impl<'a> StackRequestAction<'a> {
+    /// The ID of this action on the network.
+    pub const fn as_id(&self) -> u8 {
+        match self {
+            Self::Take { .. } => 0,
+            Self::Place { .. } => 1,
+            Self::Swap { .. } => 2,
+            Self::Drop { .. } => 3,
+            Self::Destroy { .. } => 4,
+            Self::Consume { .. } => 5,
+            Self::Create { .. } => 6,
+            Self::PlaceInContainer { .. } => 7,
+            Self::TakeOutContainer { .. } => 8,
+            Self::LabTableCombine => 9,
+            Self::BeaconPayment { .. } => 10,
+            Self::MineBlock { .. } => 11,
+            Self::CraftRecipe { .. } => 12,
+            Self::AutoCraftRecipe { .. } => 13,
+            Self::CraftCreative { .. } => 14,
+            Self::CraftRecipeOptional { .. } => 15,
+            Self::CraftGrindstoneRecipe { .. } => 16,
+            Self::CraftLoomRecipe { .. } => 17,
+            Self::CraftNonImplemented => 18,
+            Self::CraftResults { .. } => 19
+        }
     }
-}   
+}
+
+impl<'a> Serialize for StackRequestAction<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u8(self.as_id())?;
+
+        match self {
+            Self::Take { count, source, destination } |
+            Self::Place { count, source, destination } |
+            Self::PlaceInContainer { count, source, destination } |
+            Self::TakeOutContainer { count, source, destination } => {
+                writer.write_u8(*count)?;
+                source.serialize_into(writer)?;
+                destination.serialize_into(writer)
+            },
+            Self::Swap { source, destination } => {
+                source.serialize_into(writer)?;
+                destination.serialize_into(writer)
+            },
+            Self::Drop { count, source, randomly } => {
+                writer.write_u8(*count)?;
+                source.serialize_into(writer)?;
+                writer.write_bool(*randomly)
+            },
+            Self::Destroy { count, source } |
+            Self::Consume { count, source } => {
+                writer.write_u8(*count)?;
+                source.serialize_into(writer)
+            },
+            Self::Create { results_slot } => writer.write_u8(*results_slot),
+            Self::LabTableCombine | Self::CraftNonImplemented => Ok(()),
+            Self::BeaconPayment { primary_effect, secondary_effect } => {
+                writer.write_var_i32(*primary_effect)?;
+                writer.write_var_i32(*secondary_effect)
+            },
+            Self::MineBlock { hotbar_slot, predicted_durability, stack_network_id } => {
+                writer.write_var_i32(*hotbar_slot)?;
+                writer.write_var_i32(*predicted_durability)?;
+                writer.write_var_i32(*stack_network_id)
+            },
+            Self::CraftRecipe { recipe_network_id } => writer.write_var_u32(*recipe_network_id),
+            Self::AutoCraftRecipe { recipe_network_id, times_crafted, ingredients } => {
+                writer.write_var_u32(*recipe_network_id)?;
+                writer.write_u8(*times_crafted)?;
+                writer.write_u8(ingredients.len() as u8)?;
+                for ingredient in ingredients {
+                    ingredient.serialize_into(writer)?;
+                }
+
+                Ok(())
+            },
+            Self::CraftCreative { creative_network_id } => writer.write_var_u32(*creative_network_id),
+            Self::CraftRecipeOptional { recipe_network_id, filter_string_index } => {
+                writer.write_var_u32(*recipe_network_id)?;
+                writer.write_i32_le(*filter_string_index)
+            },
+            Self::CraftGrindstoneRecipe { recipe_network_id, cost } => {
+                writer.write_var_u32(*recipe_network_id)?;
+                writer.write_var_i32(*cost)
+            },
+            Self::CraftLoomRecipe { pattern } => writer.write_str(pattern),
+            Self::CraftResults { result_items, times_crafted } => {
+                writer.write_var_u32(result_items.len() as u32)?;
+                for item in result_items {
+                    item.serialize_without_stack_id(writer)?;
+                }
+
+                writer.write_u8(*times_crafted)
+            }
+        }
+    }
+}
 
 impl<'a> Deserialize<'a> for StackRequestAction<'a> {
-    fn deserialize_from<R: BinaryRead<'a>>(_reader: &mut R) -> anyhow::Result<Self> {
-        todo!()
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        let id = reader.read_u8()?;
+        let action = match id {
+            0 => Self::Take {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?,
+                destination: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            1 => Self::Place {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?,
+                destination: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            2 => Self::Swap {
+                source: StackRequestSlotInfo::deserialize_from(reader)?,
+                destination: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            3 => Self::Drop {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?,
+                randomly: reader.read_bool()?
+            },
+            4 => Self::Destroy {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            5 => Self::Consume {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            6 => Self::Create {
+                results_slot: reader.read_u8()?
+            },
+            7 => Self::PlaceInContainer {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?,
+                destination: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            8 => Self::TakeOutContainer {
+                count: reader.read_u8()?,
+                source: StackRequestSlotInfo::deserialize_from(reader)?,
+                destination: StackRequestSlotInfo::deserialize_from(reader)?
+            },
+            9 => Self::LabTableCombine,
+            10 => Self::BeaconPayment {
+                primary_effect: reader.read_var_i32()?,
+                secondary_effect: reader.read_var_i32()?
+            },
+            11 => Self::MineBlock {
+                hotbar_slot: reader.read_var_i32()?,
+                predicted_durability: reader.read_var_i32()?,
+                stack_network_id: reader.read_var_i32()?
+            },
+            12 => Self::CraftRecipe {
+                recipe_network_id: reader.read_var_u32()?
+            },
+            13 => {
+                let recipe_network_id = reader.read_var_u32()?;
+                let times_crafted = reader.read_u8()?;
+
+                let ingredient_count = reader.read_u8()?;
+                let mut ingredients = Vec::with_capacity(ingredient_count as usize);
+                for _ in 0..ingredient_count {
+                    ingredients.push(ItemDescriptorCount::deserialize_from(reader)?);
+                }
+
+                Self::AutoCraftRecipe { recipe_network_id, times_crafted, ingredients }
+            },
+            14 => Self::CraftCreative {
+                creative_network_id: reader.read_var_u32()?
+            },
+            15 => Self::CraftRecipeOptional {
+                recipe_network_id: reader.read_var_u32()?,
+                filter_string_index: reader.read_i32_le()?
+            },
+            16 => Self::CraftGrindstoneRecipe {
+                recipe_network_id: reader.read_var_u32()?,
+                cost: reader.read_var_i32()?
+            },
+            17 => Self::CraftLoomRecipe {
+                pattern: reader.read_str()?
+            },
+            18 => Self::CraftNonImplemented,
+            19 => {
+                let item_count = list_length(reader.read_var_u32()?, "crafting results")?;
+                let mut result_items = Vec::with_capacity(item_count);
+                for _ in 0..item_count {
+                    result_items.push(ItemInstance::deserialize_without_stack_id(reader)?);
+                }
+
+                Self::CraftResults { result_items, times_crafted: reader.read_u8()? }
+            },
+            _ => anyhow::bail!("Invalid stack request action type {id}")
+        };
+
+        Ok(action)
     }
 }
 
@@ -637,18 +1004,36 @@ pub struct StackRequest<'a> {
     pub filter_cause: FilterCause
 }
 
+impl<'a> Serialize for StackRequest<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_i32(self.request_id)?;
+
+        writer.write_var_u32(self.actions.len() as u32)?;
+        for action in &self.actions {
+            action.serialize_into(writer)?;
+        }
+
+        writer.write_var_u32(self.filters.len() as u32)?;
+        for filter in &self.filters {
+            writer.write_str(filter)?;
+        }
+
+        writer.write_i32_le(self.filter_cause as i32)
+    }
+}
+
 impl<'a> Deserialize<'a> for StackRequest<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let request_id = reader.read_var_i32()?;
 
-        let actions_count = reader.read_var_u32()?;
-        let mut actions = Vec::with_capacity(actions_count as usize);
+        let actions_count = list_length(reader.read_var_u32()?, "stack request actions")?;
+        let mut actions = Vec::with_capacity(actions_count);
         for _ in 0..actions_count {
             actions.push(StackRequestAction::deserialize_from(reader)?);
         }
 
-        let filter_count = reader.read_var_u32()?;
-        let mut filters = Vec::with_capacity(filter_count as usize);
+        let filter_count = list_length(reader.read_var_u32()?, "stack request filters")?;
+        let mut filters = Vec::with_capacity(filter_count);
         for _ in 0..filter_count {
             filters.push(reader.read_str()?);
         }
@@ -674,7 +1059,7 @@ macro_rules! impl_getters {
 }
 
 /// Bitfield that specifies which kinds of inputs were performed in the last tick.
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct InputData(pub u64);
 
 impl InputData {
@@ -697,6 +1082,7 @@ impl InputData {
         UpRight ,
         WantUp ,
         WantDown ,
+        WantDownSlow ,
         WantUpSlow ,
         Sprinting ,
         AscendBlock ,
@@ -721,11 +1107,65 @@ impl InputData {
         StartCrawling ,
         StopCrawling ,
         StartFlying ,
-        AcknowledgeServerData,
-        StopFlying
+        StopFlying ,
+        AcknowledgeServerData ,
+        ClientPredictedVehicle ,
+        PaddlingLeft ,
+        PaddlingRight ,
+        BlockBreakingDelayEnabled
     );
 }
 
+/// A block action performed by the player in the last tick, such as starting to break a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerBlockAction {
+    /// The action that was performed.
+    pub action: PlayerActionType,
+    /// Position of the block the action was performed on.
+    /// This is only sent for actions that involve breaking blocks.
+    pub position: Vector<i32, 3>,
+    /// Face of the block the action was performed on.
+    pub face: i32
+}
+
+impl PlayerBlockAction {
+    /// Whether the given action carries a block position and face.
+    const fn has_position(action: PlayerActionType) -> bool {
+        matches!(
+            action,
+            PlayerActionType::StartBreak | PlayerActionType::AbortBreak | PlayerActionType::CrackBreak |
+            PlayerActionType::PredictBreak | PlayerActionType::ContinueBreak
+        )
+    }
+}
+
+impl Serialize for PlayerBlockAction {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_i32(self.action as i32)?;
+        if Self::has_position(self.action) {
+            writer.write_veci(&self.position)?;
+            writer.write_var_i32(self.face)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Deserialize<'a> for PlayerBlockAction {
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        let action = PlayerActionType::try_from(reader.read_var_i32()?)?;
+        let (position, face) = if Self::has_position(action) {
+            (reader.read_veci()?, reader.read_var_i32()?)
+        } else {
+            (Vector::from([0, 0, 0]), 0)
+        };
+
+        Ok(Self {
+            action, position, face
+        })
+    }
+}
+
 /// Sent every tick for server authoritative movement and inventory transactions.
 #[derive(Debug)]
 pub struct PlayerAuthInput<'a> {
@@ -760,13 +1200,69 @@ pub struct PlayerAuthInput<'a> {
     /// Item stack requests that were performed in the last tick.
     pub item_stack: Option<StackRequest<'a>>,
     /// Block actions that were performed in the last tick.
-    pub block_actions: Option<Vec<PlayerAction>>
+    pub block_actions: Option<Vec<PlayerBlockAction>>,
+    /// Rotation of the vehicle the player is riding.
+    /// Only set if the client predicts the movement of the vehicle.
+    pub vehicle_rotation: Vector<f32, 2>,
+    /// Unique ID of the vehicle whose movement is predicted by the client.
+    pub predicted_vehicle: i64
 }
 
 impl ConnectedPacket for PlayerAuthInput<'_> {
     const ID: u32 = 0x90;
 }
 
+impl<'a> Serialize for PlayerAuthInput<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_f32_le(self.pitch)?;
+        writer.write_f32_le(self.yaw)?;
+        writer.write_vecf(&self.position)?;
+        writer.write_vecf(&self.moved)?;
+        writer.write_f32_le(self.head_yaw)?;
+        writer.write_var_u64(self.input_data.0)?;
+        writer.write_var_u32(self.input_mode as u32)?;
+        writer.write_var_u32(self.play_mode as u32)?;
+        writer.write_var_u32(self.interaction_model as u32)?;
+
+        if self.play_mode == PlayMode::VirtualReality {
+            writer.write_vecf(&self.gaze_direction)?;
+        }
+
+        writer.write_var_u64(self.tick)?;
+        writer.write_vecf(&self.delta)?;
+
+        if self.input_data.perform_item_transaction() {
+            let transaction = self.item_transaction.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Item transaction flag is set but no transaction was given"))?;
+
+            transaction.serialize_into(writer)?;
+        }
+
+        if self.input_data.perform_item_stack_request() {
+            let request = self.item_stack.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Item stack request flag is set but no request was given"))?;
+
+            request.serialize_into(writer)?;
+        }
+
+        if self.input_data.perform_block_actions() {
+            let actions = self.block_actions.as_deref().unwrap_or_default();
+
+            writer.write_var_i32(actions.len() as i32)?;
+            for action in actions {
+                action.serialize_into(writer)?;
+            }
+        }
+
+        if self.input_data.client_predicted_vehicle() {
+            writer.write_vecf(&self.vehicle_rotation)?;
+            writer.write_var_i64(self.predicted_vehicle)?;
+        }
+
+        writer.write_vecf(&self.analogue_moved)
+    }
+}
+
 impl<'a> Deserialize<'a> for PlayerAuthInput<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let pitch = reader.read_f32_le()?;
@@ -777,7 +1273,7 @@ impl<'a> Deserialize<'a> for PlayerAuthInput<'a> {
         let input_data = InputData(reader.read_var_u64()?);
         let input_mode = InputMode::try_from(reader.read_var_u32()?)?;
         let play_mode = PlayMode::try_from(reader.read_var_u32()?)?;
-        let interaction_model = InteractionModel::try_from(reader.read_var_i32()?)?;
+        let interaction_model = InteractionModel::try_from(reader.read_var_u32()?)?;
 
         let gaze_direction = if play_mode == PlayMode::VirtualReality {
             reader.read_vecf()?
@@ -789,13 +1285,34 @@ impl<'a> Deserialize<'a> for PlayerAuthInput<'a> {
         let delta = reader.read_vecf()?;
 
         let item_transaction = input_data.perform_item_transaction().then(|| TransactionData::deserialize_from(reader)).transpose()?;
-        let item_stack = input_data.perform_item_stack_request().then(|| todo!());
-        let block_actions = input_data.perform_block_actions().then(|| todo!());
+        let item_stack = input_data.perform_item_stack_request().then(|| StackRequest::deserialize_from(reader)).transpose()?;
+        let block_actions = if input_data.perform_block_actions() {
+            let count = reader.read_var_i32()?;
+            anyhow::ensure!(count >= 0, "Block action count cannot be negative ({count})");
+
+            let count = list_length(count as u32, "block actions")?;
+            let mut actions = Vec::with_capacity(count);
+            for _ in 0..count {
+                actions.push(PlayerBlockAction::deserialize_from(reader)?);
+            }
+
+            Some(actions)
+        } else {
+            None
+        };
+
+        let (vehicle_rotation, predicted_vehicle) = if input_data.client_predicted_vehicle() {
+            (reader.read_vecf()?, reader.read_var_i64()?)
+        } else {
+            (Vector::from([0.0, 0.0]), 0)
+        };
+
         let analogue_moved = reader.read_vecf()?;
-        
+
         Ok(Self {
             pitch, yaw, head_yaw, position, moved, analogue_moved, input_data, input_mode, play_mode,
-            interaction_model, gaze_direction, tick, delta, item_transaction, item_stack, block_actions
+            interaction_model, gaze_direction, tick, delta, item_transaction, item_stack, block_actions,
+            vehicle_rotation, predicted_vehicle
         })
     }
-}
\ No newline at end of file
+}
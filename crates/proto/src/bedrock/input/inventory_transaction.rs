@@ -403,10 +403,19 @@ impl<'a> ItemInstance<'a> {
             blocking_tick: 0
         }
     }
-}
 
-impl<'a> Serialize for ItemInstance<'a> {
-    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+    /// Serializes the item in the format that has no stack network ID.
+    /// This format is used by deprecated crafting results.
+    pub fn serialize_without_stack_id<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.serialize_with(writer, false)
+    }
+
+    /// Deserializes an item in the format that has no stack network ID.
+    pub fn deserialize_without_stack_id<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<ItemInstance<'a>> {
+        Self::deserialize_with(reader, false)
+    }
+
+    fn serialize_with<W: BinaryWrite>(&self, writer: &mut W, has_stack_id: bool) -> anyhow::Result<()> {
         writer.write_var_i32(self.network_id)?;
         if self.network_id == 0 {
             // Item is AIR.
@@ -415,10 +424,12 @@ impl<'a> Serialize for ItemInstance<'a> {
 
         writer.write_u16_le(self.count)?;
         writer.write_var_u32(self.metadata)?;
-        writer.write_bool(self.stack_id.is_some())?;
 
-        if let Some(stack_id) = self.stack_id {
-            writer.write_var_i32(stack_id)?;
+        if has_stack_id {
+            writer.write_bool(self.stack_id.is_some())?;
+            if let Some(stack_id) = self.stack_id {
+                writer.write_var_i32(stack_id)?;
+            }
         }
 
         writer.write_var_i32(self.block_runtime_id)?;
@@ -454,10 +465,8 @@ impl<'a> Serialize for ItemInstance<'a> {
 
         Ok(())
     }
-}
 
-impl<'a> Deserialize<'a> for ItemInstance<'a> {
-    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+    fn deserialize_with<R: BinaryRead<'a>>(reader: &mut R, has_stack_id: bool) -> anyhow::Result<ItemInstance<'a>> {
         let network_id = reader.read_var_i32()?;
         // tracing::debug!("Network ID: {network_id}");
         if network_id == 0 {
@@ -476,8 +485,11 @@ impl<'a> Deserialize<'a> for ItemInstance<'a> {
         let metadata = reader.read_var_u32()?;
         // tracing::debug!("Metadata: {metadata}");
 
-        let has_stack_id = reader.read_bool()?;
-        let stack_id = has_stack_id.then(|| reader.read_var_i32()).transpose()?;
+        let stack_id = if has_stack_id && reader.read_bool()? {
+            Some(reader.read_var_i32()?)
+        } else {
+            None
+        };
         // tracing::debug!("Stack ID: {stack_id:?}");
 
         let block_runtime_id = reader.read_var_i32()?;
@@ -548,6 +560,18 @@ impl<'a> Deserialize<'a> for ItemInstance<'a> {
     }
 }
 
+impl<'a> Serialize for ItemInstance<'a> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.serialize_with(writer, true)
+    }
+}
+
+impl<'a> Deserialize<'a> for ItemInstance<'a> {
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        Self::deserialize_with(reader, true)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InventoryTransaction<'a> {
     pub legacy_request_id: i32,
@@ -0,0 +1,163 @@
+use macros::variant_count;
+use util::{BinaryRead, BinaryWrite, Deserialize, Serialize};
+
+use crate::bedrock::ConnectedPacket;
+
+use super::{list_length, StackRequest};
+
+/// Container that an item stack request or response refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[variant_count]
+pub enum ContainerSlotType {
+    AnvilInput,
+    AnvilMaterial,
+    AnvilResultPreview,
+    SmithingTableInput,
+    SmithingTableMaterial,
+    SmithingTableResultPreview,
+    Armor,
+    LevelEntity,
+    BeaconPayment,
+    BrewingInput,
+    BrewingResult,
+    BrewingFuel,
+    CombinedHotbarAndInventory,
+    CraftingInput,
+    CraftingOutputPreview,
+    RecipeConstruction,
+    RecipeNature,
+    RecipeItems,
+    RecipeSearch,
+    RecipeSearchBar,
+    RecipeEquipment,
+    RecipeBook,
+    EnchantingInput,
+    EnchantingMaterial,
+    FurnaceFuel,
+    FurnaceIngredient,
+    FurnaceResultPreview,
+    HorseEquip,
+    Hotbar,
+    Inventory,
+    ShulkerBox,
+    TradeIngredient1,
+    TradeIngredient2,
+    TradeResultPreview,
+    Offhand,
+    CompoundCreatorInput,
+    CompoundCreatorOutputPreview,
+    ElementConstructorOutputPreview,
+    MaterialReducerInput,
+    MaterialReducerOutput,
+    LabTableInput,
+    LoomInput,
+    LoomDye,
+    LoomMaterial,
+    LoomResultPreview,
+    BlastFurnaceIngredient,
+    SmokerIngredient,
+    Trade2Ingredient1,
+    Trade2Ingredient2,
+    Trade2ResultPreview,
+    GrindstoneInput,
+    GrindstoneAdditional,
+    GrindstoneResultPreview,
+    StonecutterInput,
+    StonecutterResultPreview,
+    CartographyInput,
+    CartographyAdditional,
+    CartographyResultPreview,
+    Barrel,
+    Cursor,
+    CreatedOutput,
+    SmithingTableTemplate,
+    CrafterLevelEntity,
+}
+
+impl TryFrom<u8> for ContainerSlotType {
+    type Error = anyhow::Error;
+
+    fn try_from(v: u8) -> anyhow::Result<ContainerSlotType> {
+        if v < ContainerSlotType::variant_count() as u8 {
+            // SAFETY: This is safe because the discriminant is in range and
+            // the representations are the same. Additionally, none of the enum members
+            // have a manually assigned value (this is ensured by the `variant_count` macro).
+            Ok(unsafe { std::mem::transmute::<u8, ContainerSlotType>(v) })
+        } else {
+            anyhow::bail!("Container slot type out of range ({v} >= {})", ContainerSlotType::variant_count())
+        }
+    }
+}
+
+/// Identifies a container, optionally including the ID of a dynamic container such as a bundle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FullContainerName {
+    /// Type of the container.
+    pub container: ContainerSlotType,
+    /// ID of the dynamic container, if the container is dynamic.
+    pub dynamic_id: Option<u32>,
+}
+
+impl FullContainerName {
+    /// Creates the name of a container that is not dynamic.
+    #[inline]
+    pub const fn new(container: ContainerSlotType) -> FullContainerName {
+        FullContainerName { container, dynamic_id: None }
+    }
+}
+
+impl Serialize for FullContainerName {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u8(self.container as u8)?;
+        writer.write_bool(self.dynamic_id.is_some())?;
+        if let Some(id) = self.dynamic_id {
+            writer.write_u32_le(id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Deserialize<'a> for FullContainerName {
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        let container = ContainerSlotType::try_from(reader.read_u8()?)?;
+        let dynamic_id = if reader.read_bool()? { Some(reader.read_u32_le()?) } else { None };
+
+        Ok(FullContainerName { container, dynamic_id })
+    }
+}
+
+/// Sent by the client to change item stacks in its inventory when server authoritative inventories are enabled.
+#[derive(Debug)]
+pub struct ItemStackRequest<'a> {
+    /// Requests to perform. These are handled in order.
+    pub requests: Vec<StackRequest<'a>>,
+}
+
+impl ConnectedPacket for ItemStackRequest<'_> {
+    const ID: u32 = 0x93;
+}
+
+impl Serialize for ItemStackRequest<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u32(self.requests.len() as u32)?;
+        for request in &self.requests {
+            request.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Deserialize<'a> for ItemStackRequest<'a> {
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        let count = list_length(reader.read_var_u32()?, "stack requests")?;
+        let mut requests = Vec::with_capacity(count);
+        for _ in 0..count {
+            requests.push(StackRequest::deserialize_from(reader)?);
+        }
+
+        Ok(ItemStackRequest { requests })
+    }
+}
@@ -3,4 +3,5 @@ use util::glob_export;
 glob_export!(auth_input);
 glob_export!(move_player);
 glob_export!(inventory_transaction);
+glob_export!(item_stack_request);
 glob_export!(mob_equipment);
\ No newline at end of file
@@ -0,0 +1,28 @@
+use util::{BinaryWrite, Serialize};
+
+use crate::bedrock::{ConnectedPacket, ItemInstance};
+
+/// Sets the full contents of an inventory window.
+#[derive(Debug, Clone)]
+pub struct InventoryContent<'a> {
+    /// ID of the window to update, such as [`WindowId::Inventory`](crate::bedrock::WindowId::Inventory).
+    pub window_id: u32,
+    /// New content of every slot in the window.
+    pub content: &'a [ItemInstance<'a>],
+}
+
+impl ConnectedPacket for InventoryContent<'_> {
+    const ID: u32 = 0x31;
+}
+
+impl Serialize for InventoryContent<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u32(self.window_id)?;
+        writer.write_var_u32(self.content.len() as u32)?;
+        for item in self.content {
+            item.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use util::{BinaryWrite, Serialize};
+
+use crate::bedrock::{ConnectedPacket, ItemInstance};
+
+/// Sets the content of a single slot in an inventory window.
+#[derive(Debug, Clone)]
+pub struct InventorySlot<'a> {
+    /// ID of the window the slot is in.
+    pub window_id: u32,
+    /// Index of the slot within the window.
+    pub slot: u32,
+    /// New content of the slot.
+    pub item: &'a ItemInstance<'a>,
+}
+
+impl ConnectedPacket for InventorySlot<'_> {
+    const ID: u32 = 0x32;
+}
+
+impl Serialize for InventorySlot<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u32(self.window_id)?;
+        writer.write_var_u32(self.slot)?;
+        self.item.serialize_into(writer)
+    }
+}
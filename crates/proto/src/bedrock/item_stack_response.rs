@@ -0,0 +1,112 @@
+use util::{BinaryWrite, Serialize};
+
+use crate::bedrock::{ConnectedPacket, FullContainerName};
+
+/// Whether an item stack request was accepted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StackResponseStatus {
+    /// The request was accepted and the client's prediction is correct.
+    Ok = 0,
+    /// The request was rejected and the client should revert its changes.
+    Error = 1,
+}
+
+/// The state of a slot after an item stack request was applied.
+#[derive(Debug, Clone)]
+pub struct StackResponseSlotInfo<'a> {
+    /// Index of the slot within the container.
+    pub slot: u8,
+    /// Index of the slot in the hotbar. This is equal to `slot` for most containers.
+    pub hotbar_slot: u8,
+    /// Amount of items in the slot.
+    pub count: u8,
+    /// Stack network ID of the item in the slot.
+    pub stack_network_id: i32,
+    /// Custom name of the item, if it has one.
+    pub custom_name: &'a str,
+    /// Durability the client should use if its own prediction was incorrect.
+    pub durability_correction: i32,
+}
+
+impl Serialize for StackResponseSlotInfo<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u8(self.slot)?;
+        writer.write_u8(self.hotbar_slot)?;
+        writer.write_u8(self.count)?;
+        writer.write_var_i32(self.stack_network_id)?;
+        writer.write_str(self.custom_name)?;
+        writer.write_var_i32(self.durability_correction)
+    }
+}
+
+/// The slots of a single container that were changed by an item stack request.
+#[derive(Debug, Clone)]
+pub struct StackResponseContainerInfo<'a> {
+    /// Container the slots are in.
+    pub container: FullContainerName,
+    /// New state of the changed slots.
+    pub slots: Vec<StackResponseSlotInfo<'a>>,
+}
+
+impl Serialize for StackResponseContainerInfo<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.container.serialize_into(writer)?;
+        writer.write_var_u32(self.slots.len() as u32)?;
+        for slot in &self.slots {
+            slot.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Response to a single [`StackRequest`](crate::bedrock::StackRequest).
+#[derive(Debug, Clone)]
+pub struct StackResponse<'a> {
+    /// Whether the request was accepted.
+    pub status: StackResponseStatus,
+    /// ID of the request this is a response to.
+    pub request_id: i32,
+    /// Changed containers. These are only sent if the request was accepted.
+    pub containers: Vec<StackResponseContainerInfo<'a>>,
+}
+
+impl Serialize for StackResponse<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u8(self.status as u8)?;
+        writer.write_var_i32(self.request_id)?;
+        if self.status != StackResponseStatus::Ok {
+            return Ok(())
+        }
+
+        writer.write_var_u32(self.containers.len() as u32)?;
+        for container in &self.containers {
+            container.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Responds to the requests in an [`ItemStackRequest`](crate::bedrock::ItemStackRequest).
+#[derive(Debug, Clone)]
+pub struct ItemStackResponse<'a> {
+    /// Responses in the same order as the requests.
+    pub responses: &'a [StackResponse<'a>],
+}
+
+impl ConnectedPacket for ItemStackResponse<'_> {
+    const ID: u32 = 0x94;
+}
+
+impl Serialize for ItemStackResponse<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u32(self.responses.len() as u32)?;
+        for response in self.responses {
+            response.serialize_into(writer)?;
+        }
+
+        Ok(())
+    }
+}
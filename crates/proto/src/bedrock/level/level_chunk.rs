@@ -57,7 +57,7 @@ impl Serialize for LevelChunk {
         if let Some(hashes) = &self.blob_hashes {
             writer.write_var_u32(hashes.len() as u32)?;
             for hash in hashes {
-                writer.write_u64_be(*hash)?;
+                writer.write_u64_le(*hash)?;
             }
         }
 
@@ -15,7 +15,7 @@ impl ConnectedPacket for SubChunkRequest {
 
 impl<'a> Deserialize<'a> for SubChunkRequest {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
-        let dimension = Dimension::try_from(reader.read_var_u32()?)?;
+        let dimension = Dimension::try_from(reader.read_var_i32()? as u32)?;
         let position = reader.read_veci()?;
 
         let count = reader.read_u32_le()?;
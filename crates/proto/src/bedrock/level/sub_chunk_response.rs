@@ -49,9 +49,20 @@ impl Default for SubChunkEntry {
 }
 
 impl SubChunkEntry {
+    /// Serializes an entry for a client that supports the blob cache.
+    ///
+    /// The payload is omitted for empty subchunks and the hash of the blob
+    /// containing the subchunk data is appended.
     #[inline]
-    fn serialize_cached<W: BinaryWrite>(&self, _writer: &mut W) -> anyhow::Result<()> {
-        todo!();
+    fn serialize_cached<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_vecb(&self.offset)?;
+        writer.write_u8(self.result as u8)?;
+        if self.result != SubChunkResult::AllAir {
+            writer.write_var_u32(self.payload.len() as u32)?;
+            writer.write_all(&self.payload)?;
+        }
+        self.serialize_heightmap(writer)?;
+        writer.write_u64_le(self.blob_hash)
     }
 
     #[inline]
@@ -60,10 +71,17 @@ impl SubChunkEntry {
         writer.write_u8(self.result as u8)?;
         writer.write_var_u32(self.payload.len() as u32)?;
         writer.write_all(&self.payload)?;
+        self.serialize_heightmap(writer)
+    }
+
+    #[inline]
+    fn serialize_heightmap<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
         writer.write_u8(self.heightmap_type as u8)?;
         if self.heightmap_type == HeightmapType::WithData {
-            let slice: &[i8; 256] = self.heightmap.as_ref().unwrap();
-            writer.write_all(bytemuck::cast_slice(slice))?;
+            let Some(heightmap) = &self.heightmap else {
+                anyhow::bail!("Subchunk entry is marked as having a heightmap but none was provided");
+            };
+            writer.write_all(bytemuck::cast_slice(heightmap.as_ref()))?;
         }
         Ok(())
     }
@@ -5,10 +5,10 @@ use crate::bedrock::ConnectedPacket;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u32)]
 pub enum UpdateBlockFlags {
-    UpdateNeighbors = 1 << 1,
-    UpdateNetwork = 1 << 2,
-    UpdateNoGraphics = 1 << 3,
-    UpdatePriority = 1 << 4
+    UpdateNeighbors = 1 << 0,
+    UpdateNetwork = 1 << 1,
+    UpdateNoGraphics = 1 << 2,
+    UpdatePriority = 1 << 3
 }
 
 /// Updates a single block in a chunk rather than sending the entire chunk.
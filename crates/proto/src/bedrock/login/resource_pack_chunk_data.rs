@@ -0,0 +1,37 @@
+use util::{size_of_varint, BinaryWrite, Serialize, VarString};
+
+use crate::bedrock::ConnectedPacket;
+
+/// Contains a single chunk of a pack. Sent in response to a [`ResourcePackChunkRequest`](crate::bedrock::ResourcePackChunkRequest).
+#[derive(Debug, Clone)]
+pub struct ResourcePackChunkData<'a> {
+    /// UUID of the pack, optionally followed by an underscore and its version.
+    pub uuid: &'a str,
+    /// Index of this chunk.
+    pub chunk_index: u32,
+    /// Offset of this chunk in the pack archive.
+    pub offset: u64,
+    /// Content of the chunk.
+    pub data: &'a [u8],
+}
+
+impl ConnectedPacket for ResourcePackChunkData<'_> {
+    const ID: u32 = 0x53;
+
+    fn serialized_size(&self) -> usize {
+        self.uuid.var_len() + 4 + 8 + size_of_varint(self.data.len() as u32) + self.data.len()
+    }
+}
+
+impl Serialize for ResourcePackChunkData<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_str(self.uuid)?;
+        writer.write_u32_le(self.chunk_index)?;
+        writer.write_u64_le(self.offset)?;
+
+        writer.write_var_u32(self.data.len() as u32)?;
+        writer.write_all(self.data)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use util::{BinaryRead, Deserialize};
+
+use crate::bedrock::ConnectedPacket;
+
+/// Requests a single chunk of a pack that was announced in [`ResourcePackDataInfo`](crate::bedrock::ResourcePackDataInfo).
+#[derive(Debug)]
+pub struct ResourcePackChunkRequest<'a> {
+    /// UUID of the pack, optionally followed by an underscore and its version.
+    pub uuid: &'a str,
+    /// Index of the requested chunk.
+    pub chunk_index: u32,
+}
+
+impl ConnectedPacket for ResourcePackChunkRequest<'_> {
+    const ID: u32 = 0x54;
+}
+
+impl<'a> Deserialize<'a> for ResourcePackChunkRequest<'a> {
+    fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
+        let uuid = reader.read_str()?;
+        let chunk_index = reader.read_u32_le()?;
+
+        Ok(Self { uuid, chunk_index })
+    }
+}
@@ -6,7 +6,7 @@ use util::Deserialize;
 use crate::bedrock::ConnectedPacket;
 
 /// Status contained in [`ResourcePackClientResponse`].
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ResourcePackStatus {
     /// No status.
     None,
@@ -54,7 +54,7 @@ impl<'a> ConnectedPacket for ResourcePackClientResponse<'a> {
 impl<'a> Deserialize<'a> for ResourcePackClientResponse<'a> {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         let status = ResourcePackStatus::try_from(reader.read_u8()?)?;
-        let length = reader.read_u16_be()?;
+        let length = reader.read_u16_le()?;
 
         let mut pack_ids = Vec::with_capacity(length as usize);
         for _ in 0..length {
@@ -0,0 +1,59 @@
+use util::{BinaryWrite, Serialize};
+
+use crate::bedrock::ConnectedPacket;
+
+/// Type of a pack that is being downloaded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PackType {
+    Invalid,
+    Addon,
+    Cached,
+    CopyProtected,
+    Behavior,
+    PersonaPiece,
+    Resources,
+    Skins,
+    WorldTemplate,
+}
+
+/// Sent in response to a [`ResourcePackClientResponse`](crate::bedrock::ResourcePackClientResponse) requesting packs.
+/// This tells the client how a pack will be split up into chunks.
+///
+/// The client then requests every chunk separately using [`ResourcePackChunkRequest`](crate::bedrock::ResourcePackChunkRequest).
+#[derive(Debug, Clone)]
+pub struct ResourcePackDataInfo<'a> {
+    /// UUID of the pack, optionally followed by an underscore and its version.
+    pub uuid: &'a str,
+    /// Maximum size of a single chunk in bytes.
+    pub chunk_size: u32,
+    /// Amount of chunks that the pack is split into.
+    pub chunk_count: u32,
+    /// Size of the entire pack archive in bytes.
+    pub size: u64,
+    /// SHA-256 hash of the pack archive.
+    pub hash: &'a [u8],
+    /// Whether the pack is a marketplace pack.
+    pub premium: bool,
+    /// Type of the pack.
+    pub pack_type: PackType,
+}
+
+impl ConnectedPacket for ResourcePackDataInfo<'_> {
+    const ID: u32 = 0x52;
+}
+
+impl Serialize for ResourcePackDataInfo<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_str(self.uuid)?;
+        writer.write_u32_le(self.chunk_size)?;
+        writer.write_u32_le(self.chunk_count)?;
+        writer.write_u64_le(self.size)?;
+
+        writer.write_var_u32(self.hash.len() as u32)?;
+        writer.write_all(self.hash)?;
+
+        writer.write_bool(self.premium)?;
+        writer.write_u8(self.pack_type as u8)
+    }
+}
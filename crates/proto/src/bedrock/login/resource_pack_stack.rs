@@ -86,7 +86,7 @@ impl Serialize for ResourcePackStack<'_> {
 
         writer.write_str(self.game_version)?;
 
-        writer.write_u32_be(self.experiments.len() as u32)?;
+        writer.write_u32_le(self.experiments.len() as u32)?;
         for experiment in self.experiments {
             experiment.serialize_into(writer)?;
         }
@@ -117,22 +117,22 @@ impl<'a> Serialize for ResourcePacksInfo<'a> {
         writer.write_bool(self.scripting_enabled)?;
         writer.write_bool(self.forcing_server_packs)?;
 
-        writer.write_u16_be(self.behavior_info.len() as u16)?;
+        writer.write_u16_le(self.behavior_info.len() as u16)?;
         for pack in self.behavior_info {
             writer.write_str(&pack.uuid)?;
             writer.write_str(&pack.version)?;
-            writer.write_u64_be(pack.size)?;
+            writer.write_u64_le(pack.size)?;
             writer.write_str(&pack.content_key)?;
             writer.write_str(&pack.subpack_name)?;
             writer.write_str(&pack.content_identity)?;
             writer.write_bool(pack.has_scripts)?;
         }
 
-        writer.write_u16_be(self.resource_info.len() as u16)?;
+        writer.write_u16_le(self.resource_info.len() as u16)?;
         for pack in self.resource_info {
             writer.write_str(&pack.uuid)?;
             writer.write_str(&pack.version)?;
-            writer.write_u64_be(pack.size)?;
+            writer.write_u64_le(pack.size)?;
             writer.write_str(&pack.content_key)?;
             writer.write_str(&pack.subpack_name)?;
             writer.write_str(&pack.content_identity)?;
@@ -23,6 +23,21 @@ pub enum WorldGenerator {
     End,
 }
 
+impl TryFrom<i32> for WorldGenerator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> anyhow::Result<Self> {
+        Ok(match value {
+            0 => Self::OldLimited,
+            1 => Self::Infinite,
+            2 => Self::Flat,
+            3 => Self::Nether,
+            4 => Self::End,
+            _ => anyhow::bail!("Invalid world generator type {value}"),
+        })
+    }
+}
+
 impl WorldGenerator {
     /// Serializes the enum.
     pub fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
@@ -347,7 +362,7 @@ pub struct StartGame<'a> {
     pub level_name: &'a str,
     pub template_content_identity: &'a str,
     pub movement_settings: PlayerMovementSettings,
-    /// Current time.
+    /// Current tick of the server.
     pub time: i64,
     pub enchantment_seed: i32,
     pub block_properties: &'a [BlockEntry],
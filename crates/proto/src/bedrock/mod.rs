@@ -10,6 +10,8 @@ glob_export!(level);
 glob_export!(settings);
 
 glob_export!(action);
+glob_export!(actor_data);
+glob_export!(add_actor);
 glob_export!(add_player);
 glob_export!(add_painting);
 glob_export!(animate);
@@ -31,14 +33,21 @@ glob_export!(form_response);
 glob_export!(generic_level_event);
 glob_export!(header);
 glob_export!(interact);
+glob_export!(inventory_content);
 glob_export!(inventory_options);
+glob_export!(inventory_slot);
+glob_export!(item_stack_response);
 glob_export!(level_event);
+glob_export!(move_actor_absolute);
 glob_export!(mob_effect);
 glob_export!(network_chunk_publisher_update);
 glob_export!(play_sound);
 glob_export!(player_list);
+glob_export!(remove_actor);
 glob_export!(request_ability);
 glob_export!(respawn);
+glob_export!(set_actor_data);
+glob_export!(set_actor_motion);
 glob_export!(set_hud);
 glob_export!(set_local_player_as_initialized);
 glob_export!(show_credits);
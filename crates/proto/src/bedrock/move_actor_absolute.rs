@@ -0,0 +1,52 @@
+use util::{BinaryWrite, Serialize, Vector};
+
+use crate::bedrock::ConnectedPacket;
+
+/// The entity is standing on the ground.
+pub const MOVE_ACTOR_ON_GROUND: u8 = 1 << 0;
+/// The entity was teleported and should not be interpolated to its new position.
+pub const MOVE_ACTOR_TELEPORT: u8 = 1 << 1;
+/// Forces the client to apply the movement, even to entities it controls itself.
+pub const MOVE_ACTOR_FORCE: u8 = 1 << 2;
+
+/// Moves an entity to an absolute position.
+///
+/// This is used for all entities other than players, which use [`MovePlayer`](crate::bedrock::MovePlayer) instead.
+#[derive(Debug, Clone)]
+pub struct MoveActorAbsolute {
+    /// Runtime ID of the entity.
+    pub runtime_id: u64,
+    /// Combination of the `MOVE_ACTOR` flags.
+    pub flags: u8,
+    /// New position of the entity.
+    pub position: Vector<f32, 3>,
+    /// New rotation of the entity in degrees.
+    /// The x and y components are the pitch and yaw, the z component is the head yaw.
+    pub rotation: Vector<f32, 3>,
+}
+
+impl MoveActorAbsolute {
+    /// Compresses an angle in degrees into a single byte.
+    fn compress_angle(degrees: f32) -> u8 {
+        (degrees.rem_euclid(360.0) / (360.0 / 256.0)) as u8
+    }
+}
+
+impl ConnectedPacket for MoveActorAbsolute {
+    const ID: u32 = 0x12;
+
+    fn serialized_size(&self) -> usize {
+        util::size_of_varint(self.runtime_id) + 1 + 3 * 4 + 3
+    }
+}
+
+impl Serialize for MoveActorAbsolute {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u64(self.runtime_id)?;
+        writer.write_u8(self.flags)?;
+        writer.write_vecf(&self.position)?;
+        writer.write_u8(Self::compress_angle(self.rotation.x))?;
+        writer.write_u8(Self::compress_angle(self.rotation.y))?;
+        writer.write_u8(Self::compress_angle(self.rotation.z))
+    }
+}
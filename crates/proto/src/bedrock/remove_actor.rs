@@ -0,0 +1,23 @@
+use util::{BinaryWrite, Serialize};
+
+use crate::bedrock::ConnectedPacket;
+
+/// Removes an entity from the client's world.
+///
+/// For players, this does not remove them from the player list. Use [`PlayerListRemove`](crate::bedrock::PlayerListRemove)
+/// for that.
+#[derive(Debug, Clone)]
+pub struct RemoveActor {
+    /// Unique ID of the entity to remove.
+    pub unique_id: i64,
+}
+
+impl ConnectedPacket for RemoveActor {
+    const ID: u32 = 0x0e;
+}
+
+impl Serialize for RemoveActor {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_i64(self.unique_id)
+    }
+}
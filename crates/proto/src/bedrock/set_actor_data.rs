@@ -0,0 +1,28 @@
+use util::{BinaryWrite, Serialize};
+
+use crate::bedrock::{ActorData, ConnectedPacket};
+
+/// Updates the metadata of an entity.
+#[derive(Debug, Clone)]
+pub struct SetActorData<'a> {
+    /// Runtime ID of the entity.
+    pub runtime_id: u64,
+    /// Metadata entries that have changed.
+    pub metadata: &'a ActorData,
+    /// Server tick at which the metadata changed.
+    pub tick: u64,
+}
+
+impl ConnectedPacket for SetActorData<'_> {
+    const ID: u32 = 0x27;
+}
+
+impl Serialize for SetActorData<'_> {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u64(self.runtime_id)?;
+        self.metadata.serialize_into(writer)?;
+        writer.write_var_u32(0)?; // Entity properties are unused.
+        writer.write_var_u32(0)?; // Entity properties are unused.
+        writer.write_var_u64(self.tick)
+    }
+}
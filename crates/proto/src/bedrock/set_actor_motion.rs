@@ -0,0 +1,26 @@
+use util::{BinaryWrite, Serialize, Vector};
+
+use crate::bedrock::ConnectedPacket;
+
+/// Sets the velocity of an entity.
+#[derive(Debug, Clone)]
+pub struct SetActorMotion {
+    /// Runtime ID of the entity.
+    pub runtime_id: u64,
+    /// New velocity of the entity.
+    pub velocity: Vector<f32, 3>,
+    /// Server tick at which the velocity changed.
+    pub tick: u64,
+}
+
+impl ConnectedPacket for SetActorMotion {
+    const ID: u32 = 0x28;
+}
+
+impl Serialize for SetActorMotion {
+    fn serialize_into<W: BinaryWrite>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_var_u64(self.runtime_id)?;
+        writer.write_vecf(&self.velocity)?;
+        writer.write_var_u64(self.tick)
+    }
+}
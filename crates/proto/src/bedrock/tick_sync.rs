@@ -19,7 +19,7 @@ impl ConnectedPacket for TickSync {
     const ID: u32 = 0x17;
 
     fn serialized_size(&self) -> usize {
-        8
+        16
     }
 }
 
@@ -1,9 +1,12 @@
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 
 use util::{BinaryWrite, IPV4_MEM_SIZE, IPV6_MEM_SIZE};
 
 use util::Serialize;
 
+/// Amount of internal system addresses sent in the connection handshake.
+pub const INTERNAL_ADDRESS_COUNT: usize = 20;
+
 /// Sent in response to [`ConnectionRequest`](crate::raknet::ConnectionRequest).
 #[derive(Debug)]
 pub struct ConnectionRequestAccepted {
@@ -19,7 +22,8 @@ impl ConnectionRequestAccepted {
 
     /// Estimates the size of the packet when serialized.
     pub const fn size_hint(&self) -> usize {
-        1 + IPV6_MEM_SIZE + 2 + 10 * IPV4_MEM_SIZE + 8 + 8
+        let addr_size = if self.client_address.is_ipv4() { IPV4_MEM_SIZE } else { IPV6_MEM_SIZE };
+        1 + addr_size + 2 + INTERNAL_ADDRESS_COUNT * addr_size + 8 + 8
     }
 }
 
@@ -29,9 +33,12 @@ impl Serialize for ConnectionRequestAccepted {
         writer.write_addr(&self.client_address)?;
         writer.write_u16_be(0)?; // System index
 
-        let null_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 19132));
-        for _ in 0..20 {
-            // 20 internal IDs
+        // Internal addresses use the same address family as the client.
+        let null_addr = match self.client_address {
+            SocketAddr::V4(_) => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 19132)),
+            SocketAddr::V6(_) => SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 19133, 0, 0)),
+        };
+        for _ in 0..INTERNAL_ADDRESS_COUNT {
             writer.write_addr(&null_addr)?;
         }
         writer.write_i64_be(self.request_time)?;
@@ -1,9 +1,22 @@
+use std::net::SocketAddr;
+
 use util::iassert;
 use util::{BinaryRead, Deserialize};
 
+use crate::raknet::INTERNAL_ADDRESS_COUNT;
+
 /// Confirms that the connection was successfully initiated.
 #[derive(Debug)]
-pub struct NewIncomingConnection;
+pub struct NewIncomingConnection {
+    /// Address of the server as seen by the client.
+    pub server_address: SocketAddr,
+    /// Internal addresses of the client.
+    pub internal_addresses: Vec<SocketAddr>,
+    /// Timestamp of the ping that was sent with this packet.
+    pub ping_time: i64,
+    /// Timestamp of the pong that was sent with this packet.
+    pub pong_time: i64,
+}
 
 impl NewIncomingConnection {
     /// Unique ID of this packet.
@@ -14,7 +27,18 @@ impl<'a> Deserialize<'a> for NewIncomingConnection {
     fn deserialize_from<R: BinaryRead<'a>>(reader: &mut R) -> anyhow::Result<Self> {
         iassert!(reader.read_u8()? == Self::ID);
 
-        // No data in this packet is used, there is no point in decoding it
-        Ok(Self)
+        let server_address = reader.read_addr()?;
+
+        // The amount of internal addresses differs between RakNet versions,
+        // only the two timestamps are guaranteed to follow them.
+        let mut internal_addresses = Vec::with_capacity(INTERNAL_ADDRESS_COUNT);
+        while reader.remaining() > 16 {
+            internal_addresses.push(reader.read_addr()?);
+        }
+
+        let ping_time = reader.read_i64_be()?;
+        let pong_time = reader.read_i64_be()?;
+
+        Ok(Self { server_address, internal_addresses, ping_time, pong_time })
     }
 }
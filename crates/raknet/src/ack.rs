@@ -34,12 +34,7 @@ impl RakNetClient {
         for frame_batch in frame_batches {
             frame_batch.serialize_into(&mut serialized)?;
 
-            self
-                .socket
-                .send_to(
-                    serialized.as_ref(), self.address,
-                )
-                .await?;
+            self.send_datagram(serialized.as_ref()).await?;
 
             serialized.clear();
         }
@@ -144,6 +144,15 @@ impl RakNetClient {
         (state, output_rx)
     }
 
+    /// Sends a single datagram to the client.
+    ///
+    /// The socket that the client connected through always has the same address family as the client,
+    /// because IPv4 and IPv6 clients are served by separate sockets.
+    pub async fn send_datagram(&self, data: &[u8]) -> anyhow::Result<()> {
+        self.socket.send_to(data, self.address).await?;
+        Ok(())
+    }
+
     /// Resets the request budget of this client.
     #[inline]
     pub fn refill_budget(&self) {
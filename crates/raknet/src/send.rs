@@ -133,10 +133,7 @@ impl RakNetClient {
         let mut serialized = RVec::alloc_with_capacity(ack.serialized_size());
         ack.serialize_into(&mut serialized)?;
 
-        self
-            .socket
-            .send_to(serialized.as_ref(), self.address)
-            .await?;
+        self.send_datagram(serialized.as_ref()).await?;
 
         Ok(())
     }
@@ -229,10 +226,7 @@ impl RakNetClient {
                 batch.sequence_number = self.batch_number.fetch_add(1, Ordering::SeqCst);
                 batch.serialize_into(&mut serialized)?;
 
-                // TODO: Add IPv6 support
-                self.socket
-                    .send_to(serialized.as_ref(), self.address)
-                    .await?;
+                self.send_datagram(serialized.as_ref()).await?;
 
                 if has_reliable_packet {
                     self.recovery.insert(batch);
@@ -260,10 +254,7 @@ impl RakNetClient {
                 self.recovery.insert(batch);
             }
 
-            // TODO: Add IPv6 support
-            self.socket
-                .send_to(serialized.as_ref(), self.address)
-                .await?;
+            self.send_datagram(serialized.as_ref()).await?;
         }
         // } else {
         //     self.batch_number.fetch_sub(1, Ordering::SeqCst);
@@ -245,7 +245,8 @@ pub trait BinaryRead<'a>: AsRef<[u8]> {
         let variant = self.read_u8()?;
         Ok(match variant {
             4 => {
-                let addr = IpAddr::V4(Ipv4Addr::from(self.read_u32_be()?));
+                // RakNet stores IPv4 addresses with every bit inverted.
+                let addr = IpAddr::V4(Ipv4Addr::from(!self.read_u32_be()?));
                 let port = self.read_u16_be()?;
 
                 SocketAddr::new(addr, port)
@@ -164,12 +164,13 @@ pub trait BinaryWrite: Write + AsRef<[u8]> + AsMut<[u8]> {
         match v {
             SocketAddr::V4(addr_v4) => {
                 self.write_u8(4)?;
-                self.write_all(addr_v4.ip().octets().as_ref())?;
+                // RakNet stores IPv4 addresses with every bit inverted.
+                self.write_all(addr_v4.ip().octets().map(|octet| !octet).as_ref())?;
                 self.write_u16_be(v.port())
             }
             SocketAddr::V6(addr_v6) => {
                 self.write_u8(6)?;
-                self.write_u16_be(23)?; // AF_INET6 family
+                self.write_u16_le(23)?; // AF_INET6 family, stored in the byte order of the original (Windows) sockaddr
                 self.write_u16_be(v.port())?;
                 self.write_u32_be(0)?; // Flow information
                 self.write_all(addr_v6.ip().octets().as_ref())?;